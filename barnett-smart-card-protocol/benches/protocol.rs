@@ -0,0 +1,477 @@
+//! Criterion benchmarks for every operation of the discrete log card protocol, across deck sizes,
+//! player counts and curves.
+//!
+//! Run all benchmarks with `cargo bench`, or a subset with e.g. `cargo bench -- starknet/shuffle`.
+
+use ark_ec::ProjectiveCurve;
+use ark_ff::{to_bytes, UniformRand};
+use ark_serialize::CanonicalSerialize;
+use barnett_smart_card_protocol::discrete_log_cards;
+use barnett_smart_card_protocol::discrete_log_cards::{MultiExpMode, ShuffleArgument};
+use barnett_smart_card_protocol::BarnettSmartProtocol;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use proof_essentials::utils::permutation::Permutation;
+use proof_essentials::utils::rand::sample_vector;
+use rand::{thread_rng, Rng};
+
+// Deck sizes as (m, n) pairs, with m * n cards in the deck
+const DECK_SIZES: [(usize, usize); 3] = [(2, 5), (4, 13), (10, 30)];
+const PLAYER_COUNTS: [usize; 3] = [2, 4, 8];
+
+// Deck sizes of 52 and 300 cards on which the multi-exponentiation modes of the shuffle are compared
+const MULTI_EXP_DECK_SIZES: [(usize, usize); 2] = [(4, 13), (10, 30)];
+
+// Parameters used by the benchmarks that do not depend on the deck size
+const M: usize = 4;
+const N: usize = 13;
+
+fn bench_curve<C: ProjectiveCurve>(c: &mut Criterion, curve: &str) {
+    let rng = &mut thread_rng();
+
+    let parameters = discrete_log_cards::DLCards::<C>::setup(rng, M, N).unwrap();
+    let (pk, sk) = discrete_log_cards::DLCards::<C>::player_keygen(rng, &parameters).unwrap();
+    let player_info = to_bytes![b"Alice"].unwrap();
+
+    let mut group = c.benchmark_group(format!("{}/keys", curve));
+    group.bench_function("player_keygen", |b| {
+        b.iter(|| discrete_log_cards::DLCards::<C>::player_keygen(rng, &parameters).unwrap())
+    });
+
+    let key_proof = discrete_log_cards::DLCards::<C>::prove_key_ownership(
+        rng,
+        &parameters,
+        &pk,
+        &sk,
+        &player_info,
+    )
+    .unwrap();
+    group.bench_function("prove_key_ownership", |b| {
+        b.iter(|| {
+            discrete_log_cards::DLCards::<C>::prove_key_ownership(
+                rng,
+                &parameters,
+                &pk,
+                &sk,
+                &player_info,
+            )
+            .unwrap()
+        })
+    });
+    group.bench_function("verify_key_ownership", |b| {
+        b.iter(|| {
+            discrete_log_cards::DLCards::<C>::verify_key_ownership(
+                &parameters,
+                &pk,
+                &player_info,
+                &key_proof,
+            )
+            .unwrap()
+        })
+    });
+
+    for &num_of_players in PLAYER_COUNTS.iter() {
+        let key_proof_info = (0..num_of_players)
+            .map(|_| {
+                let (pk, sk) =
+                    discrete_log_cards::DLCards::<C>::player_keygen(rng, &parameters).unwrap();
+                let proof = discrete_log_cards::DLCards::<C>::prove_key_ownership(
+                    rng,
+                    &parameters,
+                    &pk,
+                    &sk,
+                    &player_info,
+                )
+                .unwrap();
+                (pk, proof, player_info.clone())
+            })
+            .collect::<Vec<_>>();
+
+        group.bench_with_input(
+            BenchmarkId::new("compute_aggregate_key", num_of_players),
+            &key_proof_info,
+            |b, key_proof_info| {
+                b.iter(|| {
+                    discrete_log_cards::DLCards::<C>::compute_aggregate_key(
+                        &parameters,
+                        key_proof_info,
+                    )
+                    .unwrap()
+                })
+            },
+        );
+    }
+    group.finish();
+
+    let shared_key = C::rand(rng).into_affine();
+    let card = discrete_log_cards::Card::<C>::rand(rng);
+    let alpha = C::ScalarField::rand(rng);
+
+    let mut group = c.benchmark_group(format!("{}/masking", curve));
+    let (masked, masking_proof) =
+        discrete_log_cards::DLCards::<C>::mask(rng, &parameters, &shared_key, &card, &alpha)
+            .unwrap();
+    group.bench_function("mask", |b| {
+        b.iter(|| {
+            discrete_log_cards::DLCards::<C>::mask(rng, &parameters, &shared_key, &card, &alpha)
+                .unwrap()
+        })
+    });
+    group.bench_function("verify_mask", |b| {
+        b.iter(|| {
+            discrete_log_cards::DLCards::<C>::verify_mask(
+                &parameters,
+                &shared_key,
+                &card,
+                &masked,
+                &masking_proof,
+            )
+            .unwrap()
+        })
+    });
+
+    let (remasked, remasking_proof) =
+        discrete_log_cards::DLCards::<C>::remask(rng, &parameters, &shared_key, &masked, &alpha)
+            .unwrap();
+    group.bench_function("remask", |b| {
+        b.iter(|| {
+            discrete_log_cards::DLCards::<C>::remask(rng, &parameters, &shared_key, &masked, &alpha)
+                .unwrap()
+        })
+    });
+    group.bench_function("verify_remask", |b| {
+        b.iter(|| {
+            discrete_log_cards::DLCards::<C>::verify_remask(
+                &parameters,
+                &shared_key,
+                &masked,
+                &remasked,
+                &remasking_proof,
+            )
+            .unwrap()
+        })
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group(format!("{}/reveal", curve));
+    let (reveal_token, reveal_proof) =
+        discrete_log_cards::DLCards::<C>::compute_reveal_token(rng, &parameters, &sk, &pk, &masked)
+            .unwrap();
+    group.bench_function("compute_reveal_token", |b| {
+        b.iter(|| {
+            discrete_log_cards::DLCards::<C>::compute_reveal_token(
+                rng,
+                &parameters,
+                &sk,
+                &pk,
+                &masked,
+            )
+            .unwrap()
+        })
+    });
+    group.bench_function("verify_reveal", |b| {
+        b.iter(|| {
+            discrete_log_cards::DLCards::<C>::verify_reveal(
+                &parameters,
+                &pk,
+                &reveal_token,
+                &masked,
+                &reveal_proof,
+            )
+            .unwrap()
+        })
+    });
+
+    for &num_of_players in PLAYER_COUNTS.iter() {
+        let players = (0..num_of_players)
+            .map(|_| discrete_log_cards::DLCards::<C>::player_keygen(rng, &parameters).unwrap())
+            .collect::<Vec<_>>();
+        let joint_key = players
+            .iter()
+            .map(|p| p.0)
+            .sum::<discrete_log_cards::PublicKey<C>>();
+        let (masked, _) =
+            discrete_log_cards::DLCards::<C>::mask(rng, &parameters, &joint_key, &card, &alpha)
+                .unwrap();
+
+        let decryption_key = players
+            .iter()
+            .map(|(pk, sk)| {
+                let (token, proof) = discrete_log_cards::DLCards::<C>::compute_reveal_token(
+                    rng,
+                    &parameters,
+                    sk,
+                    pk,
+                    &masked,
+                )
+                .unwrap();
+                (token, proof, *pk)
+            })
+            .collect::<Vec<_>>();
+
+        group.bench_with_input(
+            BenchmarkId::new("unmask", num_of_players),
+            &decryption_key,
+            |b, decryption_key| {
+                b.iter(|| {
+                    discrete_log_cards::DLCards::<C>::unmask(&parameters, decryption_key, &masked)
+                        .unwrap()
+                })
+            },
+        );
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group(format!("{}/shuffle", curve));
+    group.sample_size(10);
+    for &(m, n) in DECK_SIZES.iter() {
+        let parameters = discrete_log_cards::DLCards::<C>::setup(rng, m, n).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("setup", m * n), &(m, n), |b, &(m, n)| {
+            b.iter(|| discrete_log_cards::DLCards::<C>::setup(rng, m, n).unwrap())
+        });
+
+        let deck: Vec<discrete_log_cards::MaskedCard<C>> = sample_vector(rng, m * n);
+        let masking_factors: Vec<C::ScalarField> = sample_vector(rng, m * n);
+        let permutation = Permutation::new(rng, m * n);
+
+        let (shuffled_deck, shuffle_proof) = discrete_log_cards::DLCards::<C>::shuffle_and_remask(
+            rng,
+            &parameters,
+            &shared_key,
+            &deck,
+            &masking_factors,
+            &permutation,
+        )
+        .unwrap();
+
+        group.bench_with_input(
+            BenchmarkId::new("shuffle_and_remask", m * n),
+            &deck,
+            |b, deck| {
+                b.iter(|| {
+                    discrete_log_cards::DLCards::<C>::shuffle_and_remask(
+                        rng,
+                        &parameters,
+                        &shared_key,
+                        deck,
+                        &masking_factors,
+                        &permutation,
+                    )
+                    .unwrap()
+                })
+            },
+        );
+
+        let precomputed_key = discrete_log_cards::PrecomputedKey::new(&shared_key, m * n);
+
+        group.bench_with_input(
+            BenchmarkId::new("shuffle_and_remask_precomputed", m * n),
+            &deck,
+            |b, deck| {
+                b.iter(|| {
+                    discrete_log_cards::DLCards::<C>::shuffle_and_remask_precomputed(
+                        rng,
+                        &parameters,
+                        &precomputed_key,
+                        deck,
+                        &masking_factors,
+                        &permutation,
+                    )
+                    .unwrap()
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("verify_shuffle", m * n),
+            &deck,
+            |b, deck| {
+                b.iter(|| {
+                    discrete_log_cards::DLCards::<C>::verify_shuffle(
+                        &parameters,
+                        &shared_key,
+                        deck,
+                        &shuffled_deck,
+                        &shuffle_proof,
+                    )
+                    .unwrap()
+                })
+            },
+        );
+    }
+    group.finish();
+
+    // Both multi-exponentiation modes produce proofs of the same size, which is part of the
+    // benchmark name so that reports show it next to the prover time
+    let mut group = c.benchmark_group(format!("{}/shuffle_multi_exp", curve));
+    group.sample_size(10);
+    for &(m, n) in MULTI_EXP_DECK_SIZES.iter() {
+        let deck: Vec<discrete_log_cards::MaskedCard<C>> = sample_vector(rng, m * n);
+        let masking_factors: Vec<C::ScalarField> = sample_vector(rng, m * n);
+        let permutation = Permutation::new(rng, m * n);
+
+        for mode in [MultiExpMode::Direct, MultiExpMode::Interpolation] {
+            let parameters = discrete_log_cards::DLCards::<C>::setup(rng, m, n)
+                .unwrap()
+                .with_multi_exp_mode(mode);
+            let (_, proof) = discrete_log_cards::DLCards::<C>::shuffle_and_remask(
+                rng,
+                &parameters,
+                &shared_key,
+                &deck,
+                &masking_factors,
+                &permutation,
+            )
+            .unwrap();
+
+            group.bench_with_input(
+                BenchmarkId::new(
+                    format!("{:?}", mode).to_lowercase(),
+                    format!("{} cards, {} byte proof", m * n, proof.serialized_size()),
+                ),
+                &deck,
+                |b, deck| {
+                    b.iter(|| {
+                        discrete_log_cards::DLCards::<C>::shuffle_and_remask(
+                            rng,
+                            &parameters,
+                            &shared_key,
+                            deck,
+                            &masking_factors,
+                            &permutation,
+                        )
+                        .unwrap()
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+
+    // The logarithmic argument trades verifier time for proof size; both times are measured on
+    // the same decks, with the proof size in the benchmark name
+    let mut group = c.benchmark_group(format!("{}/shuffle_argument", curve));
+    group.sample_size(10);
+    for &(m, n) in MULTI_EXP_DECK_SIZES.iter() {
+        let deck: Vec<discrete_log_cards::MaskedCard<C>> = sample_vector(rng, m * n);
+        let masking_factors: Vec<C::ScalarField> = sample_vector(rng, m * n);
+        let permutation = Permutation::new(rng, m * n);
+
+        for argument in [ShuffleArgument::BayerGroth, ShuffleArgument::Logarithmic] {
+            let parameters =
+                discrete_log_cards::DLCards::<C>::setup_with_argument(rng, m, n, argument).unwrap();
+            let (shuffled_deck, proof) = discrete_log_cards::DLCards::<C>::shuffle_and_remask(
+                rng,
+                &parameters,
+                &shared_key,
+                &deck,
+                &masking_factors,
+                &permutation,
+            )
+            .unwrap();
+            let name = format!("{} cards, {} byte proof", m * n, proof.serialized_size());
+            let argument = format!("{:?}", argument).to_lowercase();
+
+            group.bench_with_input(
+                BenchmarkId::new(format!("prove/{}", argument), &name),
+                &deck,
+                |b, deck| {
+                    b.iter(|| {
+                        discrete_log_cards::DLCards::<C>::shuffle_and_remask(
+                            rng,
+                            &parameters,
+                            &shared_key,
+                            deck,
+                            &masking_factors,
+                            &permutation,
+                        )
+                        .unwrap()
+                    })
+                },
+            );
+            group.bench_with_input(
+                BenchmarkId::new(format!("verify/{}", argument), &name),
+                &deck,
+                |b, deck| {
+                    b.iter(|| {
+                        discrete_log_cards::DLCards::<C>::verify_shuffle(
+                            &parameters,
+                            &shared_key,
+                            deck,
+                            &shuffled_deck,
+                            &proof,
+                        )
+                        .unwrap()
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+
+    // Folding the decks for every offset dominates the cut proofs
+    let mut group = c.benchmark_group(format!("{}/cut", curve));
+    group.sample_size(10);
+    for &(m, n) in DECK_SIZES.iter() {
+        let deck: Vec<discrete_log_cards::MaskedCard<C>> = sample_vector(rng, m * n);
+        let masking_factors: Vec<C::ScalarField> = sample_vector(rng, m * n);
+        let offset = rng.gen_range(0..m * n);
+
+        let (cut_deck, cut_proof) = discrete_log_cards::DLCards::<C>::cut_and_remask(
+            rng,
+            &parameters,
+            &shared_key,
+            &deck,
+            &masking_factors,
+            offset,
+        )
+        .unwrap();
+
+        group.bench_with_input(
+            BenchmarkId::new("cut_and_remask", m * n),
+            &deck,
+            |b, deck| {
+                b.iter(|| {
+                    discrete_log_cards::DLCards::<C>::cut_and_remask(
+                        rng,
+                        &parameters,
+                        &shared_key,
+                        deck,
+                        &masking_factors,
+                        offset,
+                    )
+                    .unwrap()
+                })
+            },
+        );
+
+        group.bench_with_input(BenchmarkId::new("verify_cut", m * n), &deck, |b, deck| {
+            b.iter(|| {
+                discrete_log_cards::DLCards::<C>::verify_cut(
+                    &parameters,
+                    &shared_key,
+                    deck,
+                    &cut_deck,
+                    &cut_proof,
+                )
+                .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn starknet(c: &mut Criterion) {
+    bench_curve::<starknet_curve::Projective>(c, "starknet");
+}
+
+fn bls12_377(c: &mut Criterion) {
+    bench_curve::<ark_bls12_377::G1Projective>(c, "bls12_377");
+}
+
+fn bn254(c: &mut Criterion) {
+    bench_curve::<ark_bn254::G1Projective>(c, "bn254");
+}
+
+criterion_group!(benches, starknet, bls12_377, bn254);
+criterion_main!(benches);
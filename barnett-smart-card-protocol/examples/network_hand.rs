@@ -0,0 +1,76 @@
+//! An example running a hand between players in separate processes, connected through a TCP relay.
+//! Every player derives the same protocol parameters from the domain tag of the table, exchanges
+//! keys, shuffles the deck, receives two private cards and finally sees the cards of every other
+//! player. The relay seats players in the order in which they connect.
+//!
+//! Start the relay for three players, then the three players:
+//!
+//! ```text
+//! cargo run --example network_hand --features network -- relay 127.0.0.1:4000 3
+//! cargo run --example network_hand --features network -- player 127.0.0.1:4000 3
+//! cargo run --example network_hand --features network -- player 127.0.0.1:4000 3
+//! cargo run --example network_hand --features network -- player 127.0.0.1:4000 3
+//! ```
+
+use anyhow::{anyhow, bail};
+use barnett_smart_card_protocol::network::{
+    run_tcp_relay, PlayerNode, TableConfig, TcpTransport, Transport,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+// Choose elliptic curve setting
+type Curve = starknet_curve::Projective;
+
+// All players must agree on the domain tag of the table
+const DOMAIN: &[u8] = b"network_hand example table";
+const M: usize = 2;
+const N: usize = 26;
+const CARDS_PER_PLAYER: usize = 2;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = std::env::args().collect::<Vec<_>>();
+    let arg = |i: usize| {
+        args.get(i)
+            .ok_or_else(|| anyhow!("usage: network_hand (relay|player) <addr> <players>"))
+    };
+
+    let addr = arg(2)?;
+    let num_of_players: usize = arg(3)?.parse()?;
+
+    match arg(1)?.as_str() {
+        "relay" => {
+            let listener = TcpListener::bind(addr).await?;
+            println!("Relay listening on {}", listener.local_addr()?);
+            run_tcp_relay(listener, num_of_players).await?;
+        }
+        "player" => {
+            let config = TableConfig {
+                domain: DOMAIN.to_vec(),
+                m: M,
+                n: N,
+                num_of_players,
+                cards_per_player: CARDS_PER_PLAYER,
+                timeout: Duration::from_secs(300),
+            };
+
+            let mut rng = StdRng::from_entropy();
+            let transport = TcpTransport::connect(addr.as_str()).await?;
+            let seat = transport.seat();
+            let name = format!("Player {}", seat).into_bytes();
+            let mut node = PlayerNode::<Curve, _>::new(&mut rng, &name, config, transport)?;
+
+            let transcript = node.play_hand(&mut rng).await?;
+            println!("Seat {} was dealt {:?}", seat, transcript.own_cards);
+            for (other, cards) in transcript.opened_cards.iter().enumerate() {
+                println!("Seat {} showed {:?}", other, cards);
+            }
+        }
+        mode => bail!("unknown mode {}", mode),
+    }
+
+    Ok(())
+}
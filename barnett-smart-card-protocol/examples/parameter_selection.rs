@@ -1,19 +1,19 @@
 //! An example that showcases how the prover time and proof size are affected by the parameter selection.
-//! As per the Bayer-Groth paper, for a deck of $N = m \times n$ cards:
-//! - the prover performs m*N exponentiations
-//! - the proof is approximately 6m*|G|+4n*|Z| where |G| is the size of a EC point and |Z| is the size of a scalar
-//! (note that this is because we are not using the FFT-like improvement suggested in the paper)
-//! 
-//! Analysis: increasing m will always increase the prover time. Assuming |G| ≈≈ 2*|Z|, proof size is approx 12m+4n and will
-//! be minimised when m ≈≈ n/3.
-//! 
+//! With the Bayer-Groth shuffle argument of this crate, for a deck of $N = m \times n$ cards:
+//! - the prover performs about 2m*N exponentiations, or about 4N with the interpolation-based (FFT-like) multi-exponentiation
+//!   argument suggested in the paper (`MultiExpMode::Interpolation`)
+//! - the proof is approximately 11m*|G|+5n*|Z| where |G| is the size of a EC point and |Z| is the size of a scalar, in both modes
+//!
+//! Analysis: with the direct mode, increasing m will always increase the prover time; the interpolation mode makes the
+//! prover time grow much slower with m. Assuming |G| ≈≈ 1.5*|Z| (as on BLS12-377), proof size is approx 16.5m+5n and will be minimised when m ≈≈ n/3.
+//!
 //! Run the example `cargo run --example parameter_selection --release` and notice how proof size hits a minimum at m=10, n=30
 
 use anyhow::anyhow;
 use ark_ec::ProjectiveCurve;
 use ark_ff::UniformRand;
 use ark_serialize::CanonicalSerialize;
-use barnett_smart_card_protocol::discrete_log_cards::MaskedCard;
+use barnett_smart_card_protocol::discrete_log_cards::{MaskedCard, MultiExpMode};
 use barnett_smart_card_protocol::{discrete_log_cards, BarnettSmartProtocol};
 use byte_unit::Byte;
 use proof_essentials::utils::permutation::Permutation;
@@ -69,31 +69,33 @@ fn benchmark_parameters<R: Rng>(
         return Err(anyhow!("Parameters do not match the deck size."));
     }
 
-    println!("\n---------------------------------------------------");
-    println!(
-        "  Running a shuffle with parameters m = {} and n = {}",
-        m, n
-    );
+    for mode in [MultiExpMode::Direct, MultiExpMode::Interpolation] {
+        println!("\n---------------------------------------------------");
+        println!(
+            "  Running a shuffle with parameters m = {} and n = {} in {:?} mode",
+            m, n, mode
+        );
 
-    let parameters = CardProtocol::setup(rng, m, n)?;
+        let parameters = CardProtocol::setup(rng, m, n)?.with_multi_exp_mode(mode);
 
-    let prover_start_time = Instant::now();
-    let (_shuffled_deck, proof) = CardProtocol::shuffle_and_remask(
-        rng,
-        &parameters,
-        &shared_key.into_affine(),
-        deck,
-        masking_factors,
-        permutation,
-    )?;
-    let prover_end_time = Instant::now();
-    let prover_duration = prover_end_time - prover_start_time;
+        let prover_start_time = Instant::now();
+        let (_shuffled_deck, proof) = CardProtocol::shuffle_and_remask(
+            rng,
+            &parameters,
+            &shared_key.into_affine(),
+            deck,
+            masking_factors,
+            permutation,
+        )?;
+        let prover_end_time = Instant::now();
+        let prover_duration = prover_end_time - prover_start_time;
 
-    println!("    Prover time: {} seconds", prover_duration.as_secs_f32());
-    println!(
-        "    Proof size: {}\n",
-        Byte::from_bytes(proof.serialized_size() as u128).get_appropriate_unit(false)
-    );
+        println!("    Prover time: {} seconds", prover_duration.as_secs_f32());
+        println!(
+            "    Proof size: {}\n",
+            Byte::from_bytes(proof.serialized_size() as u128).get_appropriate_unit(false)
+        );
+    }
 
     Ok(())
 }
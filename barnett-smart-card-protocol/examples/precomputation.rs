@@ -0,0 +1,94 @@
+//! An example that compares the shuffle prover with and without fixed-base window tables for a
+//! 52-card deck. The tables for the generator are built once in `setup`, the table for the
+//! aggregate key is built once per game with `PrecomputedKey::new`; both are then reused by every
+//! remasking performed during the shuffle.
+//!
+//! Only the remasking of the deck is affected: the shuffle argument itself performs the same work
+//! in both cases, so the speed-up is bounded by the share of the prover time spent remasking.
+//!
+//! Run the example with `cargo run --example precomputation --release`
+
+use ark_ec::ProjectiveCurve;
+use barnett_smart_card_protocol::discrete_log_cards::{MaskedCard, PrecomputedKey};
+use barnett_smart_card_protocol::{discrete_log_cards, BarnettSmartProtocol};
+use proof_essentials::utils::permutation::Permutation;
+use proof_essentials::utils::rand::sample_vector;
+use rand::thread_rng;
+use std::time::Instant;
+
+// Choose elliptic curve setting
+type Curve = ark_bls12_377::G1Projective;
+type Scalar = ark_bls12_377::Fr;
+
+// Instantiate concrete type for our card protocol
+type CardProtocol = discrete_log_cards::DLCards<Curve>;
+
+const M: usize = 4;
+const N: usize = 13;
+const NUMBER_OF_RUNS: u32 = 10;
+
+fn main() -> anyhow::Result<()> {
+    let mut rng = thread_rng();
+
+    let parameters = CardProtocol::setup(&mut rng, M, N)?;
+    let (shared_key, _) = CardProtocol::player_keygen(&mut rng, &parameters)?;
+
+    let deck: Vec<MaskedCard<Curve>> = sample_vector(&mut rng, M * N);
+    let masking_factors: Vec<Scalar> = sample_vector(&mut rng, M * N);
+    let permutation = Permutation::new(&mut rng, M * N);
+
+    let precomputation_start_time = Instant::now();
+    let precomputed_key = PrecomputedKey::<Curve>::new(&shared_key, M * N);
+    let precomputation_duration = Instant::now() - precomputation_start_time;
+
+    let generic_start_time = Instant::now();
+    for _ in 0..NUMBER_OF_RUNS {
+        CardProtocol::shuffle_and_remask(
+            &mut rng,
+            &parameters,
+            &shared_key,
+            &deck,
+            &masking_factors,
+            &permutation,
+        )?;
+    }
+    let generic_duration = (Instant::now() - generic_start_time) / NUMBER_OF_RUNS;
+
+    let precomputed_start_time = Instant::now();
+    for _ in 0..NUMBER_OF_RUNS {
+        CardProtocol::shuffle_and_remask_precomputed(
+            &mut rng,
+            &parameters,
+            &precomputed_key,
+            &deck,
+            &masking_factors,
+            &permutation,
+        )?;
+    }
+    let precomputed_duration = (Instant::now() - precomputed_start_time) / NUMBER_OF_RUNS;
+
+    println!("\n---------------------------------------------------");
+    println!(
+        "  Shuffling {} cards on {}",
+        M * N,
+        std::any::type_name::<<Curve as ProjectiveCurve>::Affine>()
+    );
+    println!(
+        "    Key table precomputation: {} seconds",
+        precomputation_duration.as_secs_f32()
+    );
+    println!(
+        "    Prover time (generic): {} seconds",
+        generic_duration.as_secs_f32()
+    );
+    println!(
+        "    Prover time (precomputed): {} seconds",
+        precomputed_duration.as_secs_f32()
+    );
+    println!(
+        "    Speed-up: {:.2}x\n",
+        generic_duration.as_secs_f32() / precomputed_duration.as_secs_f32()
+    );
+
+    Ok(())
+}
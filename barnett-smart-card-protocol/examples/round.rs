@@ -6,7 +6,6 @@ use ark_ff::{to_bytes, UniformRand};
 use ark_std::{rand::Rng, One};
 use proof_essentials::utils::permutation::Permutation;
 use proof_essentials::utils::rand::sample_vector;
-use proof_essentials::zkp::proofs::{chaum_pedersen_dl_equality, schnorr_identification};
 use rand::thread_rng;
 use std::collections::HashMap;
 use std::iter::Iterator;
@@ -23,12 +22,17 @@ type PublicKey = discrete_log_cards::PublicKey<Curve>;
 type SecretKey = discrete_log_cards::PlayerSecretKey<Curve>;
 
 type Card = discrete_log_cards::Card<Curve>;
+type DeckState = discrete_log_cards::DeckState<Curve>;
 type MaskedCard = discrete_log_cards::MaskedCard<Curve>;
 type RevealToken = discrete_log_cards::RevealToken<Curve>;
+type RevealTokenStore = discrete_log_cards::RevealTokenStore<Curve>;
 
-type ProofKeyOwnership = schnorr_identification::proof::Proof<Curve>;
-type RemaskingProof = chaum_pedersen_dl_equality::proof::Proof<Curve>;
-type RevealProof = chaum_pedersen_dl_equality::proof::Proof<Curve>;
+type ProofKeyOwnership = discrete_log_cards::SchnorrProof<Curve>;
+type RemaskingProof = discrete_log_cards::DLEqualityProof<Curve>;
+type RevealProof = discrete_log_cards::DLEqualityProof<Curve>;
+
+// Reveal tokens are cached for the duration of a hand
+const SESSION: &'static [u8] = b"Hand 1";
 
 #[derive(Error, Debug, PartialEq)]
 pub enum GameErrors {
@@ -135,6 +139,7 @@ struct Player {
     proof_key: ProofKeyOwnership,
     cards: Vec<MaskedCard>,
     opened_cards: Vec<Option<ClassicPlayingCard>>,
+    reveal_tokens: RevealTokenStore,
 }
 
 impl Player {
@@ -148,6 +153,7 @@ impl Player {
             proof_key,
             cards: vec![],
             opened_cards: vec![],
+            reveal_tokens: RevealTokenStore::new(&pk),
         })
     }
 
@@ -159,7 +165,7 @@ impl Player {
     pub fn peek_at_card(
         &mut self,
         parameters: &CardParameters,
-        reveal_tokens: &mut Vec<(RevealToken, RevealProof, PublicKey)>,
+        reveal_tokens: &Vec<(RevealToken, RevealProof, PublicKey)>,
         card_mappings: &HashMap<Card, ClassicPlayingCard>,
         card: &MaskedCard,
     ) -> Result<(), anyhow::Error> {
@@ -167,12 +173,8 @@ impl Player {
 
         let i = i.ok_or(GameErrors::CardNotFound)?;
 
-        //TODO add function to create that without the proof
-        let rng = &mut thread_rng();
-        let own_reveal_token = self.compute_reveal_token(rng, parameters, card)?;
-        reveal_tokens.push(own_reveal_token);
-
-        let unmasked_card = CardProtocol::unmask(&parameters, reveal_tokens, card)?;
+        let unmasked_card =
+            CardProtocol::unmask_with_own_key(&parameters, &self.sk, reveal_tokens, card)?;
         let opened_card = card_mappings.get(&unmasked_card);
         let opened_card = opened_card.ok_or(GameErrors::InvalidCard)?;
 
@@ -181,13 +183,20 @@ impl Player {
     }
 
     pub fn compute_reveal_token<R: Rng>(
-        &self,
+        &mut self,
         rng: &mut R,
         pp: &CardParameters,
         card: &MaskedCard,
     ) -> anyhow::Result<(RevealToken, RevealProof, PublicKey)> {
-        let (reveal_token, reveal_proof) =
-            CardProtocol::compute_reveal_token(rng, &pp, &self.sk, &self.pk, card)?;
+        let (reveal_token, reveal_proof) = CardProtocol::compute_reveal_token_cached(
+            rng,
+            &pp,
+            &self.sk,
+            &self.pk,
+            &mut self.reveal_tokens,
+            SESSION,
+            card,
+        )?;
 
         Ok((reveal_token, reveal_proof, self.pk))
     }
@@ -350,12 +359,14 @@ fn main() -> anyhow::Result<()> {
     )?;
 
     // CARDS ARE SHUFFLED. ROUND OF THE GAME CAN BEGIN
+    // The deck state tracks the dealt cards and the reveal tokens collected for them
+    let mut deck_state = DeckState::new(&joint_pk, &final_shuffled_deck);
     let deck = final_shuffled_deck;
 
-    andrija.receive_card(deck[0]);
-    kobi.receive_card(deck[1]);
-    nico.receive_card(deck[2]);
-    tom.receive_card(deck[3]);
+    for player in [&mut andrija, &mut kobi, &mut nico, &mut tom] {
+        let (_, card) = deck_state.deal(Some(&player.pk))?;
+        player.receive_card(card);
+    }
 
     let andrija_rt_1 = andrija.compute_reveal_token(rng, &parameters, &deck[1])?;
     let andrija_rt_2 = andrija.compute_reveal_token(rng, &parameters, &deck[2])?;
@@ -373,19 +384,38 @@ fn main() -> anyhow::Result<()> {
     let tom_rt_1 = tom.compute_reveal_token(rng, &parameters, &deck[1])?;
     let tom_rt_2 = tom.compute_reveal_token(rng, &parameters, &deck[2])?;
 
-    let mut rts_andrija = vec![kobi_rt_0, nico_rt_0, tom_rt_0];
-    let mut rts_kobi = vec![andrija_rt_1, nico_rt_1, tom_rt_1];
-    let mut rts_nico = vec![andrija_rt_2, kobi_rt_2, tom_rt_2];
-    let mut rts_tom = vec![andrija_rt_3, kobi_rt_3, nico_rt_3];
+    // Tokens are verified as they are recorded
+    let received = vec![
+        (0, vec![kobi_rt_0, nico_rt_0, tom_rt_0]),
+        (1, vec![andrija_rt_1, nico_rt_1, tom_rt_1]),
+        (2, vec![andrija_rt_2, kobi_rt_2, tom_rt_2]),
+        (3, vec![andrija_rt_3, kobi_rt_3, nico_rt_3]),
+    ];
+    for (index, tokens) in received {
+        for token in tokens {
+            deck_state.add_reveal_token(&parameters, index, token)?;
+        }
+    }
+
+    // A player restarting mid-hand resumes from a snapshot of the deck state
+    let snapshot_path = std::env::temp_dir().join("barnett-smart-card-round.snapshot");
+    deck_state.save(&snapshot_path)?;
+    let deck_state = DeckState::load(&snapshot_path)?;
+    std::fs::remove_file(&snapshot_path)?;
+
+    let rts_andrija = deck_state.reveal_tokens(0);
+    let rts_kobi = deck_state.reveal_tokens(1);
+    let rts_nico = deck_state.reveal_tokens(2);
+    let rts_tom = deck_state.reveal_tokens(3);
 
     //At this moment players privately open their cards and only they know that values
-    andrija.peek_at_card(&parameters, &mut rts_andrija, &card_mapping, &deck[0])?;
-    kobi.peek_at_card(&parameters, &mut rts_kobi, &card_mapping, &deck[1])?;
-    nico.peek_at_card(&parameters, &mut rts_nico, &card_mapping, &deck[2])?;
-    tom.peek_at_card(&parameters, &mut rts_tom, &card_mapping, &deck[3])?;
+    andrija.peek_at_card(&parameters, &rts_andrija, &card_mapping, &deck[0])?;
+    kobi.peek_at_card(&parameters, &rts_kobi, &card_mapping, &deck[1])?;
+    nico.peek_at_card(&parameters, &rts_nico, &card_mapping, &deck[2])?;
+    tom.peek_at_card(&parameters, &rts_tom, &card_mapping, &deck[3])?;
 
     /* Here we can add custom logic of a game:
-        1. swap card
+        1. swap card (see `DLCards::swap_and_remask`)
         2. place a bet
         3. ...
     */
@@ -398,8 +428,7 @@ fn main() -> anyhow::Result<()> {
     let nico_rt_2 = nico.compute_reveal_token(rng, &parameters, &deck[2])?;
     let tom_rt_3 = tom.compute_reveal_token(rng, &parameters, &deck[3])?;
 
-    //2. tokens for all other cards are exchanged
-    //TODO add struct for this so that we can just clone
+    //2. tokens for all other cards are exchanged, reusing the ones computed for the private peeks
     let andrija_rt_1 = andrija.compute_reveal_token(rng, &parameters, &deck[1])?;
     let andrija_rt_2 = andrija.compute_reveal_token(rng, &parameters, &deck[2])?;
     let andrija_rt_3 = andrija.compute_reveal_token(rng, &parameters, &deck[3])?;
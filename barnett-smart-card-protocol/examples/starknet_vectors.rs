@@ -0,0 +1,116 @@
+//! Generate cross-check vectors for Cairo verifiers of the StarkNet mode of `DLCards`. The output is
+//! a JSON object holding Pedersen hash vectors and a masking, remasking and reveal proof, each with
+//! its statement, as arrays of hex-encoded felts.
+//!
+//! Run the example with `cargo run --example starknet_vectors > vectors.json`
+
+use ark_ff::{BigInteger, PrimeField, UniformRand};
+use barnett_smart_card_protocol::discrete_log_cards::starknet::{
+    hash_on_elements, pedersen_hash, point_to_felts, Felt, StarknetProof,
+};
+use barnett_smart_card_protocol::{discrete_log_cards, BarnettSmartProtocol};
+use rand::thread_rng;
+
+// Choose elliptic curve setting
+type Curve = starknet_curve::Projective;
+type Scalar = starknet_curve::Fr;
+
+// Instantiate concrete type for our card protocol
+type CardProtocol = discrete_log_cards::DLCards<Curve>;
+type Card = discrete_log_cards::Card<Curve>;
+
+fn hex(felt: &Felt) -> String {
+    let digits = felt
+        .into_repr()
+        .to_bytes_be()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    format!("\"0x{}\"", digits)
+}
+
+fn array(felts: &[Felt]) -> String {
+    let felts = felts.iter().map(hex).collect::<Vec<_>>();
+    format!("[{}]", felts.join(", "))
+}
+
+fn vector(name: &str, statement: &[Felt], proof: &StarknetProof) -> String {
+    format!(
+        "    \"{}\": {{ \"statement\": {}, \"proof\": {} }}",
+        name,
+        array(statement),
+        array(&proof.to_felts())
+    )
+}
+
+fn main() -> anyhow::Result<()> {
+    let rng = &mut thread_rng();
+
+    let parameters = CardProtocol::setup(rng, 2, 26)?;
+    let (pk, sk) = CardProtocol::player_keygen(rng, &parameters)?;
+
+    let x = Felt::rand(rng);
+    let y = Felt::rand(rng);
+    let elements = (0..4).map(|_| Felt::rand(rng)).collect::<Vec<_>>();
+
+    // Masking: the statement is the shared key, the card and the masked card
+    let card = Card::rand(rng);
+    let r = Scalar::rand(rng);
+    let (masked, masking_proof) = CardProtocol::mask_starknet(rng, &parameters, &pk, &card, &r)?;
+    let masking_statement = [
+        point_to_felts(&pk),
+        point_to_felts(&card.0),
+        point_to_felts(&masked.0),
+        point_to_felts(&masked.1),
+    ]
+    .concat();
+
+    // Remasking: the statement is the shared key, the original and the remasked card
+    let alpha = Scalar::rand(rng);
+    let (remasked, remasking_proof) =
+        CardProtocol::remask_starknet(rng, &parameters, &pk, &masked, &alpha)?;
+    let remasking_statement = [
+        point_to_felts(&pk),
+        point_to_felts(&masked.0),
+        point_to_felts(&masked.1),
+        point_to_felts(&remasked.0),
+        point_to_felts(&remasked.1),
+    ]
+    .concat();
+
+    // Reveal: the statement is the player key, the masked card and the reveal token
+    let (token, reveal_proof) =
+        CardProtocol::compute_reveal_token_starknet(rng, &parameters, &sk, &pk, &remasked)?;
+    let reveal_statement = [
+        point_to_felts(&pk),
+        point_to_felts(&remasked.0),
+        point_to_felts(&remasked.1),
+        point_to_felts(&token.0),
+    ]
+    .concat();
+
+    println!("{{");
+    println!(
+        "    \"generator\": {},",
+        array(&point_to_felts(parameters.encryption_generator()))
+    );
+    println!(
+        "    \"pedersen\": {{ \"inputs\": {}, \"output\": {} }},",
+        array(&[x, y]),
+        hex(&pedersen_hash(&x, &y))
+    );
+    println!(
+        "    \"hash_on_elements\": {{ \"inputs\": {}, \"output\": {} }},",
+        array(&elements),
+        hex(&hash_on_elements(&elements))
+    );
+    println!("{},", vector("masking", &masking_statement, &masking_proof));
+    println!(
+        "{},",
+        vector("remasking", &remasking_statement, &remasking_proof)
+    );
+    println!("{}", vector("reveal", &reveal_statement, &reveal_proof));
+    println!("}}");
+
+    Ok(())
+}
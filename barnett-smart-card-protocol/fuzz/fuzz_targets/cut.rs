@@ -0,0 +1,34 @@
+#![no_main]
+
+use barnett_smart_card_protocol::BarnettSmartProtocol;
+use barnett_smart_card_protocol_fuzz::*;
+use libfuzzer_sys::fuzz_target;
+
+fn run<C: FuzzCurve>(mut input: &[u8]) -> Option<()> {
+    let shared_key: PublicKey<C> = decode(&mut input)?;
+    check_point::<C>(&shared_key);
+    let original_deck: Vec<MaskedCard<C>> = decode(&mut input)?;
+    check_deck::<C>(&original_deck);
+    let cut_deck: Vec<MaskedCard<C>> = decode(&mut input)?;
+    check_deck::<C>(&cut_deck);
+    let proof = decode(&mut input)?;
+
+    let result = CardProtocol::<C>::verify_cut(
+        C::parameters(),
+        &shared_key,
+        &original_deck,
+        &cut_deck,
+        &proof,
+    );
+    let mut statement = vec![shared_key];
+    statement.extend(deck_points::<C>(&original_deck));
+    statement.extend(deck_points::<C>(&cut_deck));
+    check_accepted::<C>(&result, &statement);
+
+    Some(())
+}
+
+fuzz_target!(|data: &[u8]| {
+    run::<Starknet>(data);
+    run::<Bls12_377>(data);
+});
@@ -0,0 +1,38 @@
+#![no_main]
+
+use barnett_smart_card_protocol::discrete_log_cards::{DeckState, DECK_STATE_VERSION};
+use barnett_smart_card_protocol_fuzz::*;
+use blake2::{Blake2s, Digest};
+use libfuzzer_sys::fuzz_target;
+
+fn run<C: FuzzCurve>(data: &[u8]) {
+    // Raw snapshots almost always fail the integrity check, so the input is also sealed with the
+    // version and hash of the snapshot format for its content to reach the decoder
+    let mut sealed = vec![DECK_STATE_VERSION];
+    sealed.extend_from_slice(data);
+    sealed.extend_from_slice(
+        &Blake2s::new()
+            .chain([DECK_STATE_VERSION])
+            .chain(data)
+            .finalize(),
+    );
+
+    for snapshot in [data, &sealed[..]] {
+        if let Ok(state) = DeckState::<C>::from_snapshot(snapshot) {
+            check_point::<C>(state.shared_key());
+            check_deck::<C>(state.deck());
+            assert!(
+                state.next_card() <= state.deck().len(),
+                "dealt more cards than the deck holds"
+            );
+
+            let resumed = DeckState::<C>::from_snapshot(&state.to_snapshot().unwrap()).unwrap();
+            assert_eq!(resumed.digest().unwrap(), state.digest().unwrap());
+        }
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    run::<Starknet>(data);
+    run::<Bls12_377>(data);
+});
@@ -0,0 +1,27 @@
+#![no_main]
+
+use barnett_smart_card_protocol::BarnettSmartProtocol;
+use barnett_smart_card_protocol_fuzz::*;
+use libfuzzer_sys::fuzz_target;
+
+fn run<C: FuzzCurve>(mut input: &[u8]) -> Option<()> {
+    let pk: PublicKey<C> = decode(&mut input)?;
+    check_point::<C>(&pk);
+    let player_public_info: Vec<u8> = decode(&mut input)?;
+    let proof = decode(&mut input)?;
+
+    let result =
+        CardProtocol::<C>::verify_key_ownership(C::parameters(), &pk, &player_public_info, &proof);
+    check_accepted::<C>(&result, &[pk]);
+    let _ = CardProtocol::<C>::compute_aggregate_key(
+        C::parameters(),
+        &vec![(pk, proof, player_public_info)],
+    );
+
+    Some(())
+}
+
+fuzz_target!(|data: &[u8]| {
+    run::<Starknet>(data);
+    run::<Bls12_377>(data);
+});
@@ -0,0 +1,26 @@
+#![no_main]
+
+use barnett_smart_card_protocol::BarnettSmartProtocol;
+use barnett_smart_card_protocol_fuzz::*;
+use libfuzzer_sys::fuzz_target;
+
+fn run<C: FuzzCurve>(mut input: &[u8]) -> Option<()> {
+    let shared_key: PublicKey<C> = decode(&mut input)?;
+    check_point::<C>(&shared_key);
+    let card: Card<C> = decode(&mut input)?;
+    check_point::<C>(&card.0);
+    let masked_card: MaskedCard<C> = decode(&mut input)?;
+    check_masked_card::<C>(&masked_card);
+    let proof = decode(&mut input)?;
+
+    let result =
+        CardProtocol::<C>::verify_mask(C::parameters(), &shared_key, &card, &masked_card, &proof);
+    check_accepted::<C>(&result, &[shared_key, card.0, masked_card.0, masked_card.1]);
+
+    Some(())
+}
+
+fuzz_target!(|data: &[u8]| {
+    run::<Starknet>(data);
+    run::<Bls12_377>(data);
+});
@@ -0,0 +1,31 @@
+#![no_main]
+
+use barnett_smart_card_protocol::discrete_log_cards::OpenedCard;
+use barnett_smart_card_protocol_fuzz::*;
+use libfuzzer_sys::fuzz_target;
+
+fn run<C: FuzzCurve>(mut input: &[u8]) -> Option<()> {
+    let player_keys: Vec<PublicKey<C>> = decode(&mut input)?;
+    player_keys.iter().for_each(check_point::<C>);
+    let opened: OpenedCard<C> = decode(&mut input)?;
+    check_masked_card::<C>(&opened.masked_card);
+    check_point::<C>(&opened.card.0);
+
+    let mut statement = player_keys.clone();
+    statement.extend([opened.masked_card.0, opened.masked_card.1, opened.card.0]);
+    for (reveal_token, _, pk) in opened.reveal_tokens.iter() {
+        check_point::<C>(&reveal_token.0);
+        check_point::<C>(pk);
+        statement.extend([reveal_token.0, *pk]);
+    }
+
+    let result = opened.verify(C::parameters(), &player_keys);
+    check_accepted::<C>(&result, &statement);
+
+    Some(())
+}
+
+fuzz_target!(|data: &[u8]| {
+    run::<Starknet>(data);
+    run::<Bls12_377>(data);
+});
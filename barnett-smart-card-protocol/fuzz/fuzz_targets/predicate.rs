@@ -0,0 +1,53 @@
+#![no_main]
+
+use barnett_smart_card_protocol::discrete_log_cards::predicates::{Predicate, PredicateProof};
+use barnett_smart_card_protocol::texas_holdem::cards::{ClassicPlayingCard, Suite, Value};
+use barnett_smart_card_protocol_fuzz::*;
+use libfuzzer_sys::fuzz_target;
+
+fn run<C: FuzzCurve>(mut input: &[u8]) -> Option<()> {
+    let predicate = match decode::<u8>(&mut input)? % 3 {
+        0 => Predicate::SameValue,
+        1 => Predicate::SameSuite,
+        _ => {
+            // One bit per card of the classic deck
+            let members: u64 = decode(&mut input)?;
+            let cards = Value::VALUES.iter().flat_map(|&value| {
+                Suite::VALUES
+                    .iter()
+                    .map(move |&suite| ClassicPlayingCard::new(value, suite))
+            });
+            Predicate::MemberOf(
+                cards
+                    .enumerate()
+                    .filter(|(i, _)| (members >> i) & 1 == 1)
+                    .map(|(_, card)| card)
+                    .collect(),
+            )
+        }
+    };
+    let pk: PublicKey<C> = decode(&mut input)?;
+    check_point::<C>(&pk);
+    let cards: Vec<MaskedCard<C>> = decode(&mut input)?;
+    check_deck::<C>(&cards);
+    let proof: PredicateProof<C> = decode(&mut input)?;
+
+    let result = CardProtocol::<C>::verify_predicate(
+        C::parameters(),
+        &pk,
+        C::encoding(),
+        &predicate,
+        &cards,
+        &proof,
+    );
+    let mut statement = vec![pk];
+    statement.extend(deck_points::<C>(&cards));
+    check_accepted::<C>(&result, &statement);
+
+    Some(())
+}
+
+fuzz_target!(|data: &[u8]| {
+    run::<Starknet>(data);
+    run::<Bls12_377>(data);
+});
@@ -0,0 +1,45 @@
+#![no_main]
+
+use barnett_smart_card_protocol::BarnettSmartProtocol;
+use barnett_smart_card_protocol_fuzz::*;
+use libfuzzer_sys::fuzz_target;
+
+fn run<C: FuzzCurve>(mut input: &[u8]) -> Option<()> {
+    let remove: bool = decode(&mut input)?;
+    let pk: PublicKey<C> = decode(&mut input)?;
+    check_point::<C>(&pk);
+    let original_deck: Vec<MaskedCard<C>> = decode(&mut input)?;
+    check_deck::<C>(&original_deck);
+    let rekeyed_deck: Vec<MaskedCard<C>> = decode(&mut input)?;
+    check_deck::<C>(&rekeyed_deck);
+    let proofs = decode(&mut input)?;
+
+    let result = if remove {
+        CardProtocol::<C>::verify_remove_key_from_deck(
+            C::parameters(),
+            &pk,
+            &original_deck,
+            &rekeyed_deck,
+            &proofs,
+        )
+    } else {
+        CardProtocol::<C>::verify_add_key_to_deck(
+            C::parameters(),
+            &pk,
+            &original_deck,
+            &rekeyed_deck,
+            &proofs,
+        )
+    };
+    let mut statement = vec![pk];
+    statement.extend(deck_points::<C>(&original_deck));
+    statement.extend(deck_points::<C>(&rekeyed_deck));
+    check_accepted::<C>(&result, &statement);
+
+    Some(())
+}
+
+fuzz_target!(|data: &[u8]| {
+    run::<Starknet>(data);
+    run::<Bls12_377>(data);
+});
@@ -0,0 +1,34 @@
+#![no_main]
+
+use barnett_smart_card_protocol::BarnettSmartProtocol;
+use barnett_smart_card_protocol_fuzz::*;
+use libfuzzer_sys::fuzz_target;
+
+fn run<C: FuzzCurve>(mut input: &[u8]) -> Option<()> {
+    let shared_key: PublicKey<C> = decode(&mut input)?;
+    check_point::<C>(&shared_key);
+    let original: MaskedCard<C> = decode(&mut input)?;
+    check_masked_card::<C>(&original);
+    let remasked: MaskedCard<C> = decode(&mut input)?;
+    check_masked_card::<C>(&remasked);
+    let proof = decode(&mut input)?;
+
+    let result = CardProtocol::<C>::verify_remask(
+        C::parameters(),
+        &shared_key,
+        &original,
+        &remasked,
+        &proof,
+    );
+    check_accepted::<C>(
+        &result,
+        &[shared_key, original.0, original.1, remasked.0, remasked.1],
+    );
+
+    Some(())
+}
+
+fuzz_target!(|data: &[u8]| {
+    run::<Starknet>(data);
+    run::<Bls12_377>(data);
+});
@@ -0,0 +1,33 @@
+#![no_main]
+
+use barnett_smart_card_protocol::BarnettSmartProtocol;
+use barnett_smart_card_protocol_fuzz::*;
+use libfuzzer_sys::fuzz_target;
+
+fn run<C: FuzzCurve>(mut input: &[u8]) -> Option<()> {
+    let pk: PublicKey<C> = decode(&mut input)?;
+    check_point::<C>(&pk);
+    let reveal_token: RevealToken<C> = decode(&mut input)?;
+    check_point::<C>(&reveal_token.0);
+    let masked_card: MaskedCard<C> = decode(&mut input)?;
+    check_masked_card::<C>(&masked_card);
+    let proof = decode(&mut input)?;
+
+    let result =
+        CardProtocol::<C>::verify_reveal(C::parameters(), &pk, &reveal_token, &masked_card, &proof);
+    check_accepted::<C>(&result, &[pk, reveal_token.0, masked_card.0, masked_card.1]);
+    if result.is_ok() {
+        let _ = CardProtocol::<C>::unmask(
+            C::parameters(),
+            &vec![(reveal_token, proof, pk)],
+            &masked_card,
+        );
+    }
+
+    Some(())
+}
+
+fuzz_target!(|data: &[u8]| {
+    run::<Starknet>(data);
+    run::<Bls12_377>(data);
+});
@@ -0,0 +1,32 @@
+#![no_main]
+
+use barnett_smart_card_protocol_fuzz::*;
+use libfuzzer_sys::fuzz_target;
+
+fn run<C: FuzzCurve>(mut input: &[u8]) -> Option<()> {
+    let shared_key: PublicKey<C> = decode(&mut input)?;
+    check_point::<C>(&shared_key);
+    let cards: Vec<MaskedCard<C>> = decode(&mut input)?;
+    check_deck::<C>(&cards);
+    let selected: MaskedCard<C> = decode(&mut input)?;
+    check_masked_card::<C>(&selected);
+    let proof = decode(&mut input)?;
+
+    let result = CardProtocol::<C>::verify_selection(
+        C::parameters(),
+        &shared_key,
+        &cards,
+        &selected,
+        &proof,
+    );
+    let mut statement = vec![shared_key, selected.0, selected.1];
+    statement.extend(deck_points::<C>(&cards));
+    check_accepted::<C>(&result, &statement);
+
+    Some(())
+}
+
+fuzz_target!(|data: &[u8]| {
+    run::<Starknet>(data);
+    run::<Bls12_377>(data);
+});
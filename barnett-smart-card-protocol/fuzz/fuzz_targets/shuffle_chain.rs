@@ -0,0 +1,60 @@
+#![no_main]
+
+use barnett_smart_card_protocol::discrete_log_cards::ShuffleChain;
+use barnett_smart_card_protocol_fuzz::*;
+use libfuzzer_sys::fuzz_target;
+
+fn run<C: FuzzCurve>(mut input: &[u8]) -> Option<()> {
+    let shared_key: PublicKey<C> = decode(&mut input)?;
+    check_point::<C>(&shared_key);
+    let deck: Vec<MaskedCard<C>> = decode(&mut input)?;
+    check_deck::<C>(&deck);
+
+    // Resume from a claimed commitment, or start a new chain
+    let mut chain = if decode::<bool>(&mut input)? {
+        let length: u64 = decode(&mut input)?;
+        let commitment: Vec<u8> = decode(&mut input)?;
+        let chain =
+            ShuffleChain::<C>::resume(&shared_key, &deck, length as usize, &commitment).ok()?;
+        assert!(
+            chain.commitment() == &commitment[..],
+            "resumed another chain"
+        );
+        chain
+    } else {
+        ShuffleChain::<C>::new(&shared_key, &deck).ok()?
+    };
+
+    // Append links until the input runs out
+    loop {
+        let shuffled_deck: Vec<MaskedCard<C>> = decode(&mut input)?;
+        check_deck::<C>(&shuffled_deck);
+        let proof = decode(&mut input)?;
+
+        let previous_deck = chain.deck().clone();
+        let previous_commitment = chain.commitment().to_vec();
+        let previous_length = chain.len();
+
+        let result = chain.push(C::parameters(), shuffled_deck.clone(), &proof);
+        let mut statement = vec![shared_key];
+        statement.extend(deck_points::<C>(&previous_deck));
+        statement.extend(deck_points::<C>(&shuffled_deck));
+        check_accepted::<C>(&result, &statement);
+
+        if result.is_ok() {
+            assert_eq!(chain.len(), previous_length + 1);
+        } else {
+            assert!(
+                chain.deck() == &previous_deck
+                    && chain.len() == previous_length
+                    && chain.commitment() == &previous_commitment[..],
+                "a rejected link changed the chain"
+            );
+        }
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    run::<Starknet>(data);
+    run::<Bls12_377>(data);
+});
@@ -0,0 +1,25 @@
+#![no_main]
+
+use barnett_smart_card_protocol::discrete_log_cards::RevealTokenStore;
+use barnett_smart_card_protocol_fuzz::*;
+use libfuzzer_sys::fuzz_target;
+use std::path::Path;
+
+fn run<C: FuzzCurve>(data: &[u8], path: &Path) {
+    std::fs::write(path, data).unwrap();
+
+    if let Ok(store) = RevealTokenStore::<C>::load(path) {
+        check_point::<C>(store.public_key());
+
+        store.save(path).unwrap();
+        let reloaded = RevealTokenStore::<C>::load(path).unwrap();
+        assert!(reloaded.public_key() == store.public_key() && reloaded.len() == store.len());
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    // Stores are read from a file, as by a player restarting mid-hand
+    let path = std::env::temp_dir().join(format!("reveal-token-store-{}", std::process::id()));
+    run::<Starknet>(data, &path);
+    run::<Bls12_377>(data, &path);
+});
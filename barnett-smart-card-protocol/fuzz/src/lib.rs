@@ -0,0 +1,128 @@
+//! Helpers shared by the fuzz targets. Each target decodes its inputs from the fuzzer bytes, the
+//! way a node decodes the messages of its peers, and hands them to the matching verifier of
+//! `DLCards`. Verifiers must reject malformed inputs with an error, never panic.
+//!
+//! Every target runs on two curves. The Starknet curve has cofactor 1, so all of its points are in
+//! the prime-order subgroup; BLS12-377 has points of small order, which verifiers must reject.
+
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_serialize::CanonicalDeserialize;
+use barnett_smart_card_protocol::discrete_log_cards;
+use barnett_smart_card_protocol::error::CardProtocolError;
+use barnett_smart_card_protocol::texas_holdem::cards::{encode_cards, ClassicPlayingCard};
+use barnett_smart_card_protocol::BarnettSmartProtocol;
+use once_cell::sync::Lazy;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::HashMap;
+
+// Choose elliptic curve settings
+pub type Starknet = starknet_curve::Projective;
+pub type Bls12_377 = ark_bls12_377::G1Projective;
+
+// Instantiate concrete types for our card protocol
+pub type CardProtocol<C> = discrete_log_cards::DLCards<C>;
+pub type CardParameters<C> = discrete_log_cards::Parameters<C>;
+pub type PublicKey<C> = discrete_log_cards::PublicKey<C>;
+
+pub type Card<C> = discrete_log_cards::Card<C>;
+pub type MaskedCard<C> = discrete_log_cards::MaskedCard<C>;
+pub type RevealToken<C> = discrete_log_cards::RevealToken<C>;
+
+pub type Encoding<C> = HashMap<Card<C>, ClassicPlayingCard>;
+
+const M: usize = 2;
+const N: usize = 2;
+
+static STARKNET_PARAMETERS: Lazy<CardParameters<Starknet>> = Lazy::new(setup::<Starknet>);
+static BLS12_377_PARAMETERS: Lazy<CardParameters<Bls12_377>> = Lazy::new(setup::<Bls12_377>);
+
+static STARKNET_ENCODING: Lazy<Encoding<Starknet>> = Lazy::new(encoding::<Starknet>);
+static BLS12_377_ENCODING: Lazy<Encoding<Bls12_377>> = Lazy::new(encoding::<Bls12_377>);
+
+fn setup<C: ProjectiveCurve>() -> CardParameters<C> {
+    let rng = &mut StdRng::seed_from_u64(0);
+    CardProtocol::<C>::setup(rng, M, N).unwrap()
+}
+
+fn encoding<C: ProjectiveCurve>() -> Encoding<C> {
+    let rng = &mut StdRng::seed_from_u64(0);
+    encode_cards::<C, _>(rng)
+}
+
+/// A curve the targets run on, with its parameters and card encoding generated once per fuzzing
+/// process
+pub trait FuzzCurve: ProjectiveCurve {
+    fn parameters() -> &'static CardParameters<Self>;
+
+    fn encoding() -> &'static Encoding<Self>;
+
+    fn is_on_curve(point: &Self::Affine) -> bool;
+}
+
+impl FuzzCurve for Starknet {
+    fn parameters() -> &'static CardParameters<Self> {
+        &STARKNET_PARAMETERS
+    }
+
+    fn encoding() -> &'static Encoding<Self> {
+        &STARKNET_ENCODING
+    }
+
+    fn is_on_curve(point: &Self::Affine) -> bool {
+        point.is_on_curve()
+    }
+}
+
+impl FuzzCurve for Bls12_377 {
+    fn parameters() -> &'static CardParameters<Self> {
+        &BLS12_377_PARAMETERS
+    }
+
+    fn encoding() -> &'static Encoding<Self> {
+        &BLS12_377_ENCODING
+    }
+
+    fn is_on_curve(point: &Self::Affine) -> bool {
+        point.is_on_curve()
+    }
+}
+
+/// Decode the next value from the input, if the remaining bytes hold one
+pub fn decode<T: CanonicalDeserialize>(input: &mut &[u8]) -> Option<T> {
+    T::deserialize(input).ok()
+}
+
+/// Decoding must only ever yield points on the curve
+pub fn check_point<C: FuzzCurve>(point: &C::Affine) {
+    assert!(C::is_on_curve(point), "decoded a point off the curve");
+}
+
+pub fn check_masked_card<C: FuzzCurve>(masked_card: &MaskedCard<C>) {
+    check_point::<C>(&masked_card.0);
+    check_point::<C>(&masked_card.1);
+}
+
+pub fn check_deck<C: FuzzCurve>(deck: &Vec<MaskedCard<C>>) {
+    deck.iter().for_each(check_masked_card::<C>);
+}
+
+/// Points of a deck, in order
+pub fn deck_points<C: FuzzCurve>(deck: &Vec<MaskedCard<C>>) -> Vec<C::Affine> {
+    deck.iter().flat_map(|card| [card.0, card.1]).collect()
+}
+
+/// A verifier must never accept a statement holding a point outside the prime-order subgroup
+pub fn check_accepted<C: FuzzCurve>(
+    result: &Result<(), CardProtocolError>,
+    statement: &[C::Affine],
+) {
+    if result.is_ok() {
+        for point in statement {
+            assert!(
+                point.is_in_correct_subgroup_assuming_on_curve(),
+                "accepted a point outside of the prime-order subgroup"
+            );
+        }
+    }
+}
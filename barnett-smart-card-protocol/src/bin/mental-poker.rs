@@ -0,0 +1,567 @@
+//! Command-line tool to run and audit a game offline. Every step of the protocol reads its inputs
+//! from files and writes its outputs to files, so that any party holding the transcript of a hand
+//! can replay and check every proof.
+//!
+//! Protocol parameters are hashed to the curve from a public domain tag, which is stored in the
+//! parameters file together with the deck dimensions. Anyone holding that file recomputes the same
+//! parameters, and nobody knows a discrete log relation between the generators. Secret keys are
+//! written to files readable by their owner only.
+//!
+//! A typical audit of a disputed shuffle and reveal looks like:
+//!
+//! ```text
+//! mental-poker verify-key --params table.params --key alice.key
+//! mental-poker aggregate-key --params table.params --keys alice.key bob.key --out table.pk
+//! mental-poker verify-shuffle --params table.params --shared-key table.pk \
+//!     --deck deck-0.bin --shuffled deck-1.bin --proof shuffle-1.proof
+//! mental-poker unmask --params table.params --deck deck-2.bin --index 3 \
+//!     --tokens alice-3.token bob-3.token --cards cards.bin
+//! ```
+
+use barnett_smart_card_protocol::discrete_log_cards;
+use barnett_smart_card_protocol::BarnettSmartProtocol;
+
+use anyhow::anyhow;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::One;
+use clap::{Parser, Subcommand};
+use proof_essentials::utils::permutation::Permutation;
+use proof_essentials::utils::rand::sample_vector;
+use rand::{thread_rng, RngCore};
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, Write};
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+// Choose elliptic curve setting
+type Curve = starknet_curve::Projective;
+type Scalar = starknet_curve::Fr;
+
+// Instantiate concrete type for our card protocol
+type CardProtocol = discrete_log_cards::DLCards<Curve>;
+type CardParameters = discrete_log_cards::Parameters<Curve>;
+type PublicKey = discrete_log_cards::PublicKey<Curve>;
+type SecretKey = discrete_log_cards::PlayerSecretKey<Curve>;
+
+type Card = discrete_log_cards::Card<Curve>;
+type MaskedCard = discrete_log_cards::MaskedCard<Curve>;
+type RevealToken = discrete_log_cards::RevealToken<Curve>;
+
+type ProofKeyOwnership = discrete_log_cards::SchnorrProof<Curve>;
+type RevealProof = discrete_log_cards::DLEqualityProof<Curve>;
+type ShuffleProof = <CardProtocol as BarnettSmartProtocol>::ZKProofShuffle;
+
+/// Contents of a parameters file: the domain tag and the deck dimensions
+type ParametersFile = (Vec<u8>, usize, usize);
+/// Contents of a key file: a public key, its proof of ownership and the name it is bound to
+type KeyFile = (PublicKey, ProofKeyOwnership, Vec<u8>);
+/// Contents of a token file: a reveal token, its proof and the key of the player who computed it
+type TokenFile = (RevealToken, RevealProof, PublicKey);
+
+#[derive(Parser)]
+#[clap(
+    name = "mental-poker",
+    about = "Run and audit Barnett-Smart mental poker sessions"
+)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate the parameters of a table for decks of m * n cards
+    Setup {
+        #[clap(long)]
+        m: usize,
+        #[clap(long)]
+        n: usize,
+        /// Public domain tag from which the parameters are derived. A random tag is used if omitted
+        #[clap(long)]
+        domain: Option<String>,
+        #[clap(long, parse(from_os_str))]
+        out: PathBuf,
+    },
+    /// Create a keypair
+    Keygen {
+        #[clap(long, parse(from_os_str))]
+        params: PathBuf,
+        #[clap(long, parse(from_os_str))]
+        out_pk: PathBuf,
+        #[clap(long, parse(from_os_str))]
+        out_sk: PathBuf,
+    },
+    /// Prove ownership of a keypair, binding it to a player name
+    ProveKey {
+        #[clap(long, parse(from_os_str))]
+        params: PathBuf,
+        #[clap(long, parse(from_os_str))]
+        pk: PathBuf,
+        #[clap(long, parse(from_os_str))]
+        sk: PathBuf,
+        #[clap(long)]
+        name: String,
+        #[clap(long, parse(from_os_str))]
+        out: PathBuf,
+    },
+    /// Verify a proof of key ownership
+    VerifyKey {
+        #[clap(long, parse(from_os_str))]
+        params: PathBuf,
+        #[clap(long, parse(from_os_str))]
+        key: PathBuf,
+    },
+    /// Verify every key ownership proof and compute the aggregate key of the table
+    AggregateKey {
+        #[clap(long, parse(from_os_str))]
+        params: PathBuf,
+        #[clap(long, parse(from_os_str), multiple_values = true)]
+        keys: Vec<PathBuf>,
+        #[clap(long, parse(from_os_str))]
+        out: PathBuf,
+    },
+    /// Sample an encoding of the cards and mask it with the aggregate key
+    NewDeck {
+        #[clap(long, parse(from_os_str))]
+        params: PathBuf,
+        #[clap(long, parse(from_os_str))]
+        shared_key: PathBuf,
+        #[clap(long, parse(from_os_str))]
+        out_cards: PathBuf,
+        #[clap(long, parse(from_os_str))]
+        out_deck: PathBuf,
+    },
+    /// Shuffle and remask a deck, proving that the shuffle was done correctly
+    Shuffle {
+        #[clap(long, parse(from_os_str))]
+        params: PathBuf,
+        #[clap(long, parse(from_os_str))]
+        shared_key: PathBuf,
+        #[clap(long, parse(from_os_str))]
+        deck: PathBuf,
+        #[clap(long, parse(from_os_str))]
+        out_deck: PathBuf,
+        #[clap(long, parse(from_os_str))]
+        out_proof: PathBuf,
+    },
+    /// Verify a shuffle proof
+    VerifyShuffle {
+        #[clap(long, parse(from_os_str))]
+        params: PathBuf,
+        #[clap(long, parse(from_os_str))]
+        shared_key: PathBuf,
+        #[clap(long, parse(from_os_str))]
+        deck: PathBuf,
+        #[clap(long, parse(from_os_str))]
+        shuffled: PathBuf,
+        #[clap(long, parse(from_os_str))]
+        proof: PathBuf,
+    },
+    /// Compute a reveal token for a card of a deck
+    RevealToken {
+        #[clap(long, parse(from_os_str))]
+        params: PathBuf,
+        #[clap(long, parse(from_os_str))]
+        pk: PathBuf,
+        #[clap(long, parse(from_os_str))]
+        sk: PathBuf,
+        #[clap(long, parse(from_os_str))]
+        deck: PathBuf,
+        #[clap(long)]
+        index: usize,
+        #[clap(long, parse(from_os_str))]
+        out: PathBuf,
+    },
+    /// Verify the reveal tokens of every player and unmask a card of a deck
+    Unmask {
+        #[clap(long, parse(from_os_str))]
+        params: PathBuf,
+        #[clap(long, parse(from_os_str))]
+        deck: PathBuf,
+        #[clap(long)]
+        index: usize,
+        #[clap(long, parse(from_os_str), multiple_values = true)]
+        tokens: Vec<PathBuf>,
+        /// Encoding of the cards, used to print the position of the unmasked card
+        #[clap(long, parse(from_os_str))]
+        cards: Option<PathBuf>,
+    },
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    run(cli.command)
+}
+
+fn run(command: Command) -> anyhow::Result<()> {
+    let rng = &mut thread_rng();
+
+    match command {
+        Command::Setup { m, n, domain, out } => {
+            let domain = match domain {
+                Some(tag) => tag.into_bytes(),
+                None => {
+                    let mut domain = vec![0u8; 32];
+                    rng.fill_bytes(&mut domain);
+                    domain
+                }
+            };
+
+            // Check that the dimensions are usable before writing the file
+            CardProtocol::setup_from_domain(&domain, m, n)?;
+            write(&out, &(domain, m, n))?;
+        }
+        Command::Keygen {
+            params,
+            out_pk,
+            out_sk,
+        } => {
+            let parameters = read_parameters(&params)?;
+            let (pk, sk) = CardProtocol::player_keygen(rng, &parameters)?;
+            write(&out_pk, &pk)?;
+            write_secret(&out_sk, &sk)?;
+        }
+        Command::ProveKey {
+            params,
+            pk,
+            sk,
+            name,
+            out,
+        } => {
+            let parameters = read_parameters(&params)?;
+            let pk: PublicKey = read(&pk)?;
+            let sk: SecretKey = read(&sk)?;
+            let name = name.into_bytes();
+
+            let proof = CardProtocol::prove_key_ownership(rng, &parameters, &pk, &sk, &name)?;
+            write(&out, &(pk, proof, name))?;
+        }
+        Command::VerifyKey { params, key } => {
+            let parameters = read_parameters(&params)?;
+            let (pk, proof, name): KeyFile = read(&key)?;
+
+            CardProtocol::verify_key_ownership(&parameters, &pk, &name, &proof)?;
+            println!(
+                "Valid key ownership proof for {}",
+                String::from_utf8_lossy(&name)
+            );
+        }
+        Command::AggregateKey { params, keys, out } => {
+            let parameters = read_parameters(&params)?;
+            let keys = keys
+                .iter()
+                .map(|path| read::<KeyFile>(path))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            let shared_key = CardProtocol::compute_aggregate_key(&parameters, &keys)?;
+            write(&out, &shared_key)?;
+        }
+        Command::NewDeck {
+            params,
+            shared_key,
+            out_cards,
+            out_deck,
+        } => {
+            let (parameters, m, n) = read_parameters_and_size(&params)?;
+            let shared_key: PublicKey = read(&shared_key)?;
+
+            let cards: Vec<Card> = sample_vector(rng, m * n);
+            let deck = cards
+                .iter()
+                .map(|card| {
+                    Ok(CardProtocol::mask(rng, &parameters, &shared_key, card, &Scalar::one())?.0)
+                })
+                .collect::<anyhow::Result<Vec<MaskedCard>>>()?;
+
+            write(&out_cards, &cards)?;
+            write(&out_deck, &deck)?;
+        }
+        Command::Shuffle {
+            params,
+            shared_key,
+            deck,
+            out_deck,
+            out_proof,
+        } => {
+            let parameters = read_parameters(&params)?;
+            let shared_key: PublicKey = read(&shared_key)?;
+            let deck: Vec<MaskedCard> = read(&deck)?;
+
+            let permutation = Permutation::new(rng, deck.len());
+            let masking_factors: Vec<Scalar> = sample_vector(rng, deck.len());
+            let (shuffled_deck, proof) = CardProtocol::shuffle_and_remask(
+                rng,
+                &parameters,
+                &shared_key,
+                &deck,
+                &masking_factors,
+                &permutation,
+            )?;
+
+            write(&out_deck, &shuffled_deck)?;
+            write(&out_proof, &proof)?;
+        }
+        Command::VerifyShuffle {
+            params,
+            shared_key,
+            deck,
+            shuffled,
+            proof,
+        } => {
+            let parameters = read_parameters(&params)?;
+            let shared_key: PublicKey = read(&shared_key)?;
+            let deck: Vec<MaskedCard> = read(&deck)?;
+            let shuffled: Vec<MaskedCard> = read(&shuffled)?;
+            let proof: ShuffleProof = read(&proof)?;
+
+            CardProtocol::verify_shuffle(&parameters, &shared_key, &deck, &shuffled, &proof)?;
+            println!("Valid shuffle proof");
+        }
+        Command::RevealToken {
+            params,
+            pk,
+            sk,
+            deck,
+            index,
+            out,
+        } => {
+            let parameters = read_parameters(&params)?;
+            let pk: PublicKey = read(&pk)?;
+            let sk: SecretKey = read(&sk)?;
+            let deck: Vec<MaskedCard> = read(&deck)?;
+            let masked_card = card_at(&deck, index)?;
+
+            let (token, proof) =
+                CardProtocol::compute_reveal_token(rng, &parameters, &sk, &pk, masked_card)?;
+            write(&out, &(token, proof, pk))?;
+        }
+        Command::Unmask {
+            params,
+            deck,
+            index,
+            tokens,
+            cards,
+        } => {
+            let parameters = read_parameters(&params)?;
+            let deck: Vec<MaskedCard> = read(&deck)?;
+            let masked_card = card_at(&deck, index)?;
+            let tokens = tokens
+                .iter()
+                .map(|path| read::<TokenFile>(path))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            let card = CardProtocol::unmask(&parameters, &tokens, masked_card)?;
+
+            match cards {
+                Some(path) => {
+                    let cards: Vec<Card> = read(&path)?;
+                    let position = cards
+                        .iter()
+                        .position(|c| *c == card)
+                        .ok_or_else(|| anyhow!("card {} is not part of the encoding", index))?;
+                    println!("Card {} is card number {} of the encoding", index, position);
+                }
+                None => {
+                    let mut bytes = Vec::new();
+                    card.serialize(&mut bytes)?;
+                    println!("Card {} is {}", index, to_hex(&bytes));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn read_parameters_and_size(path: &Path) -> anyhow::Result<(CardParameters, usize, usize)> {
+    let (domain, m, n): ParametersFile = read(path)?;
+    Ok((CardProtocol::setup_from_domain(&domain, m, n)?, m, n))
+}
+
+fn read_parameters(path: &Path) -> anyhow::Result<CardParameters> {
+    Ok(read_parameters_and_size(path)?.0)
+}
+
+fn card_at(deck: &Vec<MaskedCard>, index: usize) -> anyhow::Result<&MaskedCard> {
+    deck.get(index)
+        .ok_or_else(|| anyhow!("the deck only has {} cards", deck.len()))
+}
+
+fn read<T: CanonicalDeserialize>(path: &Path) -> anyhow::Result<T> {
+    let file = File::open(path).map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+    let value = T::deserialize(&mut BufReader::new(file))
+        .map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+
+    Ok(value)
+}
+
+fn write<T: CanonicalSerialize>(path: &Path, value: &T) -> anyhow::Result<()> {
+    let file = File::create(path).map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+    write_to(path, file, value)
+}
+
+/// Same as `write`, with the file readable and writable by its owner only
+fn write_secret<T: CanonicalSerialize>(path: &Path, value: &T) -> anyhow::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+
+    let file = options
+        .open(path)
+        .map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+    // The mode only applies to new files, an existing file keeps its permissions otherwise
+    #[cfg(unix)]
+    file.set_permissions(std::fs::Permissions::from_mode(0o600))
+        .map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+
+    write_to(path, file, value)
+}
+
+fn write_to<T: CanonicalSerialize>(path: &Path, file: File, value: &T) -> anyhow::Result<()> {
+    let mut writer = BufWriter::new(file);
+    value.serialize(&mut writer)?;
+    // Dropping the writer would swallow a failed flush
+    writer
+        .flush()
+        .map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+
+    Ok(())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{read, run, Command, TokenFile};
+
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_offline_hand() {
+        let dir = std::env::temp_dir().join(format!("mental-poker-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| -> PathBuf { dir.join(name) };
+
+        run(Command::Setup {
+            m: 2,
+            n: 5,
+            domain: Some(String::from("audit")),
+            out: path("table.params"),
+        })
+        .unwrap();
+
+        let players = ["alice", "bob"];
+        for player in players.iter() {
+            run(Command::Keygen {
+                params: path("table.params"),
+                out_pk: path(&format!("{}.pk", player)),
+                out_sk: path(&format!("{}.sk", player)),
+            })
+            .unwrap();
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+
+                let metadata = std::fs::metadata(path(&format!("{}.sk", player))).unwrap();
+                assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+            }
+            run(Command::ProveKey {
+                params: path("table.params"),
+                pk: path(&format!("{}.pk", player)),
+                sk: path(&format!("{}.sk", player)),
+                name: player.to_string(),
+                out: path(&format!("{}.key", player)),
+            })
+            .unwrap();
+            run(Command::VerifyKey {
+                params: path("table.params"),
+                key: path(&format!("{}.key", player)),
+            })
+            .unwrap();
+        }
+
+        run(Command::AggregateKey {
+            params: path("table.params"),
+            keys: players
+                .iter()
+                .map(|p| path(&format!("{}.key", p)))
+                .collect(),
+            out: path("table.pk"),
+        })
+        .unwrap();
+        run(Command::NewDeck {
+            params: path("table.params"),
+            shared_key: path("table.pk"),
+            out_cards: path("cards.bin"),
+            out_deck: path("deck-0.bin"),
+        })
+        .unwrap();
+        run(Command::Shuffle {
+            params: path("table.params"),
+            shared_key: path("table.pk"),
+            deck: path("deck-0.bin"),
+            out_deck: path("deck-1.bin"),
+            out_proof: path("shuffle-1.proof"),
+        })
+        .unwrap();
+        run(Command::VerifyShuffle {
+            params: path("table.params"),
+            shared_key: path("table.pk"),
+            deck: path("deck-0.bin"),
+            shuffled: path("deck-1.bin"),
+            proof: path("shuffle-1.proof"),
+        })
+        .unwrap();
+
+        // A shuffle proof does not verify against another deck
+        assert!(run(Command::VerifyShuffle {
+            params: path("table.params"),
+            shared_key: path("table.pk"),
+            deck: path("deck-1.bin"),
+            shuffled: path("deck-0.bin"),
+            proof: path("shuffle-1.proof"),
+        })
+        .is_err());
+
+        for player in players.iter() {
+            run(Command::RevealToken {
+                params: path("table.params"),
+                pk: path(&format!("{}.pk", player)),
+                sk: path(&format!("{}.sk", player)),
+                deck: path("deck-1.bin"),
+                index: 3,
+                out: path(&format!("{}-3.token", player)),
+            })
+            .unwrap();
+        }
+        let _: TokenFile = read(&path("alice-3.token")).unwrap();
+
+        run(Command::Unmask {
+            params: path("table.params"),
+            deck: path("deck-1.bin"),
+            index: 3,
+            tokens: players
+                .iter()
+                .map(|p| path(&format!("{}-3.token", p)))
+                .collect(),
+            cards: Some(path("cards.bin")),
+        })
+        .unwrap();
+
+        // Tokens of a single player are not enough to find the card in the encoding
+        assert!(run(Command::Unmask {
+            params: path("table.params"),
+            deck: path("deck-1.bin"),
+            index: 3,
+            tokens: vec![path("alice-3.token")],
+            cards: Some(path("cards.bin")),
+        })
+        .is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
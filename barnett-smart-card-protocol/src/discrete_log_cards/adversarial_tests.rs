@@ -0,0 +1,1347 @@
+//! Property-based tests checking that every verifier of `DLCards` rejects the proofs an adversary
+//! could derive from honest ones: proofs with a mutated field, proofs replayed against another
+//! statement or player, statements built from identity points or another generator, and
+//! reordered decks.
+
+#[cfg(test)]
+mod test {
+    use crate::discrete_log_cards;
+    use crate::discrete_log_cards::predicates::Predicate;
+    use crate::discrete_log_cards::ShuffleArgument;
+    use crate::error::CardProtocolError;
+    use crate::texas_holdem::cards::{encode_cards, ClassicPlayingCard, Suite, Value};
+    use crate::BarnettSmartProtocol;
+
+    use ark_ec::ProjectiveCurve;
+    use ark_ff::{UniformRand, Zero};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_std::rand::Rng;
+    use proof_essentials::homomorphic_encryption::el_gamal;
+    use proof_essentials::utils::permutation::Permutation;
+    use proof_essentials::utils::rand::sample_vector;
+    use proptest::prelude::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    // Choose elliptic curve setting
+    type Curve = starknet_curve::Projective;
+    type Affine = starknet_curve::Affine;
+    type Scalar = starknet_curve::Fr;
+
+    // Instantiate concrete type for our card protocol
+    type CardProtocol = discrete_log_cards::DLCards<Curve>;
+    type CardParameters = discrete_log_cards::Parameters<Curve>;
+    type PublicKey = discrete_log_cards::PublicKey<Curve>;
+
+    type Card = discrete_log_cards::Card<Curve>;
+    type MaskedCard = discrete_log_cards::MaskedCard<Curve>;
+    type RevealToken = discrete_log_cards::RevealToken<Curve>;
+    type OpenedCard = discrete_log_cards::OpenedCard<Curve>;
+    type ShuffleChain = discrete_log_cards::ShuffleChain<Curve>;
+
+    type DLEqualityProof = discrete_log_cards::DLEqualityProof<Curve>;
+    type SchnorrProof = discrete_log_cards::SchnorrProof<Curve>;
+    type ShuffleProof = discrete_log_cards::ShuffleProof<Curve>;
+    type BayerGrothProof = discrete_log_cards::BayerGrothProof<Curve>;
+    type LogShuffleProof = discrete_log_cards::LogShuffleProof<Curve>;
+    type LogProductProof = discrete_log_cards::LogProductProof<Curve>;
+    type LogMultiExpProof = discrete_log_cards::LogMultiExpProof<Curve>;
+    type InnerProductProof = discrete_log_cards::InnerProductProof<Curve>;
+    type ProductProof = discrete_log_cards::ProductProof<Curve>;
+    type HadamardProof = discrete_log_cards::HadamardProof<Curve>;
+    type ZeroArgumentProof = discrete_log_cards::ZeroArgumentProof<Curve>;
+    type SingleValueProductProof = discrete_log_cards::SingleValueProductProof<Curve>;
+    type MultiExpProof = discrete_log_cards::MultiExpProof<Curve>;
+    type OneOfManyProof = discrete_log_cards::OneOfManyProof<Curve>;
+    type PredicateProof = discrete_log_cards::predicates::PredicateProof<Curve>;
+
+    type RekeyVerifier = fn(
+        &CardParameters,
+        &PublicKey,
+        &Vec<MaskedCard>,
+        &Vec<MaskedCard>,
+        &Vec<DLEqualityProof>,
+    ) -> Result<(), CardProtocolError>;
+
+    const M: usize = 2;
+    const N: usize = 2;
+
+    /// Proofs derived from an honest one by replacing a single field, or a single element of a
+    /// vector field, with a random element or with the identity. Vector fields are also truncated
+    /// and extended.
+    trait Mutations: Sized {
+        fn mutations<R: Rng>(&self, rng: &mut R) -> Vec<Self>;
+    }
+
+    fn points<R: Rng>(rng: &mut R) -> Vec<Affine> {
+        vec![Curve::rand(rng).into_affine(), Affine::zero()]
+    }
+
+    fn scalars<R: Rng>(rng: &mut R) -> Vec<Scalar> {
+        vec![Scalar::rand(rng), Scalar::zero()]
+    }
+
+    fn identity() -> MaskedCard {
+        el_gamal::Ciphertext(Affine::zero(), Affine::zero())
+    }
+
+    /// Every vector obtained by replacing one element of `values`, dropping the last one or
+    /// appending one
+    fn vectors<T: Clone>(values: &Vec<T>, replacements: &Vec<T>) -> Vec<Vec<T>> {
+        let mut mutated = Vec::new();
+        for i in 0..values.len() {
+            for replacement in replacements {
+                let mut tampered = values.clone();
+                tampered[i] = replacement.clone();
+                mutated.push(tampered);
+            }
+        }
+
+        if !values.is_empty() {
+            mutated.push(values[..values.len() - 1].to_vec());
+        }
+        let mut extended = values.clone();
+        extended.push(replacements[0].clone());
+        mutated.push(extended);
+
+        mutated
+    }
+
+    impl Mutations for DLEqualityProof {
+        fn mutations<R: Rng>(&self, rng: &mut R) -> Vec<Self> {
+            let mut mutated = Vec::new();
+            for point in points(rng) {
+                mutated.push(Self { a: point, ..*self });
+                mutated.push(Self { b: point, ..*self });
+            }
+            for scalar in scalars(rng) {
+                mutated.push(Self { r: scalar, ..*self });
+            }
+
+            mutated
+        }
+    }
+
+    impl Mutations for SchnorrProof {
+        fn mutations<R: Rng>(&self, rng: &mut R) -> Vec<Self> {
+            let mut mutated = Vec::new();
+            for point in points(rng) {
+                mutated.push(Self {
+                    random_commit: point,
+                    ..*self
+                });
+            }
+            for scalar in scalars(rng) {
+                mutated.push(Self {
+                    opening: scalar,
+                    ..*self
+                });
+            }
+
+            mutated
+        }
+    }
+
+    impl Mutations for ShuffleProof {
+        fn mutations<R: Rng>(&self, rng: &mut R) -> Vec<Self> {
+            match self {
+                Self::BayerGroth(proof) => proof
+                    .mutations(rng)
+                    .into_iter()
+                    .map(Self::BayerGroth)
+                    .collect(),
+                Self::Logarithmic(proof) => proof
+                    .mutations(rng)
+                    .into_iter()
+                    .map(Self::Logarithmic)
+                    .collect(),
+            }
+        }
+    }
+
+    impl Mutations for BayerGrothProof {
+        fn mutations<R: Rng>(&self, rng: &mut R) -> Vec<Self> {
+            let points = points(rng);
+
+            let mut mutated = Vec::new();
+            for a_commitments in vectors(&self.a_commitments, &points) {
+                mutated.push(Self {
+                    a_commitments,
+                    ..self.clone()
+                });
+            }
+            for b_commitments in vectors(&self.b_commitments, &points) {
+                mutated.push(Self {
+                    b_commitments,
+                    ..self.clone()
+                });
+            }
+            for product in self.product.mutations(rng) {
+                mutated.push(Self {
+                    product,
+                    ..self.clone()
+                });
+            }
+            for multi_exp in self.multi_exp.mutations(rng) {
+                mutated.push(Self {
+                    multi_exp,
+                    ..self.clone()
+                });
+            }
+
+            mutated
+        }
+    }
+
+    impl Mutations for ProductProof {
+        fn mutations<R: Rng>(&self, rng: &mut R) -> Vec<Self> {
+            let mut mutated = vec![Self {
+                hadamard: None,
+                ..self.clone()
+            }];
+            if let Some(hadamard) = &self.hadamard {
+                for hadamard in hadamard.mutations(rng) {
+                    mutated.push(Self {
+                        hadamard: Some(hadamard),
+                        ..self.clone()
+                    });
+                }
+            }
+            for single_value in self.single_value.mutations(rng) {
+                mutated.push(Self {
+                    single_value,
+                    ..self.clone()
+                });
+            }
+
+            mutated
+        }
+    }
+
+    impl Mutations for HadamardProof {
+        fn mutations<R: Rng>(&self, rng: &mut R) -> Vec<Self> {
+            let points = points(rng);
+
+            let mut mutated = Vec::new();
+            for &point in points.iter() {
+                mutated.push(Self {
+                    product_commitment: point,
+                    ..self.clone()
+                });
+            }
+            for partial_commitments in vectors(&self.partial_commitments, &points) {
+                mutated.push(Self {
+                    partial_commitments,
+                    ..self.clone()
+                });
+            }
+            for zero_argument in self.zero_argument.mutations(rng) {
+                mutated.push(Self {
+                    zero_argument,
+                    ..self.clone()
+                });
+            }
+
+            mutated
+        }
+    }
+
+    impl Mutations for ZeroArgumentProof {
+        fn mutations<R: Rng>(&self, rng: &mut R) -> Vec<Self> {
+            let points = points(rng);
+            let scalars = scalars(rng);
+
+            let mut mutated = Vec::new();
+            for diagonal_commitments in vectors(&self.diagonal_commitments, &points) {
+                mutated.push(Self {
+                    diagonal_commitments,
+                    ..self.clone()
+                });
+            }
+            for a in vectors(&self.a, &scalars) {
+                mutated.push(Self { a, ..self.clone() });
+            }
+            for b in vectors(&self.b, &scalars) {
+                mutated.push(Self { b, ..self.clone() });
+            }
+
+            for &point in points.iter() {
+                mutated.push(Self {
+                    a_0_commitment: point,
+                    ..self.clone()
+                });
+                mutated.push(Self {
+                    b_m_commitment: point,
+                    ..self.clone()
+                });
+            }
+            for &scalar in scalars.iter() {
+                mutated.push(Self {
+                    r: scalar,
+                    ..self.clone()
+                });
+                mutated.push(Self {
+                    s: scalar,
+                    ..self.clone()
+                });
+                mutated.push(Self {
+                    t: scalar,
+                    ..self.clone()
+                });
+            }
+
+            mutated
+        }
+    }
+
+    impl Mutations for SingleValueProductProof {
+        fn mutations<R: Rng>(&self, rng: &mut R) -> Vec<Self> {
+            let points = points(rng);
+            let scalars = scalars(rng);
+
+            let mut mutated = Vec::new();
+            for a_blinded in vectors(&self.a_blinded, &scalars) {
+                mutated.push(Self {
+                    a_blinded,
+                    ..self.clone()
+                });
+            }
+            for b_blinded in vectors(&self.b_blinded, &scalars) {
+                mutated.push(Self {
+                    b_blinded,
+                    ..self.clone()
+                });
+            }
+
+            for &point in points.iter() {
+                mutated.push(Self {
+                    d_commitment: point,
+                    ..self.clone()
+                });
+                mutated.push(Self {
+                    delta_commitment: point,
+                    ..self.clone()
+                });
+                mutated.push(Self {
+                    difference_commitment: point,
+                    ..self.clone()
+                });
+            }
+            for &scalar in scalars.iter() {
+                mutated.push(Self {
+                    r_blinded: scalar,
+                    ..self.clone()
+                });
+                mutated.push(Self {
+                    s_blinded: scalar,
+                    ..self.clone()
+                });
+            }
+
+            mutated
+        }
+    }
+
+    impl Mutations for MultiExpProof {
+        fn mutations<R: Rng>(&self, rng: &mut R) -> Vec<Self> {
+            let points = points(rng);
+            let scalars = scalars(rng);
+            let ciphertexts = vec![MaskedCard::rand(rng), identity()];
+
+            let mut mutated = Vec::new();
+            for b_commitments in vectors(&self.b_commitments, &points) {
+                mutated.push(Self {
+                    b_commitments,
+                    ..self.clone()
+                });
+            }
+            for diagonals in vectors(&self.diagonals, &ciphertexts) {
+                mutated.push(Self {
+                    diagonals,
+                    ..self.clone()
+                });
+            }
+            for a in vectors(&self.a, &scalars) {
+                mutated.push(Self { a, ..self.clone() });
+            }
+
+            for &point in points.iter() {
+                mutated.push(Self {
+                    a_0_commitment: point,
+                    ..self.clone()
+                });
+            }
+            for &scalar in scalars.iter() {
+                mutated.push(Self {
+                    r: scalar,
+                    ..self.clone()
+                });
+                mutated.push(Self {
+                    b: scalar,
+                    ..self.clone()
+                });
+                mutated.push(Self {
+                    s: scalar,
+                    ..self.clone()
+                });
+                mutated.push(Self {
+                    tau: scalar,
+                    ..self.clone()
+                });
+            }
+
+            mutated
+        }
+    }
+
+    impl Mutations for LogShuffleProof {
+        fn mutations<R: Rng>(&self, rng: &mut R) -> Vec<Self> {
+            let mut mutated = Vec::new();
+            for point in points(rng) {
+                mutated.push(Self {
+                    a_commitment: point,
+                    ..self.clone()
+                });
+                mutated.push(Self {
+                    b_commitment: point,
+                    ..self.clone()
+                });
+            }
+            for product in self.product.mutations(rng) {
+                mutated.push(Self {
+                    product,
+                    ..self.clone()
+                });
+            }
+            for multi_exp in self.multi_exp.mutations(rng) {
+                mutated.push(Self {
+                    multi_exp,
+                    ..self.clone()
+                });
+            }
+
+            mutated
+        }
+    }
+
+    impl Mutations for LogProductProof {
+        fn mutations<R: Rng>(&self, rng: &mut R) -> Vec<Self> {
+            let points = points(rng);
+            let scalars = scalars(rng);
+
+            let mut mutated = Vec::new();
+            for t_commitments in vectors(&self.t_commitments, &points) {
+                mutated.push(Self {
+                    t_commitments,
+                    ..self.clone()
+                });
+            }
+            for inner_product in self.inner_product.mutations(rng) {
+                mutated.push(Self {
+                    inner_product,
+                    ..self.clone()
+                });
+            }
+
+            for &point in points.iter() {
+                mutated.push(Self {
+                    partials_commitment: point,
+                    ..self.clone()
+                });
+                mutated.push(Self {
+                    mask_commitment: point,
+                    ..self.clone()
+                });
+            }
+            for &scalar in scalars.iter() {
+                mutated.push(Self {
+                    t: scalar,
+                    ..self.clone()
+                });
+                mutated.push(Self {
+                    tau: scalar,
+                    ..self.clone()
+                });
+                mutated.push(Self {
+                    mu: scalar,
+                    ..self.clone()
+                });
+            }
+
+            mutated
+        }
+    }
+
+    impl Mutations for InnerProductProof {
+        fn mutations<R: Rng>(&self, rng: &mut R) -> Vec<Self> {
+            let points = points(rng);
+            let scalars = scalars(rng);
+
+            let mut mutated = Vec::new();
+            for lefts in vectors(&self.lefts, &points) {
+                mutated.push(Self {
+                    lefts,
+                    ..self.clone()
+                });
+            }
+            for rights in vectors(&self.rights, &points) {
+                mutated.push(Self {
+                    rights,
+                    ..self.clone()
+                });
+            }
+            for &scalar in scalars.iter() {
+                mutated.push(Self {
+                    a: scalar,
+                    ..self.clone()
+                });
+                mutated.push(Self {
+                    b: scalar,
+                    ..self.clone()
+                });
+            }
+
+            mutated
+        }
+    }
+
+    impl Mutations for LogMultiExpProof {
+        fn mutations<R: Rng>(&self, rng: &mut R) -> Vec<Self> {
+            let points = points(rng);
+            let scalars = scalars(rng);
+            let ciphertexts = vec![MaskedCard::rand(rng), identity()];
+
+            let mut mutated = Vec::new();
+            for left_commitments in vectors(&self.left_commitments, &points) {
+                mutated.push(Self {
+                    left_commitments,
+                    ..self.clone()
+                });
+            }
+            for right_commitments in vectors(&self.right_commitments, &points) {
+                mutated.push(Self {
+                    right_commitments,
+                    ..self.clone()
+                });
+            }
+            for left_ciphertexts in vectors(&self.left_ciphertexts, &ciphertexts) {
+                mutated.push(Self {
+                    left_ciphertexts,
+                    ..self.clone()
+                });
+            }
+            for right_ciphertexts in vectors(&self.right_ciphertexts, &ciphertexts) {
+                mutated.push(Self {
+                    right_ciphertexts,
+                    ..self.clone()
+                });
+            }
+
+            for &point in points.iter() {
+                mutated.push(Self {
+                    mask_commitment: point,
+                    ..self.clone()
+                });
+            }
+            for &ciphertext in ciphertexts.iter() {
+                mutated.push(Self {
+                    mask_ciphertext: ciphertext,
+                    ..self.clone()
+                });
+            }
+            for &scalar in scalars.iter() {
+                mutated.push(Self {
+                    z: scalar,
+                    ..self.clone()
+                });
+            }
+
+            mutated
+        }
+    }
+
+    impl Mutations for OneOfManyProof {
+        fn mutations<R: Rng>(&self, rng: &mut R) -> Vec<Self> {
+            let scalars = scalars(rng);
+
+            let mut mutated = Vec::new();
+            for challenges in vectors(&self.challenges, &scalars) {
+                mutated.push(Self {
+                    challenges,
+                    responses: self.responses.clone(),
+                });
+            }
+            for responses in vectors(&self.responses, &scalars) {
+                mutated.push(Self {
+                    challenges: self.challenges.clone(),
+                    responses,
+                });
+            }
+
+            mutated
+        }
+    }
+
+    impl Mutations for PredicateProof {
+        fn mutations<R: Rng>(&self, rng: &mut R) -> Vec<Self> {
+            let scalars = scalars(rng);
+
+            let mut mutated = Vec::new();
+            for challenges in vectors(&self.challenges, &scalars) {
+                mutated.push(Self {
+                    challenges,
+                    responses: self.responses.clone(),
+                });
+            }
+            for responses in vectors(&self.responses, &scalars) {
+                mutated.push(Self {
+                    challenges: self.challenges.clone(),
+                    responses,
+                });
+            }
+
+            mutated
+        }
+    }
+
+    fn to_bytes<T: CanonicalSerialize>(value: &T) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        value.serialize(&mut bytes).unwrap();
+        bytes
+    }
+
+    fn setup(seed: u64) -> (StdRng, CardParameters) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let parameters = CardProtocol::setup(&mut rng, M, N).unwrap();
+        (rng, parameters)
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(8))]
+
+        #[test]
+        fn key_ownership_rejects_forgeries(seed in any::<u64>()) {
+            let (mut rng, parameters) = setup(seed);
+            let rng = &mut rng;
+
+            let (pk, sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            let (other_pk, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            let name = b"Alice".to_vec();
+            let proof =
+                CardProtocol::prove_key_ownership(rng, &parameters, &pk, &sk, &name).unwrap();
+            prop_assert!(
+                CardProtocol::verify_key_ownership(&parameters, &pk, &name, &proof).is_ok()
+            );
+
+            for tampered in proof.mutations(rng) {
+                prop_assert!(
+                    CardProtocol::verify_key_ownership(&parameters, &pk, &name, &tampered).is_err()
+                );
+            }
+
+            // Replay under another identity, another key or the identity key
+            let other_name = b"Mallory".to_vec();
+            prop_assert!(
+                CardProtocol::verify_key_ownership(&parameters, &pk, &other_name, &proof).is_err()
+            );
+            prop_assert!(
+                CardProtocol::verify_key_ownership(&parameters, &other_pk, &name, &proof).is_err()
+            );
+            prop_assert!(
+                CardProtocol::verify_key_ownership(&parameters, &Affine::zero(), &name, &proof)
+                    .is_err()
+            );
+
+            // A proof is bound to the generator of the parameters
+            let other_parameters =
+                CardProtocol::setup_from_domain(&rng.gen::<[u8; 32]>(), M, N).unwrap();
+            prop_assert!(
+                CardProtocol::verify_key_ownership(&other_parameters, &pk, &name, &proof).is_err()
+            );
+        }
+
+        #[test]
+        fn masking_rejects_forgeries(seed in any::<u64>()) {
+            let (mut rng, parameters) = setup(seed);
+            let rng = &mut rng;
+
+            let (shared_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            let card = Card::rand(rng);
+            let r = Scalar::rand(rng);
+            let (masked, proof) =
+                CardProtocol::mask(rng, &parameters, &shared_key, &card, &r).unwrap();
+            prop_assert!(
+                CardProtocol::verify_mask(&parameters, &shared_key, &card, &masked, &proof).is_ok()
+            );
+
+            for tampered in proof.mutations(rng) {
+                prop_assert!(
+                    CardProtocol::verify_mask(&parameters, &shared_key, &card, &masked, &tampered)
+                        .is_err()
+                );
+            }
+
+            // Replay for another card, another masked card or another key
+            let other_card = Card::rand(rng);
+            let identity = el_gamal::Ciphertext(Affine::zero(), Affine::zero());
+            let (other_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            prop_assert!(
+                CardProtocol::verify_mask(&parameters, &shared_key, &other_card, &masked, &proof)
+                    .is_err()
+            );
+            prop_assert!(
+                CardProtocol::verify_mask(&parameters, &shared_key, &card, &identity, &proof)
+                    .is_err()
+            );
+            prop_assert!(
+                CardProtocol::verify_mask(&parameters, &other_key, &card, &masked, &proof).is_err()
+            );
+
+            let other_parameters =
+                CardProtocol::setup_from_domain(&rng.gen::<[u8; 32]>(), M, N).unwrap();
+            prop_assert!(
+                CardProtocol::verify_mask(&other_parameters, &shared_key, &card, &masked, &proof)
+                    .is_err()
+            );
+        }
+
+        #[test]
+        fn remasking_rejects_forgeries(seed in any::<u64>()) {
+            let (mut rng, parameters) = setup(seed);
+            let rng = &mut rng;
+
+            let (shared_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            let masked = MaskedCard::rand(rng);
+            let alpha = Scalar::rand(rng);
+            let (remasked, proof) =
+                CardProtocol::remask(rng, &parameters, &shared_key, &masked, &alpha).unwrap();
+            prop_assert!(
+                CardProtocol::verify_remask(&parameters, &shared_key, &masked, &remasked, &proof)
+                    .is_ok()
+            );
+
+            for tampered in proof.mutations(rng) {
+                prop_assert!(
+                    CardProtocol::verify_remask(
+                        &parameters,
+                        &shared_key,
+                        &masked,
+                        &remasked,
+                        &tampered
+                    )
+                    .is_err()
+                );
+            }
+
+            // A proof of another remasking does not carry over
+            let other_masked = MaskedCard::rand(rng);
+            let other_alpha = Scalar::rand(rng);
+            let (other_remasked, other_proof) =
+                CardProtocol::remask(rng, &parameters, &shared_key, &other_masked, &other_alpha)
+                    .unwrap();
+            prop_assert!(
+                CardProtocol::verify_remask(
+                    &parameters,
+                    &shared_key,
+                    &masked,
+                    &remasked,
+                    &other_proof
+                )
+                .is_err()
+            );
+            prop_assert!(
+                CardProtocol::verify_remask(
+                    &parameters,
+                    &shared_key,
+                    &masked,
+                    &other_remasked,
+                    &proof
+                )
+                .is_err()
+            );
+
+            // Leaving the card untouched is not a remasking
+            prop_assert!(
+                CardProtocol::verify_remask(&parameters, &shared_key, &masked, &masked, &proof)
+                    .is_err()
+            );
+        }
+
+        #[test]
+        fn reveal_rejects_forgeries(seed in any::<u64>()) {
+            let (mut rng, parameters) = setup(seed);
+            let rng = &mut rng;
+
+            let (pk, sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            let (other_pk, other_sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            let masked = MaskedCard::rand(rng);
+            let (token, proof) =
+                CardProtocol::compute_reveal_token(rng, &parameters, &sk, &pk, &masked).unwrap();
+            prop_assert!(
+                CardProtocol::verify_reveal(&parameters, &pk, &token, &masked, &proof).is_ok()
+            );
+
+            for tampered in proof.mutations(rng) {
+                prop_assert!(
+                    CardProtocol::verify_reveal(&parameters, &pk, &token, &masked, &tampered)
+                        .is_err()
+                );
+            }
+
+            // Replay the token or the proof of one player for another
+            let (other_token, other_proof) =
+                CardProtocol::compute_reveal_token(rng, &parameters, &other_sk, &other_pk, &masked)
+                    .unwrap();
+            prop_assert!(
+                CardProtocol::verify_reveal(&parameters, &other_pk, &token, &masked, &proof)
+                    .is_err()
+            );
+            prop_assert!(
+                CardProtocol::verify_reveal(&parameters, &pk, &other_token, &masked, &other_proof)
+                    .is_err()
+            );
+            prop_assert!(
+                CardProtocol::verify_reveal(&parameters, &pk, &token, &masked, &other_proof)
+                    .is_err()
+            );
+
+            // Replay for another card, or with identity tokens
+            let other_masked = MaskedCard::rand(rng);
+            prop_assert!(
+                CardProtocol::verify_reveal(&parameters, &pk, &token, &other_masked, &proof)
+                    .is_err()
+            );
+            prop_assert!(
+                CardProtocol::verify_reveal(&parameters, &pk, &RevealToken::zero(), &masked, &proof)
+                    .is_err()
+            );
+
+            let other_parameters =
+                CardProtocol::setup_from_domain(&rng.gen::<[u8; 32]>(), M, N).unwrap();
+            prop_assert!(
+                CardProtocol::verify_reveal(&other_parameters, &pk, &token, &masked, &proof)
+                    .is_err()
+            );
+        }
+
+        #[test]
+        fn shuffle_rejects_forgeries(seed in any::<u64>(), byte in any::<usize>(), bit in 0u8..8) {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let rng = &mut rng;
+
+            for argument in [ShuffleArgument::BayerGroth, ShuffleArgument::Logarithmic] {
+                let parameters = CardProtocol::setup_with_argument(rng, M, N, argument).unwrap();
+
+                let (shared_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+                let deck: Vec<MaskedCard> = sample_vector(rng, M * N);
+                let permutation = Permutation::new(rng, M * N);
+                let masking_factors: Vec<Scalar> = sample_vector(rng, M * N);
+                let (shuffled, proof) = CardProtocol::shuffle_and_remask(
+                    rng,
+                    &parameters,
+                    &shared_key,
+                    &deck,
+                    &masking_factors,
+                    &permutation,
+                )
+                .unwrap();
+                prop_assert!(
+                    CardProtocol::verify_shuffle(&parameters, &shared_key, &deck, &shuffled, &proof)
+                        .is_ok()
+                );
+
+                for tampered in proof.mutations(rng) {
+                    prop_assert!(
+                        CardProtocol::verify_shuffle(
+                            &parameters,
+                            &shared_key,
+                            &deck,
+                            &shuffled,
+                            &tampered
+                        )
+                        .is_err()
+                    );
+                }
+
+                // Reordering the output, or swapping in the identity, breaks the proof
+                let mut reordered = shuffled.clone();
+                reordered.swap(0, 1);
+                prop_assert!(
+                    CardProtocol::verify_shuffle(&parameters, &shared_key, &deck, &reordered, &proof)
+                        .is_err()
+                );
+                let mut with_identity = shuffled.clone();
+                with_identity[0] = identity();
+                prop_assert!(
+                    CardProtocol::verify_shuffle(
+                        &parameters,
+                        &shared_key,
+                        &deck,
+                        &with_identity,
+                        &proof
+                    )
+                    .is_err()
+                );
+
+                // The proof is bound to the input deck and to the key
+                let other_deck: Vec<MaskedCard> = sample_vector(rng, M * N);
+                let (other_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+                prop_assert!(
+                    CardProtocol::verify_shuffle(
+                        &parameters,
+                        &shared_key,
+                        &other_deck,
+                        &shuffled,
+                        &proof
+                    )
+                    .is_err()
+                );
+                prop_assert!(
+                    CardProtocol::verify_shuffle(&parameters, &other_key, &deck, &shuffled, &proof)
+                        .is_err()
+                );
+
+                // Flipping any bit of the proof either fails to decode or fails to verify
+                let mut bytes = to_bytes(&proof);
+                let index = byte % bytes.len();
+                bytes[index] ^= 1 << bit;
+                if let Ok(tampered) = CanonicalDeserialize::deserialize(&bytes[..]) {
+                    prop_assert!(
+                        CardProtocol::verify_shuffle(
+                            &parameters,
+                            &shared_key,
+                            &deck,
+                            &shuffled,
+                            &tampered
+                        )
+                        .is_err()
+                    );
+                }
+
+            }
+        }
+
+        #[test]
+        fn rekey_rejects_forgeries(seed in any::<u64>()) {
+            let (mut rng, parameters) = setup(seed);
+            let rng = &mut rng;
+
+            let (pk, sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            let (other_pk, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            let deck: Vec<MaskedCard> = sample_vector(rng, M * N);
+            let (added, add_proofs) =
+                CardProtocol::add_key_to_deck(rng, &parameters, &sk, &pk, &deck).unwrap();
+            let (removed, remove_proofs) =
+                CardProtocol::remove_key_from_deck(rng, &parameters, &sk, &pk, &deck).unwrap();
+
+            let cases = [
+                (
+                    CardProtocol::verify_add_key_to_deck as RekeyVerifier,
+                    &added,
+                    &add_proofs,
+                    &remove_proofs,
+                ),
+                (
+                    CardProtocol::verify_remove_key_from_deck as RekeyVerifier,
+                    &removed,
+                    &remove_proofs,
+                    &add_proofs,
+                ),
+            ];
+            for (verify, rekeyed, proofs, other_proofs) in cases {
+                prop_assert!(verify(&parameters, &pk, &deck, rekeyed, proofs).is_ok());
+
+                for i in 0..proofs.len() {
+                    for tampered in proofs[i].mutations(rng) {
+                        let mut tampered_proofs = proofs.clone();
+                        tampered_proofs[i] = tampered;
+                        prop_assert!(
+                            verify(&parameters, &pk, &deck, rekeyed, &tampered_proofs).is_err()
+                        );
+                    }
+                }
+
+                // Reordered or missing proofs, and reordered decks
+                let mut reordered_proofs = proofs.clone();
+                reordered_proofs.swap(0, 1);
+                let mut reordered_deck = rekeyed.clone();
+                reordered_deck.swap(0, 1);
+                prop_assert!(
+                    verify(&parameters, &pk, &deck, rekeyed, &reordered_proofs).is_err()
+                );
+                prop_assert!(
+                    verify(&parameters, &pk, &deck, rekeyed, &proofs[1..].to_vec()).is_err()
+                );
+                prop_assert!(
+                    verify(&parameters, &pk, &deck, &reordered_deck, proofs).is_err()
+                );
+
+                // Replay under another key, or as the opposite operation
+                prop_assert!(verify(&parameters, &other_pk, &deck, rekeyed, proofs).is_err());
+                prop_assert!(verify(&parameters, &pk, &deck, rekeyed, other_proofs).is_err());
+            }
+        }
+
+        #[test]
+        fn cut_and_swap_reject_forgeries(seed in any::<u64>()) {
+            let (mut rng, parameters) = setup(seed);
+            let rng = &mut rng;
+
+            let (shared_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            let (other_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            let deck: Vec<MaskedCard> = sample_vector(rng, M * N);
+            let masking_factors: Vec<Scalar> = sample_vector(rng, M * N);
+            let offset = rng.gen_range(0..M * N);
+            let (cut_deck, proof) = CardProtocol::cut_and_remask(
+                rng,
+                &parameters,
+                &shared_key,
+                &deck,
+                &masking_factors,
+                offset,
+            )
+            .unwrap();
+            prop_assert!(
+                CardProtocol::verify_cut(&parameters, &shared_key, &deck, &cut_deck, &proof).is_ok()
+            );
+
+            for tampered in proof.mutations(rng) {
+                prop_assert!(
+                    CardProtocol::verify_cut(&parameters, &shared_key, &deck, &cut_deck, &tampered)
+                        .is_err()
+                );
+            }
+
+            // A deck that is not a cyclic shift, and a proof replayed for another deck or key
+            let mut reordered = cut_deck.clone();
+            reordered.swap(0, 1);
+            let other_deck: Vec<MaskedCard> = sample_vector(rng, M * N);
+            prop_assert!(
+                CardProtocol::verify_cut(&parameters, &shared_key, &deck, &reordered, &proof)
+                    .is_err()
+            );
+            prop_assert!(
+                CardProtocol::verify_cut(&parameters, &shared_key, &other_deck, &cut_deck, &proof)
+                    .is_err()
+            );
+            prop_assert!(
+                CardProtocol::verify_cut(&parameters, &other_key, &deck, &cut_deck, &proof)
+                    .is_err()
+            );
+
+            let cards = (deck[0], deck[1]);
+            let factors = (Scalar::rand(rng), Scalar::rand(rng));
+            let swap = rng.gen::<bool>();
+            let (output, proof) =
+                CardProtocol::swap_and_remask(rng, &parameters, &shared_key, &cards, &factors, swap)
+                    .unwrap();
+            prop_assert!(
+                CardProtocol::verify_swap(&parameters, &shared_key, &cards, &output, &proof).is_ok()
+            );
+
+            for tampered in proof.mutations(rng) {
+                prop_assert!(
+                    CardProtocol::verify_swap(&parameters, &shared_key, &cards, &output, &tampered)
+                        .is_err()
+                );
+            }
+
+            // The proof does not carry over to the outputs in the other order, or to the identity
+            let exchanged = (output.1, output.0);
+            let with_identity = (output.0, identity());
+            prop_assert!(
+                CardProtocol::verify_swap(&parameters, &shared_key, &cards, &exchanged, &proof)
+                    .is_err()
+            );
+            prop_assert!(
+                CardProtocol::verify_swap(&parameters, &shared_key, &cards, &with_identity, &proof)
+                    .is_err()
+            );
+        }
+
+        #[test]
+        fn selection_rejects_forgeries(seed in any::<u64>()) {
+            let (mut rng, parameters) = setup(seed);
+            let rng = &mut rng;
+
+            let (shared_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            let (other_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            let cards: Vec<MaskedCard> = sample_vector(rng, M * N);
+            let index = rng.gen_range(0..cards.len());
+            let alpha = Scalar::rand(rng);
+            let (selected, proof) = CardProtocol::select_and_remask(
+                rng,
+                &parameters,
+                &shared_key,
+                &cards,
+                index,
+                &alpha,
+            )
+            .unwrap();
+            prop_assert!(
+                CardProtocol::verify_selection(&parameters, &shared_key, &cards, &selected, &proof)
+                    .is_ok()
+            );
+
+            for tampered in proof.mutations(rng) {
+                prop_assert!(
+                    CardProtocol::verify_selection(
+                        &parameters,
+                        &shared_key,
+                        &cards,
+                        &selected,
+                        &tampered
+                    )
+                    .is_err()
+                );
+            }
+
+            // A card that does not come from the candidates, or candidates without the selected
+            // card
+            let mut others = cards.clone();
+            others[index] = MaskedCard::rand(rng);
+            prop_assert!(
+                CardProtocol::verify_selection(
+                    &parameters,
+                    &shared_key,
+                    &cards,
+                    &MaskedCard::rand(rng),
+                    &proof
+                )
+                .is_err()
+            );
+            prop_assert!(
+                CardProtocol::verify_selection(&parameters, &shared_key, &others, &selected, &proof)
+                    .is_err()
+            );
+            prop_assert!(
+                CardProtocol::verify_selection(&parameters, &other_key, &cards, &selected, &proof)
+                    .is_err()
+            );
+        }
+
+        #[test]
+        fn predicate_rejects_forgeries(seed in any::<u64>()) {
+            let (mut rng, parameters) = setup(seed);
+            let rng = &mut rng;
+
+            let (pk, sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            let (other_pk, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            let encoding = encode_cards::<Curve, _>(rng);
+
+            // A pair of queens, masked under the prover's key only
+            let mut hand = Vec::new();
+            for suite in [Suite::Heart, Suite::Spade] {
+                let queen = ClassicPlayingCard::new(Value::Queen, suite);
+                let card = encoding.iter().find(|(_, c)| **c == queen).unwrap().0;
+                let r = Scalar::rand(rng);
+                let (masked, _) = CardProtocol::mask(rng, &parameters, &pk, card, &r).unwrap();
+                hand.push(masked);
+            }
+
+            let predicate = Predicate::SameValue;
+            let proof = CardProtocol::prove_predicate(
+                rng,
+                &parameters,
+                &sk,
+                &pk,
+                &encoding,
+                &predicate,
+                &hand,
+            )
+            .unwrap();
+            prop_assert!(
+                CardProtocol::verify_predicate(
+                    &parameters,
+                    &pk,
+                    &encoding,
+                    &predicate,
+                    &hand,
+                    &proof
+                )
+                .is_ok()
+            );
+
+            for tampered in proof.mutations(rng) {
+                prop_assert!(
+                    CardProtocol::verify_predicate(
+                        &parameters,
+                        &pk,
+                        &encoding,
+                        &predicate,
+                        &hand,
+                        &tampered
+                    )
+                    .is_err()
+                );
+            }
+
+            // Replay for another card, another key or another predicate
+            let mut other_hand = hand.clone();
+            other_hand[1] = MaskedCard::rand(rng);
+            prop_assert!(
+                CardProtocol::verify_predicate(
+                    &parameters,
+                    &pk,
+                    &encoding,
+                    &predicate,
+                    &other_hand,
+                    &proof
+                )
+                .is_err()
+            );
+            prop_assert!(
+                CardProtocol::verify_predicate(
+                    &parameters,
+                    &other_pk,
+                    &encoding,
+                    &predicate,
+                    &hand,
+                    &proof
+                )
+                .is_err()
+            );
+            prop_assert!(
+                CardProtocol::verify_predicate(
+                    &parameters,
+                    &pk,
+                    &encoding,
+                    &Predicate::SameSuite,
+                    &hand,
+                    &proof
+                )
+                .is_err()
+            );
+        }
+
+        #[test]
+        fn shuffle_chain_rejects_forgeries(seed in any::<u64>()) {
+            let (mut rng, parameters) = setup(seed);
+            let rng = &mut rng;
+
+            let (shared_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            let deck: Vec<MaskedCard> = sample_vector(rng, M * N);
+            let permutation = Permutation::new(rng, M * N);
+            let masking_factors: Vec<Scalar> = sample_vector(rng, M * N);
+            let (shuffled, proof) = CardProtocol::shuffle_and_remask(
+                rng,
+                &parameters,
+                &shared_key,
+                &deck,
+                &masking_factors,
+                &permutation,
+            )
+            .unwrap();
+
+            // Rejected links leave the chain untouched
+            let mut chain = ShuffleChain::new(&shared_key, &deck).unwrap();
+            let initial_commitment = chain.commitment().to_vec();
+            for tampered in proof.mutations(rng) {
+                prop_assert!(chain.push(&parameters, shuffled.clone(), &tampered).is_err());
+            }
+            let mut reordered = shuffled.clone();
+            reordered.swap(0, 1);
+            prop_assert!(chain.push(&parameters, reordered, &proof).is_err());
+            prop_assert_eq!(chain.commitment(), &initial_commitment[..]);
+            prop_assert_eq!(chain.deck(), &deck);
+            prop_assert!(chain.is_empty());
+
+            // A link cannot be replayed on top of itself
+            chain.push(&parameters, shuffled.clone(), &proof).unwrap();
+            prop_assert!(chain.push(&parameters, shuffled.clone(), &proof).is_err());
+            prop_assert_eq!(chain.len(), 1);
+
+            // Resuming checks the deck and length against the commitment
+            let commitment = chain.commitment().to_vec();
+            let mut tampered_commitment = commitment.clone();
+            *tampered_commitment.last_mut().unwrap() ^= 1;
+            prop_assert!(ShuffleChain::resume(&shared_key, &shuffled, 1, &commitment).is_ok());
+            prop_assert!(ShuffleChain::resume(&shared_key, &deck, 1, &commitment).is_err());
+            prop_assert!(ShuffleChain::resume(&shared_key, &shuffled, 0, &commitment).is_err());
+            prop_assert!(
+                ShuffleChain::resume(&shared_key, &shuffled, 1, &tampered_commitment).is_err()
+            );
+        }
+
+        #[test]
+        fn opened_card_rejects_forgeries(seed in any::<u64>()) {
+            let (mut rng, parameters) = setup(seed);
+            let rng = &mut rng;
+
+            let players = (0..3)
+                .map(|_| CardProtocol::player_keygen(rng, &parameters).unwrap())
+                .collect::<Vec<_>>();
+            let player_keys = players.iter().map(|(pk, _)| *pk).collect::<Vec<_>>();
+            let shared_key = player_keys.iter().fold(PublicKey::zero(), |acc, pk| acc + *pk);
+
+            let card = Card::rand(rng);
+            let r = Scalar::rand(rng);
+            let (masked, _) = CardProtocol::mask(rng, &parameters, &shared_key, &card, &r).unwrap();
+            let deck = vec![masked];
+            let indices = vec![0];
+            let mut seat_tokens = Vec::with_capacity(players.len());
+            for (pk, sk) in players.iter() {
+                let tokens = CardProtocol::compute_public_reveal_tokens(
+                    rng,
+                    &parameters,
+                    sk,
+                    pk,
+                    &deck,
+                    &indices,
+                )
+                .unwrap();
+                seat_tokens.push((*pk, tokens));
+            }
+            let opened =
+                CardProtocol::reveal_public(&parameters, &player_keys, &deck, &indices, &seat_tokens)
+                    .unwrap()
+                    .remove(0);
+            prop_assert!(opened.verify(&parameters, &player_keys).is_ok());
+
+            for i in 0..opened.reveal_tokens.len() {
+                for tampered in opened.reveal_tokens[i].1.mutations(rng) {
+                    let mut forged = opened.clone();
+                    forged.reveal_tokens[i].1 = tampered;
+                    prop_assert!(forged.verify(&parameters, &player_keys).is_err());
+                }
+
+                let mut forged = opened.clone();
+                forged.reveal_tokens[i].0 = RevealToken::zero();
+                prop_assert!(forged.verify(&parameters, &player_keys).is_err());
+            }
+
+            // Another card or masked card than the one opened
+            let other_card = OpenedCard {
+                card: Card::rand(rng),
+                ..opened.clone()
+            };
+            let other_masked_card = OpenedCard {
+                masked_card: MaskedCard::rand(rng),
+                ..opened.clone()
+            };
+            prop_assert!(other_card.verify(&parameters, &player_keys).is_err());
+            prop_assert!(other_masked_card.verify(&parameters, &player_keys).is_err());
+
+            // Missing, duplicated or foreign tokens
+            let mut missing = opened.clone();
+            missing.reveal_tokens.pop();
+            let mut duplicated = opened.clone();
+            duplicated.reveal_tokens[1] = duplicated.reveal_tokens[0];
+            let (outsider_pk, outsider_sk) =
+                CardProtocol::player_keygen(rng, &parameters).unwrap();
+            let (token, proof) = CardProtocol::compute_reveal_token(
+                rng,
+                &parameters,
+                &outsider_sk,
+                &outsider_pk,
+                &masked,
+            )
+            .unwrap();
+            let mut foreign = opened.clone();
+            foreign.reveal_tokens[0] = (token, proof, outsider_pk);
+            prop_assert!(missing.verify(&parameters, &player_keys).is_err());
+            prop_assert!(duplicated.verify(&parameters, &player_keys).is_err());
+            prop_assert!(foreign.verify(&parameters, &player_keys).is_err());
+        }
+    }
+}
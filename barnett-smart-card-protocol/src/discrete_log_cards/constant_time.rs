@@ -0,0 +1,269 @@
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{FpParameters, FromBytes, PrimeField, ToBytes};
+use std::hint::black_box;
+
+/// Multiply `base` by a secret `scalar` (a secret key, a masking factor or a proof nonce) with a
+/// Montgomery ladder. `AffineCurve::mul` skips the leading zeros of the scalar and only adds for its
+/// set bits, so its running time depends on the scalar. The ladder performs one addition and one
+/// doubling for every bit of a scalar whose length does not depend on its value, and exchanges its
+/// two points with a masked swap rather than by branching on the bits or indexing memory with them.
+///
+/// This removes the scalar-dependent control flow and memory accesses of the multiplication itself;
+/// the field arithmetic of arkworks underneath is not audited to be constant time.
+pub fn ladder_mul<C: ProjectiveCurve>(base: &C::Affine, scalar: &C::ScalarField) -> C {
+    let limbs = fixed_length_scalar(scalar);
+    let bits = <C::ScalarField as PrimeField>::Params::MODULUS_BITS as usize;
+
+    // The top bit of the scalar is always set: start from (base, 2 * base)
+    let mut r0 = base.into_projective();
+    let mut r1 = r0.double();
+    let mut swapped = 0;
+    for i in (0..bits).rev() {
+        let bit = (limbs[i / 64] >> (i % 64)) & 1;
+        conditional_swap(&mut r0, &mut r1, swapped ^ bit);
+        swapped = bit;
+
+        r1 = r0 + r1;
+        r0.double_in_place();
+    }
+    conditional_swap(&mut r0, &mut r1, swapped);
+
+    r0
+}
+
+/// Exchange `a` and `b` if `choice` is 1 and leave them untouched if it is 0. The encodings of
+/// both points are swapped under a mask, so neither the instructions nor the memory accessed
+/// depend on `choice`.
+pub fn conditional_swap<T: ToBytes + FromBytes>(a: &mut T, b: &mut T, choice: u64) {
+    let mask = black_box((choice as u8).wrapping_neg());
+
+    let mut a_bytes = encode(a);
+    let mut b_bytes = encode(b);
+    for (x, y) in a_bytes.iter_mut().zip(b_bytes.iter_mut()) {
+        let t = (*x ^ *y) & mask;
+        *x ^= t;
+        *y ^= t;
+    }
+
+    *a = decode(&a_bytes);
+    *b = decode(&b_bytes);
+}
+
+/// Decode `encodings[index]` for a secret `index`, where every entry is the `ToBytes` encoding of a
+/// value of the same type. Every entry is read and combined under a mask, so the memory accesses do
+/// not depend on `index`.
+pub fn select_encoded<T: FromBytes>(encodings: &[Vec<u8>], index: usize) -> T {
+    let mut selected = vec![0u8; encodings[0].len()];
+    for (i, encoding) in encodings.iter().enumerate() {
+        let mask = black_box(equal(i, index).wrapping_neg());
+        for (byte, entry) in selected.iter_mut().zip(encoding) {
+            *byte |= entry & mask;
+        }
+    }
+
+    decode(&selected)
+}
+
+/// `values[permutation[i]]` at every position `i`, for a secret `permutation`
+pub fn permute<T: ToBytes + FromBytes>(values: &[T], permutation: &[usize]) -> Vec<T> {
+    let encodings = values.iter().map(encode).collect::<Vec<_>>();
+
+    permutation
+        .iter()
+        .map(|&index| select_encoded(&encodings, index))
+        .collect()
+}
+
+/// 1 if `a == b`, 0 otherwise, computed without branching
+pub fn equal(a: usize, b: usize) -> u8 {
+    let difference = (a ^ b) as u64;
+    let non_zero = (difference | difference.wrapping_neg()) >> 63;
+
+    (non_zero as u8) ^ 1
+}
+
+pub fn encode<T: ToBytes>(value: &T) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    value
+        .write(&mut bytes)
+        .expect("writing to a vector does not fail");
+
+    bytes
+}
+
+fn decode<T: FromBytes>(bytes: &[u8]) -> T {
+    T::read(bytes).expect("bytes are the encoding of a value of the same type")
+}
+
+/// Either `scalar + q` or `scalar + 2q` for the group order `q`, whichever has its top bit at the
+/// bit length of `q`, as little-endian limbs. Both are congruent to `scalar`, and the selection is
+/// made with a mask so that it does not branch on the scalar either.
+fn fixed_length_scalar<F: PrimeField>(scalar: &F) -> Vec<u64> {
+    let modulus = <F as PrimeField>::Params::MODULUS;
+    let bits = <F as PrimeField>::Params::MODULUS_BITS as usize;
+
+    let mut once = scalar.into_repr().as_ref().to_vec();
+    once.push(0);
+    add_limbs(&mut once, modulus.as_ref());
+    let mut twice = once.clone();
+    add_limbs(&mut twice, modulus.as_ref());
+
+    let mask = ((once[bits / 64] >> (bits % 64)) & 1).wrapping_neg();
+    once.iter()
+        .zip(&twice)
+        .map(|(a, b)| (a & mask) | (b & !mask))
+        .collect()
+}
+
+fn add_limbs(a: &mut [u64], b: &[u64]) {
+    let mut carry = 0;
+    for (i, limb) in a.iter_mut().enumerate() {
+        let (sum, c1) = limb.overflowing_add(b.get(i).copied().unwrap_or(0));
+        let (sum, c2) = sum.overflowing_add(carry);
+        *limb = sum;
+        carry = (c1 | c2) as u64;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{conditional_swap, encode, ladder_mul, permute, select_encoded};
+
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ff::{One, PrimeField, UniformRand, Zero};
+    use rand::seq::SliceRandom;
+    use rand::{thread_rng, Rng};
+    use std::hint::black_box;
+    use std::time::Instant;
+
+    // Choose elliptic curve setting
+    type Curve = starknet_curve::Projective;
+    type Scalar = starknet_curve::Fr;
+
+    #[test]
+    fn test_ladder_mul() {
+        let rng = &mut thread_rng();
+        let base = Curve::rand(rng).into_affine();
+
+        let mut scalars = vec![Scalar::zero(), Scalar::one(), -Scalar::one()];
+        scalars.extend((0..20).map(|_| Scalar::rand(rng)));
+        for scalar in scalars {
+            assert_eq!(
+                ladder_mul::<Curve>(&base, &scalar),
+                base.mul(scalar.into_repr())
+            );
+        }
+    }
+
+    #[test]
+    fn test_conditional_swap_and_select() {
+        let rng = &mut thread_rng();
+        let points = (0..5).map(|_| Curve::rand(rng)).collect::<Vec<_>>();
+
+        let (mut a, mut b) = (points[0], points[1]);
+        conditional_swap(&mut a, &mut b, 0);
+        assert_eq!((a, b), (points[0], points[1]));
+        conditional_swap(&mut a, &mut b, 1);
+        assert_eq!((a, b), (points[1], points[0]));
+
+        let encodings = points.iter().map(encode).collect::<Vec<_>>();
+        for (i, point) in points.iter().enumerate() {
+            assert_eq!(select_encoded::<Curve>(&encodings, i), *point);
+        }
+    }
+
+    #[test]
+    fn test_permute() {
+        let rng = &mut thread_rng();
+        let scalars = (0..10).map(|_| Scalar::rand(rng)).collect::<Vec<_>>();
+        let mut permutation = (0..10).collect::<Vec<usize>>();
+        permutation.shuffle(rng);
+
+        let permuted = permute(&scalars, &permutation);
+        for i in 0..10 {
+            assert_eq!(permuted[i], scalars[permutation[i]]);
+        }
+    }
+
+    /// Welch's t statistic between the running times of `f` on two classes of scalars, measured in
+    /// random order and cropped of the slowest measurements, as in dudect (Reparaz, Balasch and
+    /// Verbauwhede, "Dude, is my code constant time?").
+    fn timing_t_statistic<F: Fn(&Scalar)>(f: F, fixed: &Scalar, samples: usize) -> f64 {
+        let rng = &mut thread_rng();
+        let mut timings: [Vec<f64>; 2] = [Vec::new(), Vec::new()];
+        for _ in 0..samples {
+            let class = rng.gen_range(0..2);
+            let scalar = if class == 0 {
+                *fixed
+            } else {
+                Scalar::rand(rng)
+            };
+
+            let start = Instant::now();
+            f(&scalar);
+            timings[class].push(start.elapsed().as_nanos() as f64);
+        }
+
+        let mut all = timings.concat();
+        all.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let threshold = all[all.len() * 9 / 10];
+
+        let moments = |class: &Vec<f64>| {
+            let kept = class
+                .iter()
+                .filter(|&&t| t <= threshold)
+                .collect::<Vec<_>>();
+            let n = kept.len() as f64;
+            let mean = kept.iter().copied().sum::<f64>() / n;
+            let variance = kept.iter().map(|&&t| (t - mean).powi(2)).sum::<f64>() / (n - 1.0);
+            (n, mean, variance)
+        };
+        let (n0, mean0, var0) = moments(&timings[0]);
+        let (n1, mean1, var1) = moments(&timings[1]);
+
+        (mean0 - mean1) / (var0 / n0 + var1 / n1).sqrt()
+    }
+
+    /// Check that the timing harness detects the leak of the variable-time multiplication, and
+    /// none in the ladder
+    fn check_ladder_timing(samples: usize, threshold: f64) {
+        let rng = &mut thread_rng();
+        let base = Curve::rand(rng).into_affine();
+        let fixed = Scalar::from(3u64);
+
+        let variable_time = timing_t_statistic(
+            |scalar| {
+                let _ = black_box(base.mul(scalar.into_repr()));
+            },
+            &fixed,
+            samples,
+        );
+        assert!(variable_time.abs() > threshold, "t = {}", variable_time);
+
+        let ladder = timing_t_statistic(
+            |scalar| {
+                let _ = black_box(ladder_mul::<Curve>(&base, scalar));
+            },
+            &fixed,
+            samples,
+        );
+        assert!(ladder.abs() < threshold, "t = {}", ladder);
+    }
+
+    // Timing measurements are only meaningful in release mode on an idle machine, both runs are
+    // ignored by default: cargo test --release -- --ignored test_ladder_timing
+
+    // A short run with a loose threshold, which only catches leaks as large as the one of the
+    // variable-time multiplication
+    #[test]
+    #[ignore]
+    fn test_ladder_timing_smoke() {
+        check_ladder_timing(1_000, 25.0);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_ladder_timing() {
+        check_ladder_timing(20_000, 10.0);
+    }
+}
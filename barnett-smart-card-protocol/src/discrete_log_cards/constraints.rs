@@ -0,0 +1,427 @@
+//! R1CS gadgets checking reveal (Chaum-Pedersen) and key ownership (Schnorr) proofs inside a SNARK.
+//! The card protocol must run over a curve `C` embedded in the SNARK's scalar field, e.g. Jubjub or
+//! Ed-on-BLS12-377, so that group operations are native to the circuit.
+//!
+//! The challenge of these proofs is a single digest of their seed and of the encodings of the
+//! statement and commitments. The gadgets check the `ZKProofReveal` and `ZKProofKeyOwnership` of
+//! `DLCards<C, PoseidonDigest<ConstraintF<C>>>`, recomputing that digest in-circuit with the
+//! Poseidon instance of `PoseidonDigest`. Proofs made with another hash do not verify.
+//!
+//! Like the native verifiers, the gadgets expect the public key, masked card and reveal token to be
+//! in the prime-order subgroup: the challenge is multiplied into points as an integer of 256 bits
+//! rather than reduced modulo the group order.
+
+use crate::discrete_log_cards::transcript::poseidon::{poseidon_parameters, CHUNK_SIZE};
+use crate::discrete_log_cards::{
+    DLEqualityProof, MaskedCard, Parameters, PublicKey, RevealToken, SchnorrProof,
+    KEY_OWN_RNG_SEED, REVEAL_RNG_SEED,
+};
+
+use ark_ec::ProjectiveCurve;
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSystemRef, Namespace, SynthesisError};
+use ark_sponge::constraints::CryptographicSpongeVar;
+use ark_sponge::poseidon::constraints::PoseidonSpongeVar;
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+
+pub type ConstraintF<C> = <<C as ProjectiveCurve>::BaseField as Field>::BasePrimeField;
+
+/// In-circuit `PoseidonDigest` of `bytes`, as the little-endian bits of the 32-byte digest
+fn digest_var<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    bytes: &[UInt8<F>],
+) -> Result<Vec<Boolean<F>>, SynthesisError> {
+    let mut elements = bytes
+        .chunks(CHUNK_SIZE)
+        .map(|chunk| Boolean::le_bits_to_fp_var(&chunk.to_bits_le()?))
+        .collect::<Result<Vec<_>, _>>()?;
+    elements.push(FpVar::Constant(F::from(bytes.len() as u64)));
+
+    let mut sponge = PoseidonSpongeVar::new(cs, &*poseidon_parameters::<F>());
+    sponge.absorb(&elements)?;
+    let squeezed = sponge.squeeze_field_elements(2)?;
+
+    let mut bits = Vec::with_capacity(256);
+    for element in &squeezed {
+        bits.extend_from_slice(&element.to_bits_le()?[..128]);
+    }
+
+    Ok(bits)
+}
+
+/// In-circuit counterpart of `proofs::challenge`: the digest of `seed` followed by the encodings of
+/// `points`. The digest is kept as an integer, which multiplies points of the prime-order subgroup
+/// like its reduction does.
+fn challenge_var<C, GC>(
+    cs: ConstraintSystemRef<ConstraintF<C>>,
+    seed: &[UInt8<ConstraintF<C>>],
+    points: &[&GC],
+) -> Result<ScalarVar<C>, SynthesisError>
+where
+    C: ProjectiveCurve,
+    GC: CurveVar<C, ConstraintF<C>>,
+{
+    let mut bytes = seed.to_vec();
+    for point in points {
+        bytes.extend(point.to_bytes()?);
+    }
+
+    Ok(ScalarVar {
+        bits: digest_var(cs, &bytes)?,
+        _curve: PhantomData,
+    })
+}
+
+/// A scalar of the embedded curve, as little-endian bits of the constraint field
+pub struct ScalarVar<C: ProjectiveCurve> {
+    bits: Vec<Boolean<ConstraintF<C>>>,
+    _curve: PhantomData<C>,
+}
+
+impl<C: ProjectiveCurve> AllocVar<C::ScalarField, ConstraintF<C>> for ScalarVar<C> {
+    fn new_variable<T: Borrow<C::ScalarField>>(
+        cs: impl Into<Namespace<ConstraintF<C>>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let bits = f().map(|scalar| scalar.borrow().into_repr().to_bits_le());
+        let bits = match bits {
+            Ok(bits) => bits.into_iter().map(Ok).collect::<Vec<_>>(),
+            Err(e) => (0..C::ScalarField::size_in_bits())
+                .map(|_| Err(e))
+                .collect(),
+        };
+
+        let cs = cs.into().cs();
+        let bits = bits
+            .into_iter()
+            .map(|bit| Boolean::new_variable(cs.clone(), || bit, mode))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            bits,
+            _curve: PhantomData,
+        })
+    }
+}
+
+/// Check a Chaum-Pedersen proof for the statement `(g, h, x, y)`, i.e. that the prover knows `w`
+/// such that `x = g * w` and `y = h * w`. The result is returned rather than enforced.
+pub fn verify_dl_equality<C, GC>(
+    statement: (&GC, &GC, &GC, &GC),
+    a: &GC,
+    b: &GC,
+    r: &ScalarVar<C>,
+    challenge: &ScalarVar<C>,
+) -> Result<Boolean<ConstraintF<C>>, SynthesisError>
+where
+    C: ProjectiveCurve,
+    GC: CurveVar<C, ConstraintF<C>>,
+    for<'a> &'a GC: GroupOpsBounds<'a, C, GC>,
+{
+    let (g, h, x, y) = statement;
+
+    let first = g
+        .scalar_mul_le(r.bits.iter())?
+        .is_eq(&(a + &x.scalar_mul_le(challenge.bits.iter())?))?;
+    let second = h
+        .scalar_mul_le(r.bits.iter())?
+        .is_eq(&(b + &y.scalar_mul_le(challenge.bits.iter())?))?;
+
+    first.and(&second)
+}
+
+/// Check a Schnorr proof, i.e. that the prover knows the secret key of `pk`. The result is
+/// returned rather than enforced.
+pub fn verify_schnorr<C, GC>(
+    g: &GC,
+    pk: &GC,
+    random_commit: &GC,
+    opening: &ScalarVar<C>,
+    challenge: &ScalarVar<C>,
+) -> Result<Boolean<ConstraintF<C>>, SynthesisError>
+where
+    C: ProjectiveCurve,
+    GC: CurveVar<C, ConstraintF<C>>,
+    for<'a> &'a GC: GroupOpsBounds<'a, C, GC>,
+{
+    g.scalar_mul_le(opening.bits.iter())?
+        .is_eq(&(random_commit + &pk.scalar_mul_le(challenge.bits.iter())?))
+}
+
+/// Gadget verifying the `ZKProofReveal` of `DLCards<C, PoseidonDigest<ConstraintF<C>>>`. The masked
+/// card, reveal token, public key and commitments of the proof are public inputs, the response is
+/// a witness, and the challenge is recomputed from the public inputs.
+pub struct RevealVerifierGadget<C: ProjectiveCurve, GC: CurveVar<C, ConstraintF<C>>> {
+    _curve: PhantomData<(C, GC)>,
+}
+
+impl<C, GC> RevealVerifierGadget<C, GC>
+where
+    C: ProjectiveCurve,
+    GC: CurveVar<C, ConstraintF<C>>,
+    for<'a> &'a GC: GroupOpsBounds<'a, C, GC>,
+{
+    pub fn verify(
+        cs: ConstraintSystemRef<ConstraintF<C>>,
+        pp: &Parameters<C>,
+        pk: &PublicKey<C>,
+        reveal_token: &RevealToken<C>,
+        masked_card: &MaskedCard<C>,
+        proof: &DLEqualityProof<C>,
+    ) -> Result<Boolean<ConstraintF<C>>, SynthesisError> {
+        let generator = GC::new_constant(cs.clone(), pp.encryption_generator().into_projective())?;
+        let randomness = GC::new_input(cs.clone(), || Ok(masked_card.0.into_projective()))?;
+        let token = GC::new_input(cs.clone(), || Ok(reveal_token.0.into_projective()))?;
+        let pk = GC::new_input(cs.clone(), || Ok(pk.into_projective()))?;
+        let a = GC::new_input(cs.clone(), || Ok(proof.a.into_projective()))?;
+        let b = GC::new_input(cs.clone(), || Ok(proof.b.into_projective()))?;
+
+        let r = ScalarVar::new_witness(cs.clone(), || Ok(proof.r))?;
+
+        // Same mapping to Chaum-Pedersen parameters as `verify_reveal`
+        let challenge = challenge_var::<C, GC>(
+            cs,
+            &UInt8::constant_vec(REVEAL_RNG_SEED),
+            &[&randomness, &generator, &token, &pk, &a, &b],
+        )?;
+
+        verify_dl_equality(
+            (&randomness, &generator, &token, &pk),
+            &a,
+            &b,
+            &r,
+            &challenge,
+        )
+    }
+}
+
+/// Gadget verifying the `ZKProofKeyOwnership` of `DLCards<C, PoseidonDigest<ConstraintF<C>>>`.
+/// `player_public_info` is the encoding of the information the proof is bound to, i.e.
+/// `to_bytes![player_public_info]` of `prove_key_ownership`. The public key, that encoding and the
+/// commitment of the proof are public inputs, the opening is a witness, and the challenge is
+/// recomputed from the public inputs.
+pub struct KeyOwnershipVerifierGadget<C: ProjectiveCurve, GC: CurveVar<C, ConstraintF<C>>> {
+    _curve: PhantomData<(C, GC)>,
+}
+
+impl<C, GC> KeyOwnershipVerifierGadget<C, GC>
+where
+    C: ProjectiveCurve,
+    GC: CurveVar<C, ConstraintF<C>>,
+    for<'a> &'a GC: GroupOpsBounds<'a, C, GC>,
+{
+    pub fn verify(
+        cs: ConstraintSystemRef<ConstraintF<C>>,
+        pp: &Parameters<C>,
+        pk: &PublicKey<C>,
+        player_public_info: &[u8],
+        proof: &SchnorrProof<C>,
+    ) -> Result<Boolean<ConstraintF<C>>, SynthesisError> {
+        let generator = GC::new_constant(cs.clone(), pp.encryption_generator().into_projective())?;
+        let pk = GC::new_input(cs.clone(), || Ok(pk.into_projective()))?;
+        let info = UInt8::new_input_vec(cs.clone(), player_public_info)?;
+        let random_commit =
+            GC::new_input(cs.clone(), || Ok(proof.random_commit.into_projective()))?;
+
+        let opening = ScalarVar::new_witness(cs.clone(), || Ok(proof.opening))?;
+
+        let mut seed = UInt8::constant_vec(KEY_OWN_RNG_SEED);
+        seed.extend(info);
+        let challenge = challenge_var::<C, GC>(cs, &seed, &[&generator, &pk, &random_commit])?;
+
+        verify_schnorr(&generator, &pk, &random_commit, &opening, &challenge)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::discrete_log_cards::constraints::{
+        KeyOwnershipVerifierGadget, RevealVerifierGadget,
+    };
+    use crate::discrete_log_cards::transcript::PoseidonDigest;
+    use crate::discrete_log_cards::{self, DLCards, DLEqualityProof, SchnorrProof};
+    use crate::BarnettSmartProtocol;
+
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ed_on_bls12_381::{constraints::EdwardsVar, EdwardsProjective, Fq, Fr};
+    use ark_ff::UniformRand;
+    use ark_r1cs_std::R1CSVar;
+    use ark_relations::r1cs::ConstraintSystem;
+    use proof_essentials::homomorphic_encryption::el_gamal;
+    use rand::thread_rng;
+
+    // Jubjub is embedded in the scalar field of BLS12-381, over which the transcript is hashed
+    type Curve = EdwardsProjective;
+    type CardProtocol = DLCards<Curve, PoseidonDigest<Fq>>;
+    type CardParameters = discrete_log_cards::Parameters<Curve>;
+    type PublicKey = discrete_log_cards::PublicKey<Curve>;
+    type Card = discrete_log_cards::Card<Curve>;
+    type MaskedCard = discrete_log_cards::MaskedCard<Curve>;
+    type RevealToken = discrete_log_cards::RevealToken<Curve>;
+
+    // Check a reveal proof natively and in-circuit, and return the verdict both agree on
+    fn check_reveal(
+        parameters: &CardParameters,
+        pk: &PublicKey,
+        token: &RevealToken,
+        masked_card: &MaskedCard,
+        proof: &DLEqualityProof<Curve>,
+    ) -> bool {
+        let native = CardProtocol::verify_reveal(parameters, pk, token, masked_card, proof).is_ok();
+
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        let result = RevealVerifierGadget::<Curve, EdwardsVar>::verify(
+            cs.clone(),
+            parameters,
+            pk,
+            token,
+            masked_card,
+            proof,
+        )
+        .unwrap();
+
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(native, result.value().unwrap());
+        native
+    }
+
+    // Check a key ownership proof natively and in-circuit, and return the verdict both agree on
+    fn check_key_ownership(
+        parameters: &CardParameters,
+        pk: &PublicKey,
+        name: &[u8],
+        proof: &SchnorrProof<Curve>,
+    ) -> bool {
+        let native = CardProtocol::verify_key_ownership(parameters, pk, &name, proof).is_ok();
+
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        let result = KeyOwnershipVerifierGadget::<Curve, EdwardsVar>::verify(
+            cs.clone(),
+            parameters,
+            pk,
+            name,
+            proof,
+        )
+        .unwrap();
+
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(native, result.value().unwrap());
+        native
+    }
+
+    #[test]
+    fn test_reveal_gadget() {
+        let rng = &mut thread_rng();
+        let parameters = CardProtocol::setup(rng, 2, 5).unwrap();
+        let (pk, sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+        let generator = *parameters.encryption_generator();
+
+        let card = Card::rand(rng);
+        let r = Fr::rand(rng);
+        let (masked_card, _) = CardProtocol::mask(rng, &parameters, &pk, &card, &r).unwrap();
+
+        let (token, proof) =
+            CardProtocol::compute_reveal_token(rng, &parameters, &sk, &pk, &masked_card).unwrap();
+        assert!(check_reveal(&parameters, &pk, &token, &masked_card, &proof));
+
+        // Wrong response
+        let mut forged = proof;
+        forged.r += Fr::from(1u64);
+        assert!(!check_reveal(
+            &parameters,
+            &pk,
+            &token,
+            &masked_card,
+            &forged
+        ));
+
+        // Commitment that does not match the challenge
+        let forged = DLEqualityProof {
+            a: proof.a + generator,
+            ..proof
+        };
+        assert!(!check_reveal(
+            &parameters,
+            &pk,
+            &token,
+            &masked_card,
+            &forged
+        ));
+
+        // Wrong token
+        let wrong_token = el_gamal::Plaintext(token.0 + generator);
+        assert!(!check_reveal(
+            &parameters,
+            &pk,
+            &wrong_token,
+            &masked_card,
+            &proof
+        ));
+
+        // A proof with the challenge of another hash
+        let (token, proof) =
+            DLCards::<Curve>::compute_reveal_token(rng, &parameters, &sk, &pk, &masked_card)
+                .unwrap();
+        assert!(!check_reveal(
+            &parameters,
+            &pk,
+            &token,
+            &masked_card,
+            &proof
+        ));
+
+        // A proof simulated for a challenge of the prover's choosing, without the secret key
+        let challenge = Fr::rand(rng);
+        let response = Fr::rand(rng);
+        let simulated = DLEqualityProof::<Curve> {
+            a: (masked_card.0.mul(response) - token.0.mul(challenge)).into_affine(),
+            b: (generator.mul(response) - pk.mul(challenge)).into_affine(),
+            r: response,
+        };
+        assert!(!check_reveal(
+            &parameters,
+            &pk,
+            &token,
+            &masked_card,
+            &simulated
+        ));
+    }
+
+    #[test]
+    fn test_key_ownership_gadget() {
+        let rng = &mut thread_rng();
+        let parameters = CardProtocol::setup(rng, 2, 5).unwrap();
+        let (pk, sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+        let generator = *parameters.encryption_generator();
+        let name = b"Player".to_vec();
+
+        let proof = CardProtocol::prove_key_ownership(rng, &parameters, &pk, &sk, &name).unwrap();
+        assert!(check_key_ownership(&parameters, &pk, &name, &proof));
+
+        // Wrong opening
+        let mut forged = proof;
+        forged.opening += Fr::from(1u64);
+        assert!(!check_key_ownership(&parameters, &pk, &name, &forged));
+
+        // A proof is bound to the name
+        assert!(!check_key_ownership(&parameters, &pk, b"Other", &proof));
+
+        // A proof with the challenge of another hash
+        let proof =
+            DLCards::<Curve>::prove_key_ownership(rng, &parameters, &pk, &sk, &name).unwrap();
+        assert!(!check_key_ownership(&parameters, &pk, &name, &proof));
+
+        // A proof simulated for a challenge of the prover's choosing, without the secret key
+        let challenge = Fr::rand(rng);
+        let opening = Fr::rand(rng);
+        let simulated = SchnorrProof::<Curve> {
+            random_commit: (generator.mul(opening) - pk.mul(challenge)).into_affine(),
+            opening,
+        };
+        assert!(!check_key_ownership(&parameters, &pk, &name, &simulated));
+    }
+}
@@ -0,0 +1,356 @@
+use crate::discrete_log_cards::one_of_many::{self, OneOfManyProof};
+use crate::discrete_log_cards::{DLCards, MaskedCard, Parameters, PublicKey, CUT_RNG_SEED};
+use crate::error::CardProtocolError;
+use crate::BarnettSmartProtocol;
+
+use ark_ec::msm::VariableBaseMSM;
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{to_bytes, Field, One, PrimeField, UniformRand, Zero};
+use ark_marlin::rng::FiatShamirRng;
+use ark_std::rand::Rng;
+use digest::Digest;
+use proof_essentials::error::CryptoError;
+
+/// Proof that a deck is a cyclic shift of another one, remasked under the shared key. For each of
+/// the `n` possible offsets the differences between the two decks are folded with the powers of a
+/// public random challenge into a single ciphertext, which is an encryption of zero for the true
+/// offset only. The proof is an OR of `n` Chaum-Pedersen proofs, one per offset, so it hides the
+/// offset and its size is linear in the deck length.
+pub type CutProof<C> = OneOfManyProof<C>;
+
+impl<C: ProjectiveCurve, D: Digest> DLCards<C, D> {
+    /// Prove that `cut_deck[i]` is `deck[(i + offset) % n]` remasked with `masking_factors[i]`
+    pub(super) fn prove_cut<R: Rng>(
+        rng: &mut R,
+        pp: &Parameters<C>,
+        shared_key: &PublicKey<C>,
+        deck: &Vec<MaskedCard<C>>,
+        cut_deck: &Vec<MaskedCard<C>>,
+        masking_factors: &Vec<C::ScalarField>,
+        offset: usize,
+    ) -> Result<CutProof<C>, CryptoError> {
+        let (mut fs_rng, challenge) = Self::cut_transcript(pp, shared_key, deck, cut_deck)?;
+        let differences = fold_differences(deck, cut_deck, &challenge);
+
+        let witness: C::ScalarField = powers(&challenge, deck.len())
+            .iter()
+            .zip(masking_factors)
+            .map(|(weight, alpha)| *weight * alpha)
+            .sum();
+
+        one_of_many::prove(
+            rng,
+            pp,
+            shared_key,
+            &differences,
+            offset,
+            &witness,
+            &mut fs_rng,
+        )
+    }
+
+    pub(super) fn verify_cut_proof(
+        pp: &Parameters<C>,
+        shared_key: &PublicKey<C>,
+        original_deck: &Vec<MaskedCard<C>>,
+        cut_deck: &Vec<MaskedCard<C>>,
+        proof: &CutProof<C>,
+    ) -> Result<(), CardProtocolError> {
+        if original_deck.len() != cut_deck.len() {
+            return Err(CryptoError::ProofVerificationError(String::from("Cut")).into());
+        }
+        Self::validate_public_key(shared_key)?;
+        Self::validate_deck(original_deck)?;
+        Self::validate_deck(cut_deck)?;
+
+        let (mut fs_rng, challenge) =
+            Self::cut_transcript(pp, shared_key, original_deck, cut_deck)?;
+        let differences = fold_differences(original_deck, cut_deck, &challenge);
+        if !one_of_many::verify(pp, shared_key, &differences, proof, &mut fs_rng)? {
+            return Err(CryptoError::ProofVerificationError(String::from("Cut")).into());
+        }
+
+        Ok(())
+    }
+
+    /// Bind the transcript to the statement and derive the non-zero challenge whose powers fold
+    /// the decks
+    fn cut_transcript(
+        pp: &Parameters<C>,
+        shared_key: &PublicKey<C>,
+        original_deck: &Vec<MaskedCard<C>>,
+        cut_deck: &Vec<MaskedCard<C>>,
+    ) -> Result<(FiatShamirRng<D>, C::ScalarField), CryptoError> {
+        let mut fs_rng = FiatShamirRng::<D>::from_seed(&to_bytes![CUT_RNG_SEED]?);
+        fs_rng.absorb(&to_bytes![pp.enc_parameters.generator, shared_key]?);
+        for card in original_deck.iter().chain(cut_deck) {
+            fs_rng.absorb(&to_bytes![card.0, card.1]?);
+        }
+
+        let mut challenge = C::ScalarField::rand(&mut fs_rng);
+        while challenge.is_zero() {
+            challenge = C::ScalarField::rand(&mut fs_rng);
+        }
+
+        Ok((fs_rng, challenge))
+    }
+}
+
+impl<C: ProjectiveCurve, D: Digest> DLCards<C, D> {
+    /// Exchange two masked cards, e.g. the cards of two seats or a player card and a deck card.
+    /// Both cards are remasked whether or not they are swapped, so that observers cannot tell if
+    /// the exchange took place. A swap is a cut of a deck of two cards, and is proven as such.
+    pub fn swap_and_remask<R: Rng>(
+        rng: &mut R,
+        pp: &Parameters<C>,
+        shared_key: &PublicKey<C>,
+        cards: &(MaskedCard<C>, MaskedCard<C>),
+        masking_factors: &(C::ScalarField, C::ScalarField),
+        swap: bool,
+    ) -> Result<((MaskedCard<C>, MaskedCard<C>), CutProof<C>), CardProtocolError> {
+        let (output, proof) = Self::cut_and_remask(
+            rng,
+            pp,
+            shared_key,
+            &vec![cards.0, cards.1],
+            &vec![masking_factors.0, masking_factors.1],
+            swap as usize,
+        )?;
+
+        Ok(((output[0], output[1]), proof))
+    }
+
+    /// Verify that a pair of masked cards is a remasked permutation of another pair
+    pub fn verify_swap(
+        pp: &Parameters<C>,
+        shared_key: &PublicKey<C>,
+        original: &(MaskedCard<C>, MaskedCard<C>),
+        output: &(MaskedCard<C>, MaskedCard<C>),
+        proof: &CutProof<C>,
+    ) -> Result<(), CardProtocolError> {
+        Self::verify_cut(
+            pp,
+            shared_key,
+            &vec![original.0, original.1],
+            &vec![output.0, output.1],
+            proof,
+        )
+    }
+}
+
+/// For every offset `k`, fold `cut_deck[i] - original_deck[(i + k) % n]` with the weights `x^i`
+/// for the challenge `x`. The result is an encryption of zero under the shared key exactly when `k`
+/// is the offset of the cut.
+///
+/// Writing `F_k` for the fold of the original deck rotated by `k`, `x * F_{k+1} = F_k + (x^n - 1) *
+/// original_deck[k]`: the first card of the rotation moves to its end, with weight `x^n` instead of
+/// `1`. Only `F_0` and the fold of the cut deck need a multi-scalar multiplication, every other
+/// rotation costs two scalar multiplications, so folding is linear rather than quadratic in the
+/// deck length.
+fn fold_differences<C: ProjectiveCurve>(
+    original_deck: &Vec<MaskedCard<C>>,
+    cut_deck: &Vec<MaskedCard<C>>,
+    challenge: &C::ScalarField,
+) -> Vec<(C::Affine, C::Affine)> {
+    let n = original_deck.len();
+    let weights = powers(challenge, n)
+        .iter()
+        .map(|w| w.into_repr())
+        .collect::<Vec<_>>();
+
+    let (cut_0, cut_1): (Vec<_>, Vec<_>) = cut_deck.iter().map(|card| (card.0, card.1)).unzip();
+    let folded_cut_0 = VariableBaseMSM::multi_scalar_mul(&cut_0, &weights);
+    let folded_cut_1 = VariableBaseMSM::multi_scalar_mul(&cut_1, &weights);
+
+    let (original_0, original_1): (Vec<_>, Vec<_>) =
+        original_deck.iter().map(|card| (card.0, card.1)).unzip();
+    let mut rotated_0 = VariableBaseMSM::multi_scalar_mul(&original_0, &weights);
+    let mut rotated_1 = VariableBaseMSM::multi_scalar_mul(&original_1, &weights);
+
+    // The challenge is never zero, see `cut_transcript`
+    let inverse = challenge.inverse().unwrap_or_default().into_repr();
+    let wrap_around = (challenge.pow([n as u64]) - C::ScalarField::one()).into_repr();
+
+    let mut differences = Vec::with_capacity(2 * n);
+    for offset in 0..n {
+        differences.push(folded_cut_0 - rotated_0);
+        differences.push(folded_cut_1 - rotated_1);
+
+        rotated_0 = (rotated_0 + original_0[offset].mul(wrap_around)).mul(inverse);
+        rotated_1 = (rotated_1 + original_1[offset].mul(wrap_around)).mul(inverse);
+    }
+
+    C::batch_normalization_into_affine(&differences)
+        .chunks(2)
+        .map(|pair| (pair[0], pair[1]))
+        .collect()
+}
+
+/// `1, x, ..., x^(n-1)`
+fn powers<F: Field>(x: &F, n: usize) -> Vec<F> {
+    let mut powers = Vec::with_capacity(n);
+    let mut power = F::one();
+    for _ in 0..n {
+        powers.push(power);
+        power *= x;
+    }
+
+    powers
+}
+
+#[cfg(test)]
+mod test {
+    use crate::discrete_log_cards;
+    use crate::error::CardProtocolError;
+    use crate::BarnettSmartProtocol;
+
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ff::{PrimeField, UniformRand, Zero};
+    use proof_essentials::error::CryptoError;
+    use proof_essentials::utils::rand::sample_vector;
+    use rand::thread_rng;
+
+    // Choose elliptic curve setting
+    type Curve = starknet_curve::Projective;
+    type Scalar = starknet_curve::Fr;
+
+    // Instantiate concrete type for our card protocol
+    type CardProtocol = discrete_log_cards::DLCards<Curve>;
+
+    type MaskedCard = discrete_log_cards::MaskedCard<Curve>;
+
+    #[test]
+    fn test_cut_and_remask() {
+        let rng = &mut thread_rng();
+
+        let parameters = CardProtocol::setup(rng, 2, 5).unwrap();
+        let (shared_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+
+        // The deck length does not need to match the shuffle parameters
+        for &deck_size in [1, 2, 7].iter() {
+            let deck: Vec<MaskedCard> = sample_vector(rng, deck_size);
+
+            for offset in 0..deck_size {
+                let masking_factors: Vec<Scalar> = sample_vector(rng, deck_size);
+                let (cut_deck, proof) = CardProtocol::cut_and_remask(
+                    rng,
+                    &parameters,
+                    &shared_key,
+                    &deck,
+                    &masking_factors,
+                    offset,
+                )
+                .unwrap();
+
+                assert_eq!(
+                    Ok(()),
+                    CardProtocol::verify_cut(&parameters, &shared_key, &deck, &cut_deck, &proof)
+                );
+
+                // Swapping two cards of the output breaks the proof
+                if deck_size > 1 {
+                    let mut tampered = cut_deck.clone();
+                    tampered.swap(0, 1);
+                    assert_eq!(
+                        CardProtocol::verify_cut(
+                            &parameters,
+                            &shared_key,
+                            &deck,
+                            &tampered,
+                            &proof
+                        ),
+                        Err(CardProtocolError::ProofVerificationError(
+                            CryptoError::ProofVerificationError(String::from("Cut"))
+                        ))
+                    );
+                }
+            }
+        }
+
+        let deck: Vec<MaskedCard> = sample_vector(rng, 3);
+        let masking_factors: Vec<Scalar> = sample_vector(rng, 3);
+        assert_eq!(
+            CardProtocol::cut_and_remask(rng, &parameters, &shared_key, &deck, &masking_factors, 3)
+                .err(),
+            Some(CardProtocolError::InvalidCardIndex(3))
+        );
+    }
+
+    #[test]
+    fn test_fold_differences() {
+        let rng = &mut thread_rng();
+
+        let original_deck: Vec<MaskedCard> = sample_vector(rng, 7);
+        let cut_deck: Vec<MaskedCard> = sample_vector(rng, 7);
+        let challenge = Scalar::rand(rng);
+        let weights = super::powers(&challenge, 7);
+
+        // The incremental folds match the folds computed rotation by rotation
+        let fold = |deck: &mut dyn Iterator<Item = &MaskedCard>| {
+            deck.zip(&weights).fold(
+                (Curve::zero(), Curve::zero()),
+                |(sum_0, sum_1), (card, weight)| {
+                    (
+                        sum_0 + card.0.mul(weight.into_repr()),
+                        sum_1 + card.1.mul(weight.into_repr()),
+                    )
+                },
+            )
+        };
+        let folded_cut = fold(&mut cut_deck.iter());
+        let differences = super::fold_differences(&original_deck, &cut_deck, &challenge);
+        for (offset, difference) in differences.iter().enumerate() {
+            let rotated = fold(
+                &mut original_deck[offset..]
+                    .iter()
+                    .chain(&original_deck[..offset]),
+            );
+            assert_eq!(
+                *difference,
+                (
+                    (folded_cut.0 - rotated.0).into_affine(),
+                    (folded_cut.1 - rotated.1).into_affine()
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_swap_and_remask() {
+        let rng = &mut thread_rng();
+
+        let parameters = CardProtocol::setup(rng, 2, 5).unwrap();
+        let (shared_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+
+        let cards = (MaskedCard::rand(rng), MaskedCard::rand(rng));
+        for &swap in [false, true].iter() {
+            let masking_factors = (Scalar::rand(rng), Scalar::rand(rng));
+            let (output, proof) = CardProtocol::swap_and_remask(
+                rng,
+                &parameters,
+                &shared_key,
+                &cards,
+                &masking_factors,
+                swap,
+            )
+            .unwrap();
+
+            // Both cards are remasked, whether or not they were swapped
+            assert!(output.0 != cards.0 && output.0 != cards.1);
+            assert!(output.1 != cards.0 && output.1 != cards.1);
+
+            assert_eq!(
+                Ok(()),
+                CardProtocol::verify_swap(&parameters, &shared_key, &cards, &output, &proof)
+            );
+
+            let tampered = (output.0, MaskedCard::rand(rng));
+            assert_eq!(
+                CardProtocol::verify_swap(&parameters, &shared_key, &cards, &tampered, &proof),
+                Err(CardProtocolError::ProofVerificationError(
+                    CryptoError::ProofVerificationError(String::from("Cut"))
+                ))
+            );
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use crate::discrete_log_cards::{
+    DLCards, DLEqualityProof, MaskedCard, Parameters, PublicKey, RevealToken,
+};
+use crate::error::CardProtocolError;
+use crate::BarnettSmartProtocol;
+
+use ark_ec::ProjectiveCurve;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use blake2::Blake2s;
+use digest::Digest;
+use std::fs::{self, File};
+use std::marker::PhantomData;
+use std::path::Path;
+
+type RevealProof<C> = DLEqualityProof<C>;
+
+/// Version of the snapshot format written by `DeckState::to_snapshot`
+pub const DECK_STATE_VERSION: u8 = 1;
+
+const DIGEST_SIZE: usize = 32;
+
+/// Everything a player needs to resume a hand after a restart: the aggregate key, the shuffled
+/// deck, the cards dealt so far and the reveal tokens collected for them. Snapshots start with a
+/// format version and end with a hash of their content, so that truncated or corrupted files are
+/// rejected instead of resuming from a wrong state. `D` is the hash of the Fiat-Shamir transform
+/// with which reveal tokens are verified.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct DeckState<C: ProjectiveCurve, D: Digest = Blake2s> {
+    shared_key: PublicKey<C>,
+    deck: Vec<MaskedCard<C>>,
+    // Deck positions dealt so far, with the key of the receiving player (none for public cards)
+    dealt: Vec<(usize, Option<PublicKey<C>>)>,
+    reveal_tokens: Vec<(usize, Vec<(RevealToken<C>, RevealProof<C>, PublicKey<C>)>)>,
+    _transcript: PhantomData<D>,
+}
+
+impl<C: ProjectiveCurve, D: Digest> DeckState<C, D> {
+    /// State of a hand played with a freshly shuffled deck
+    pub fn new(shared_key: &PublicKey<C>, deck: &Vec<MaskedCard<C>>) -> Self {
+        Self {
+            shared_key: *shared_key,
+            deck: deck.clone(),
+            dealt: Vec::new(),
+            reveal_tokens: Vec::new(),
+            _transcript: PhantomData,
+        }
+    }
+
+    pub fn shared_key(&self) -> &PublicKey<C> {
+        &self.shared_key
+    }
+
+    pub fn deck(&self) -> &Vec<MaskedCard<C>> {
+        &self.deck
+    }
+
+    pub fn dealt(&self) -> &Vec<(usize, Option<PublicKey<C>>)> {
+        &self.dealt
+    }
+
+    /// Position of the next card to deal
+    pub fn next_card(&self) -> usize {
+        self.dealt.len()
+    }
+
+    /// Deal the next card of the deck, to a player or, without a recipient, to the table
+    pub fn deal(
+        &mut self,
+        recipient: Option<&PublicKey<C>>,
+    ) -> Result<(usize, MaskedCard<C>), CardProtocolError> {
+        let position = self.next_card();
+        let card = self
+            .deck
+            .get(position)
+            .ok_or(CardProtocolError::InvalidCardIndex(position))?;
+
+        self.dealt.push((position, recipient.copied()));
+
+        Ok((position, *card))
+    }
+
+    /// Record a reveal token received for the card at `index` of the deck. The token is verified
+    /// first, and left out of the state if its proof does not verify.
+    pub fn add_reveal_token(
+        &mut self,
+        pp: &Parameters<C>,
+        index: usize,
+        token: (RevealToken<C>, RevealProof<C>, PublicKey<C>),
+    ) -> Result<(), CardProtocolError> {
+        let card = self
+            .deck
+            .get(index)
+            .ok_or(CardProtocolError::InvalidCardIndex(index))?;
+
+        let (reveal_token, proof, pk) = &token;
+        DLCards::<C, D>::verify_reveal(pp, pk, reveal_token, card, proof)?;
+
+        match self.reveal_tokens.iter_mut().find(|(i, _)| *i == index) {
+            Some((_, tokens)) => tokens.push(token),
+            None => self.reveal_tokens.push((index, vec![token])),
+        }
+
+        Ok(())
+    }
+
+    /// Reveal tokens collected so far for the card at `index` of the deck
+    pub fn reveal_tokens(
+        &self,
+        index: usize,
+    ) -> Vec<(RevealToken<C>, RevealProof<C>, PublicKey<C>)> {
+        self.reveal_tokens
+            .iter()
+            .find(|(i, _)| *i == index)
+            .map(|(_, tokens)| tokens.clone())
+            .unwrap_or_default()
+    }
+
+    /// Hash of the state, which players can compare to check that they resume the same hand
+    pub fn digest(&self) -> Result<Vec<u8>, CardProtocolError> {
+        let mut bytes = Vec::new();
+        self.serialize(&mut bytes)
+            .map_err(|e| CardProtocolError::IoError(e.to_string()))?;
+
+        Ok(Self::hash(DECK_STATE_VERSION, &bytes))
+    }
+
+    /// Encode the state as a versioned snapshot, followed by its integrity hash
+    pub fn to_snapshot(&self) -> Result<Vec<u8>, CardProtocolError> {
+        let mut snapshot = vec![DECK_STATE_VERSION];
+        self.serialize(&mut snapshot)
+            .map_err(|e| CardProtocolError::IoError(e.to_string()))?;
+
+        let digest = Self::hash(DECK_STATE_VERSION, &snapshot[1..]);
+        snapshot.extend_from_slice(&digest);
+
+        Ok(snapshot)
+    }
+
+    pub fn from_snapshot(snapshot: &[u8]) -> Result<Self, CardProtocolError> {
+        if snapshot.len() < 1 + DIGEST_SIZE {
+            return Err(CardProtocolError::CorruptedSnapshot);
+        }
+
+        let version = snapshot[0];
+        if version != DECK_STATE_VERSION {
+            return Err(CardProtocolError::UnsupportedSnapshotVersion(version));
+        }
+
+        let (content, digest) = snapshot[1..].split_at(snapshot.len() - 1 - DIGEST_SIZE);
+        if Self::hash(version, content) != digest {
+            return Err(CardProtocolError::CorruptedSnapshot);
+        }
+
+        let state = Self::deserialize(content).map_err(|_| CardProtocolError::CorruptedSnapshot)?;
+
+        // Cards are dealt in deck order, and tokens can only be held for cards of the deck
+        let dealt_in_order = state
+            .dealt
+            .iter()
+            .enumerate()
+            .all(|(i, (position, _))| *position == i);
+        if state.dealt.len() > state.deck.len()
+            || !dealt_in_order
+            || state
+                .reveal_tokens
+                .iter()
+                .any(|(i, _)| *i >= state.deck.len())
+        {
+            return Err(CardProtocolError::CorruptedSnapshot);
+        }
+
+        Ok(state)
+    }
+
+    /// Write a snapshot to `path` atomically: the snapshot is written to a temporary file next to
+    /// it, synced to disk and renamed over `path`, so a crash leaves either the old or the new
+    /// snapshot.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), CardProtocolError> {
+        write_atomically(path.as_ref(), &self.to_snapshot()?)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, CardProtocolError> {
+        Self::from_snapshot(&fs::read(path)?)
+    }
+
+    fn hash(version: u8, content: &[u8]) -> Vec<u8> {
+        let mut hasher = Blake2s::new();
+        hasher.update([version]);
+        hasher.update(content);
+
+        hasher.finalize().to_vec()
+    }
+}
+
+/// Write `content` to a temporary file next to `path`, sync it to disk and rename it over `path`,
+/// so a crash leaves either the old or the new content
+pub(crate) fn write_atomically(path: &Path, content: &[u8]) -> Result<(), CardProtocolError> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let mut file = File::create(&temp_path)?;
+    file.write_all(content)?;
+    file.sync_all()?;
+    fs::rename(&temp_path, path)?;
+
+    // Persist the rename itself
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        File::open(dir)?.sync_all()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::discrete_log_cards;
+    use crate::error::CardProtocolError;
+    use crate::BarnettSmartProtocol;
+
+    use proof_essentials::utils::rand::sample_vector;
+    use rand::thread_rng;
+
+    // Choose elliptic curve setting
+    type Curve = starknet_curve::Projective;
+
+    // Instantiate concrete type for our card protocol
+    type CardProtocol = discrete_log_cards::DLCards<Curve>;
+    type DeckState = discrete_log_cards::DeckState<Curve>;
+
+    type MaskedCard = discrete_log_cards::MaskedCard<Curve>;
+
+    #[test]
+    fn test_deck_state_snapshot() {
+        let rng = &mut thread_rng();
+        let parameters = CardProtocol::setup(rng, 2, 5).unwrap();
+        let (pk, sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+
+        let deck: Vec<MaskedCard> = sample_vector(rng, 10);
+        let mut state = DeckState::new(&pk, &deck);
+
+        let (position, card) = state.deal(Some(&pk)).unwrap();
+        let (token, proof) =
+            CardProtocol::compute_reveal_token(rng, &parameters, &sk, &pk, &card).unwrap();
+
+        // Tokens are verified before being recorded
+        assert!(state
+            .add_reveal_token(&parameters, position + 1, (token, proof, pk))
+            .is_err());
+        assert!(state
+            .add_reveal_token(&parameters, deck.len(), (token, proof, pk))
+            .is_err());
+        assert!(state.reveal_tokens(position + 1).is_empty());
+
+        state
+            .add_reveal_token(&parameters, position, (token, proof, pk))
+            .unwrap();
+        state.deal(None).unwrap();
+
+        // Resume from a snapshot
+        let snapshot = state.to_snapshot().unwrap();
+        let mut resumed = DeckState::from_snapshot(&snapshot).unwrap();
+        assert_eq!(resumed.digest().unwrap(), state.digest().unwrap());
+        assert_eq!(resumed.deck(), state.deck());
+        assert_eq!(resumed.next_card(), 2);
+        assert_eq!(resumed.reveal_tokens(0).len(), 1);
+        assert_eq!(resumed.deal(None).unwrap(), (2, deck[2]));
+
+        // Corrupted, truncated and future snapshots are rejected
+        let mut corrupted = snapshot.clone();
+        corrupted[10] ^= 1;
+        assert_eq!(
+            DeckState::from_snapshot(&corrupted).err(),
+            Some(CardProtocolError::CorruptedSnapshot)
+        );
+        assert_eq!(
+            DeckState::from_snapshot(&snapshot[..snapshot.len() - 1]).err(),
+            Some(CardProtocolError::CorruptedSnapshot)
+        );
+
+        let mut future = snapshot.clone();
+        future[0] += 1;
+        assert_eq!(
+            DeckState::from_snapshot(&future).err(),
+            Some(CardProtocolError::UnsupportedSnapshotVersion(future[0]))
+        );
+
+        // Snapshots with a well-formed hash but more dealt cards than the deck are rejected
+        let mut overdealt = DeckState::new(&pk, &deck[..1].to_vec());
+        overdealt.deal(None).unwrap();
+        overdealt.deck = vec![];
+        assert_eq!(
+            DeckState::from_snapshot(&overdealt.to_snapshot().unwrap()).err(),
+            Some(CardProtocolError::CorruptedSnapshot)
+        );
+
+        // Saving replaces the file without leaving the temporary file behind
+        let dir = std::env::temp_dir().join(format!("deck-state-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("hand.snapshot");
+        DeckState::new(&pk, &deck).save(&path).unwrap();
+        state.save(&path).unwrap();
+        let loaded = DeckState::load(&path).unwrap();
+        let leftovers = std::fs::read_dir(&dir).unwrap().count();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(loaded.digest().unwrap(), state.digest().unwrap());
+        assert_eq!(leftovers, 1);
+    }
+}
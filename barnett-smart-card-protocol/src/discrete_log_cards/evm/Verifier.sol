@@ -0,0 +1,122 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Generated by barnett-smart-card-protocol. Checks EVM-mode reveal and key ownership proofs of
+// `DLCards` over BN254 G1.
+pragma solidity ^0.8.0;
+
+contract CardVerifier {
+    uint256 constant FIELD_MODULUS = {{FIELD_MODULUS}};
+    uint256 constant GROUP_ORDER = {{GROUP_ORDER}};
+    uint256 constant GENERATOR_X = {{GENERATOR_X}};
+    uint256 constant GENERATOR_Y = {{GENERATOR_Y}};
+
+    /// Check that `token` was computed from the first component of a masked card with the secret
+    /// key of `pk`. `proof` holds the commitments `a` and `b` followed by the response `r`.
+    function verifyReveal(
+        uint256[2] calldata pk,
+        uint256[2] calldata maskedCard,
+        uint256[2] calldata token,
+        uint256[5] calldata proof
+    ) external view returns (bool) {
+        if (proof[4] >= GROUP_ORDER) {
+            return false;
+        }
+
+        uint256[2] memory generator = [GENERATOR_X, GENERATOR_Y];
+        uint256[2] memory a = [proof[0], proof[1]];
+        uint256[2] memory b = [proof[2], proof[3]];
+
+        uint256 c = uint256(
+            keccak256(
+                abi.encodePacked(
+                    "Reveal Proof",
+                    generator,
+                    pk,
+                    maskedCard,
+                    token,
+                    a,
+                    b
+                )
+            )
+        ) % GROUP_ORDER;
+
+        (bool ok1, uint256[2] memory lhs1) = ecMul(maskedCard, proof[4]);
+        (bool ok2, uint256[2] memory rhs1) = ecMulAdd(token, c, a);
+        (bool ok3, uint256[2] memory lhs2) = ecMul(generator, proof[4]);
+        (bool ok4, uint256[2] memory rhs2) = ecMulAdd(pk, c, b);
+
+        return
+            ok1 &&
+            ok2 &&
+            ok3 &&
+            ok4 &&
+            lhs1[0] == rhs1[0] &&
+            lhs1[1] == rhs1[1] &&
+            lhs2[0] == rhs2[0] &&
+            lhs2[1] == rhs2[1];
+    }
+
+    /// Check that the prover knows the secret key of `pk`. `proof` holds the commitment followed by
+    /// the opening.
+    function verifyKeyOwnership(
+        uint256[2] calldata pk,
+        bytes32 playerInfo,
+        uint256[3] calldata proof
+    ) external view returns (bool) {
+        if (proof[2] >= GROUP_ORDER) {
+            return false;
+        }
+
+        uint256[2] memory generator = [GENERATOR_X, GENERATOR_Y];
+        uint256[2] memory commit = [proof[0], proof[1]];
+
+        uint256 c = uint256(
+            keccak256(
+                abi.encodePacked(
+                    "Key Ownership Proof",
+                    playerInfo,
+                    generator,
+                    pk,
+                    commit
+                )
+            )
+        ) % GROUP_ORDER;
+
+        (bool ok1, uint256[2] memory lhs) = ecMul(generator, proof[2]);
+        (bool ok2, uint256[2] memory rhs) = ecMulAdd(pk, c, commit);
+
+        return ok1 && ok2 && lhs[0] == rhs[0] && lhs[1] == rhs[1];
+    }
+
+    function ecMul(uint256[2] memory p, uint256 s)
+        internal
+        view
+        returns (bool ok, uint256[2] memory r)
+    {
+        if (p[0] >= FIELD_MODULUS || p[1] >= FIELD_MODULUS) {
+            return (false, r);
+        }
+
+        uint256[3] memory input = [p[0], p[1], s];
+        assembly {
+            ok := staticcall(gas(), 0x07, input, 0x60, r, 0x40)
+        }
+    }
+
+    /// Compute `p * s + q`
+    function ecMulAdd(
+        uint256[2] memory p,
+        uint256 s,
+        uint256[2] memory q
+    ) internal view returns (bool ok, uint256[2] memory r) {
+        uint256[2] memory ps;
+        (ok, ps) = ecMul(p, s);
+        if (!ok || q[0] >= FIELD_MODULUS || q[1] >= FIELD_MODULUS) {
+            return (false, r);
+        }
+
+        uint256[4] memory input = [ps[0], ps[1], q[0], q[1]];
+        assembly {
+            ok := staticcall(gas(), 0x06, input, 0x80, r, 0x40)
+        }
+    }
+}
@@ -0,0 +1,438 @@
+//! An EVM-friendly mode of the reveal and key ownership proofs of `DLCards` over BN254 G1.
+//!
+//! The default proofs take their challenge from a Blake2s digest of arkworks encodings, which a
+//! contract cannot reproduce cheaply. Proofs of this mode hash a Keccak-256 transcript over the
+//! `abi.encodePacked` encoding of the statement instead: points as big-endian `(x, y)` words, with the
+//! point at infinity encoded as `(0, 0)` like the EVM precompiles, and the challenge is the hash
+//! reduced modulo the group order. `solidity_verifier` emits a contract checking the same proofs
+//! with the `ecAdd` and `ecMul` precompiles.
+
+use crate::discrete_log_cards::constant_time::ladder_mul;
+use crate::discrete_log_cards::{DLCards, MaskedCard, Parameters, PlayerSecretKey, PublicKey};
+use crate::error::CardProtocolError;
+
+use ark_bn254::{Fq, Fr, G1Affine, G1Projective};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{BigInteger, FpParameters, PrimeField, UniformRand};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use ark_std::rand::Rng;
+use ark_std::Zero;
+use proof_essentials::error::CryptoError;
+use proof_essentials::homomorphic_encryption::el_gamal;
+use sha3::{Digest, Keccak256};
+
+const REVEAL_DOMAIN: &'static [u8] = b"Reveal Proof";
+const KEY_OWN_DOMAIN: &'static [u8] = b"Key Ownership Proof";
+
+const VERIFIER_TEMPLATE: &'static str = include_str!("Verifier.sol");
+
+pub type EvmRevealToken = el_gamal::Plaintext<G1Projective>;
+
+/// Chaum-Pedersen proof that a reveal token was computed with the secret key of a player
+#[derive(Clone, Copy, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct EvmRevealProof {
+    pub a: G1Affine,
+    pub b: G1Affine,
+    pub r: Fr,
+}
+
+/// Schnorr proof of ownership of a public key, bound to 32 bytes of player information (e.g. a
+/// padded address)
+#[derive(Clone, Copy, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct EvmKeyOwnershipProof {
+    pub random_commit: G1Affine,
+    pub opening: Fr,
+}
+
+/// Keccak-256 transcript over the `abi.encodePacked` encoding of its inputs
+struct KeccakTranscript {
+    data: Vec<u8>,
+}
+
+impl KeccakTranscript {
+    fn new(domain: &[u8]) -> Self {
+        Self {
+            data: domain.to_vec(),
+        }
+    }
+
+    fn append_bytes(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+
+    fn append_point(&mut self, point: &G1Affine) {
+        self.data.extend_from_slice(&encode_point(point));
+    }
+
+    fn challenge(&self) -> Fr {
+        Fr::from_be_bytes_mod_order(&Keccak256::digest(&self.data))
+    }
+}
+
+fn encode_word<F: PrimeField>(value: &F) -> [u8; 32] {
+    let bytes = value.into_repr().to_bytes_be();
+    let mut word = [0u8; 32];
+    word[32 - bytes.len()..].copy_from_slice(&bytes);
+    word
+}
+
+fn encode_point(point: &G1Affine) -> [u8; 64] {
+    let (x, y) = if point.is_zero() {
+        (Fq::zero(), Fq::zero())
+    } else {
+        (point.x, point.y)
+    };
+
+    let mut bytes = [0u8; 64];
+    bytes[..32].copy_from_slice(&encode_word(&x));
+    bytes[32..].copy_from_slice(&encode_word(&y));
+    bytes
+}
+
+fn hex<F: PrimeField>(value: &F) -> String {
+    let digits = encode_word(value)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    format!("0x{}", digits)
+}
+
+fn modulus_hex<F: PrimeField>() -> String {
+    let digits = F::Params::MODULUS
+        .to_bytes_be()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    format!("0x{}", digits)
+}
+
+fn selector(signature: &str) -> [u8; 4] {
+    let mut selector = [0u8; 4];
+    selector.copy_from_slice(&Keccak256::digest(signature.as_bytes())[..4]);
+    selector
+}
+
+fn reveal_challenge(
+    generator: &G1Affine,
+    pk: &PublicKey<G1Projective>,
+    masked_card: &MaskedCard<G1Projective>,
+    reveal_token: &EvmRevealToken,
+    a: &G1Affine,
+    b: &G1Affine,
+) -> Fr {
+    let mut transcript = KeccakTranscript::new(REVEAL_DOMAIN);
+    for point in [generator, pk, &masked_card.0, &reveal_token.0, a, b] {
+        transcript.append_point(point);
+    }
+    transcript.challenge()
+}
+
+fn key_ownership_challenge(
+    generator: &G1Affine,
+    pk: &PublicKey<G1Projective>,
+    player_info: &[u8; 32],
+    random_commit: &G1Affine,
+) -> Fr {
+    let mut transcript = KeccakTranscript::new(KEY_OWN_DOMAIN);
+    transcript.append_bytes(player_info);
+    transcript.append_point(generator);
+    transcript.append_point(pk);
+    transcript.append_point(random_commit);
+    transcript.challenge()
+}
+
+impl<D: Digest> DLCards<G1Projective, D> {
+    /// Same as `compute_reveal_token`, with a proof that can be checked by `verify_reveal_evm` and
+    /// by the contract emitted by `solidity_verifier`.
+    pub fn compute_reveal_token_evm<R: Rng>(
+        rng: &mut R,
+        pp: &Parameters<G1Projective>,
+        sk: &PlayerSecretKey<G1Projective>,
+        pk: &PublicKey<G1Projective>,
+        masked_card: &MaskedCard<G1Projective>,
+    ) -> Result<(EvmRevealToken, EvmRevealProof), CardProtocolError> {
+        Self::validate_masked_card(masked_card)?;
+
+        let generator = pp.enc_parameters.generator;
+        let reveal_token =
+            el_gamal::Plaintext(ladder_mul::<G1Projective>(&masked_card.0, sk).into_affine());
+
+        let k = Fr::rand(rng);
+        let a = ladder_mul::<G1Projective>(&masked_card.0, &k).into_affine();
+        let b = ladder_mul::<G1Projective>(&generator, &k).into_affine();
+        let c = reveal_challenge(&generator, pk, masked_card, &reveal_token, &a, &b);
+
+        Ok((
+            reveal_token,
+            EvmRevealProof {
+                a,
+                b,
+                r: k + c * sk,
+            },
+        ))
+    }
+
+    pub fn verify_reveal_evm(
+        pp: &Parameters<G1Projective>,
+        pk: &PublicKey<G1Projective>,
+        reveal_token: &EvmRevealToken,
+        masked_card: &MaskedCard<G1Projective>,
+        proof: &EvmRevealProof,
+    ) -> Result<(), CryptoError> {
+        let generator = pp.enc_parameters.generator;
+        let c = reveal_challenge(
+            &generator,
+            pk,
+            masked_card,
+            reveal_token,
+            &proof.a,
+            &proof.b,
+        );
+
+        if masked_card.0.mul(proof.r) != reveal_token.0.mul(c).add_mixed(&proof.a)
+            || generator.mul(proof.r) != pk.mul(c).add_mixed(&proof.b)
+        {
+            return Err(CryptoError::ProofVerificationError(String::from(
+                "EVM Reveal",
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Same as `prove_key_ownership`, with a proof that can be checked by
+    /// `verify_key_ownership_evm` and by the contract emitted by `solidity_verifier`.
+    pub fn prove_key_ownership_evm<R: Rng>(
+        rng: &mut R,
+        pp: &Parameters<G1Projective>,
+        pk: &PublicKey<G1Projective>,
+        sk: &PlayerSecretKey<G1Projective>,
+        player_info: &[u8; 32],
+    ) -> Result<EvmKeyOwnershipProof, CryptoError> {
+        let generator = pp.enc_parameters.generator;
+
+        let k = Fr::rand(rng);
+        let random_commit = ladder_mul::<G1Projective>(&generator, &k).into_affine();
+        let c = key_ownership_challenge(&generator, pk, player_info, &random_commit);
+
+        Ok(EvmKeyOwnershipProof {
+            random_commit,
+            opening: k + c * sk,
+        })
+    }
+
+    pub fn verify_key_ownership_evm(
+        pp: &Parameters<G1Projective>,
+        pk: &PublicKey<G1Projective>,
+        player_info: &[u8; 32],
+        proof: &EvmKeyOwnershipProof,
+    ) -> Result<(), CryptoError> {
+        let generator = pp.enc_parameters.generator;
+        let c = key_ownership_challenge(&generator, pk, player_info, &proof.random_commit);
+
+        if generator.mul(proof.opening) != pk.mul(c).add_mixed(&proof.random_commit) {
+            return Err(CryptoError::ProofVerificationError(String::from(
+                "EVM Key Ownership",
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Solidity source of a `CardVerifier` contract checking EVM-mode proofs for the generator of
+    /// `pp`. The contract exposes `verifyReveal(uint256[2],uint256[2],uint256[2],uint256[5])` and
+    /// `verifyKeyOwnership(uint256[2],bytes32,uint256[3])`.
+    pub fn solidity_verifier(pp: &Parameters<G1Projective>) -> String {
+        let generator = pp.enc_parameters.generator;
+
+        VERIFIER_TEMPLATE
+            .replace("{{FIELD_MODULUS}}", &modulus_hex::<Fq>())
+            .replace("{{GROUP_ORDER}}", &modulus_hex::<Fr>())
+            .replace("{{GENERATOR_X}}", &hex(&generator.x))
+            .replace("{{GENERATOR_Y}}", &hex(&generator.y))
+    }
+
+    /// ABI-encoded call to `verifyReveal` of the contract emitted by `solidity_verifier`
+    pub fn encode_verify_reveal_call(
+        pk: &PublicKey<G1Projective>,
+        reveal_token: &EvmRevealToken,
+        masked_card: &MaskedCard<G1Projective>,
+        proof: &EvmRevealProof,
+    ) -> Vec<u8> {
+        let mut calldata =
+            selector("verifyReveal(uint256[2],uint256[2],uint256[2],uint256[5])").to_vec();
+        for point in [pk, &masked_card.0, &reveal_token.0, &proof.a, &proof.b] {
+            calldata.extend_from_slice(&encode_point(point));
+        }
+        calldata.extend_from_slice(&encode_word(&proof.r));
+
+        calldata
+    }
+
+    /// ABI-encoded call to `verifyKeyOwnership` of the contract emitted by `solidity_verifier`
+    pub fn encode_verify_key_ownership_call(
+        pk: &PublicKey<G1Projective>,
+        player_info: &[u8; 32],
+        proof: &EvmKeyOwnershipProof,
+    ) -> Vec<u8> {
+        let mut calldata = selector("verifyKeyOwnership(uint256[2],bytes32,uint256[3])").to_vec();
+        calldata.extend_from_slice(&encode_point(pk));
+        calldata.extend_from_slice(player_info);
+        calldata.extend_from_slice(&encode_point(&proof.random_commit));
+        calldata.extend_from_slice(&encode_word(&proof.opening));
+
+        calldata
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::discrete_log_cards::DLCards;
+    use crate::BarnettSmartProtocol;
+
+    use ark_bn254::{Fr, G1Projective};
+    use ark_ff::UniformRand;
+    use proof_essentials::homomorphic_encryption::el_gamal;
+    use rand::thread_rng;
+
+    type CardProtocol = DLCards<G1Projective>;
+    type MaskedCard = crate::discrete_log_cards::MaskedCard<G1Projective>;
+
+    #[test]
+    fn test_evm_proofs() {
+        let rng = &mut thread_rng();
+        let parameters = CardProtocol::setup(rng, 2, 5).unwrap();
+        let (pk, sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+
+        let masked_card = MaskedCard::rand(rng);
+        let (token, proof) =
+            CardProtocol::compute_reveal_token_evm(rng, &parameters, &sk, &pk, &masked_card)
+                .unwrap();
+        assert_eq!(
+            Ok(()),
+            CardProtocol::verify_reveal_evm(&parameters, &pk, &token, &masked_card, &proof)
+        );
+
+        let wrong_token = el_gamal::Plaintext(token.0 + token.0);
+        assert!(CardProtocol::verify_reveal_evm(
+            &parameters,
+            &pk,
+            &wrong_token,
+            &masked_card,
+            &proof
+        )
+        .is_err());
+
+        let info = [7u8; 32];
+        let key_proof =
+            CardProtocol::prove_key_ownership_evm(rng, &parameters, &pk, &sk, &info).unwrap();
+        assert_eq!(
+            Ok(()),
+            CardProtocol::verify_key_ownership_evm(&parameters, &pk, &info, &key_proof)
+        );
+        assert!(
+            CardProtocol::verify_key_ownership_evm(&parameters, &pk, &[8u8; 32], &key_proof)
+                .is_err()
+        );
+    }
+
+    /// Compile the emitted verifier with `solc` and run it in revm. Requires the `evm-solc` feature
+    /// and a `solc` binary on the path; the `evm` job of the CI workflow provides both.
+    #[cfg(feature = "evm-solc")]
+    #[test]
+    fn test_solidity_verifier() {
+        use revm::primitives::{
+            AccountInfo, Bytecode, Bytes, ExecutionResult, Output, TransactTo, B160,
+        };
+        use revm::{InMemoryDB, EVM};
+        use std::process::Command;
+
+        let rng = &mut thread_rng();
+        let parameters = CardProtocol::setup(rng, 2, 5).unwrap();
+        let (pk, sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+
+        let dir = std::env::temp_dir().join(format!("card-verifier-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("CardVerifier.sol");
+        std::fs::write(&source, CardProtocol::solidity_verifier(&parameters)).unwrap();
+
+        let output = Command::new("solc")
+            .arg("--bin-runtime")
+            .arg("--optimize")
+            .arg(&source)
+            .output();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let output = output.expect("the evm-solc feature requires solc on the path");
+        assert!(
+            output.status.success(),
+            "solc failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let runtime = stdout
+            .lines()
+            .skip_while(|line| !line.starts_with("Binary of the runtime part"))
+            .nth(1)
+            .unwrap();
+        let code = (0..runtime.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&runtime[i..i + 2], 16).unwrap())
+            .collect::<Vec<_>>();
+
+        let address = B160::from_low_u64_be(0xcafe);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            address,
+            AccountInfo {
+                code: Some(Bytecode::new_raw(Bytes::from(code))),
+                ..Default::default()
+            },
+        );
+        let mut evm = EVM::new();
+        evm.database(db);
+
+        let mut call = |calldata: Vec<u8>| -> bool {
+            evm.env.tx.transact_to = TransactTo::Call(address);
+            evm.env.tx.data = Bytes::from(calldata);
+            match evm.transact_ref().unwrap().result {
+                ExecutionResult::Success {
+                    output: Output::Call(output),
+                    ..
+                } => output[31] == 1,
+                _ => false,
+            }
+        };
+
+        let masked_card = MaskedCard::rand(rng);
+        let (token, proof) =
+            CardProtocol::compute_reveal_token_evm(rng, &parameters, &sk, &pk, &masked_card)
+                .unwrap();
+        assert!(call(CardProtocol::encode_verify_reveal_call(
+            &pk,
+            &token,
+            &masked_card,
+            &proof
+        )));
+
+        let mut wrong_proof = proof;
+        wrong_proof.r += Fr::rand(rng);
+        assert!(!call(CardProtocol::encode_verify_reveal_call(
+            &pk,
+            &token,
+            &masked_card,
+            &wrong_proof
+        )));
+
+        let info = [7u8; 32];
+        let key_proof =
+            CardProtocol::prove_key_ownership_evm(rng, &parameters, &pk, &sk, &info).unwrap();
+        assert!(call(CardProtocol::encode_verify_key_ownership_call(
+            &pk, &info, &key_proof
+        )));
+        assert!(!call(CardProtocol::encode_verify_key_ownership_call(
+            &pk, &[8u8; 32], &key_proof
+        )));
+    }
+}
@@ -0,0 +1,375 @@
+use crate::discrete_log_cards::constant_time::{encode, permute, select_encoded};
+use crate::discrete_log_cards::proofs::Base;
+use crate::discrete_log_cards::{
+    Card, DLCards, DLEqualityProof, MaskedCard, Parameters, PublicKey, ShuffleProof,
+};
+use crate::error::CardProtocolError;
+
+use ark_ec::msm::FixedBaseMSM;
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{FpParameters, PrimeField};
+use ark_std::rand::Rng;
+use digest::Digest;
+use proof_essentials::homomorphic_encryption::el_gamal;
+use proof_essentials::utils::permutation::Permutation;
+use rand::thread_rng;
+
+// Rows are scanned in full on every lookup: larger windows save additions but cost more reads
+const MAX_WINDOW: usize = 6;
+
+/// Window table of multiples of a fixed base point. Building the table costs a few hundred group
+/// additions, after which every multiplication by the base is a single addition per window instead
+/// of a double-and-add over the whole scalar.
+///
+/// Multiplications are constant time, so that the table can be used with secret scalars: every row
+/// is read in full and the entry of the scalar digit is selected under a mask, and each row is
+/// offset by a random point so that no entry is the identity and no addition hits a special case of
+/// the addition law.
+pub struct FixedBaseTable<C: ProjectiveCurve> {
+    base: C::Affine,
+    window: usize,
+    // Encodings of `offsets[i] + base * (d << (window * i))` for every digit `d`
+    rows: Vec<Vec<Vec<u8>>>,
+    // Minus the sum of the offsets
+    correction: C,
+}
+
+impl<C: ProjectiveCurve> FixedBaseTable<C> {
+    /// Build a table for `base`, with a window size tuned for roughly `num_of_muls`
+    /// multiplications.
+    pub fn new(base: &C::Affine, num_of_muls: usize) -> Self {
+        let scalar_size = <C::ScalarField as PrimeField>::Params::MODULUS_BITS as usize;
+        let window = FixedBaseMSM::get_mul_window_size(num_of_muls).min(MAX_WINDOW);
+        let num_of_rows = (scalar_size + window - 1) / window;
+
+        let rng = &mut thread_rng();
+        let mut row_base = base.into_projective();
+        let mut rows = Vec::with_capacity(num_of_rows);
+        let mut correction = C::zero();
+        for _ in 0..num_of_rows {
+            let offset = C::rand(rng);
+            correction -= offset;
+
+            let mut entries = Vec::with_capacity(1 << window);
+            let mut entry = offset;
+            for _ in 0..(1 << window) {
+                entries.push(entry);
+                entry += row_base;
+            }
+            rows.push(
+                C::batch_normalization_into_affine(&entries)
+                    .iter()
+                    .map(encode)
+                    .collect(),
+            );
+
+            for _ in 0..window {
+                row_base.double_in_place();
+            }
+        }
+
+        Self {
+            base: *base,
+            window,
+            rows,
+            correction,
+        }
+    }
+
+    pub fn base(&self) -> &C::Affine {
+        &self.base
+    }
+
+    pub fn mul(&self, scalar: &C::ScalarField) -> C {
+        let repr = scalar.into_repr();
+        let limbs = repr.as_ref();
+
+        let mut product = self.correction;
+        for (i, row) in self.rows.iter().enumerate() {
+            let digit = digit(limbs, i * self.window, self.window);
+            product.add_assign_mixed(&select_encoded::<C::Affine>(row, digit));
+        }
+
+        product
+    }
+
+    pub fn batch_mul(&self, scalars: &[C::ScalarField]) -> Vec<C::Affine> {
+        let products = scalars
+            .iter()
+            .map(|scalar| self.mul(scalar))
+            .collect::<Vec<_>>();
+        C::batch_normalization_into_affine(&products)
+    }
+}
+
+/// The `width` bits of a little-endian scalar starting at bit `start`
+fn digit(limbs: &[u64], start: usize, width: usize) -> usize {
+    let mut digit = 0;
+    for j in 0..width {
+        let position = start + j;
+        let limb = limbs.get(position / 64).copied().unwrap_or(0);
+        digit |= (((limb >> (position % 64)) & 1) as usize) << j;
+    }
+
+    digit
+}
+
+/// An aggregate public key together with its window table. The trait methods of `DLCards` keep the
+/// table of the last key they were given in the parameters; a `PrecomputedKey` lets a table hold
+/// on to it explicitly, e.g. when it uses the same parameters with several keys.
+pub struct PrecomputedKey<C: ProjectiveCurve> {
+    key: PublicKey<C>,
+    table: FixedBaseTable<C>,
+}
+
+impl<C: ProjectiveCurve> PrecomputedKey<C> {
+    /// Precompute the table of `key` for a game dealing with decks of `num_of_cards` cards
+    pub fn new(key: &PublicKey<C>, num_of_cards: usize) -> Self {
+        Self {
+            key: *key,
+            table: FixedBaseTable::new(key, num_of_cards),
+        }
+    }
+
+    pub fn key(&self) -> &PublicKey<C> {
+        &self.key
+    }
+
+    pub(super) fn base(&self) -> Base<'_, C> {
+        Base::Table(&self.table)
+    }
+
+    /// `card` remasked with `alpha`
+    pub(super) fn remask(
+        &self,
+        pp: &Parameters<C>,
+        card: &MaskedCard<C>,
+        alpha: &C::ScalarField,
+    ) -> MaskedCard<C> {
+        let masking_point = el_gamal::Ciphertext(
+            pp.generator_table().mul(alpha).into_affine(),
+            self.table.mul(alpha).into_affine(),
+        );
+
+        *card + masking_point
+    }
+}
+
+impl<C: ProjectiveCurve, D: Digest> DLCards<C, D> {
+    /// Same as `mask`, with the fixed-base multiplications done through the window tables of the
+    /// generator and of the aggregate key. The proof is identical and is checked by `verify_mask`.
+    pub fn mask_precomputed<R: Rng>(
+        rng: &mut R,
+        pp: &Parameters<C>,
+        shared_key: &PrecomputedKey<C>,
+        original_card: &Card<C>,
+        r: &C::ScalarField,
+    ) -> Result<(MaskedCard<C>, DLEqualityProof<C>), CardProtocolError> {
+        let masked_card = el_gamal::Ciphertext(
+            pp.generator_table().mul(r).into_affine(),
+            shared_key
+                .table
+                .mul(r)
+                .add_mixed(&original_card.0)
+                .into_affine(),
+        );
+        let proof =
+            Self::prove_masking(rng, pp, shared_key.base(), original_card, &masked_card, r)?;
+
+        Ok((masked_card, proof))
+    }
+
+    /// Same as `remask`, with the fixed-base multiplications done through the window tables of the
+    /// generator and of the aggregate key. The proof is identical and is checked by `verify_remask`.
+    pub fn remask_precomputed<R: Rng>(
+        rng: &mut R,
+        pp: &Parameters<C>,
+        shared_key: &PrecomputedKey<C>,
+        original_card: &MaskedCard<C>,
+        alpha: &C::ScalarField,
+    ) -> Result<(MaskedCard<C>, DLEqualityProof<C>), CardProtocolError> {
+        let remasked = shared_key.remask(pp, original_card, alpha);
+        let proof =
+            Self::prove_remasking(rng, pp, shared_key.base(), original_card, &remasked, alpha)?;
+
+        Ok((remasked, proof))
+    }
+
+    /// Same as `shuffle_and_remask`, with the remasking points of the whole deck computed through
+    /// the window tables of the generator and of the aggregate key. The proof is identical and is
+    /// checked by `verify_shuffle`.
+    pub fn shuffle_and_remask_precomputed<R: Rng>(
+        rng: &mut R,
+        pp: &Parameters<C>,
+        shared_key: &PrecomputedKey<C>,
+        deck: &Vec<MaskedCard<C>>,
+        masking_factors: &Vec<C::ScalarField>,
+        permutation: &Permutation,
+    ) -> Result<(Vec<MaskedCard<C>>, ShuffleProof<C>), CardProtocolError> {
+        if deck.len() != pp.m * pp.n {
+            return Err(CardProtocolError::InvalidDeckSize(deck.len()));
+        }
+        if masking_factors.len() != deck.len() {
+            return Err(CardProtocolError::MaskingFactorsMismatch);
+        }
+
+        let positions = permutation.permute_array(&(0..deck.len()).collect());
+        let permuted_deck = permute(deck, &positions);
+
+        let randomness = pp.generator_table().batch_mul(masking_factors);
+        let key_shares = shared_key.table.batch_mul(masking_factors);
+
+        let masked_shuffled = permuted_deck
+            .iter()
+            .zip(randomness.iter().zip(key_shares.iter()))
+            .map(|(masked_card, (&a, &b))| *masked_card + el_gamal::Ciphertext(a, b))
+            .collect::<Vec<_>>();
+
+        let proof = Self::prove_shuffle(
+            rng,
+            pp,
+            shared_key.base(),
+            deck,
+            &masked_shuffled,
+            masking_factors,
+            &positions,
+        )?;
+
+        Ok((masked_shuffled, proof))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::discrete_log_cards;
+    use crate::{BarnettSmartProtocol, Mask, Remask};
+
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ff::{One, UniformRand, Zero};
+    use proof_essentials::utils::permutation::Permutation;
+    use proof_essentials::utils::rand::sample_vector;
+    use rand::thread_rng;
+    use std::sync::Arc;
+
+    // Choose elliptic curve setting
+    type Curve = starknet_curve::Projective;
+    type Scalar = starknet_curve::Fr;
+
+    // Instantiate concrete type for our card protocol
+    type CardProtocol = discrete_log_cards::DLCards<Curve>;
+    type FixedBaseTable = discrete_log_cards::FixedBaseTable<Curve>;
+    type PrecomputedKey = discrete_log_cards::PrecomputedKey<Curve>;
+
+    type Card = discrete_log_cards::Card<Curve>;
+    type MaskedCard = discrete_log_cards::MaskedCard<Curve>;
+
+    #[test]
+    fn test_fixed_base_mul() {
+        let rng = &mut thread_rng();
+
+        let base = Curve::rand(rng).into_affine();
+        let table = FixedBaseTable::new(&base, 52);
+
+        let mut scalars: Vec<Scalar> = sample_vector(rng, 10);
+        scalars.extend([Scalar::zero(), Scalar::one(), -Scalar::one()]);
+        let products = table.batch_mul(&scalars);
+
+        for (scalar, product) in scalars.iter().zip(products.iter()) {
+            let expected = base.mul(*scalar);
+            assert_eq!(table.mul(scalar), expected);
+            assert_eq!(*product, expected.into_affine());
+        }
+    }
+
+    #[test]
+    fn test_precomputed_proofs() {
+        let rng = &mut thread_rng();
+        let m = 4;
+        let n = 13;
+
+        let parameters = CardProtocol::setup(rng, m, n).unwrap();
+        let (shared_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+        let precomputed_key = PrecomputedKey::new(&shared_key, m * n);
+
+        let card = Card::rand(rng);
+        let alpha = Scalar::rand(rng);
+        let (masked, masking_proof) =
+            CardProtocol::mask_precomputed(rng, &parameters, &precomputed_key, &card, &alpha)
+                .unwrap();
+        assert_eq!(
+            Ok(()),
+            CardProtocol::verify_mask(&parameters, &shared_key, &card, &masked, &masking_proof)
+        );
+
+        let (remasked, remasking_proof) =
+            CardProtocol::remask_precomputed(rng, &parameters, &precomputed_key, &masked, &alpha)
+                .unwrap();
+        assert_eq!(
+            Ok(()),
+            CardProtocol::verify_remask(
+                &parameters,
+                &shared_key,
+                &masked,
+                &remasked,
+                &remasking_proof
+            )
+        );
+
+        let deck: Vec<MaskedCard> = sample_vector(rng, m * n);
+        let permutation = Permutation::new(rng, m * n);
+        let masking_factors: Vec<Scalar> = sample_vector(rng, m * n);
+
+        let (shuffled_deck, shuffle_proof) = CardProtocol::shuffle_and_remask_precomputed(
+            rng,
+            &parameters,
+            &precomputed_key,
+            &deck,
+            &masking_factors,
+            &permutation,
+        )
+        .unwrap();
+        assert_eq!(
+            Ok(()),
+            CardProtocol::verify_shuffle(
+                &parameters,
+                &shared_key,
+                &deck,
+                &shuffled_deck,
+                &shuffle_proof
+            )
+        );
+    }
+
+    #[test]
+    fn test_cached_key_table() {
+        let rng = &mut thread_rng();
+
+        let parameters = CardProtocol::setup(rng, 2, 2).unwrap();
+        let (shared_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+        let (other_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+
+        // The table of a key is built once and kept until another key is used
+        let key_table = parameters.key_table(&shared_key);
+        assert!(Arc::ptr_eq(&key_table, &parameters.key_table(&shared_key)));
+        let other_table = parameters.key_table(&other_key);
+        assert_eq!(other_table.key(), &other_key);
+        assert!(!Arc::ptr_eq(&key_table, &parameters.key_table(&shared_key)));
+
+        let card = Card::rand(rng);
+        let alpha = Scalar::rand(rng);
+        let (masked, _) = CardProtocol::mask(rng, &parameters, &shared_key, &card, &alpha).unwrap();
+        assert_eq!(
+            masked,
+            card.mask(&parameters.enc_parameters, &shared_key, &alpha)
+                .unwrap()
+        );
+        let (remasked, _) =
+            CardProtocol::remask(rng, &parameters, &shared_key, &masked, &alpha).unwrap();
+        assert_eq!(
+            remasked,
+            masked
+                .remask(&parameters.enc_parameters, &shared_key, &alpha)
+                .unwrap()
+        );
+    }
+}
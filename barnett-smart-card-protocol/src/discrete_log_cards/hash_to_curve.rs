@@ -0,0 +1,84 @@
+//! Transparent derivation of the generators of the protocol. Every generator is hashed to the curve
+//! from a public domain tag and a label, so that anyone can recompute the parameters and nobody
+//! knows a discrete logarithm relation between the generators.
+
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use digest::Digest;
+
+// Hash output fed to `from_random_bytes`, enough for base fields of up to 512 bits
+const EXPANDED_SIZE: usize = 64;
+
+/// Hash `(domain, label, index)` to a point of the prime-order subgroup. Candidate x-coordinates are
+/// tried with an increasing counter until one lies on the curve, and the point is then cleared of
+/// its cofactor.
+pub fn hash_to_curve<C: ProjectiveCurve, D: Digest>(
+    domain: &[u8],
+    label: &[u8],
+    index: u64,
+) -> C::Affine {
+    let mut counter = 0u64;
+
+    loop {
+        let mut bytes = Vec::with_capacity(EXPANDED_SIZE);
+        let mut block = 0u8;
+        while bytes.len() < EXPANDED_SIZE {
+            let mut hasher = D::new();
+            hasher.update((domain.len() as u64).to_le_bytes());
+            hasher.update(domain);
+            hasher.update((label.len() as u64).to_le_bytes());
+            hasher.update(label);
+            hasher.update(index.to_le_bytes());
+            hasher.update(counter.to_le_bytes());
+            hasher.update([block]);
+            bytes.extend_from_slice(&hasher.finalize());
+            block += 1;
+        }
+
+        if let Some(point) = C::Affine::from_random_bytes(&bytes) {
+            let point = point.mul_by_cofactor_to_projective();
+            if !point.is_zero() {
+                return point.into_affine();
+            }
+        }
+
+        counter += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::hash_to_curve;
+    use crate::discrete_log_cards::validation::in_prime_order_subgroup;
+
+    use ark_std::Zero;
+    use blake2::Blake2s;
+
+    type Curve = ark_bls12_377::G1Projective;
+
+    #[test]
+    fn test_hash_to_curve() {
+        let point = hash_to_curve::<Curve, Blake2s>(b"domain", b"label", 0);
+
+        assert!(!point.is_zero());
+        assert!(point.is_on_curve());
+        assert!(in_prime_order_subgroup::<Curve>(&point));
+
+        // Deterministic, and separated by domain, label and index
+        assert_eq!(
+            point,
+            hash_to_curve::<Curve, Blake2s>(b"domain", b"label", 0)
+        );
+        assert_ne!(
+            point,
+            hash_to_curve::<Curve, Blake2s>(b"other", b"label", 0)
+        );
+        assert_ne!(
+            point,
+            hash_to_curve::<Curve, Blake2s>(b"domain", b"other", 0)
+        );
+        assert_ne!(
+            point,
+            hash_to_curve::<Curve, Blake2s>(b"domain", b"label", 1)
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use crate::discrete_log_cards::shuffle::{absorb_points, inner_product, linear_combination};
+
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{Field, PrimeField, UniformRand};
+use ark_marlin::rng::FiatShamirRng;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use digest::Digest;
+use proof_essentials::error::CryptoError;
+
+/// Proof that `P = <a, G> + <b, H> + <a, b> Q` for vectors `a` and `b` of a power of two length,
+/// after Bootle et al. and Bulletproofs. Each round halves the vectors and sends two points, the
+/// last round sends `a` and `b`. The argument is not zero-knowledge: the callers blind `a` and `b`
+/// beforehand.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct InnerProductProof<C: ProjectiveCurve> {
+    pub lefts: Vec<C::Affine>,
+    pub rights: Vec<C::Affine>,
+    pub a: C::ScalarField,
+    pub b: C::ScalarField,
+}
+
+impl<C: ProjectiveCurve> InnerProductProof<C> {
+    /// Every group element of the proof
+    pub(super) fn points(&self) -> impl Iterator<Item = &C::Affine> {
+        self.lefts.iter().chain(&self.rights)
+    }
+}
+
+/// The bases of the argument. `H` is given as generators and public factors, `H_i = factors_i h_i`.
+pub(super) struct InnerProductBases<'a, C: ProjectiveCurve> {
+    pub g: &'a [C::Affine],
+    pub h: &'a [C::Affine],
+    pub h_factors: &'a [C::ScalarField],
+    pub q: &'a C::Affine,
+}
+
+/// A round with challenge `c` folds `a` into `a_lo + c a_hi`, `b` into `c b_lo + b_hi`, `G` into
+/// `c G_lo + G_hi` and `H` into `H_lo + c H_hi`, which turns `P` into `L + c P + c^2 R`. The
+/// vectors are public to whoever knows `a` and `b`, so the multiplications are variable-time.
+pub(super) fn prove_inner_product<C: ProjectiveCurve, D: Digest>(
+    bases: &InnerProductBases<C>,
+    mut a: Vec<C::ScalarField>,
+    mut b: Vec<C::ScalarField>,
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<InnerProductProof<C>, CryptoError> {
+    debug_assert!(a.len().is_power_of_two());
+    let mut g = bases.g[..a.len()].to_vec();
+    let h = bases.h[..a.len()]
+        .iter()
+        .zip(bases.h_factors)
+        .map(|(h, factor)| h.mul(factor.into_repr()))
+        .collect::<Vec<_>>();
+    let mut h = C::batch_normalization_into_affine(&h);
+
+    let mut lefts = Vec::new();
+    let mut rights = Vec::new();
+    while a.len() > 1 {
+        let half = a.len() / 2;
+        let (a_lo, a_hi) = a.split_at(half);
+        let (b_lo, b_hi) = b.split_at(half);
+        let (g_lo, g_hi) = g.split_at(half);
+        let (h_lo, h_hi) = h.split_at(half);
+
+        let left = linear_combination::<C>(g_hi, a_lo)
+            + linear_combination::<C>(h_lo, b_hi)
+            + bases.q.mul(inner_product(a_lo, b_hi).into_repr());
+        let right = linear_combination::<C>(g_lo, a_hi)
+            + linear_combination::<C>(h_hi, b_lo)
+            + bases.q.mul(inner_product(a_hi, b_lo).into_repr());
+        let left = left.into_affine();
+        let right = right.into_affine();
+
+        absorb_points::<C, D>(fs_rng, &[left, right])?;
+        let c = C::ScalarField::rand(fs_rng);
+        let c_repr = c.into_repr();
+
+        a = a_lo.iter().zip(a_hi).map(|(lo, hi)| c * hi + lo).collect();
+        b = b_lo.iter().zip(b_hi).map(|(lo, hi)| c * lo + hi).collect();
+        let folded_g = g_lo
+            .iter()
+            .zip(g_hi)
+            .map(|(lo, hi)| lo.mul(c_repr).add_mixed(hi))
+            .collect::<Vec<_>>();
+        let folded_h = h_lo
+            .iter()
+            .zip(h_hi)
+            .map(|(lo, hi)| hi.mul(c_repr).add_mixed(lo))
+            .collect::<Vec<_>>();
+        g = C::batch_normalization_into_affine(&folded_g);
+        h = C::batch_normalization_into_affine(&folded_h);
+
+        lefts.push(left);
+        rights.push(right);
+    }
+
+    Ok(InnerProductProof {
+        lefts,
+        rights,
+        a: a[0],
+        b: b[0],
+    })
+}
+
+/// Check the proof against `commitment`, which stands for `P`. The folded bases are computed with a
+/// single multi-exponentiation from the weights of `folding_weights`.
+pub(super) fn verify_inner_product<C: ProjectiveCurve, D: Digest>(
+    bases: &InnerProductBases<C>,
+    commitment: C,
+    proof: &InnerProductProof<C>,
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<(), CryptoError> {
+    let size = bases.h_factors.len();
+    let rounds = size.trailing_zeros() as usize;
+    if !size.is_power_of_two()
+        || bases.g.len() < size
+        || bases.h.len() < size
+        || proof.lefts.len() != rounds
+        || proof.rights.len() != rounds
+    {
+        return Err(inner_product_error());
+    }
+
+    let mut challenges = Vec::with_capacity(rounds);
+    for (left, right) in proof.lefts.iter().zip(&proof.rights) {
+        absorb_points::<C, D>(fs_rng, &[*left, *right])?;
+        challenges.push(C::ScalarField::rand(fs_rng));
+    }
+    let commitment = fold_commitment(commitment, &proof.lefts, &proof.rights, &challenges);
+
+    let g_weights = folding_weights(&challenges, true);
+    let h_weights = folding_weights(&challenges, false);
+    let points = bases.g[..size]
+        .iter()
+        .chain(&bases.h[..size])
+        .chain(Some(bases.q))
+        .copied()
+        .collect::<Vec<_>>();
+    let scalars = g_weights
+        .iter()
+        .map(|weight| proof.a * weight)
+        .chain(
+            h_weights
+                .iter()
+                .zip(bases.h_factors)
+                .map(|(weight, factor)| proof.b * weight * factor),
+        )
+        .chain(Some(proof.a * proof.b))
+        .collect::<Vec<_>>();
+
+    if linear_combination::<C>(&points, &scalars) != commitment {
+        return Err(inner_product_error());
+    }
+
+    Ok(())
+}
+
+/// `P` after the rounds with the given challenges: `L + c P + c^2 R` for each round
+pub(super) fn fold_commitment<C: ProjectiveCurve>(
+    commitment: C,
+    lefts: &[C::Affine],
+    rights: &[C::Affine],
+    challenges: &[C::ScalarField],
+) -> C {
+    lefts
+        .iter()
+        .zip(rights)
+        .zip(challenges)
+        .fold(commitment, |commitment, ((left, right), c)| {
+            commitment.mul(c.into_repr()) + right.mul(c.square().into_repr()).add_mixed(left)
+        })
+}
+
+/// The weight of every base in the folded base, when each round multiplies the low half by its
+/// challenge (`low`) or the high half. The first round splits on the most significant bit.
+pub(super) fn folding_weights<F: Field>(challenges: &[F], low: bool) -> Vec<F> {
+    let mut weights = vec![F::one()];
+    for c in challenges {
+        weights = weights
+            .iter()
+            .flat_map(|weight| {
+                if low {
+                    [*weight * c, *weight]
+                } else {
+                    [*weight, *weight * c]
+                }
+            })
+            .collect();
+    }
+
+    weights
+}
+
+fn inner_product_error() -> CryptoError {
+    CryptoError::ProofVerificationError(String::from("Inner Product Argument"))
+}
@@ -0,0 +1,341 @@
+use crate::discrete_log_cards::constant_time::ladder_mul;
+use crate::discrete_log_cards::inner_product::{fold_commitment, folding_weights};
+use crate::discrete_log_cards::multi_exp::{EncryptionBases, MultiExpStatement, MultiExpWitness};
+use crate::discrete_log_cards::pedersen::CommitKey;
+use crate::discrete_log_cards::shuffle::{absorb_points, linear_combination, sample_scalars};
+use crate::discrete_log_cards::MaskedCard;
+
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{PrimeField, UniformRand, Zero};
+use ark_marlin::rng::FiatShamirRng;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use ark_std::rand::Rng;
+use digest::Digest;
+use proof_essentials::error::CryptoError;
+use proof_essentials::homomorphic_encryption::el_gamal;
+
+/// Proof of logarithmic size of the statement of the multi-exponentiation argument for a single
+/// row: `commitment = <b, H> + s h` and `target = Enc(0; rho) + <cards, b>` for the same `b`. Both
+/// equations are the image of `(b, s, rho)` by one linear map into three copies of the group, which
+/// is proven with a compressed Sigma protocol after Attema and Cramer: the prover masks the
+/// witness, then folds the masked witness and the bases of the map in half each round, sending the
+/// cross terms, until a single scalar is left.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct LogMultiExpProof<C: ProjectiveCurve> {
+    pub mask_commitment: C::Affine,
+    pub mask_ciphertext: MaskedCard<C>,
+    pub left_commitments: Vec<C::Affine>,
+    pub left_ciphertexts: Vec<MaskedCard<C>>,
+    pub right_commitments: Vec<C::Affine>,
+    pub right_ciphertexts: Vec<MaskedCard<C>>,
+    pub z: C::ScalarField,
+}
+
+impl<C: ProjectiveCurve> LogMultiExpProof<C> {
+    /// Every group element of the proof
+    pub(super) fn points(&self) -> Vec<&C::Affine> {
+        let mut points = vec![&self.mask_commitment];
+        points.extend(&self.left_commitments);
+        points.extend(&self.right_commitments);
+        for ciphertext in std::iter::once(&self.mask_ciphertext)
+            .chain(&self.left_ciphertexts)
+            .chain(&self.right_ciphertexts)
+        {
+            points.push(&ciphertext.0);
+            points.push(&ciphertext.1);
+        }
+
+        points
+    }
+}
+
+/// The bases of the linear map: `(H_k, cards_k)` for every card, then `(h, 0)` for `s` and
+/// `(0, (g, pk))` for `rho`, padded with the identity to a power of two
+struct MapBases<C: ProjectiveCurve> {
+    commitment: Vec<C>,
+    ciphertext_0: Vec<C>,
+    ciphertext_1: Vec<C>,
+}
+
+impl<C: ProjectiveCurve> MapBases<C> {
+    fn new(ck: &CommitKey<C>, bases: &EncryptionBases<C>, cards: &[MaskedCard<C>]) -> Self {
+        let size = map_size(cards.len());
+        let mut commitment = ck.g()[..cards.len()]
+            .iter()
+            .chain(Some(ck.h()))
+            .map(|g| g.into_projective())
+            .collect::<Vec<_>>();
+        let mut ciphertext_0 = cards
+            .iter()
+            .map(|card| card.0.into_projective())
+            .collect::<Vec<_>>();
+        let mut ciphertext_1 = cards
+            .iter()
+            .map(|card| card.1.into_projective())
+            .collect::<Vec<_>>();
+        ciphertext_0.extend([C::zero(), bases.generator.point().into_projective()]);
+        ciphertext_1.extend([C::zero(), bases.shared_key.point().into_projective()]);
+
+        commitment.resize(size, C::zero());
+        ciphertext_0.resize(size, C::zero());
+        ciphertext_1.resize(size, C::zero());
+
+        Self {
+            commitment,
+            ciphertext_0,
+            ciphertext_1,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.commitment.len()
+    }
+
+    /// The image of `values` under the bases from `start` to `end`
+    fn image(
+        &self,
+        values: &[C::ScalarField],
+        start: usize,
+        end: usize,
+    ) -> (C::Affine, MaskedCard<C>) {
+        let image = |bases: &[C]| {
+            linear_combination::<C>(
+                &C::batch_normalization_into_affine(&bases[start..end]),
+                values,
+            )
+            .into_affine()
+        };
+
+        (
+            image(&self.commitment),
+            el_gamal::Ciphertext(image(&self.ciphertext_0), image(&self.ciphertext_1)),
+        )
+    }
+
+    /// `c bases_lo + bases_hi`
+    fn fold(&mut self, c: &C::ScalarField) {
+        let c = c.into_repr();
+        for bases in [
+            &mut self.commitment,
+            &mut self.ciphertext_0,
+            &mut self.ciphertext_1,
+        ] {
+            let half = bases.len() / 2;
+            let folded = bases[..half]
+                .iter()
+                .zip(&bases[half..])
+                .map(|(lo, hi)| lo.mul(c) + hi)
+                .collect();
+            *bases = folded;
+        }
+    }
+}
+
+/// The witness is laid out as `(b, s, rho)` padded with zeros
+fn map_size(cards: usize) -> usize {
+    (cards + 2).next_power_of_two()
+}
+
+/// Prove a statement of a single row. The mask is committed through the window tables of the key
+/// and multiplied with the cards through the ladder. The masked witness reveals nothing, so the
+/// folding rounds are variable-time.
+pub(super) fn prove_log_multi_exp<C: ProjectiveCurve, D: Digest, R: Rng>(
+    rng: &mut R,
+    ck: &CommitKey<C>,
+    bases: &EncryptionBases<C>,
+    statement: &MultiExpStatement<C>,
+    witness: &MultiExpWitness<C>,
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<LogMultiExpProof<C>, CryptoError> {
+    let cards = &statement.rows[0];
+    let mask = sample_scalars::<C, R>(rng, cards.len() + 2);
+    let (mask_exponents, mask_randomness) = mask.split_at(cards.len());
+
+    let mut mask_ciphertext = (
+        bases.generator.mul_secret(&mask_randomness[1]),
+        bases.shared_key.mul_secret(&mask_randomness[1]),
+    );
+    for (card, a) in cards.iter().zip(mask_exponents) {
+        mask_ciphertext.0 += ladder_mul::<C>(&card.0, a);
+        mask_ciphertext.1 += ladder_mul::<C>(&card.1, a);
+    }
+    let mask_points = C::batch_normalization_into_affine(&[
+        ck.commit(mask_exponents, &mask_randomness[0]),
+        mask_ciphertext.0,
+        mask_ciphertext.1,
+    ]);
+    let mask_commitment = mask_points[0];
+    let mask_ciphertext = el_gamal::Ciphertext(mask_points[1], mask_points[2]);
+
+    absorb_points::<C, D>(fs_rng, &mask_points)?;
+    let e = C::ScalarField::rand(fs_rng);
+
+    let mut map_bases = MapBases::new(ck, bases, cards);
+    let mut z = witness.exponents[0]
+        .iter()
+        .chain([&witness.randomness[0], witness.rho])
+        .zip(&mask)
+        .map(|(w, mask)| e * w + mask)
+        .collect::<Vec<_>>();
+    z.resize(map_bases.len(), C::ScalarField::zero());
+
+    let mut left_commitments = Vec::new();
+    let mut left_ciphertexts = Vec::new();
+    let mut right_commitments = Vec::new();
+    let mut right_ciphertexts = Vec::new();
+    while z.len() > 1 {
+        let half = z.len() / 2;
+        let (left_commitment, left_ciphertext) = map_bases.image(&z[..half], half, 2 * half);
+        let (right_commitment, right_ciphertext) = map_bases.image(&z[half..], 0, half);
+
+        let c = folding_challenge::<C, D>(
+            (&left_commitment, &left_ciphertext),
+            (&right_commitment, &right_ciphertext),
+            fs_rng,
+        )?;
+        z = z[..half]
+            .iter()
+            .zip(&z[half..])
+            .map(|(lo, hi)| c * hi + lo)
+            .collect();
+        map_bases.fold(&c);
+
+        left_commitments.push(left_commitment);
+        left_ciphertexts.push(left_ciphertext);
+        right_commitments.push(right_commitment);
+        right_ciphertexts.push(right_ciphertext);
+    }
+
+    Ok(LogMultiExpProof {
+        mask_commitment,
+        mask_ciphertext,
+        left_commitments,
+        left_ciphertexts,
+        right_commitments,
+        right_ciphertexts,
+        z: z[0],
+    })
+}
+
+pub(super) fn verify_log_multi_exp<C: ProjectiveCurve, D: Digest>(
+    ck: &CommitKey<C>,
+    bases: &EncryptionBases<C>,
+    statement: &MultiExpStatement<C>,
+    proof: &LogMultiExpProof<C>,
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<(), CryptoError> {
+    if statement.rows.len() != 1 || statement.commitments.len() != 1 {
+        return Err(log_multi_exp_error());
+    }
+    let cards = &statement.rows[0];
+    let size = map_size(cards.len());
+    let rounds = size.trailing_zeros() as usize;
+    if ck.max_len() < cards.len()
+        || proof.left_commitments.len() != rounds
+        || proof.left_ciphertexts.len() != rounds
+        || proof.right_commitments.len() != rounds
+        || proof.right_ciphertexts.len() != rounds
+    {
+        return Err(log_multi_exp_error());
+    }
+
+    absorb_points::<C, D>(
+        fs_rng,
+        &[
+            proof.mask_commitment,
+            proof.mask_ciphertext.0,
+            proof.mask_ciphertext.1,
+        ],
+    )?;
+    let e = C::ScalarField::rand(fs_rng);
+    let e_repr = e.into_repr();
+
+    let mut challenges = Vec::with_capacity(rounds);
+    for k in 0..rounds {
+        challenges.push(folding_challenge::<C, D>(
+            (&proof.left_commitments[k], &proof.left_ciphertexts[k]),
+            (&proof.right_commitments[k], &proof.right_ciphertexts[k]),
+            fs_rng,
+        )?);
+    }
+
+    // The statement masked and folded, against the folded bases weighed by z
+    let weights = folding_weights(&challenges, true)
+        .iter()
+        .map(|weight| proof.z * weight)
+        .collect::<Vec<_>>();
+    let (card_weights, other_weights) = weights.split_at(cards.len());
+    let components = |ciphertexts: &[MaskedCard<C>]| {
+        ciphertexts
+            .iter()
+            .map(|ciphertext| (ciphertext.0, ciphertext.1))
+            .unzip::<_, _, Vec<_>, Vec<_>>()
+    };
+    let (left_0, left_1) = components(&proof.left_ciphertexts);
+    let (right_0, right_1) = components(&proof.right_ciphertexts);
+
+    let statement_commitment = fold_commitment(
+        statement.commitments[0]
+            .mul(e_repr)
+            .add_mixed(&proof.mask_commitment),
+        &proof.left_commitments,
+        &proof.right_commitments,
+        &challenges,
+    );
+    let statement_0 = fold_commitment(
+        statement
+            .target
+            .0
+            .mul(e_repr)
+            .add_mixed(&proof.mask_ciphertext.0),
+        &left_0,
+        &right_0,
+        &challenges,
+    );
+    let statement_1 = fold_commitment(
+        statement
+            .target
+            .1
+            .mul(e_repr)
+            .add_mixed(&proof.mask_ciphertext.1),
+        &left_1,
+        &right_1,
+        &challenges,
+    );
+
+    let (cards_0, cards_1): (Vec<_>, Vec<_>) = cards.iter().map(|card| (card.0, card.1)).unzip();
+    let commitment_check = linear_combination::<C>(&ck.g()[..cards.len()], card_weights)
+        + ck.h().mul(other_weights[0].into_repr())
+        == statement_commitment;
+    let ciphertext_check = linear_combination::<C>(&cards_0, card_weights)
+        + bases.generator.mul_public(&other_weights[1])
+        == statement_0
+        && linear_combination::<C>(&cards_1, card_weights)
+            + bases.shared_key.mul_public(&other_weights[1])
+            == statement_1;
+
+    if !(commitment_check && ciphertext_check) {
+        return Err(log_multi_exp_error());
+    }
+
+    Ok(())
+}
+
+fn folding_challenge<C: ProjectiveCurve, D: Digest>(
+    left: (&C::Affine, &MaskedCard<C>),
+    right: (&C::Affine, &MaskedCard<C>),
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<C::ScalarField, CryptoError> {
+    absorb_points::<C, D>(
+        fs_rng,
+        &[
+            *left.0, left.1 .0, left.1 .1, *right.0, right.1 .0, right.1 .1,
+        ],
+    )?;
+
+    Ok(C::ScalarField::rand(fs_rng))
+}
+
+fn log_multi_exp_error() -> CryptoError {
+    CryptoError::ProofVerificationError(String::from("Logarithmic Multi-exponentiation"))
+}
@@ -0,0 +1,320 @@
+use crate::discrete_log_cards::inner_product::{
+    prove_inner_product, verify_inner_product, InnerProductBases, InnerProductProof,
+};
+use crate::discrete_log_cards::pedersen::CommitKey;
+use crate::discrete_log_cards::shuffle::{
+    absorb_points, hadamard_product, inner_product, linear_combination, powers, sample_scalars,
+};
+
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{to_bytes, Field, One, PrimeField, UniformRand, Zero};
+use ark_marlin::rng::FiatShamirRng;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use ark_std::rand::Rng;
+use digest::Digest;
+use proof_essentials::error::CryptoError;
+
+/// Degrees of the coefficients of `t(X)` committed in the proof. The coefficient of degree 2 is the
+/// one the verifier knows.
+const T_DEGREES: [usize; 5] = [1, 3, 4, 5, 6];
+
+/// Proof that the vector `d` committed in `D = <d, H> + t h` multiplies to a public product, of
+/// logarithmic size. The key holds `H`, then as many generators `G`, then a generator `g_0` for
+/// the coefficients of `t(X)`.
+///
+/// The prover commits to `d` again together with the partial products `u_k = d_1 ... d_(k - 1)` in
+/// `A = <u, G> + <d, H> + alpha h`. For challenges `w` and `zeta`, the constraints `u_1 = 1`,
+/// `u_(k + 1) = u_k d_k` and `u_n d_n = product` and the equality of the two copies of `d` are
+/// the coefficient of degree 2 of `t(X) = <l(X), r(X)>`, where `l` and `r` open
+/// `x A + x^2 D + x^3 S` over `G` and `H` scaled by the powers of `w^-1`. `D` stands on its own
+/// power of `x` so that whatever it holds on `G` does not enter that coefficient. The prover
+/// commits to the other coefficients and proves `<l(x), r(x)> = t(x)` with an inner product
+/// argument, as in the arithmetic circuits of Bulletproofs.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct LogProductProof<C: ProjectiveCurve> {
+    pub partials_commitment: C::Affine,
+    pub mask_commitment: C::Affine,
+    /// Commitments to the coefficients of `t(X)` of degree 1, 3, 4, 5 and 6
+    pub t_commitments: Vec<C::Affine>,
+    pub t: C::ScalarField,
+    pub tau: C::ScalarField,
+    pub mu: C::ScalarField,
+    pub inner_product: InnerProductProof<C>,
+}
+
+impl<C: ProjectiveCurve> LogProductProof<C> {
+    /// Every group element of the proof
+    pub(super) fn points(&self) -> impl Iterator<Item = &C::Affine> {
+        [&self.partials_commitment, &self.mask_commitment]
+            .into_iter()
+            .chain(&self.t_commitments)
+            .chain(self.inner_product.points())
+    }
+}
+
+/// The public vectors of the argument, which the prover and the verifier derive from the challenges
+struct Challenges<F: Field> {
+    /// `w, w^2, ..., w^n`
+    w: Vec<F>,
+    /// `w^-1, w^-2, ..., w^-n`
+    w_inverses: Vec<F>,
+    /// `zeta w^-1, zeta^2 w^-2, ..., zeta^n w^-n`
+    zeta: Vec<F>,
+    /// `-1, w, w^2, ..., w^(n - 1)`, the weights of `u` in the constraints
+    c: Vec<F>,
+}
+
+impl<F: Field> Challenges<F> {
+    fn new(w: F, zeta: F, size: usize) -> Option<Self> {
+        let w_inverse = w.inverse()?;
+        let w_powers = powers(&w, size + 1);
+        let w_inverses = powers(&w_inverse, size + 1)[1..].to_vec();
+        let zeta = powers(&zeta, size + 1)[1..]
+            .iter()
+            .zip(&w_inverses)
+            .map(|(zeta, w_inverse)| *zeta * w_inverse)
+            .collect();
+        let c = std::iter::once(-F::one())
+            .chain(w_powers[1..size].iter().copied())
+            .collect();
+
+        Some(Self {
+            w: w_powers[1..].to_vec(),
+            w_inverses,
+            zeta,
+            c,
+        })
+    }
+}
+
+/// The generators of `D` and `A`, those of the partial products and the generator of the
+/// coefficients of `t(X)`
+fn split_key<C: ProjectiveCurve>(
+    ck: &CommitKey<C>,
+    size: usize,
+) -> (&[C::Affine], &[C::Affine], &C::Affine) {
+    let g = ck.g();
+
+    (&g[..size], &g[size..2 * size], &g[2 * size])
+}
+
+/// Prove that `values`, committed with `randomness`, multiply to their product. Commitments to secret values go through the window tables of the key.
+pub(super) fn prove_log_product<C: ProjectiveCurve, D: Digest, R: Rng>(
+    rng: &mut R,
+    ck: &CommitKey<C>,
+    values: &[C::ScalarField],
+    randomness: &C::ScalarField,
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<LogProductProof<C>, CryptoError> {
+    let size = values.len();
+    let (h, g, g_0) = split_key(ck, size);
+
+    let mut partials = Vec::with_capacity(size);
+    let mut product = C::ScalarField::one();
+    for value in values {
+        partials.push(product);
+        product *= value;
+    }
+
+    let alpha = C::ScalarField::rand(rng);
+    let partials_commitment = ck.commit(&[values, &partials].concat(), &alpha);
+    let s_l = sample_scalars::<C, R>(rng, size);
+    let s_r = sample_scalars::<C, R>(rng, size);
+    let rho = C::ScalarField::rand(rng);
+    let mask_commitment = ck.commit(&[s_r.as_slice(), &s_l].concat(), &rho);
+    let commitments = C::batch_normalization_into_affine(&[partials_commitment, mask_commitment]);
+
+    let challenges = product_challenges::<C, D>(&commitments, size, fs_rng)?;
+    let Challenges { w, zeta, c, .. } = &challenges;
+
+    // l(X) = l_0 + l_1 X + l_3 X^3 and r(X) = r_1 X + r_2 X^2 + r_3 X^3, the coefficient l_2 is
+    // what D holds on G
+    let l_0 = zeta.iter().map(|zeta| -*zeta).collect::<Vec<_>>();
+    let l_1 = add(&partials, zeta);
+    let l_3 = s_l;
+    let r_2 = hadamard_product(w, values);
+    let r_1 = r_2.iter().zip(c).map(|(r, c)| *r - c).collect::<Vec<_>>();
+    let r_3 = hadamard_product(w, &s_r);
+
+    let t_coefficients = [
+        inner_product(&l_0, &r_1),
+        inner_product(&l_0, &r_3) + inner_product(&l_1, &r_2),
+        inner_product(&l_1, &r_3) + inner_product(&l_3, &r_1),
+        inner_product(&l_3, &r_2),
+        inner_product(&l_3, &r_3),
+    ];
+    let taus = sample_scalars::<C, R>(rng, T_DEGREES.len());
+    let t_commitments = t_coefficients
+        .iter()
+        .zip(&taus)
+        .map(|(t, tau)| ck.commit_at(2 * size, &[*t], tau))
+        .collect::<Vec<_>>();
+    let t_commitments = C::batch_normalization_into_affine(&t_commitments);
+
+    absorb_points::<C, D>(fs_rng, &t_commitments)?;
+    let x = C::ScalarField::rand(fs_rng);
+    let x_powers = powers(&x, 7);
+
+    let l = l_0
+        .iter()
+        .zip(&l_1)
+        .zip(&l_3)
+        .map(|((l_0, l_1), l_3)| *l_0 + x * l_1 + x_powers[3] * l_3)
+        .collect::<Vec<_>>();
+    let r = r_1
+        .iter()
+        .zip(&r_2)
+        .zip(&r_3)
+        .map(|((r_1, r_2), r_3)| x * r_1 + x_powers[2] * r_2 + x_powers[3] * r_3)
+        .collect::<Vec<_>>();
+    let t = inner_product(&l, &r);
+    let tau = T_DEGREES
+        .iter()
+        .zip(&taus)
+        .map(|(&degree, tau)| x_powers[degree] * tau)
+        .sum::<C::ScalarField>();
+    let mu = alpha * x + *randomness * x_powers[2] + rho * x_powers[3];
+
+    let q = value_base::<C, D>(g_0, &t, &tau, &mu, fs_rng)?;
+    let inner_product = prove_inner_product::<C, D>(
+        &InnerProductBases {
+            g,
+            h,
+            h_factors: &challenges.w_inverses,
+            q: &q,
+        },
+        l,
+        r,
+        fs_rng,
+    )?;
+
+    Ok(LogProductProof {
+        partials_commitment: commitments[0],
+        mask_commitment: commitments[1],
+        t_commitments,
+        t,
+        tau,
+        mu,
+        inner_product,
+    })
+}
+
+/// Check that the vector of `size` entries committed in `commitment` multiplies to `product`
+pub(super) fn verify_log_product<C: ProjectiveCurve, D: Digest>(
+    ck: &CommitKey<C>,
+    size: usize,
+    commitment: &C::Affine,
+    product: &C::ScalarField,
+    proof: &LogProductProof<C>,
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<(), CryptoError> {
+    if ck.max_len() < 2 * size + 1 || proof.t_commitments.len() != T_DEGREES.len() {
+        return Err(log_product_error());
+    }
+    let (h, g, g_0) = split_key(ck, size);
+
+    let challenges = product_challenges::<C, D>(
+        &[proof.partials_commitment, proof.mask_commitment],
+        size,
+        fs_rng,
+    )?;
+    let Challenges {
+        w,
+        w_inverses,
+        zeta,
+        c,
+    } = &challenges;
+
+    absorb_points::<C, D>(fs_rng, &proof.t_commitments)?;
+    let x = C::ScalarField::rand(fs_rng);
+    let x_powers = powers(&x, 7);
+
+    // The constraints weighed by the powers of w add up to w^n product + 1, the equality of the
+    // copies of d cancels out
+    let t_2 = w[size - 1] * product + C::ScalarField::one() - inner_product(zeta, c);
+    let t_degrees = T_DEGREES
+        .iter()
+        .map(|&degree| x_powers[degree])
+        .collect::<Vec<_>>();
+    if g_0.mul((proof.t - t_2 * x_powers[2]).into_repr()) + ck.h().mul(proof.tau.into_repr())
+        != linear_combination::<C>(&proof.t_commitments, &t_degrees)
+    {
+        return Err(log_product_error());
+    }
+
+    let q = value_base::<C, D>(g_0, &proof.t, &proof.tau, &proof.mu, fs_rng)?;
+
+    // x A + x^2 D + x^3 S, with the public parts of l(x) and r(x)
+    let g_scalars = zeta.iter().map(|zeta| (x - C::ScalarField::one()) * zeta);
+    let h_scalars = c
+        .iter()
+        .zip(w_inverses)
+        .map(|(c, w_inverse)| -x * c * w_inverse);
+    let points = g
+        .iter()
+        .chain(h)
+        .chain([
+            &proof.partials_commitment,
+            commitment,
+            &proof.mask_commitment,
+            ck.h(),
+            &q,
+        ])
+        .copied()
+        .collect::<Vec<_>>();
+    let scalars = g_scalars
+        .chain(h_scalars)
+        .chain([x, x_powers[2], x_powers[3], -proof.mu, proof.t])
+        .collect::<Vec<_>>();
+    let statement = linear_combination::<C>(&points, &scalars);
+
+    verify_inner_product::<C, D>(
+        &InnerProductBases {
+            g,
+            h,
+            h_factors: w_inverses,
+            q: &q,
+        },
+        statement,
+        &proof.inner_product,
+        fs_rng,
+    )
+    .map_err(|_| log_product_error())
+}
+
+fn product_challenges<C: ProjectiveCurve, D: Digest>(
+    commitments: &[C::Affine],
+    size: usize,
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<Challenges<C::ScalarField>, CryptoError> {
+    absorb_points::<C, D>(fs_rng, commitments)?;
+    let w = C::ScalarField::rand(fs_rng);
+    let zeta = C::ScalarField::rand(fs_rng);
+
+    Challenges::new(w, zeta, size).ok_or_else(log_product_error)
+}
+
+/// `Q`, a multiple of `g_0` by a challenge, on which the inner product argument carries `t(x)`
+fn value_base<C: ProjectiveCurve, D: Digest>(
+    g_0: &C::Affine,
+    t: &C::ScalarField,
+    tau: &C::ScalarField,
+    mu: &C::ScalarField,
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<C::Affine, CryptoError> {
+    fs_rng.absorb(&to_bytes![t, tau, mu]?);
+    let xi = C::ScalarField::rand(fs_rng);
+    if xi.is_zero() {
+        return Err(log_product_error());
+    }
+
+    Ok(g_0.mul(xi.into_repr()).into_affine())
+}
+
+fn add<F: Field>(a: &[F], b: &[F]) -> Vec<F> {
+    a.iter().zip(b).map(|(a, b)| *a + b).collect()
+}
+
+fn log_product_error() -> CryptoError {
+    CryptoError::ProofVerificationError(String::from("Logarithmic Product Argument"))
+}
@@ -0,0 +1,212 @@
+use crate::discrete_log_cards::constant_time::permute;
+use crate::discrete_log_cards::log_multi_exp::{
+    prove_log_multi_exp, verify_log_multi_exp, LogMultiExpProof,
+};
+use crate::discrete_log_cards::log_product::{
+    prove_log_product, verify_log_product, LogProductProof,
+};
+use crate::discrete_log_cards::multi_exp::{EncryptionBases, MultiExpStatement, MultiExpWitness};
+use crate::discrete_log_cards::pedersen::CommitKey;
+use crate::discrete_log_cards::proofs::Base;
+use crate::discrete_log_cards::shuffle::{
+    absorb_points, multi_exp_target, powers, shuffle_error, ShuffleArgument,
+};
+use crate::discrete_log_cards::validation::in_prime_order_subgroup;
+use crate::discrete_log_cards::{DLCards, MaskedCard, Parameters, LOG_SHUFFLE_RNG_SEED};
+
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{One, PrimeField, UniformRand, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use ark_std::rand::Rng;
+use digest::Digest;
+use proof_essentials::error::CryptoError;
+
+/// Proof of a shuffle of logarithmic size. The deck of `N` cards is padded to `N'`, the next power
+/// of two. As in the argument of Bayer and Groth, the prover commits to the permutation `a` and to
+/// `b = x^a` for a challenge `x`, this time in a single commitment each. The logarithmic product
+/// argument shows that the entries of `y a + b - z`, padded with ones, multiply to the product for
+/// the identity permutation, and the logarithmic multi-exponentiation argument that the shuffled
+/// deck raised to `b` is a remasking of the original deck raised to the powers of `x`.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct LogShuffleProof<C: ProjectiveCurve> {
+    pub a_commitment: C::Affine,
+    pub b_commitment: C::Affine,
+    pub product: LogProductProof<C>,
+    pub multi_exp: LogMultiExpProof<C>,
+}
+
+impl<C: ProjectiveCurve, D: Digest> DLCards<C, D> {
+    pub(super) fn prove_log_shuffle<R: Rng>(
+        rng: &mut R,
+        pp: &Parameters<C>,
+        shared_key: Base<C>,
+        deck: &[MaskedCard<C>],
+        shuffled_deck: &[MaskedCard<C>],
+        masking_factors: &[C::ScalarField],
+        positions: &[usize],
+    ) -> Result<LogShuffleProof<C>, CryptoError> {
+        let size = deck.len();
+        let ck = &pp.commit_parameters;
+        let mut fs_rng = Self::shuffle_transcript(
+            LOG_SHUFFLE_RNG_SEED,
+            pp,
+            shared_key.point(),
+            deck,
+            shuffled_deck,
+        )?;
+
+        // a_k is the position in the original deck of the k-th card of the shuffled deck, from 1
+        let indices = (1..=size)
+            .map(|i| C::ScalarField::from(i as u64))
+            .collect::<Vec<_>>();
+        let a = permute(&indices, positions);
+        let r = C::ScalarField::rand(rng);
+        let a_commitment = ck.commit(&a, &r).into_affine();
+
+        absorb_points::<C, D>(&mut fs_rng, &[a_commitment])?;
+        let x = C::ScalarField::rand(&mut fs_rng);
+
+        let x_powers = powers(&x, size + 1);
+        let b = permute(&x_powers[1..], positions);
+        let s = C::ScalarField::rand(rng);
+        let b_commitment = ck.commit(&b, &s).into_affine();
+
+        absorb_points::<C, D>(&mut fs_rng, &[b_commitment])?;
+        let y = C::ScalarField::rand(&mut fs_rng);
+        let z = C::ScalarField::rand(&mut fs_rng);
+
+        let mut d = a
+            .iter()
+            .zip(&b)
+            .map(|(a, b)| y * a + b - z)
+            .collect::<Vec<_>>();
+        d.resize(size.next_power_of_two(), C::ScalarField::one());
+        let product = prove_log_product::<C, D, R>(rng, ck, &d, &(y * r + s), &mut fs_rng)?;
+
+        let rho = -b
+            .iter()
+            .zip(masking_factors)
+            .map(|(b, alpha)| *b * alpha)
+            .sum::<C::ScalarField>();
+        let target = multi_exp_target(deck, &x_powers[1..]);
+        let multi_exp = prove_log_multi_exp::<C, D, R>(
+            rng,
+            ck,
+            &EncryptionBases {
+                generator: pp.generator_base(),
+                shared_key,
+                message_generator: &pp.generator.0,
+            },
+            &MultiExpStatement {
+                rows: &[shuffled_deck.to_vec()],
+                target: &target,
+                commitments: &[b_commitment],
+            },
+            &MultiExpWitness {
+                exponents: &[b],
+                randomness: &[s],
+                rho: &rho,
+            },
+            &mut fs_rng,
+        )?;
+
+        Ok(LogShuffleProof {
+            a_commitment,
+            b_commitment,
+            product,
+            multi_exp,
+        })
+    }
+
+    pub(super) fn verify_log_shuffle(
+        pp: &Parameters<C>,
+        shared_key: Base<C>,
+        original_deck: &[MaskedCard<C>],
+        shuffled_deck: &[MaskedCard<C>],
+        proof: &LogShuffleProof<C>,
+    ) -> Result<(), CryptoError> {
+        let size = pp.m * pp.n;
+        let ck = &pp.commit_parameters;
+        let well_formed = original_deck.len() == size
+            && shuffled_deck.len() == size
+            && ck.max_len() >= ShuffleArgument::Logarithmic.commit_key_len(pp.m, pp.n)
+            && proof_points(proof).all(in_prime_order_subgroup::<C>);
+        if !well_formed {
+            return Err(shuffle_error());
+        }
+
+        let mut fs_rng = Self::shuffle_transcript(
+            LOG_SHUFFLE_RNG_SEED,
+            pp,
+            shared_key.point(),
+            original_deck,
+            shuffled_deck,
+        )?;
+        absorb_points::<C, D>(&mut fs_rng, &[proof.a_commitment])?;
+        let x = C::ScalarField::rand(&mut fs_rng);
+        absorb_points::<C, D>(&mut fs_rng, &[proof.b_commitment])?;
+        let y = C::ScalarField::rand(&mut fs_rng);
+        let z = C::ScalarField::rand(&mut fs_rng);
+
+        let x_powers = powers(&x, size + 1);
+        let product = x_powers[1..]
+            .iter()
+            .enumerate()
+            .map(|(k, power)| y * C::ScalarField::from((k + 1) as u64) + power - z)
+            .product::<C::ScalarField>();
+        let d_commitment =
+            product_statement(ck, size, &proof.a_commitment, &proof.b_commitment, &y, &z);
+        verify_log_product::<C, D>(
+            ck,
+            size.next_power_of_two(),
+            &d_commitment,
+            &product,
+            &proof.product,
+            &mut fs_rng,
+        )?;
+
+        let target = multi_exp_target(original_deck, &x_powers[1..]);
+        verify_log_multi_exp::<C, D>(
+            ck,
+            &EncryptionBases {
+                generator: pp.generator_base(),
+                shared_key,
+                message_generator: &pp.generator.0,
+            },
+            &MultiExpStatement {
+                rows: &[shuffled_deck.to_vec()],
+                target: &target,
+                commitments: &[proof.b_commitment],
+            },
+            &proof.multi_exp,
+            &mut fs_rng,
+        )
+    }
+}
+
+/// Commitment to `y a + b - z` for the `size` cards, padded with ones to a power of two
+fn product_statement<C: ProjectiveCurve>(
+    ck: &CommitKey<C>,
+    size: usize,
+    a_commitment: &C::Affine,
+    b_commitment: &C::Affine,
+    y: &C::ScalarField,
+    z: &C::ScalarField,
+) -> C::Affine {
+    let mut shift = vec![-*z; size];
+    shift.resize(size.next_power_of_two(), C::ScalarField::one());
+
+    (a_commitment.mul(y.into_repr()) + ck.commit_public(&shift, &C::ScalarField::zero()))
+        .add_mixed(b_commitment)
+        .into_affine()
+}
+
+/// Every group element of the proof
+fn proof_points<C: ProjectiveCurve>(
+    proof: &LogShuffleProof<C>,
+) -> impl Iterator<Item = &C::Affine> {
+    [&proof.a_commitment, &proof.b_commitment]
+        .into_iter()
+        .chain(proof.product.points())
+        .chain(proof.multi_exp.points())
+}
@@ -1,11 +1,10 @@
+use crate::discrete_log_cards::constant_time::ladder_mul;
 use crate::discrete_log_cards::Card;
 use crate::error::CardProtocolError;
 use crate::Mask;
 
 use ark_ec::ProjectiveCurve;
-use proof_essentials::homomorphic_encryption::{
-    el_gamal, el_gamal::ElGamal, HomomorphicEncryptionScheme,
-};
+use proof_essentials::homomorphic_encryption::{el_gamal, el_gamal::ElGamal};
 
 impl<C: ProjectiveCurve> Mask<C::ScalarField, ElGamal<C>> for Card<C> {
     fn mask(
@@ -14,7 +13,13 @@ impl<C: ProjectiveCurve> Mask<C::ScalarField, ElGamal<C>> for Card<C> {
         shared_key: &el_gamal::PublicKey<C>,
         r: &C::ScalarField,
     ) -> Result<el_gamal::Ciphertext<C>, CardProtocolError> {
-        let ciphertext = ElGamal::<C>::encrypt(pp, shared_key, self, r)?;
+        // ElGamal encryption, with the multiplications by `r` done in constant time
+        let ciphertext = el_gamal::Ciphertext(
+            ladder_mul::<C>(&pp.generator, r).into_affine(),
+            ladder_mul::<C>(shared_key, r)
+                .add_mixed(&self.0)
+                .into_affine(),
+        );
         Ok(ciphertext)
     }
 }
@@ -22,12 +27,12 @@ impl<C: ProjectiveCurve> Mask<C::ScalarField, ElGamal<C>> for Card<C> {
 #[cfg(test)]
 mod test {
     use crate::discrete_log_cards;
+    use crate::error::CardProtocolError;
     use crate::BarnettSmartProtocol;
 
     use ark_ff::UniformRand;
     use ark_std::{rand::Rng, Zero};
     use proof_essentials::error::CryptoError;
-    use proof_essentials::zkp::proofs::chaum_pedersen_dl_equality;
     use rand::thread_rng;
 
     // Choose elliptic curve setting
@@ -43,7 +48,7 @@ mod test {
     type Card = discrete_log_cards::Card<Curve>;
     type MaskedCard = discrete_log_cards::MaskedCard<Curve>;
 
-    type MaskingProof = chaum_pedersen_dl_equality::proof::Proof<Curve>;
+    type MaskingProof = discrete_log_cards::DLEqualityProof<Curve>;
 
     fn setup_players<R: Rng>(
         rng: &mut R,
@@ -100,9 +105,9 @@ mod test {
                 &wrong_masked,
                 &masking_proof
             ),
-            Err(CryptoError::ProofVerificationError(String::from(
-                "Chaum-Pedersen"
-            )))
+            Err(CardProtocolError::ProofVerificationError(
+                CryptoError::ProofVerificationError(String::from("Chaum-Pedersen"))
+            ))
         )
     }
 }
@@ -1,45 +1,100 @@
 use super::BarnettSmartProtocol;
-use super::{Mask, Remask, Reveal};
+use super::Reveal;
 
 use crate::error::CardProtocolError;
+use constant_time::ladder_mul;
+use hash_to_curve::hash_to_curve;
+use once_cell::sync::OnceCell;
+use proofs::Base;
 
 use anyhow::Result;
 use ark_ec::{AffineCurve, ProjectiveCurve};
-use ark_ff::{to_bytes, One, PrimeField, ToBytes};
-use ark_marlin::rng::FiatShamirRng;
+use ark_ff::{to_bytes, One, ToBytes, UniformRand};
 use ark_std::rand::Rng;
 use ark_std::Zero;
 use blake2::Blake2s;
+use digest::Digest;
 use proof_essentials::error::CryptoError;
 use proof_essentials::homomorphic_encryption::{
     el_gamal, el_gamal::ElGamal, HomomorphicEncryptionScheme,
 };
 use proof_essentials::utils::permutation::Permutation;
 use proof_essentials::vector_commitment::pedersen::PedersenCommitment;
-use proof_essentials::vector_commitment::{pedersen, HomomorphicCommitmentScheme};
-use proof_essentials::zkp::{
-    arguments::shuffle,
-    proofs::{chaum_pedersen_dl_equality, schnorr_identification},
-    ArgumentOfKnowledge,
-};
 use std::marker::PhantomData;
+use std::sync::{Arc, Mutex, PoisonError};
 
 // mod key_ownership;
+mod adversarial_tests;
+mod constant_time;
+#[cfg(feature = "r1cs")]
+pub mod constraints;
+mod cut;
+mod deck_state;
+#[cfg(feature = "evm")]
+pub mod evm;
+mod fixed_base;
+mod hash_to_curve;
+mod inner_product;
+mod log_multi_exp;
+mod log_product;
+mod log_shuffle;
 mod masking;
+mod multi_exp;
+mod one_of_many;
+mod pedersen;
+pub mod predicates;
+mod product_argument;
+mod proofs;
+mod public_reveal;
 mod remasking;
 mod reveal;
+mod shuffle;
+mod shuffle_chain;
+pub mod starknet;
 mod tests;
-
-pub struct DLCards<C: ProjectiveCurve> {
+mod token_store;
+pub mod transcript;
+mod validation;
+
+pub use cut::CutProof;
+pub use deck_state::{DeckState, DECK_STATE_VERSION};
+pub use fixed_base::{FixedBaseTable, PrecomputedKey};
+pub use inner_product::InnerProductProof;
+pub use log_multi_exp::LogMultiExpProof;
+pub use log_product::LogProductProof;
+pub use log_shuffle::LogShuffleProof;
+pub use multi_exp::{MultiExpMode, MultiExpProof};
+pub use one_of_many::OneOfManyProof;
+pub use pedersen::CommitKey;
+pub use product_argument::{
+    HadamardProof, ProductProof, SingleValueProductProof, ZeroArgumentProof,
+};
+pub use proofs::{DLEqualityProof, SchnorrProof};
+pub use public_reveal::OpenedCard;
+pub use shuffle::{BayerGrothProof, ShuffleArgument, ShuffleProof};
+pub use shuffle_chain::ShuffleChain;
+pub use token_store::RevealTokenStore;
+
+/// The Discrete Log-based implementation of the Barnett-Smart card protocol. `D` is the hash
+/// function used by the Fiat-Shamir transform of every proof; see the `transcript` module for the
+/// available backends.
+pub struct DLCards<C: ProjectiveCurve, D: Digest = Blake2s> {
     _group: PhantomData<&'static C>,
+    _transcript: PhantomData<D>,
 }
 
 pub struct Parameters<C: ProjectiveCurve> {
     m: usize,
     n: usize,
     enc_parameters: el_gamal::Parameters<C>,
-    commit_parameters: pedersen::CommitKey<C>,
+    commit_parameters: CommitKey<C>,
     generator: el_gamal::Generator<C>,
+    shuffle_argument: ShuffleArgument,
+    multi_exp_mode: MultiExpMode,
+    // Built on first use by the provers and verifiers multiplying by the encryption generator
+    generator_table: OnceCell<FixedBaseTable<C>>,
+    // The table of the last shared key used by the trait methods, rebuilt when the key changes
+    key_table: Mutex<Option<Arc<PrecomputedKey<C>>>>,
 }
 
 impl<C: ProjectiveCurve> Parameters<C> {
@@ -47,7 +102,7 @@ impl<C: ProjectiveCurve> Parameters<C> {
         m: usize,
         n: usize,
         enc_parameters: el_gamal::Parameters<C>,
-        commit_parameters: pedersen::CommitKey<C>,
+        commit_parameters: CommitKey<C>,
         generator: el_gamal::Generator<C>,
     ) -> Self {
         Self {
@@ -56,6 +111,55 @@ impl<C: ProjectiveCurve> Parameters<C> {
             enc_parameters,
             commit_parameters,
             generator,
+            shuffle_argument: ShuffleArgument::BayerGroth,
+            multi_exp_mode: MultiExpMode::Direct,
+            generator_table: OnceCell::new(),
+            key_table: Mutex::new(None),
+        }
+    }
+
+    /// Select how `shuffle_and_remask` computes the multi-exponentiation argument of Bayer-Groth
+    /// proofs. The proofs are the same in both modes and `verify_shuffle` checks them the same way.
+    pub fn with_multi_exp_mode(mut self, multi_exp_mode: MultiExpMode) -> Self {
+        self.multi_exp_mode = multi_exp_mode;
+        self
+    }
+
+    /// The argument proving and checking shuffles, chosen at setup
+    pub fn shuffle_argument(&self) -> ShuffleArgument {
+        self.shuffle_argument
+    }
+
+    /// The generator of the El-Gamal encryption scheme, against which players' keys are defined
+    pub fn encryption_generator(&self) -> &C::Affine {
+        &self.enc_parameters.generator
+    }
+
+    /// The window table of the encryption generator, built on first use
+    pub(super) fn generator_table(&self) -> &FixedBaseTable<C> {
+        self.generator_table
+            .get_or_init(|| FixedBaseTable::new(&self.enc_parameters.generator, self.m * self.n))
+    }
+
+    fn generator_base(&self) -> Base<'_, C> {
+        Base::Table(self.generator_table())
+    }
+
+    /// The window table of `shared_key`. The table of the last key is kept, so the masks, remasks,
+    /// shuffles and cuts of a hand, and the checks of their proofs, share the table of its
+    /// aggregate key.
+    pub(super) fn key_table(&self, shared_key: &PublicKey<C>) -> Arc<PrecomputedKey<C>> {
+        let mut cached = self
+            .key_table
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        match cached.as_ref() {
+            Some(key_table) if key_table.key() == shared_key => key_table.clone(),
+            _ => {
+                let key_table = Arc::new(PrecomputedKey::new(shared_key, self.m * self.n));
+                *cached = Some(key_table.clone());
+                key_table
+            }
         }
     }
 }
@@ -77,13 +181,23 @@ pub type MaskedCard<C> = el_gamal::Ciphertext<C>;
 /// then be aggregated to reveal the card.
 pub type RevealToken<C> = el_gamal::Plaintext<C>;
 
+/// Domain tag for `setup_from_domain`, for tables with no tag of their own
+pub const SETUP_DOMAIN: &'static [u8] = b"Barnett-Smart Card Protocol";
+
 const KEY_OWN_RNG_SEED: &'static [u8] = b"Key Ownership Proof";
 const MASKING_RNG_SEED: &'static [u8] = b"Masking Proof";
 const REMASKING_RNG_SEED: &'static [u8] = b"Remasking Proof";
 const REVEAL_RNG_SEED: &'static [u8] = b"Reveal Proof";
 const SHUFFLE_RNG_SEED: &'static [u8] = b"Shuffle Proof";
-
-impl<C: ProjectiveCurve> BarnettSmartProtocol for DLCards<C> {
+const LOG_SHUFFLE_RNG_SEED: &'static [u8] = b"Logarithmic Shuffle Proof";
+const ADD_KEY_RNG_SEED: &'static [u8] = b"Add Key Proof";
+const REMOVE_KEY_RNG_SEED: &'static [u8] = b"Remove Key Proof";
+const CUT_RNG_SEED: &'static [u8] = b"Cut Proof";
+const PREDICATE_RNG_SEED: &'static [u8] = b"Predicate Proof";
+const SELECTION_RNG_SEED: &'static [u8] = b"Selection Proof";
+const BATCH_RNG_SEED: &'static [u8] = b"Batch Verification";
+
+impl<C: ProjectiveCurve, D: Digest> BarnettSmartProtocol for DLCards<C, D> {
     type Scalar = C::ScalarField;
     type Enc = ElGamal<C>;
     type Comm = PedersenCommitment<C>;
@@ -96,35 +210,34 @@ impl<C: ProjectiveCurve> BarnettSmartProtocol for DLCards<C> {
     type MaskedCard = MaskedCard<C>;
     type RevealToken = RevealToken<C>;
 
-    type ZKProofKeyOwnership = schnorr_identification::proof::Proof<C>;
-    type ZKProofMasking = chaum_pedersen_dl_equality::proof::Proof<C>;
-    type ZKProofRemasking = chaum_pedersen_dl_equality::proof::Proof<C>;
-    type ZKProofReveal = chaum_pedersen_dl_equality::proof::Proof<C>;
-    type ZKProofShuffle = shuffle::proof::Proof<Self::Scalar, Self::Enc, Self::Comm>;
-
+    type ZKProofKeyOwnership = SchnorrProof<C>;
+    type ZKProofMasking = DLEqualityProof<C>;
+    type ZKProofRemasking = DLEqualityProof<C>;
+    type ZKProofReveal = DLEqualityProof<C>;
+    type ZKProofShuffle = ShuffleProof<C>;
+    type ZKProofRekey = DLEqualityProof<C>;
+    type ZKProofCut = CutProof<C>;
+
+    /// Sample the generators from `rng`. Whoever runs the setup could know discrete log relations
+    /// between them, so players who do not trust each other should derive the parameters with
+    /// `setup_from_domain` instead. Shuffles are proven with the argument of Bayer and Groth, see
+    /// `setup_with_argument` for the logarithmic one.
     fn setup<R: Rng>(
         rng: &mut R,
         m: usize,
         n: usize,
     ) -> Result<Self::Parameters, CardProtocolError> {
-        let enc_parameters = Self::Enc::setup(rng)?;
-        let commit_parameters = Self::Comm::setup(rng, n);
-        let generator = Self::Enc::generator(rng)?;
-
-        Ok(Self::Parameters::new(
-            m,
-            n,
-            enc_parameters,
-            commit_parameters,
-            generator,
-        ))
+        Self::setup_with_argument(rng, m, n, ShuffleArgument::BayerGroth)
     }
 
+    /// The public key is computed through the window table of the generator, which multiplies the
+    /// secret key in constant time
     fn player_keygen<R: Rng>(
         rng: &mut R,
         pp: &Self::Parameters,
     ) -> Result<(Self::PlayerPublicKey, Self::PlayerSecretKey), CardProtocolError> {
-        let (pk, sk) = Self::Enc::keygen(&pp.enc_parameters, rng)?;
+        let sk = Self::Scalar::rand(rng);
+        let pk = pp.generator_table().mul(&sk).into_affine();
 
         Ok((pk, sk))
     }
@@ -136,16 +249,9 @@ impl<C: ProjectiveCurve> BarnettSmartProtocol for DLCards<C> {
         sk: &Self::PlayerSecretKey,
         player_public_info: &B,
     ) -> Result<Self::ZKProofKeyOwnership, CryptoError> {
-        let mut fs_rng =
-            FiatShamirRng::<Blake2s>::from_seed(&to_bytes![KEY_OWN_RNG_SEED, player_public_info]?);
+        let seed = to_bytes![KEY_OWN_RNG_SEED, player_public_info]?;
 
-        schnorr_identification::SchnorrIdentification::prove(
-            rng,
-            &pp.enc_parameters.generator,
-            pk,
-            sk,
-            &mut fs_rng,
-        )
+        proofs::prove_schnorr::<C, D, R>(rng, pp.generator_base(), pk, sk, &seed)
     }
 
     fn verify_key_ownership<B: ToBytes>(
@@ -153,15 +259,13 @@ impl<C: ProjectiveCurve> BarnettSmartProtocol for DLCards<C> {
         pk: &Self::PlayerPublicKey,
         player_public_info: &B,
         proof: &Self::ZKProofKeyOwnership,
-    ) -> Result<(), CryptoError> {
-        let mut fs_rng =
-            FiatShamirRng::<Blake2s>::from_seed(&to_bytes![KEY_OWN_RNG_SEED, player_public_info]?);
-        schnorr_identification::SchnorrIdentification::verify(
-            &pp.enc_parameters.generator,
-            pk,
-            proof,
-            &mut fs_rng,
-        )
+    ) -> Result<(), CardProtocolError> {
+        Self::validate_public_key(pk)?;
+
+        let seed = to_bytes![KEY_OWN_RNG_SEED, player_public_info]?;
+        proofs::verify_schnorr::<C, D>(pp.generator_base(), pk, proof, &seed)?;
+
+        Ok(())
     }
 
     fn compute_aggregate_key<B: ToBytes>(
@@ -172,6 +276,7 @@ impl<C: ProjectiveCurve> BarnettSmartProtocol for DLCards<C> {
 
         let mut acc = zero;
         for (pk, proof, player_public_info) in player_keys_proof_info {
+            Self::validate_public_key(pk)?;
             Self::verify_key_ownership(pp, pk, player_public_info, proof)?;
             acc = acc + *pk;
         }
@@ -186,29 +291,7 @@ impl<C: ProjectiveCurve> BarnettSmartProtocol for DLCards<C> {
         original_card: &Self::Card,
         r: &Self::Scalar,
     ) -> Result<(Self::MaskedCard, Self::ZKProofMasking), CardProtocolError> {
-        let masked_card = original_card.mask(&pp.enc_parameters, shared_key, r)?;
-        let gen = pp.enc_parameters.generator;
-
-        // Map to Chaum-Pedersen parameters
-        let cp_parameters = chaum_pedersen_dl_equality::Parameters::new(&gen, shared_key);
-
-        // Map to Chaum-Pedersen statement
-        let minus_one = -Self::Scalar::one();
-        let negative_original = original_card.0.mul(minus_one).into_affine();
-        let statement_cipher = masked_card.1 + negative_original;
-        let cp_statement =
-            chaum_pedersen_dl_equality::Statement::new(&masked_card.0, &statement_cipher);
-
-        let mut fs_rng = FiatShamirRng::<Blake2s>::from_seed(&to_bytes![MASKING_RNG_SEED]?);
-        let proof = chaum_pedersen_dl_equality::DLEquality::prove(
-            rng,
-            &cp_parameters,
-            &cp_statement,
-            r,
-            &mut fs_rng,
-        )?;
-
-        Ok((masked_card, proof))
+        Self::mask_precomputed(rng, pp, &pp.key_table(shared_key), original_card, r)
     }
 
     fn verify_mask(
@@ -217,25 +300,24 @@ impl<C: ProjectiveCurve> BarnettSmartProtocol for DLCards<C> {
         card: &Self::Card,
         masked_card: &Self::MaskedCard,
         proof: &Self::ZKProofMasking,
-    ) -> Result<(), CryptoError> {
+    ) -> Result<(), CardProtocolError> {
+        Self::validate_public_key(shared_key)?;
+        Self::validate_card(card)?;
+        Self::validate_masked_card(masked_card)?;
+
         // Map to Chaum-Pedersen parameters
-        let cp_parameters =
-            chaum_pedersen_dl_equality::Parameters::new(&pp.enc_parameters.generator, shared_key);
+        let key_table = pp.key_table(shared_key);
+        let cp_parameters = (pp.generator_base(), key_table.base());
 
         // Map to Chaum-Pedersen statement
         let minus_one = -Self::Scalar::one();
         let negative_original = card.0.mul(minus_one).into_affine();
         let statement_cipher = masked_card.1 + negative_original;
-        let cp_statement =
-            chaum_pedersen_dl_equality::Statement::new(&masked_card.0, &statement_cipher);
-
-        let mut fs_rng = FiatShamirRng::<Blake2s>::from_seed(&to_bytes![MASKING_RNG_SEED]?);
-        chaum_pedersen_dl_equality::DLEquality::verify(
-            &cp_parameters,
-            &cp_statement,
-            proof,
-            &mut fs_rng,
-        )
+        let cp_statement = (&masked_card.0, &statement_cipher);
+
+        proofs::verify_dl_equality::<C, D>(cp_parameters, cp_statement, proof, MASKING_RNG_SEED)?;
+
+        Ok(())
     }
 
     fn remask<R: Rng>(
@@ -245,29 +327,7 @@ impl<C: ProjectiveCurve> BarnettSmartProtocol for DLCards<C> {
         original_card: &Self::MaskedCard,
         alpha: &Self::Scalar,
     ) -> Result<(Self::MaskedCard, Self::ZKProofRemasking), CardProtocolError> {
-        let remasked = original_card.remask(&pp.enc_parameters, shared_key, alpha)?;
-
-        // Map to Chaum-Pedersen parameters
-        let cp_parameters =
-            chaum_pedersen_dl_equality::Parameters::new(&pp.enc_parameters.generator, shared_key);
-
-        // Map to Chaum-Pedersen statement
-        let minus_one = -C::ScalarField::one();
-        let negative_original = *original_card * minus_one;
-        let statement_cipher = remasked + negative_original;
-        let cp_statement =
-            chaum_pedersen_dl_equality::Statement::new(&statement_cipher.0, &statement_cipher.1);
-
-        let mut fs_rng = FiatShamirRng::<Blake2s>::from_seed(&to_bytes![REMASKING_RNG_SEED]?);
-        let proof = chaum_pedersen_dl_equality::DLEquality::prove(
-            rng,
-            &cp_parameters,
-            &cp_statement,
-            alpha,
-            &mut fs_rng,
-        )?;
-
-        Ok((remasked, proof))
+        Self::remask_precomputed(rng, pp, &pp.key_table(shared_key), original_card, alpha)
     }
 
     fn verify_remask(
@@ -276,25 +336,24 @@ impl<C: ProjectiveCurve> BarnettSmartProtocol for DLCards<C> {
         original_masked: &Self::MaskedCard,
         remasked: &Self::MaskedCard,
         proof: &Self::ZKProofRemasking,
-    ) -> Result<(), CryptoError> {
+    ) -> Result<(), CardProtocolError> {
+        Self::validate_public_key(shared_key)?;
+        Self::validate_masked_card(original_masked)?;
+        Self::validate_masked_card(remasked)?;
+
         // Map to Chaum-Pedersen parameters
-        let cp_parameters =
-            chaum_pedersen_dl_equality::Parameters::new(&pp.enc_parameters.generator, shared_key);
+        let key_table = pp.key_table(shared_key);
+        let cp_parameters = (pp.generator_base(), key_table.base());
 
         // Map to Chaum-Pedersen statement
         let minus_one = -C::ScalarField::one();
         let negative_original = *original_masked * minus_one;
         let statement_cipher = *remasked + negative_original;
-        let cp_statement =
-            chaum_pedersen_dl_equality::Statement::new(&statement_cipher.0, &statement_cipher.1);
-
-        let mut fs_rng = FiatShamirRng::<Blake2s>::from_seed(&to_bytes![REMASKING_RNG_SEED]?);
-        chaum_pedersen_dl_equality::DLEquality::verify(
-            &cp_parameters,
-            &cp_statement,
-            proof,
-            &mut fs_rng,
-        )
+        let cp_statement = (&statement_cipher.0, &statement_cipher.1);
+
+        proofs::verify_dl_equality::<C, D>(cp_parameters, cp_statement, proof, REMASKING_RNG_SEED)?;
+
+        Ok(())
     }
 
     fn compute_reveal_token<R: Rng>(
@@ -304,25 +363,20 @@ impl<C: ProjectiveCurve> BarnettSmartProtocol for DLCards<C> {
         pk: &Self::PlayerPublicKey,
         masked_card: &Self::MaskedCard,
     ) -> Result<(Self::RevealToken, Self::ZKProofReveal), CardProtocolError> {
-        let reveal_token: RevealToken<C> =
-            el_gamal::Plaintext(masked_card.0.into().mul(sk.into_repr()).into_affine());
+        let reveal_token = Self::compute_reveal_token_unproven(pp, sk, masked_card)?;
 
         // Map to Chaum-Pedersen parameters
-        let cp_parameters = chaum_pedersen_dl_equality::Parameters::new(
-            &masked_card.0,
-            &pp.enc_parameters.generator,
-        );
+        let cp_parameters = (Base::Point(&masked_card.0), pp.generator_base());
 
         // Map to Chaum-Pedersen parameters
-        let cp_statement = chaum_pedersen_dl_equality::Statement::new(&reveal_token.0, pk);
+        let cp_statement = (&reveal_token.0, pk);
 
-        let mut fs_rng = FiatShamirRng::<Blake2s>::from_seed(&to_bytes![REVEAL_RNG_SEED]?);
-        let proof = chaum_pedersen_dl_equality::DLEquality::prove(
+        let proof = proofs::prove_dl_equality::<C, D, R>(
             rng,
-            &cp_parameters,
-            &cp_statement,
+            cp_parameters,
+            cp_statement,
             sk,
-            &mut fs_rng,
+            REVEAL_RNG_SEED,
         )?;
 
         Ok((reveal_token, proof))
@@ -334,23 +388,33 @@ impl<C: ProjectiveCurve> BarnettSmartProtocol for DLCards<C> {
         reveal_token: &Self::RevealToken,
         masked_card: &Self::MaskedCard,
         proof: &Self::ZKProofReveal,
-    ) -> Result<(), CryptoError> {
+    ) -> Result<(), CardProtocolError> {
+        Self::validate_public_key(pk)?;
+        Self::validate_masked_card(masked_card)?;
+        Self::validate_reveal_token(reveal_token)?;
+
         // Map to Chaum-Pedersen parameters
-        let cp_parameters = chaum_pedersen_dl_equality::Parameters::new(
-            &masked_card.0,
-            &pp.enc_parameters.generator,
-        );
+        let cp_parameters = (Base::Point(&masked_card.0), pp.generator_base());
 
         // Map to Chaum-Pedersen parameters
-        let cp_statement = chaum_pedersen_dl_equality::Statement::new(&reveal_token.0, pk);
-
-        let mut fs_rng = FiatShamirRng::<Blake2s>::from_seed(&to_bytes![REVEAL_RNG_SEED]?);
-        chaum_pedersen_dl_equality::DLEquality::verify(
-            &cp_parameters,
-            &cp_statement,
-            proof,
-            &mut fs_rng,
-        )
+        let cp_statement = (&reveal_token.0, pk);
+
+        proofs::verify_dl_equality::<C, D>(cp_parameters, cp_statement, proof, REVEAL_RNG_SEED)?;
+
+        Ok(())
+    }
+
+    fn compute_reveal_token_unproven(
+        _pp: &Self::Parameters,
+        sk: &Self::PlayerSecretKey,
+        masked_card: &Self::MaskedCard,
+    ) -> Result<Self::RevealToken, CardProtocolError> {
+        Self::validate_masked_card(masked_card)?;
+
+        let reveal_token: RevealToken<C> =
+            el_gamal::Plaintext(ladder_mul::<C>(&masked_card.0, sk).into_affine());
+
+        Ok(reveal_token)
     }
 
     fn unmask(
@@ -377,6 +441,29 @@ impl<C: ProjectiveCurve> BarnettSmartProtocol for DLCards<C> {
         Ok(decrypted)
     }
 
+    fn unmask_with_own_key(
+        pp: &Self::Parameters,
+        sk: &Self::PlayerSecretKey,
+        decryption_key: &Vec<(
+            Self::RevealToken,
+            Self::ZKProofReveal,
+            Self::PlayerPublicKey,
+        )>,
+        masked_card: &Self::MaskedCard,
+    ) -> Result<Self::Card, CardProtocolError> {
+        let mut aggregate_token = Self::compute_reveal_token_unproven(pp, sk, masked_card)?;
+
+        for (token, proof, pk) in decryption_key {
+            Self::verify_reveal(pp, pk, token, masked_card, proof)?;
+
+            aggregate_token = aggregate_token + *token;
+        }
+
+        let decrypted = aggregate_token.reveal(masked_card)?;
+
+        Ok(decrypted)
+    }
+
     fn shuffle_and_remask<R: Rng>(
         rng: &mut R,
         pp: &Self::Parameters,
@@ -385,60 +472,357 @@ impl<C: ProjectiveCurve> BarnettSmartProtocol for DLCards<C> {
         masking_factors: &Vec<Self::Scalar>,
         permutation: &Permutation,
     ) -> Result<(Vec<Self::MaskedCard>, Self::ZKProofShuffle), CardProtocolError> {
-        let permuted_deck = permutation.permute_array(&deck);
-        let masked_shuffled = permuted_deck
-            .iter()
-            .zip(masking_factors.iter())
-            .map(|(masked_card, masking_factor)| {
-                masked_card.remask(&pp.enc_parameters, &shared_key, masking_factor)
-            })
-            .collect::<Result<Vec<_>, CardProtocolError>>()?;
+        Self::shuffle_and_remask_precomputed(
+            rng,
+            pp,
+            &pp.key_table(shared_key),
+            deck,
+            masking_factors,
+            permutation,
+        )
+    }
 
-        let shuffle_parameters = shuffle::Parameters::new(
-            &pp.enc_parameters,
-            shared_key,
-            &pp.commit_parameters,
-            &pp.generator,
-        );
+    fn verify_shuffle(
+        pp: &Self::Parameters,
+        shared_key: &Self::AggregatePublicKey,
+        original_deck: &Vec<Self::MaskedCard>,
+        shuffled_deck: &Vec<Self::MaskedCard>,
+        proof: &Self::ZKProofShuffle,
+    ) -> Result<(), CardProtocolError> {
+        // Decks received from peers may have any length, the shuffle argument expects m * n cards
+        if original_deck.len() != pp.m * pp.n || shuffled_deck.len() != pp.m * pp.n {
+            return Err(CryptoError::ProofVerificationError(String::from("Shuffle")).into());
+        }
+        Self::validate_public_key(shared_key)?;
+        Self::validate_deck(original_deck)?;
+        Self::validate_deck(shuffled_deck)?;
 
-        let shuffle_statement = shuffle::Statement::new(deck, &masked_shuffled, pp.m, pp.n);
+        let key_table = pp.key_table(shared_key);
+        Self::verify_shuffle_proof(pp, key_table.base(), original_deck, shuffled_deck, proof)?;
 
-        let witness = shuffle::Witness::new(permutation, masking_factors);
+        Ok(())
+    }
 
-        let mut fs_rng = FiatShamirRng::<Blake2s>::from_seed(&to_bytes![SHUFFLE_RNG_SEED]?);
-        let proof = shuffle::ShuffleArgument::prove(
+    fn cut_and_remask<R: Rng>(
+        rng: &mut R,
+        pp: &Self::Parameters,
+        shared_key: &Self::AggregatePublicKey,
+        deck: &Vec<Self::MaskedCard>,
+        masking_factors: &Vec<Self::Scalar>,
+        offset: usize,
+    ) -> Result<(Vec<Self::MaskedCard>, Self::ZKProofCut), CardProtocolError> {
+        if offset >= deck.len() {
+            return Err(CardProtocolError::InvalidCardIndex(offset));
+        }
+        if masking_factors.len() != deck.len() {
+            return Err(CardProtocolError::MaskingFactorsMismatch);
+        }
+
+        let key_table = pp.key_table(shared_key);
+        let cut_deck = deck[offset..]
+            .iter()
+            .chain(&deck[..offset])
+            .zip(masking_factors.iter())
+            .map(|(masked_card, masking_factor)| key_table.remask(pp, masked_card, masking_factor))
+            .collect::<Vec<_>>();
+
+        let proof = Self::prove_cut(
             rng,
-            &shuffle_parameters,
-            &shuffle_statement,
-            &witness,
-            &mut fs_rng,
+            pp,
+            shared_key,
+            deck,
+            &cut_deck,
+            masking_factors,
+            offset,
         )?;
 
-        Ok((masked_shuffled, proof))
+        Ok((cut_deck, proof))
     }
 
-    fn verify_shuffle(
+    fn verify_cut(
         pp: &Self::Parameters,
         shared_key: &Self::AggregatePublicKey,
         original_deck: &Vec<Self::MaskedCard>,
-        shuffled_deck: &Vec<Self::MaskedCard>,
-        proof: &Self::ZKProofShuffle,
+        cut_deck: &Vec<Self::MaskedCard>,
+        proof: &Self::ZKProofCut,
+    ) -> Result<(), CardProtocolError> {
+        Self::verify_cut_proof(pp, shared_key, original_deck, cut_deck, proof)
+    }
+
+    fn add_to_aggregate_key<B: ToBytes>(
+        pp: &Self::Parameters,
+        shared_key: &Self::AggregatePublicKey,
+        pk: &Self::PlayerPublicKey,
+        proof: &Self::ZKProofKeyOwnership,
+        player_public_info: &B,
+    ) -> Result<Self::AggregatePublicKey, CardProtocolError> {
+        Self::validate_public_key(pk)?;
+        Self::verify_key_ownership(pp, pk, player_public_info, proof)?;
+
+        Ok(*shared_key + *pk)
+    }
+
+    fn remove_from_aggregate_key(
+        _pp: &Self::Parameters,
+        shared_key: &Self::AggregatePublicKey,
+        pk: &Self::PlayerPublicKey,
+    ) -> Result<Self::AggregatePublicKey, CardProtocolError> {
+        Self::validate_public_key(pk)?;
+
+        Ok(*shared_key + (-*pk))
+    }
+
+    fn add_key_to_deck<R: Rng>(
+        rng: &mut R,
+        pp: &Self::Parameters,
+        sk: &Self::PlayerSecretKey,
+        pk: &Self::PlayerPublicKey,
+        deck: &Vec<Self::MaskedCard>,
+    ) -> Result<(Vec<Self::MaskedCard>, Vec<Self::ZKProofRekey>), CardProtocolError> {
+        let mut rekeyed_deck = Vec::with_capacity(deck.len());
+        let mut proofs = Vec::with_capacity(deck.len());
+
+        for masked_card in deck {
+            let (rekey_token, proof) =
+                Self::prove_rekey_token(rng, pp, sk, pk, masked_card, ADD_KEY_RNG_SEED)?;
+
+            rekeyed_deck.push(el_gamal::Ciphertext(
+                masked_card.0,
+                masked_card.1 + rekey_token.0,
+            ));
+            proofs.push(proof);
+        }
+
+        Ok((rekeyed_deck, proofs))
+    }
+
+    fn verify_add_key_to_deck(
+        pp: &Self::Parameters,
+        pk: &Self::PlayerPublicKey,
+        original_deck: &Vec<Self::MaskedCard>,
+        rekeyed_deck: &Vec<Self::MaskedCard>,
+        proofs: &Vec<Self::ZKProofRekey>,
+    ) -> Result<(), CardProtocolError> {
+        if original_deck.len() != rekeyed_deck.len() || original_deck.len() != proofs.len() {
+            return Err(CryptoError::ProofVerificationError(String::from("Rekey")).into());
+        }
+        Self::validate_public_key(pk)?;
+        Self::validate_deck(original_deck)?;
+        Self::validate_deck(rekeyed_deck)?;
+
+        for ((original, rekeyed), proof) in original_deck.iter().zip(rekeyed_deck).zip(proofs) {
+            // Adding a key leaves the randomness of the ciphertext untouched
+            if original.0 != rekeyed.0 {
+                return Err(CryptoError::ProofVerificationError(String::from("Rekey")).into());
+            }
+
+            let rekey_token = el_gamal::Plaintext(rekeyed.1 + (-original.1));
+            Self::verify_rekey_token(pp, pk, &rekey_token, original, proof, ADD_KEY_RNG_SEED)?;
+        }
+
+        Ok(())
+    }
+
+    fn remove_key_from_deck<R: Rng>(
+        rng: &mut R,
+        pp: &Self::Parameters,
+        sk: &Self::PlayerSecretKey,
+        pk: &Self::PlayerPublicKey,
+        deck: &Vec<Self::MaskedCard>,
+    ) -> Result<(Vec<Self::MaskedCard>, Vec<Self::ZKProofRekey>), CardProtocolError> {
+        let mut rekeyed_deck = Vec::with_capacity(deck.len());
+        let mut proofs = Vec::with_capacity(deck.len());
+
+        for masked_card in deck {
+            let (rekey_token, proof) =
+                Self::prove_rekey_token(rng, pp, sk, pk, masked_card, REMOVE_KEY_RNG_SEED)?;
+
+            rekeyed_deck.push(el_gamal::Ciphertext(
+                masked_card.0,
+                masked_card.1 + (-rekey_token.0),
+            ));
+            proofs.push(proof);
+        }
+
+        Ok((rekeyed_deck, proofs))
+    }
+
+    fn verify_remove_key_from_deck(
+        pp: &Self::Parameters,
+        pk: &Self::PlayerPublicKey,
+        original_deck: &Vec<Self::MaskedCard>,
+        rekeyed_deck: &Vec<Self::MaskedCard>,
+        proofs: &Vec<Self::ZKProofRekey>,
+    ) -> Result<(), CardProtocolError> {
+        if original_deck.len() != rekeyed_deck.len() || original_deck.len() != proofs.len() {
+            return Err(CryptoError::ProofVerificationError(String::from("Rekey")).into());
+        }
+        Self::validate_public_key(pk)?;
+        Self::validate_deck(original_deck)?;
+        Self::validate_deck(rekeyed_deck)?;
+
+        for ((original, rekeyed), proof) in original_deck.iter().zip(rekeyed_deck).zip(proofs) {
+            // Removing a key leaves the randomness of the ciphertext untouched
+            if original.0 != rekeyed.0 {
+                return Err(CryptoError::ProofVerificationError(String::from("Rekey")).into());
+            }
+
+            let rekey_token = el_gamal::Plaintext(original.1 + (-rekeyed.1));
+            Self::verify_rekey_token(pp, pk, &rekey_token, original, proof, REMOVE_KEY_RNG_SEED)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<C: ProjectiveCurve, D: Digest> DLCards<C, D> {
+    /// Derive the parameters for decks of `m * n` cards from a public domain tag. Every generator
+    /// is hashed to the curve, so anyone can recompute the parameters and nobody knows a discrete
+    /// log relation between the generators. Nothing has to be trusted or kept secret: tables only
+    /// need to agree on the tag.
+    pub fn setup_from_domain(
+        domain: &[u8],
+        m: usize,
+        n: usize,
+    ) -> Result<Parameters<C>, CardProtocolError> {
+        Self::setup_from_domain_with_argument(domain, m, n, ShuffleArgument::BayerGroth)
+    }
+
+    /// `setup`, with shuffles proven and checked by `argument`. The commitment key holds as many
+    /// generators as the argument needs.
+    pub fn setup_with_argument<R: Rng>(
+        rng: &mut R,
+        m: usize,
+        n: usize,
+        argument: ShuffleArgument,
+    ) -> Result<Parameters<C>, CardProtocolError> {
+        let enc_parameters = ElGamal::<C>::setup(rng)?;
+        let commit_parameters = CommitKey::setup(rng, argument.commit_key_len(m, n));
+        let generator = ElGamal::<C>::generator(rng)?;
+
+        Ok(Parameters {
+            shuffle_argument: argument,
+            ..Parameters::new(m, n, enc_parameters, commit_parameters, generator)
+        })
+    }
+
+    /// `setup_from_domain`, with shuffles proven and checked by `argument`
+    pub fn setup_from_domain_with_argument(
+        domain: &[u8],
+        m: usize,
+        n: usize,
+        argument: ShuffleArgument,
+    ) -> Result<Parameters<C>, CardProtocolError> {
+        let enc_parameters = el_gamal::Parameters {
+            generator: hash_to_curve::<C, D>(domain, b"encryption generator", 0),
+        };
+        let commit_parameters = CommitKey::derive::<D>(domain, argument.commit_key_len(m, n));
+        let generator = el_gamal::Generator(hash_to_curve::<C, D>(domain, b"message generator", 0));
+
+        Ok(Parameters {
+            shuffle_argument: argument,
+            ..Parameters::new(m, n, enc_parameters, commit_parameters, generator)
+        })
+    }
+
+    fn prove_masking<R: Rng>(
+        rng: &mut R,
+        pp: &Parameters<C>,
+        shared_key: Base<C>,
+        original_card: &Card<C>,
+        masked_card: &MaskedCard<C>,
+        r: &C::ScalarField,
+    ) -> Result<DLEqualityProof<C>, CardProtocolError> {
+        // Map to Chaum-Pedersen parameters
+        let cp_parameters = (pp.generator_base(), shared_key);
+
+        // Map to Chaum-Pedersen statement
+        let minus_one = -C::ScalarField::one();
+        let negative_original = original_card.0.mul(minus_one).into_affine();
+        let statement_cipher = masked_card.1 + negative_original;
+        let cp_statement = (&masked_card.0, &statement_cipher);
+
+        let proof = proofs::prove_dl_equality::<C, D, R>(
+            rng,
+            cp_parameters,
+            cp_statement,
+            r,
+            MASKING_RNG_SEED,
+        )?;
+
+        Ok(proof)
+    }
+
+    fn prove_remasking<R: Rng>(
+        rng: &mut R,
+        pp: &Parameters<C>,
+        shared_key: Base<C>,
+        original_card: &MaskedCard<C>,
+        remasked: &MaskedCard<C>,
+        alpha: &C::ScalarField,
+    ) -> Result<DLEqualityProof<C>, CardProtocolError> {
+        // Map to Chaum-Pedersen parameters
+        let cp_parameters = (pp.generator_base(), shared_key);
+
+        // Map to Chaum-Pedersen statement
+        let minus_one = -C::ScalarField::one();
+        let negative_original = *original_card * minus_one;
+        let statement_cipher = *remasked + negative_original;
+        let cp_statement = (&statement_cipher.0, &statement_cipher.1);
+
+        let proof = proofs::prove_dl_equality::<C, D, R>(
+            rng,
+            cp_parameters,
+            cp_statement,
+            alpha,
+            REMASKING_RNG_SEED,
+        )?;
+
+        Ok(proof)
+    }
+
+    /// Compute `sk * c_0` for a masked card `(c_0, c_1)` together with a proof that it was computed
+    /// with the secret key matching `pk`. Adding (resp. subtracting) this point to `c_1` adds (resp.
+    /// removes) `pk` to the key under which the card is masked.
+    fn prove_rekey_token<R: Rng>(
+        rng: &mut R,
+        pp: &Parameters<C>,
+        sk: &PlayerSecretKey<C>,
+        pk: &PublicKey<C>,
+        masked_card: &MaskedCard<C>,
+        seed: &[u8],
+    ) -> Result<(RevealToken<C>, DLEqualityProof<C>), CardProtocolError> {
+        Self::validate_masked_card(masked_card)?;
+
+        let rekey_token: RevealToken<C> =
+            el_gamal::Plaintext(ladder_mul::<C>(&masked_card.0, sk).into_affine());
+
+        // Map to Chaum-Pedersen parameters
+        let cp_parameters = (Base::Point(&masked_card.0), pp.generator_base());
+
+        // Map to Chaum-Pedersen statement
+        let cp_statement = (&rekey_token.0, pk);
+
+        let proof =
+            proofs::prove_dl_equality::<C, D, R>(rng, cp_parameters, cp_statement, sk, seed)?;
+
+        Ok((rekey_token, proof))
+    }
+
+    fn verify_rekey_token(
+        pp: &Parameters<C>,
+        pk: &PublicKey<C>,
+        rekey_token: &RevealToken<C>,
+        masked_card: &MaskedCard<C>,
+        proof: &DLEqualityProof<C>,
+        seed: &[u8],
     ) -> Result<(), CryptoError> {
-        let shuffle_parameters = shuffle::Parameters::new(
-            &pp.enc_parameters,
-            shared_key,
-            &pp.commit_parameters,
-            &pp.generator,
-        );
-
-        let shuffle_statement = shuffle::Statement::new(original_deck, shuffled_deck, pp.m, pp.n);
-
-        let mut fs_rng = FiatShamirRng::<Blake2s>::from_seed(&to_bytes![SHUFFLE_RNG_SEED]?);
-        shuffle::ShuffleArgument::verify(
-            &shuffle_parameters,
-            &shuffle_statement,
-            proof,
-            &mut fs_rng,
-        )
+        // Map to Chaum-Pedersen parameters
+        let cp_parameters = (Base::Point(&masked_card.0), pp.generator_base());
+
+        // Map to Chaum-Pedersen statement
+        let cp_statement = (&rekey_token.0, pk);
+
+        proofs::verify_dl_equality::<C, D>(cp_parameters, cp_statement, proof, seed)
     }
 }
@@ -0,0 +1,383 @@
+use crate::discrete_log_cards::constant_time::ladder_mul;
+use crate::discrete_log_cards::pedersen::CommitKey;
+use crate::discrete_log_cards::proofs::Base;
+use crate::discrete_log_cards::shuffle::{
+    absorb_points, combine_rows, inner_product, linear_combination, powers, sample_scalars,
+};
+use crate::discrete_log_cards::MaskedCard;
+
+use ark_ec::msm::VariableBaseMSM;
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{PrimeField, UniformRand, Zero};
+use ark_marlin::rng::FiatShamirRng;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use ark_std::rand::Rng;
+use digest::Digest;
+use proof_essentials::error::CryptoError;
+use proof_essentials::homomorphic_encryption::el_gamal;
+
+/// Proof that a ciphertext is a remasking of the product of rows of ciphertexts raised to the
+/// rows committed in a list of commitments (Bayer and Groth, section 4). The cross terms of the
+/// rows are sent as the ciphertexts `E_k`, blinded by the encryptions of `b_k`.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct MultiExpProof<C: ProjectiveCurve> {
+    pub a_0_commitment: C::Affine,
+    /// Commitments to `b_k` for every `k` but `m`, whose commitment is the identity
+    pub b_commitments: Vec<C::Affine>,
+    /// `E_k` for every `k` but `m`, which is the target ciphertext
+    pub diagonals: Vec<MaskedCard<C>>,
+    pub a: Vec<C::ScalarField>,
+    pub r: C::ScalarField,
+    pub b: C::ScalarField,
+    pub s: C::ScalarField,
+    pub tau: C::ScalarField,
+}
+
+impl<C: ProjectiveCurve> MultiExpProof<C> {
+    /// Every group element of the proof
+    pub(super) fn points(&self) -> Vec<&C::Affine> {
+        let mut points = vec![&self.a_0_commitment];
+        points.extend(&self.b_commitments);
+        for diagonal in &self.diagonals {
+            points.push(&diagonal.0);
+            points.push(&diagonal.1);
+        }
+
+        points
+    }
+}
+
+/// How the prover of the multi-exponentiation argument computes the cross terms `E_k`. Both modes
+/// produce the same proofs, which the verifier checks the same way, so every player of a table can
+/// pick its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MultiExpMode {
+    /// Compute every cross term as a sum of products of the rows, with `2n m (m + 1)` ladder
+    /// multiplications
+    Direct,
+    /// Evaluate the polynomial whose coefficients are the cross terms at `2m` small integers and
+    /// interpolate its coefficients, as suggested by Bayer and Groth, with `4mn` ladder
+    /// multiplications
+    Interpolation,
+}
+
+/// The bases of the encryptions of the argument: the encryption generator, the shared key and the
+/// generator of the messages
+pub(super) struct EncryptionBases<'a, C: ProjectiveCurve> {
+    pub generator: Base<'a, C>,
+    pub shared_key: Base<'a, C>,
+    pub message_generator: &'a C::Affine,
+}
+
+/// The statement of the argument: `target = Enc(0; rho) + sum_i <rows_i, exponents_i>`, where the
+/// exponent rows are committed in `commitments`
+pub(super) struct MultiExpStatement<'a, C: ProjectiveCurve> {
+    pub rows: &'a [Vec<MaskedCard<C>>],
+    pub target: &'a MaskedCard<C>,
+    pub commitments: &'a [C::Affine],
+}
+
+/// The witness of the argument: the exponent rows, the randomness of their commitments and `rho`
+pub(super) struct MultiExpWitness<'a, C: ProjectiveCurve> {
+    pub exponents: &'a [Vec<C::ScalarField>],
+    pub randomness: &'a [C::ScalarField],
+    pub rho: &'a C::ScalarField,
+}
+
+pub(super) fn prove_multi_exp<C: ProjectiveCurve, D: Digest, R: Rng>(
+    rng: &mut R,
+    ck: &CommitKey<C>,
+    bases: &EncryptionBases<C>,
+    statement: &MultiExpStatement<C>,
+    witness: &MultiExpWitness<C>,
+    mode: MultiExpMode,
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<MultiExpProof<C>, CryptoError> {
+    let m = witness.exponents.len();
+    let n = witness.exponents[0].len();
+
+    let a_0 = sample_scalars::<C, R>(rng, n);
+    let r_0 = C::ScalarField::rand(rng);
+    let a_0_commitment = ck.commit(&a_0, &r_0).into_affine();
+
+    // b_m and s_m are zero and tau_m is rho, so that E_m is the target
+    let mut b = sample_scalars::<C, R>(rng, 2 * m);
+    let mut s = sample_scalars::<C, R>(rng, 2 * m);
+    let mut tau = sample_scalars::<C, R>(rng, 2 * m);
+    b[m] = C::ScalarField::zero();
+    s[m] = C::ScalarField::zero();
+    tau[m] = *witness.rho;
+
+    let exponents = std::iter::once(a_0)
+        .chain(witness.exponents.iter().cloned())
+        .collect::<Vec<_>>();
+    let cross_terms = match mode {
+        MultiExpMode::Direct => cross_terms(statement.rows, &exponents),
+        MultiExpMode::Interpolation => interpolated_cross_terms(statement.rows, &exponents),
+    };
+
+    let mut b_commitments = Vec::with_capacity(2 * m - 1);
+    let mut diagonals = Vec::with_capacity(2 * m - 1);
+    for k in (0..2 * m).filter(|&k| k != m) {
+        b_commitments.push(ck.commit(&[b[k]], &s[k]));
+
+        let masking = encrypt(bases, &b[k], &tau[k]);
+        diagonals.push(el_gamal::Ciphertext(
+            (masking.0 + cross_terms[k].0).into_affine(),
+            (masking.1 + cross_terms[k].1).into_affine(),
+        ));
+    }
+    let b_commitments = C::batch_normalization_into_affine(&b_commitments);
+
+    let x = multi_exp_challenge::<C, D>(&a_0_commitment, &b_commitments, &diagonals, fs_rng)?;
+    let x_powers = powers(&x, 2 * m);
+    let randomness = std::iter::once(r_0)
+        .chain(witness.randomness.iter().copied())
+        .collect::<Vec<_>>();
+
+    Ok(MultiExpProof {
+        a_0_commitment,
+        b_commitments,
+        diagonals,
+        a: combine_rows(&exponents, &x_powers[..=m]),
+        r: inner_product(&randomness, &x_powers[..=m]),
+        b: inner_product(&b, &x_powers),
+        s: inner_product(&s, &x_powers),
+        tau: inner_product(&tau, &x_powers),
+    })
+}
+
+pub(super) fn verify_multi_exp<C: ProjectiveCurve, D: Digest>(
+    ck: &CommitKey<C>,
+    bases: &EncryptionBases<C>,
+    statement: &MultiExpStatement<C>,
+    proof: &MultiExpProof<C>,
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<(), CryptoError> {
+    let m = statement.rows.len();
+    let n = statement.rows[0].len();
+    if proof.b_commitments.len() != 2 * m - 1
+        || proof.diagonals.len() != 2 * m - 1
+        || proof.a.len() != n
+    {
+        return Err(multi_exp_error());
+    }
+
+    let x = multi_exp_challenge::<C, D>(
+        &proof.a_0_commitment,
+        &proof.b_commitments,
+        &proof.diagonals,
+        fs_rng,
+    )?;
+    let x_powers = powers(&x, 2 * m);
+    let diagonal_powers = x_powers
+        .iter()
+        .enumerate()
+        .filter(|(k, _)| *k != m)
+        .map(|(_, power)| *power)
+        .collect::<Vec<_>>();
+
+    let exponent_commitments = std::iter::once(&proof.a_0_commitment)
+        .chain(statement.commitments)
+        .copied()
+        .collect::<Vec<_>>();
+    let commitments_check = linear_combination::<C>(&exponent_commitments, &x_powers[..=m])
+        == ck.commit_public(&proof.a, &proof.r)
+        && linear_combination::<C>(&proof.b_commitments, &diagonal_powers)
+            == ck.commit_public(&[proof.b], &proof.s);
+
+    // sum_k x^k E_k = Enc(b; tau) + sum_i x^(m - 1 - i) <rows_i, a>, with E_m the target
+    let mut bases_0 = proof.diagonals.iter().map(|e| e.0).collect::<Vec<_>>();
+    let mut bases_1 = proof.diagonals.iter().map(|e| e.1).collect::<Vec<_>>();
+    let mut scalars = diagonal_powers;
+    bases_0.push(statement.target.0);
+    bases_1.push(statement.target.1);
+    scalars.push(x_powers[m]);
+    for (i, row) in statement.rows.iter().enumerate() {
+        for (card, a) in row.iter().zip(&proof.a) {
+            bases_0.push(card.0);
+            bases_1.push(card.1);
+            scalars.push(-x_powers[m - 1 - i] * a);
+        }
+    }
+    let scalars = scalars.iter().map(|s| s.into_repr()).collect::<Vec<_>>();
+    let tau = proof.tau.into_repr();
+    let diagonals_check = VariableBaseMSM::multi_scalar_mul(&bases_0, &scalars)
+        == bases.generator.mul_public(&proof.tau)
+        && VariableBaseMSM::multi_scalar_mul(&bases_1, &scalars)
+            == bases.message_generator.mul(proof.b.into_repr()) + bases.shared_key.point().mul(tau);
+
+    if !(commitments_check && diagonals_check) {
+        return Err(multi_exp_error());
+    }
+
+    Ok(())
+}
+
+/// `sum <rows_i, exponents_j>` over the pairs with `j - i = k - m + 1`, for every `k`, where
+/// `exponents_0` is the blinding row. The exponents are secret and multiplied with the ladder.
+fn cross_terms<C: ProjectiveCurve>(
+    rows: &[Vec<MaskedCard<C>>],
+    exponents: &[Vec<C::ScalarField>],
+) -> Vec<(C, C)> {
+    let m = rows.len();
+
+    let mut cross_terms = vec![(C::zero(), C::zero()); 2 * m];
+    for (i, row) in rows.iter().enumerate() {
+        for (j, exponent) in exponents.iter().enumerate() {
+            let term = &mut cross_terms[m - 1 - i + j];
+            for (card, a) in row.iter().zip(exponent) {
+                term.0 += ladder_mul::<C>(&card.0, a);
+                term.1 += ladder_mul::<C>(&card.1, a);
+            }
+        }
+    }
+
+    cross_terms
+}
+
+/// The cross terms as the coefficients of `P(X) = <sum_i X^(m - 1 - i) rows_i, sum_j X^j exponents_j>`,
+/// interpolated from its values at `0, ..., 2m - 1`. The rows are evaluated with public small
+/// scalars, each value of `P` takes one ladder multiplication per entry and the interpolation
+/// weighs the values with public scalars.
+fn interpolated_cross_terms<C: ProjectiveCurve>(
+    rows: &[Vec<MaskedCard<C>>],
+    exponents: &[Vec<C::ScalarField>],
+) -> Vec<(C, C)> {
+    let m = rows.len();
+    let n = rows[0].len();
+
+    let mut values_0 = Vec::with_capacity(2 * m);
+    let mut values_1 = Vec::with_capacity(2 * m);
+    for t in 0..2 * m {
+        let t = C::ScalarField::from(t as u64);
+        let t_repr = t.into_repr();
+
+        // Horner's rule on the rows, from the one with the highest power of t
+        let mut evaluation = vec![(C::zero(), C::zero()); n];
+        for row in rows {
+            for (value, card) in evaluation.iter_mut().zip(row) {
+                value.0 = value.0.mul(t_repr).add_mixed(&card.0);
+                value.1 = value.1.mul(t_repr).add_mixed(&card.1);
+            }
+        }
+        let (evaluation_0, evaluation_1): (Vec<_>, Vec<_>) = evaluation.into_iter().unzip();
+        let evaluation_0 = C::batch_normalization_into_affine(&evaluation_0);
+        let evaluation_1 = C::batch_normalization_into_affine(&evaluation_1);
+        let exponent = combine_rows(exponents, &powers(&t, m + 1));
+
+        let mut value = (C::zero(), C::zero());
+        for ((card_0, card_1), a) in evaluation_0.iter().zip(&evaluation_1).zip(&exponent) {
+            value.0 += ladder_mul::<C>(card_0, a);
+            value.1 += ladder_mul::<C>(card_1, a);
+        }
+        values_0.push(value.0);
+        values_1.push(value.1);
+    }
+    let values_0 = C::batch_normalization_into_affine(&values_0);
+    let values_1 = C::batch_normalization_into_affine(&values_1);
+
+    lagrange_coefficients::<C::ScalarField>(2 * m)
+        .iter()
+        .map(|weights| {
+            (
+                linear_combination::<C>(&values_0, weights),
+                linear_combination::<C>(&values_1, weights),
+            )
+        })
+        .collect()
+}
+
+/// The matrix turning the values of a polynomial of degree less than `size` at `0, ..., size - 1`
+/// into its coefficients: row `k` holds the coefficients of `X^k` in the Lagrange basis polynomials
+fn lagrange_coefficients<F: PrimeField>(size: usize) -> Vec<Vec<F>> {
+    let points = (0..size).map(|t| F::from(t as u64)).collect::<Vec<_>>();
+
+    let mut matrix = vec![vec![F::zero(); size]; size];
+    for (t, point) in points.iter().enumerate() {
+        // prod_(u != t) (X - u) / (t - u), expanded one factor at a time
+        let mut basis = vec![F::one()];
+        let mut denominator = F::one();
+        for other in points.iter().filter(|other| *other != point) {
+            let mut product = vec![F::zero(); basis.len() + 1];
+            for (k, coefficient) in basis.iter().enumerate() {
+                product[k + 1] += coefficient;
+                product[k] -= *coefficient * other;
+            }
+            basis = product;
+            denominator *= *point - other;
+        }
+
+        let inverse = denominator.inverse().expect("the points are distinct");
+        for (k, coefficient) in basis.iter().enumerate() {
+            matrix[k][t] = *coefficient * inverse;
+        }
+    }
+
+    matrix
+}
+
+/// `Enc(b * G; tau) = (tau * g, b * G + tau * pk)` for secret `b` and `tau`
+fn encrypt<C: ProjectiveCurve>(
+    bases: &EncryptionBases<C>,
+    b: &C::ScalarField,
+    tau: &C::ScalarField,
+) -> (C, C) {
+    (
+        bases.generator.mul_secret(tau),
+        ladder_mul::<C>(bases.message_generator, b) + bases.shared_key.mul_secret(tau),
+    )
+}
+
+fn multi_exp_challenge<C: ProjectiveCurve, D: Digest>(
+    a_0_commitment: &C::Affine,
+    b_commitments: &[C::Affine],
+    diagonals: &[MaskedCard<C>],
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<C::ScalarField, CryptoError> {
+    absorb_points::<C, D>(fs_rng, &[*a_0_commitment])?;
+    absorb_points::<C, D>(fs_rng, b_commitments)?;
+    for diagonal in diagonals {
+        absorb_points::<C, D>(fs_rng, &[diagonal.0, diagonal.1])?;
+    }
+
+    Ok(C::ScalarField::rand(fs_rng))
+}
+
+fn multi_exp_error() -> CryptoError {
+    CryptoError::ProofVerificationError(String::from("Multi-exponentiation (4)"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{cross_terms, interpolated_cross_terms};
+    use crate::discrete_log_cards;
+
+    use proof_essentials::utils::rand::sample_vector;
+    use rand::thread_rng;
+
+    // Choose elliptic curve setting
+    type Curve = starknet_curve::Projective;
+    type Scalar = starknet_curve::Fr;
+
+    type MaskedCard = discrete_log_cards::MaskedCard<Curve>;
+
+    #[test]
+    fn test_interpolated_cross_terms() {
+        let rng = &mut thread_rng();
+
+        for (m, n) in [(1, 3), (2, 2), (4, 3)] {
+            let rows = (0..m)
+                .map(|_| sample_vector(rng, n))
+                .collect::<Vec<Vec<MaskedCard>>>();
+            let exponents = (0..=m)
+                .map(|_| sample_vector(rng, n))
+                .collect::<Vec<Vec<Scalar>>>();
+
+            assert_eq!(
+                interpolated_cross_terms::<Curve>(&rows, &exponents),
+                cross_terms::<Curve>(&rows, &exponents)
+            );
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use crate::discrete_log_cards::constant_time::{conditional_swap, equal, ladder_mul};
+use crate::discrete_log_cards::proofs::Base;
+use crate::discrete_log_cards::{DLCards, MaskedCard, Parameters, PublicKey, SELECTION_RNG_SEED};
+use crate::error::CardProtocolError;
+
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{to_bytes, PrimeField, UniformRand};
+use ark_marlin::rng::FiatShamirRng;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use ark_std::rand::Rng;
+use digest::Digest;
+use proof_essentials::error::CryptoError;
+
+/// Proof that one out of several ciphertexts is an encryption of zero under the shared key, without
+/// revealing which one. This is an OR of Chaum-Pedersen proofs (Cramer, Damgård and Schoenmakers),
+/// with one challenge and one response per ciphertext.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct OneOfManyProof<C: ProjectiveCurve> {
+    pub challenges: Vec<C::ScalarField>,
+    pub responses: Vec<C::ScalarField>,
+}
+
+/// Prove that `statements[index]` is `(g * witness, shared_key * witness)`. The transcript must
+/// already be bound to the statements.
+pub(super) fn prove<C: ProjectiveCurve, D: Digest, R: Rng>(
+    rng: &mut R,
+    pp: &Parameters<C>,
+    shared_key: &PublicKey<C>,
+    statements: &Vec<(C::Affine, C::Affine)>,
+    index: usize,
+    witness: &C::ScalarField,
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<OneOfManyProof<C>, CryptoError> {
+    let key_table = pp.key_table(shared_key);
+    let bases = [pp.generator_base(), key_table.base()];
+    let statements = statements
+        .iter()
+        .map(|(s0, s1)| vec![*s0, *s1])
+        .collect::<Vec<_>>();
+
+    let (challenges, responses) =
+        prove_or::<C, D, R>(rng, &bases, &statements, index, witness, fs_rng)?;
+
+    Ok(OneOfManyProof {
+        challenges,
+        responses,
+    })
+}
+
+/// Check a proof that one of `statements` is an encryption of zero under the shared key
+pub(super) fn verify<C: ProjectiveCurve, D: Digest>(
+    pp: &Parameters<C>,
+    shared_key: &PublicKey<C>,
+    statements: &Vec<(C::Affine, C::Affine)>,
+    proof: &OneOfManyProof<C>,
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<bool, CryptoError> {
+    let key_table = pp.key_table(shared_key);
+    let bases = [pp.generator_base(), key_table.base()];
+    let statements = statements
+        .iter()
+        .map(|(s0, s1)| vec![*s0, *s1])
+        .collect::<Vec<_>>();
+
+    verify_or::<C, D>(
+        &bases,
+        &statements,
+        &proof.challenges,
+        &proof.responses,
+        fs_rng,
+    )
+}
+
+/// Prove that `statements[index][j] = bases[j] * witness` for every `j`, without revealing
+/// `index`. Every branch does the same work: the commitments of all branches are simulated with
+/// the ladder, including the true one, whose simulation is then replaced by the commitment of the
+/// nonce under a masked swap, and the challenge and response of the true branch are swapped in the
+/// same way. Neither the running time nor the memory accessed depend on `index`.
+pub(super) fn prove_or<C: ProjectiveCurve, D: Digest, R: Rng>(
+    rng: &mut R,
+    bases: &[Base<C>],
+    statements: &[Vec<C::Affine>],
+    index: usize,
+    witness: &C::ScalarField,
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<(Vec<C::ScalarField>, Vec<C::ScalarField>), CryptoError> {
+    let nonce = C::ScalarField::rand(rng);
+    let nonce_commitments = bases
+        .iter()
+        .map(|base| base.mul_secret(&nonce))
+        .collect::<Vec<_>>();
+
+    let mut challenges = Vec::with_capacity(statements.len());
+    let mut responses = Vec::with_capacity(statements.len());
+    let mut commitments = Vec::with_capacity(statements.len() * bases.len());
+    for (k, targets) in statements.iter().enumerate() {
+        let is_true = equal(k, index) as u64;
+        let challenge = C::ScalarField::rand(rng);
+        let response = C::ScalarField::rand(rng);
+
+        for ((base, target), nonce_commitment) in bases.iter().zip(targets).zip(&nonce_commitments)
+        {
+            let mut commitment = base.mul_secret(&response) - ladder_mul::<C>(target, &challenge);
+            let mut nonce_commitment = *nonce_commitment;
+            conditional_swap(&mut commitment, &mut nonce_commitment, is_true);
+            commitments.push(commitment);
+        }
+
+        challenges.push(challenge);
+        responses.push(response);
+    }
+
+    // The true branch gets what the simulated challenges leave of the transcript challenge
+    let challenge = squeeze_challenge::<C, D>(fs_rng, &commitments)?;
+    let sum: C::ScalarField = challenges.iter().sum();
+    for (k, (c, z)) in challenges.iter_mut().zip(responses.iter_mut()).enumerate() {
+        let is_true = equal(k, index) as u64;
+        let mut true_challenge = challenge - (sum - *c);
+        let mut true_response = nonce + true_challenge * witness;
+        conditional_swap(c, &mut true_challenge, is_true);
+        conditional_swap(z, &mut true_response, is_true);
+    }
+
+    Ok((challenges, responses))
+}
+
+/// Check a proof produced by `prove_or`
+pub(super) fn verify_or<C: ProjectiveCurve, D: Digest>(
+    bases: &[Base<C>],
+    statements: &[Vec<C::Affine>],
+    challenges: &[C::ScalarField],
+    responses: &[C::ScalarField],
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<bool, CryptoError> {
+    let n = statements.len();
+    if n == 0
+        || challenges.len() != n
+        || responses.len() != n
+        || statements
+            .iter()
+            .any(|targets| targets.len() != bases.len())
+    {
+        return Ok(false);
+    }
+
+    let mut commitments = Vec::with_capacity(n * bases.len());
+    for (targets, (c, z)) in statements.iter().zip(challenges.iter().zip(responses)) {
+        for (base, target) in bases.iter().zip(targets) {
+            commitments.push(base.mul_public(z) - target.mul(c.into_repr()));
+        }
+    }
+
+    let challenge = squeeze_challenge::<C, D>(fs_rng, &commitments)?;
+
+    Ok(challenges.iter().sum::<C::ScalarField>() == challenge)
+}
+
+/// Absorb the commitments into the transcript and squeeze the challenge
+pub(super) fn squeeze_challenge<C: ProjectiveCurve, D: Digest>(
+    fs_rng: &mut FiatShamirRng<D>,
+    commitments: &Vec<C>,
+) -> Result<C::ScalarField, CryptoError> {
+    for commitment in C::batch_normalization_into_affine(commitments) {
+        fs_rng.absorb(&to_bytes![commitment]?);
+    }
+
+    Ok(C::ScalarField::rand(fs_rng))
+}
+
+impl<C: ProjectiveCurve, D: Digest> DLCards<C, D> {
+    /// Privately select one of several masked cards, e.g. a card picked from another player's hand.
+    /// The output is a remasked copy of `cards[index]`, with a proof that it comes from `cards`
+    /// that does not reveal which card was picked.
+    pub fn select_and_remask<R: Rng>(
+        rng: &mut R,
+        pp: &Parameters<C>,
+        shared_key: &PublicKey<C>,
+        cards: &Vec<MaskedCard<C>>,
+        index: usize,
+        alpha: &C::ScalarField,
+    ) -> Result<(MaskedCard<C>, OneOfManyProof<C>), CardProtocolError> {
+        let chosen = cards
+            .get(index)
+            .ok_or(CardProtocolError::InvalidCardIndex(index))?;
+        let selected = pp.key_table(shared_key).remask(pp, chosen, alpha);
+
+        let mut fs_rng = Self::selection_transcript(pp, shared_key, cards, &selected)?;
+        let statements = selection_statements(cards, &selected);
+        let proof = prove(rng, pp, shared_key, &statements, index, alpha, &mut fs_rng)?;
+
+        Ok((selected, proof))
+    }
+
+    /// Verify that a masked card is a remasked copy of one of `cards`
+    pub fn verify_selection(
+        pp: &Parameters<C>,
+        shared_key: &PublicKey<C>,
+        cards: &Vec<MaskedCard<C>>,
+        selected: &MaskedCard<C>,
+        proof: &OneOfManyProof<C>,
+    ) -> Result<(), CardProtocolError> {
+        Self::validate_public_key(shared_key)?;
+        Self::validate_deck(cards)?;
+        Self::validate_masked_card(selected)?;
+
+        let mut fs_rng = Self::selection_transcript(pp, shared_key, cards, selected)?;
+        let statements = selection_statements(cards, selected);
+        if !verify(pp, shared_key, &statements, proof, &mut fs_rng)? {
+            return Err(CryptoError::ProofVerificationError(String::from("Selection")).into());
+        }
+
+        Ok(())
+    }
+
+    fn selection_transcript(
+        pp: &Parameters<C>,
+        shared_key: &PublicKey<C>,
+        cards: &Vec<MaskedCard<C>>,
+        selected: &MaskedCard<C>,
+    ) -> Result<FiatShamirRng<D>, CryptoError> {
+        let mut fs_rng = FiatShamirRng::<D>::from_seed(&to_bytes![SELECTION_RNG_SEED]?);
+        fs_rng.absorb(&to_bytes![pp.enc_parameters.generator, shared_key]?);
+        for card in cards.iter().chain(std::iter::once(selected)) {
+            fs_rng.absorb(&to_bytes![card.0, card.1]?);
+        }
+
+        Ok(fs_rng)
+    }
+}
+
+/// The differences between the selected card and every candidate
+fn selection_statements<C: ProjectiveCurve>(
+    cards: &Vec<MaskedCard<C>>,
+    selected: &MaskedCard<C>,
+) -> Vec<(C::Affine, C::Affine)> {
+    let differences = cards
+        .iter()
+        .flat_map(|card| {
+            vec![
+                selected.0.into_projective() - card.0.into_projective(),
+                selected.1.into_projective() - card.1.into_projective(),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    C::batch_normalization_into_affine(&differences)
+        .chunks(2)
+        .map(|pair| (pair[0], pair[1]))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::discrete_log_cards;
+    use crate::error::CardProtocolError;
+    use crate::BarnettSmartProtocol;
+
+    use ark_ff::UniformRand;
+    use proof_essentials::error::CryptoError;
+    use proof_essentials::utils::rand::sample_vector;
+    use rand::thread_rng;
+
+    // Choose elliptic curve setting
+    type Curve = starknet_curve::Projective;
+    type Scalar = starknet_curve::Fr;
+
+    // Instantiate concrete type for our card protocol
+    type CardProtocol = discrete_log_cards::DLCards<Curve>;
+
+    type MaskedCard = discrete_log_cards::MaskedCard<Curve>;
+
+    #[test]
+    fn test_select_and_remask() {
+        let rng = &mut thread_rng();
+
+        let parameters = CardProtocol::setup(rng, 2, 5).unwrap();
+        let (shared_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+
+        let hand: Vec<MaskedCard> = sample_vector(rng, 5);
+        for index in 0..hand.len() {
+            let alpha = Scalar::rand(rng);
+            let (selected, proof) = CardProtocol::select_and_remask(
+                rng,
+                &parameters,
+                &shared_key,
+                &hand,
+                index,
+                &alpha,
+            )
+            .unwrap();
+
+            assert_eq!(
+                Ok(()),
+                CardProtocol::verify_selection(&parameters, &shared_key, &hand, &selected, &proof)
+            );
+
+            // The proof does not hold for a hand without the selected card
+            let mut other_hand = hand.clone();
+            other_hand[index] = MaskedCard::rand(rng);
+            assert_eq!(
+                CardProtocol::verify_selection(
+                    &parameters,
+                    &shared_key,
+                    &other_hand,
+                    &selected,
+                    &proof
+                ),
+                Err(CardProtocolError::ProofVerificationError(
+                    CryptoError::ProofVerificationError(String::from("Selection"))
+                ))
+            );
+        }
+
+        let alpha = Scalar::rand(rng);
+        assert_eq!(
+            CardProtocol::select_and_remask(rng, &parameters, &shared_key, &hand, 5, &alpha).err(),
+            Some(CardProtocolError::InvalidCardIndex(5))
+        );
+    }
+}
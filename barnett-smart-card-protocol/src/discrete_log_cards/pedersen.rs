@@ -0,0 +1,115 @@
+use crate::discrete_log_cards::hash_to_curve::hash_to_curve;
+use crate::discrete_log_cards::FixedBaseTable;
+
+use ark_ec::msm::VariableBaseMSM;
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::PrimeField;
+use ark_std::rand::Rng;
+use digest::Digest;
+use once_cell::sync::OnceCell;
+
+// A shuffle proof commits to a few vectors per row of the deck
+const TABLE_MULS: usize = 64;
+
+/// Pedersen commitment key of the shuffle argument, for vectors of up to `n` scalars. `g` holds one
+/// generator per entry of a vector and `h` carries the randomness of a commitment. Nobody may know a
+/// discrete log relation between the generators.
+pub struct CommitKey<C: ProjectiveCurve> {
+    g: Vec<C::Affine>,
+    h: C::Affine,
+    // Built on first use by the provers, which commit to secret vectors
+    tables: OnceCell<(Vec<FixedBaseTable<C>>, FixedBaseTable<C>)>,
+}
+
+impl<C: ProjectiveCurve> CommitKey<C> {
+    pub fn new(g: Vec<C::Affine>, h: C::Affine) -> Self {
+        Self {
+            g,
+            h,
+            tables: OnceCell::new(),
+        }
+    }
+
+    /// Sample a key for vectors of `n` scalars
+    pub fn setup<R: Rng>(rng: &mut R, n: usize) -> Self {
+        let points = (0..=n).map(|_| C::rand(rng)).collect::<Vec<_>>();
+        let mut g = C::batch_normalization_into_affine(&points);
+        let h = g.pop().expect("the key has n + 1 generators");
+
+        Self::new(g, h)
+    }
+
+    /// Derive a key for vectors of `n` scalars from a public domain tag, see `hash_to_curve`
+    pub fn derive<D: Digest>(domain: &[u8], n: usize) -> Self {
+        let g = (0..n)
+            .map(|i| hash_to_curve::<C, D>(domain, b"commitment generator", i as u64))
+            .collect();
+        let h = hash_to_curve::<C, D>(domain, b"commitment blinding", 0);
+
+        Self::new(g, h)
+    }
+
+    /// The length of the longest vector the key commits to
+    pub fn max_len(&self) -> usize {
+        self.g.len()
+    }
+
+    pub(super) fn generators(&self) -> impl Iterator<Item = &C::Affine> {
+        self.g.iter().chain(Some(&self.h))
+    }
+
+    /// The generators of the entries of a vector
+    pub(super) fn g(&self) -> &[C::Affine] {
+        &self.g
+    }
+
+    /// The generator of the randomness
+    pub(super) fn h(&self) -> &C::Affine {
+        &self.h
+    }
+
+    /// Commit to secret `values` with secret `randomness`. Every generator is multiplied through its
+    /// window table, in constant time.
+    pub(super) fn commit(&self, values: &[C::ScalarField], randomness: &C::ScalarField) -> C {
+        self.commit_at(0, values, randomness)
+    }
+
+    /// Same as `commit`, with `values` placed on the generators from the `offset`-th one on
+    pub(super) fn commit_at(
+        &self,
+        offset: usize,
+        values: &[C::ScalarField],
+        randomness: &C::ScalarField,
+    ) -> C {
+        debug_assert!(offset + values.len() <= self.g.len());
+        let (g_tables, h_table) = self.tables.get_or_init(|| {
+            (
+                self.g
+                    .iter()
+                    .map(|g| FixedBaseTable::new(g, TABLE_MULS))
+                    .collect(),
+                FixedBaseTable::new(&self.h, TABLE_MULS),
+            )
+        });
+
+        values
+            .iter()
+            .zip(&g_tables[offset..])
+            .fold(h_table.mul(randomness), |commitment, (value, table)| {
+                commitment + table.mul(value)
+            })
+    }
+
+    /// Commit to public `values`, e.g. to check the openings of a proof
+    pub(super) fn commit_public(
+        &self,
+        values: &[C::ScalarField],
+        randomness: &C::ScalarField,
+    ) -> C {
+        debug_assert!(values.len() <= self.g.len());
+        let scalars = values.iter().map(|v| v.into_repr()).collect::<Vec<_>>();
+
+        VariableBaseMSM::multi_scalar_mul(&self.g[..values.len()], &scalars)
+            + self.h.mul(randomness.into_repr())
+    }
+}
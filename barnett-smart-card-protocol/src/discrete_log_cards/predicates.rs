@@ -0,0 +1,668 @@
+//! Proofs of statements about hidden cards. A player holding some masked cards proves that the
+//! cards satisfy a `Predicate` (e.g. "my two hole cards form a pair") without opening them.
+//!
+//! Predicates are proven over cards masked under the player's own key only: the reveal tokens that
+//! the other players sent to open the cards privately are forwarded to the verifier, who strips
+//! them with `strip_reveal_tokens`. These tokens do not open the cards without the player's own
+//! token.
+//!
+//! A predicate is a list of alternatives, each a set of plaintexts of the public card encoding:
+//! one set per value for `SameValue`, one per suit for `SameSuite` and the given set for
+//! `MemberOf`. The proof is an OR over the alternatives of an AND over the cards of an OR over the
+//! members of the set, each branch proving that a card decrypts to one member. Its size grows with
+//! the number of cards times the total size of the sets, e.g. a flush of three cards takes
+//! 4 * 3 * 13 branches. The cards are not proven to be distinct, which cards dealt from one deck
+//! always are.
+//!
+//! Predicates work over any encoding whose cards implement `PlayingCard`. Comparisons between
+//! hands, e.g. "my hand beats X", are not supported: they are not a union of per-card sets.
+
+use crate::discrete_log_cards::constant_time::{conditional_swap, encode, equal, ladder_mul};
+use crate::discrete_log_cards::one_of_many::squeeze_challenge;
+use crate::discrete_log_cards::{
+    Card, DLCards, DLEqualityProof, MaskedCard, Parameters, PlayerSecretKey, PublicKey,
+    RevealToken, PREDICATE_RNG_SEED,
+};
+use crate::error::CardProtocolError;
+use crate::BarnettSmartProtocol;
+
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{to_bytes, PrimeField, UniformRand, Zero};
+use ark_marlin::rng::FiatShamirRng;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use ark_std::rand::Rng;
+use digest::Digest;
+use proof_essentials::error::CryptoError;
+use proof_essentials::homomorphic_encryption::el_gamal;
+use std::collections::HashMap;
+
+type RevealProof<C> = DLEqualityProof<C>;
+
+/// A card of a public card encoding, as seen by predicates
+pub trait PlayingCard: Copy + PartialEq {
+    fn value(&self) -> u8;
+
+    fn suite(&self) -> u8;
+}
+
+/// A statement about a hand of hidden cards
+#[derive(Clone, Debug, PartialEq)]
+pub enum Predicate<T: PlayingCard> {
+    /// All the cards have the same value
+    SameValue,
+    /// All the cards have the same suit
+    SameSuite,
+    /// Every card belongs to the given set
+    MemberOf(Vec<T>),
+}
+
+impl<T: PlayingCard> Predicate<T> {
+    pub fn holds(&self, cards: &[T]) -> bool {
+        match self {
+            Self::SameValue => cards
+                .windows(2)
+                .all(|pair| pair[0].value() == pair[1].value()),
+            Self::SameSuite => cards
+                .windows(2)
+                .all(|pair| pair[0].suite() == pair[1].suite()),
+            Self::MemberOf(set) => cards.iter().all(|card| set.contains(card)),
+        }
+    }
+
+    /// The sets of plaintexts of `encoding` such that the cards satisfy the predicate when they
+    /// all belong to one of the sets, in an order that does not depend on the iteration order of
+    /// the encoding. Empty sets are left out.
+    fn alternatives<C: ProjectiveCurve>(
+        &self,
+        encoding: &HashMap<Card<C>, T>,
+    ) -> Vec<Vec<Card<C>>> {
+        let mut deck = encoding
+            .iter()
+            .map(|(card, playing_card)| (*card, *playing_card))
+            .collect::<Vec<_>>();
+        deck.sort_by_key(|(card, playing_card)| {
+            (playing_card.value(), playing_card.suite(), encode(&card.0))
+        });
+
+        let alternatives = match self {
+            Self::SameValue => group_by(&deck, T::value),
+            Self::SameSuite => group_by(&deck, T::suite),
+            Self::MemberOf(set) => vec![deck
+                .iter()
+                .filter(|(_, playing_card)| set.contains(playing_card))
+                .map(|(card, _)| *card)
+                .collect()],
+        };
+
+        alternatives
+            .into_iter()
+            .filter(|set| !set.is_empty())
+            .collect()
+    }
+}
+
+/// Split a sorted deck into one set of plaintexts per value of `key`
+fn group_by<C: ProjectiveCurve, T: PlayingCard>(
+    deck: &[(Card<C>, T)],
+    key: fn(&T) -> u8,
+) -> Vec<Vec<Card<C>>> {
+    let mut keys = deck
+        .iter()
+        .map(|(_, playing_card)| key(playing_card))
+        .collect::<Vec<_>>();
+    keys.sort_unstable();
+    keys.dedup();
+
+    keys.iter()
+        .map(|k| {
+            deck.iter()
+                .filter(|(_, playing_card)| key(playing_card) == *k)
+                .map(|(card, _)| *card)
+                .collect()
+        })
+        .collect()
+}
+
+/// Proof that some masked cards satisfy a `Predicate`, with one challenge and one response per
+/// branch, ordered by alternative, then card, then member of the set
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PredicateProof<C: ProjectiveCurve> {
+    pub challenges: Vec<C::ScalarField>,
+    pub responses: Vec<C::ScalarField>,
+}
+
+impl<C: ProjectiveCurve, D: Digest> DLCards<C, D> {
+    /// Remove the reveal tokens of other players from a masked card, leaving it masked under the
+    /// keys of the players who did not provide a token. Every token is verified first.
+    pub fn strip_reveal_tokens(
+        pp: &Parameters<C>,
+        masked_card: &MaskedCard<C>,
+        reveal_tokens: &Vec<(RevealToken<C>, RevealProof<C>, PublicKey<C>)>,
+    ) -> Result<MaskedCard<C>, CardProtocolError> {
+        let mut aggregate_token = RevealToken::<C>::zero();
+        for (token, proof, pk) in reveal_tokens {
+            Self::verify_reveal(pp, pk, token, masked_card, proof)?;
+
+            aggregate_token = aggregate_token + *token;
+        }
+
+        Ok(el_gamal::Ciphertext(
+            masked_card.0,
+            masked_card.1 + (-aggregate_token.0),
+        ))
+    }
+
+    /// Prove that `cards`, masked under `pk` only, satisfy `predicate` for the public card
+    /// encoding `encoding`.
+    ///
+    /// Every branch does the same work: the commitments of all branches are simulated with the
+    /// ladder and the true ones are swapped in under a mask, as in `one_of_many`. The branches
+    /// are true for the first alternative holding the hand and, within it, for the member each
+    /// card decrypts to. Neither the running time nor the memory accessed depend on the hand.
+    pub fn prove_predicate<T: PlayingCard, R: Rng>(
+        rng: &mut R,
+        pp: &Parameters<C>,
+        sk: &PlayerSecretKey<C>,
+        pk: &PublicKey<C>,
+        encoding: &HashMap<Card<C>, T>,
+        predicate: &Predicate<T>,
+        cards: &Vec<MaskedCard<C>>,
+    ) -> Result<PredicateProof<C>, CardProtocolError> {
+        let alternatives = predicate.alternatives(encoding);
+        if cards.is_empty() || alternatives.is_empty() {
+            return Err(CardProtocolError::PredicateNotSatisfied);
+        }
+
+        let hand = cards
+            .iter()
+            .map(|card| {
+                let opened = card.1.into_projective() - ladder_mul::<C>(&card.0, sk);
+                encode(&opened.into_affine())
+            })
+            .collect::<Vec<_>>();
+
+        // One mask per branch, set if the branch is true
+        let mut found = 0u8;
+        let mut chosen_alternatives = Vec::with_capacity(alternatives.len());
+        let mut masks = Vec::new();
+        for set in &alternatives {
+            let members = set
+                .iter()
+                .map(|member| encode(&member.0))
+                .collect::<Vec<_>>();
+            let matches = hand
+                .iter()
+                .map(|opened| {
+                    members
+                        .iter()
+                        .map(|member| bytes_equal(opened, member))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+
+            let holds = matches.iter().fold(1u8, |holds, card| {
+                holds & card.iter().fold(0u8, |member_of, m| member_of | m)
+            });
+            let chosen = holds & !found & 1;
+            found |= holds;
+
+            chosen_alternatives.push(chosen as u64);
+            masks.extend(matches.iter().flatten().map(|m| (m & chosen) as u64));
+        }
+        if found == 0 {
+            return Err(CardProtocolError::PredicateNotSatisfied);
+        }
+
+        let g = pp.enc_parameters.generator;
+        let nonces = (0..cards.len())
+            .map(|_| C::ScalarField::rand(rng))
+            .collect::<Vec<_>>();
+        let nonce_commitments = cards
+            .iter()
+            .zip(&nonces)
+            .map(|(card, nonce)| (ladder_mul::<C>(&g, nonce), ladder_mul::<C>(&card.0, nonce)))
+            .collect::<Vec<_>>();
+
+        let mut fs_rng = Self::predicate_transcript(pp, pk, cards, &alternatives)?;
+        let targets = branch_targets(cards, &alternatives);
+
+        // The challenges of every set of a simulated alternative sum to the challenge of the
+        // alternative
+        let mut alternative_challenges = Vec::with_capacity(alternatives.len());
+        let mut challenges = Vec::with_capacity(masks.len());
+        for set in &alternatives {
+            let alternative_challenge = C::ScalarField::rand(rng);
+            for _ in cards {
+                let mut set_challenges = (0..set.len())
+                    .map(|_| C::ScalarField::rand(rng))
+                    .collect::<Vec<_>>();
+                set_challenges[0] =
+                    alternative_challenge - set_challenges[1..].iter().sum::<C::ScalarField>();
+                challenges.extend(set_challenges);
+            }
+            alternative_challenges.push(alternative_challenge);
+        }
+
+        let mut responses = Vec::with_capacity(masks.len());
+        let mut commitments = Vec::with_capacity(2 * masks.len());
+        let branches = branch_cards(&alternatives, cards.len());
+        for (k, i) in branches.iter().enumerate() {
+            let (c, z) = (challenges[k], C::ScalarField::rand(rng));
+
+            let mut key_commitment = ladder_mul::<C>(&g, &z) - ladder_mul::<C>(pk, &c);
+            let mut card_commitment =
+                ladder_mul::<C>(&cards[*i].0, &z) - ladder_mul::<C>(&targets[k], &c);
+            let (mut key_nonce, mut card_nonce) = nonce_commitments[*i];
+            conditional_swap(&mut key_commitment, &mut key_nonce, masks[k]);
+            conditional_swap(&mut card_commitment, &mut card_nonce, masks[k]);
+            commitments.push(key_commitment);
+            commitments.push(card_commitment);
+
+            responses.push(z);
+        }
+
+        // The true alternative gets what the simulated ones leave of the transcript challenge, and
+        // the true member of each of its sets what the simulated members leave of that
+        let challenge = squeeze_challenge::<C, D>(&mut fs_rng, &commitments)?;
+        let sum: C::ScalarField = alternative_challenges.iter().sum();
+        let mut true_alternative = C::ScalarField::zero();
+        for (c, chosen) in alternative_challenges.iter().zip(&chosen_alternatives) {
+            let mut candidate = challenge - (sum - c);
+            conditional_swap(&mut true_alternative, &mut candidate, *chosen);
+        }
+
+        for (set, _) in branch_sets(&alternatives, cards.len()) {
+            let set_sum: C::ScalarField = challenges[set.clone()].iter().sum();
+            for k in set {
+                let mut true_challenge = true_alternative - (set_sum - challenges[k]);
+                let mut true_response = nonces[branches[k]] + true_challenge * sk;
+                conditional_swap(&mut challenges[k], &mut true_challenge, masks[k]);
+                conditional_swap(&mut responses[k], &mut true_response, masks[k]);
+            }
+        }
+
+        Ok(PredicateProof {
+            challenges,
+            responses,
+        })
+    }
+
+    /// Verify that `cards`, masked under `pk` only, satisfy `predicate` for the public card
+    /// encoding `encoding`
+    pub fn verify_predicate<T: PlayingCard>(
+        pp: &Parameters<C>,
+        pk: &PublicKey<C>,
+        encoding: &HashMap<Card<C>, T>,
+        predicate: &Predicate<T>,
+        cards: &Vec<MaskedCard<C>>,
+        proof: &PredicateProof<C>,
+    ) -> Result<(), CardProtocolError> {
+        Self::validate_public_key(pk)?;
+        Self::validate_deck(cards)?;
+
+        let alternatives = predicate.alternatives(encoding);
+        let branches = branch_cards(&alternatives, cards.len());
+        if branches.is_empty()
+            || proof.challenges.len() != branches.len()
+            || proof.responses.len() != branches.len()
+        {
+            return Err(CryptoError::ProofVerificationError(String::from("Predicate")).into());
+        }
+
+        let mut fs_rng = Self::predicate_transcript(pp, pk, cards, &alternatives)?;
+        let targets = branch_targets(cards, &alternatives);
+
+        let g = pp.enc_parameters.generator;
+        let mut commitments = Vec::with_capacity(2 * branches.len());
+        for (k, i) in branches.iter().enumerate() {
+            let (c, z) = (
+                proof.challenges[k].into_repr(),
+                proof.responses[k].into_repr(),
+            );
+            commitments.push(g.mul(z) - pk.mul(c));
+            commitments.push(cards[*i].0.mul(z) - targets[k].mul(c));
+        }
+
+        let challenge = squeeze_challenge::<C, D>(&mut fs_rng, &commitments)?;
+
+        // Every set of an alternative sums to the challenge of the alternative, and the
+        // alternatives sum to the transcript challenge
+        let mut alternative_challenges = Vec::<C::ScalarField>::with_capacity(alternatives.len());
+        for (set, d) in branch_sets(&alternatives, cards.len()) {
+            let set_sum = proof.challenges[set].iter().sum();
+            match alternative_challenges.get(d) {
+                None => alternative_challenges.push(set_sum),
+                Some(c) if *c != set_sum => {
+                    return Err(
+                        CryptoError::ProofVerificationError(String::from("Predicate")).into(),
+                    )
+                }
+                Some(_) => {}
+            }
+        }
+
+        if alternative_challenges.iter().sum::<C::ScalarField>() != challenge {
+            return Err(CryptoError::ProofVerificationError(String::from("Predicate")).into());
+        }
+
+        Ok(())
+    }
+
+    fn predicate_transcript(
+        pp: &Parameters<C>,
+        pk: &PublicKey<C>,
+        cards: &Vec<MaskedCard<C>>,
+        alternatives: &Vec<Vec<Card<C>>>,
+    ) -> Result<FiatShamirRng<D>, CryptoError> {
+        let mut fs_rng = FiatShamirRng::<D>::from_seed(&to_bytes![PREDICATE_RNG_SEED]?);
+        fs_rng.absorb(&to_bytes![pp.enc_parameters.generator, pk]?);
+        for card in cards {
+            fs_rng.absorb(&to_bytes![card.0, card.1]?);
+        }
+        for set in alternatives {
+            fs_rng.absorb(&to_bytes![set.len() as u64]?);
+            for member in set {
+                fs_rng.absorb(&to_bytes![member.0]?);
+            }
+        }
+
+        Ok(fs_rng)
+    }
+}
+
+/// The card each branch is about, for `size` cards
+fn branch_cards<C: ProjectiveCurve>(alternatives: &Vec<Vec<Card<C>>>, size: usize) -> Vec<usize> {
+    alternatives
+        .iter()
+        .flat_map(|set| (0..size).flat_map(move |i| std::iter::repeat(i).take(set.len())))
+        .collect()
+}
+
+/// The range of branches of each set, with the alternative it belongs to
+fn branch_sets<C: ProjectiveCurve>(
+    alternatives: &Vec<Vec<Card<C>>>,
+    size: usize,
+) -> Vec<(std::ops::Range<usize>, usize)> {
+    let mut offset = 0;
+    let mut sets = Vec::with_capacity(alternatives.len() * size);
+    for (d, set) in alternatives.iter().enumerate() {
+        for _ in 0..size {
+            sets.push((offset..offset + set.len(), d));
+            offset += set.len();
+        }
+    }
+
+    sets
+}
+
+/// The statement of each branch: a card decrypts to a member under `pk` if `pk = g * sk` and
+/// `card.1 - member = card.0 * sk`, so the targets are `pk` and the card minus the member.
+fn branch_targets<C: ProjectiveCurve>(
+    cards: &Vec<MaskedCard<C>>,
+    alternatives: &Vec<Vec<Card<C>>>,
+) -> Vec<C::Affine> {
+    let differences = alternatives
+        .iter()
+        .flat_map(|set| {
+            cards.iter().flat_map(move |card| {
+                set.iter()
+                    .map(move |member| card.1.into_projective() - member.0.into_projective())
+            })
+        })
+        .collect::<Vec<C>>();
+
+    C::batch_normalization_into_affine(&differences)
+}
+
+/// 1 if `a` and `b` are equal, 0 otherwise, comparing every byte
+fn bytes_equal(a: &[u8], b: &[u8]) -> u8 {
+    let difference = a
+        .iter()
+        .zip(b)
+        .fold(0u8, |difference, (x, y)| difference | (x ^ y));
+
+    equal(difference as usize, 0) & equal(a.len(), b.len())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::discrete_log_cards;
+    use crate::discrete_log_cards::predicates::Predicate;
+    use crate::error::CardProtocolError;
+    use crate::texas_holdem::cards::{encode_cards, ClassicPlayingCard, Suite, Value};
+    use crate::BarnettSmartProtocol;
+
+    use ark_ff::UniformRand;
+    use proof_essentials::error::CryptoError;
+    use rand::thread_rng;
+
+    // Choose elliptic curve setting
+    type Curve = starknet_curve::Projective;
+    type Scalar = starknet_curve::Fr;
+
+    // Instantiate concrete type for our card protocol
+    type CardProtocol = discrete_log_cards::DLCards<Curve>;
+    type Card = discrete_log_cards::Card<Curve>;
+
+    #[test]
+    fn test_hand_predicates() {
+        let rng = &mut thread_rng();
+        let parameters = CardProtocol::setup(rng, 2, 26).unwrap();
+
+        let (pk, sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+        let (other_pk, other_sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+        let shared_key = pk + other_pk;
+
+        let encoding = encode_cards::<Curve, _>(rng);
+        let lookup = |value, suite| -> Card {
+            *encoding
+                .iter()
+                .find(|(_, classic)| **classic == ClassicPlayingCard::new(value, suite))
+                .unwrap()
+                .0
+        };
+
+        // A pair of kings, masked under the shared key and stripped of the other player's tokens
+        let hole_cards = [
+            lookup(Value::King, Suite::Heart),
+            lookup(Value::King, Suite::Spade),
+        ]
+        .iter()
+        .map(|card| {
+            let r = Scalar::rand(rng);
+            let (masked, _) = CardProtocol::mask(rng, &parameters, &shared_key, card, &r).unwrap();
+            let token =
+                CardProtocol::compute_reveal_token(rng, &parameters, &other_sk, &other_pk, &masked)
+                    .unwrap();
+            CardProtocol::strip_reveal_tokens(
+                &parameters,
+                &masked,
+                &vec![(token.0, token.1, other_pk)],
+            )
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+        let high_cards = Predicate::MemberOf(
+            [Value::Ace, Value::King]
+                .iter()
+                .flat_map(|&value| {
+                    Suite::VALUES
+                        .iter()
+                        .map(move |&suite| ClassicPlayingCard::new(value, suite))
+                })
+                .collect(),
+        );
+
+        for predicate in [Predicate::SameValue, high_cards] {
+            let proof = CardProtocol::prove_predicate(
+                rng,
+                &parameters,
+                &sk,
+                &pk,
+                &encoding,
+                &predicate,
+                &hole_cards,
+            )
+            .unwrap();
+
+            assert_eq!(
+                Ok(()),
+                CardProtocol::verify_predicate(
+                    &parameters,
+                    &pk,
+                    &encoding,
+                    &predicate,
+                    &hole_cards,
+                    &proof
+                )
+            );
+
+            // The proof is bound to the order of the cards
+            let other_cards = vec![hole_cards[1], hole_cards[0]];
+            assert_eq!(
+                CardProtocol::verify_predicate(
+                    &parameters,
+                    &pk,
+                    &encoding,
+                    &predicate,
+                    &other_cards,
+                    &proof
+                ),
+                Err(CardProtocolError::ProofVerificationError(
+                    CryptoError::ProofVerificationError(String::from("Predicate"))
+                ))
+            );
+        }
+
+        assert_eq!(
+            CardProtocol::prove_predicate(
+                rng,
+                &parameters,
+                &sk,
+                &pk,
+                &encoding,
+                &Predicate::SameSuite,
+                &hole_cards,
+            )
+            .err(),
+            Some(CardProtocolError::PredicateNotSatisfied)
+        );
+
+        // A predicate on every card of the encoding takes one branch per card and member
+        let any_card = Predicate::MemberOf(encoding.values().cloned().collect());
+        let proof = CardProtocol::prove_predicate(
+            rng,
+            &parameters,
+            &sk,
+            &pk,
+            &encoding,
+            &any_card,
+            &hole_cards,
+        )
+        .unwrap();
+        assert_eq!(proof.challenges.len(), 2 * 52);
+        assert_eq!(
+            Ok(()),
+            CardProtocol::verify_predicate(
+                &parameters,
+                &pk,
+                &encoding,
+                &any_card,
+                &hole_cards,
+                &proof
+            )
+        );
+
+        // The cards must not be empty
+        assert_eq!(
+            CardProtocol::prove_predicate(
+                rng,
+                &parameters,
+                &sk,
+                &pk,
+                &encoding,
+                &Predicate::SameValue,
+                &vec![],
+            )
+            .err(),
+            Some(CardProtocolError::PredicateNotSatisfied)
+        );
+    }
+
+    #[test]
+    fn test_flush_predicate() {
+        let rng = &mut thread_rng();
+        let parameters = CardProtocol::setup(rng, 2, 26).unwrap();
+
+        let (pk, sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+        let encoding = encode_cards::<Curve, _>(rng);
+        let mut mask = |value, suite| {
+            let card = encoding
+                .iter()
+                .find(|(_, classic)| **classic == ClassicPlayingCard::new(value, suite))
+                .unwrap()
+                .0;
+            let r = Scalar::rand(rng);
+            CardProtocol::mask(rng, &parameters, &pk, card, &r)
+                .unwrap()
+                .0
+        };
+
+        let flush = vec![
+            mask(Value::Two, Suite::Heart),
+            mask(Value::Nine, Suite::Heart),
+            mask(Value::Queen, Suite::Heart),
+        ];
+        let queen_of_clubs = mask(Value::Queen, Suite::Club);
+        let proof = CardProtocol::prove_predicate(
+            rng,
+            &parameters,
+            &sk,
+            &pk,
+            &encoding,
+            &Predicate::SameSuite,
+            &flush,
+        )
+        .unwrap();
+
+        // One branch per suit, card and member of the suit
+        assert_eq!(proof.challenges.len(), 4 * 3 * 13);
+        assert_eq!(
+            Ok(()),
+            CardProtocol::verify_predicate(
+                &parameters,
+                &pk,
+                &encoding,
+                &Predicate::SameSuite,
+                &flush,
+                &proof
+            )
+        );
+        assert!(CardProtocol::verify_predicate(
+            &parameters,
+            &pk,
+            &encoding,
+            &Predicate::SameValue,
+            &flush,
+            &proof
+        )
+        .is_err());
+
+        let mut broken = flush.clone();
+        broken[2] = queen_of_clubs;
+        assert_eq!(
+            CardProtocol::prove_predicate(
+                rng,
+                &parameters,
+                &sk,
+                &pk,
+                &encoding,
+                &Predicate::SameSuite,
+                &broken,
+            )
+            .err(),
+            Some(CardProtocolError::PredicateNotSatisfied)
+        );
+    }
+}
@@ -0,0 +1,627 @@
+use crate::discrete_log_cards::pedersen::CommitKey;
+use crate::discrete_log_cards::shuffle::{
+    absorb_points, combine_rows, hadamard_product, inner_product, linear_combination, powers,
+    sample_scalars,
+};
+
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{One, PrimeField, UniformRand, Zero};
+use ark_marlin::rng::FiatShamirRng;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use ark_std::rand::Rng;
+use digest::Digest;
+use proof_essentials::error::CryptoError;
+use std::iter;
+
+/// Proof that the entries of the rows committed in a list of commitments multiply to a public
+/// value (Bayer and Groth, section 5). The Hadamard product argument reduces the rows to a single
+/// committed row, their entrywise product, whose entries the single value product argument then
+/// multiplies.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProductProof<C: ProjectiveCurve> {
+    /// Absent for a single row, which is its own entrywise product
+    pub hadamard: Option<HadamardProof<C>>,
+    pub single_value: SingleValueProductProof<C>,
+}
+
+/// Proof that a committed row is the entrywise product of the rows committed in a list of
+/// commitments (section 5.1)
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct HadamardProof<C: ProjectiveCurve> {
+    pub product_commitment: C::Affine,
+    /// Commitments to the products of the first rows, from the first two rows to all but the last
+    pub partial_commitments: Vec<C::Affine>,
+    pub zero_argument: ZeroArgumentProof<C>,
+}
+
+/// Proof that `a_1 * b_1 + ... + a_m * b_m = 0` for committed rows and the bilinear map
+/// `a * b = a_1 b_1 y + ... + a_n b_n y^n` (section 5.2)
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ZeroArgumentProof<C: ProjectiveCurve> {
+    pub a_0_commitment: C::Affine,
+    pub b_m_commitment: C::Affine,
+    /// Commitments to the diagonals of the products, except for the middle one which is zero
+    pub diagonal_commitments: Vec<C::Affine>,
+    pub a: Vec<C::ScalarField>,
+    pub b: Vec<C::ScalarField>,
+    pub r: C::ScalarField,
+    pub s: C::ScalarField,
+    pub t: C::ScalarField,
+}
+
+/// Proof that the entries of a committed row multiply to a public value (section 5.3)
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SingleValueProductProof<C: ProjectiveCurve> {
+    pub d_commitment: C::Affine,
+    pub delta_commitment: C::Affine,
+    pub difference_commitment: C::Affine,
+    pub a_blinded: Vec<C::ScalarField>,
+    pub b_blinded: Vec<C::ScalarField>,
+    pub r_blinded: C::ScalarField,
+    pub s_blinded: C::ScalarField,
+}
+
+impl<C: ProjectiveCurve> ProductProof<C> {
+    /// Every group element of the proof
+    pub(super) fn points(&self) -> Vec<&C::Affine> {
+        let mut points = Vec::new();
+        if let Some(hadamard) = &self.hadamard {
+            let zero_argument = &hadamard.zero_argument;
+            points.push(&hadamard.product_commitment);
+            points.extend(&hadamard.partial_commitments);
+            points.push(&zero_argument.a_0_commitment);
+            points.push(&zero_argument.b_m_commitment);
+            points.extend(&zero_argument.diagonal_commitments);
+        }
+        points.push(&self.single_value.d_commitment);
+        points.push(&self.single_value.delta_commitment);
+        points.push(&self.single_value.difference_commitment);
+
+        points
+    }
+}
+
+/// Prove that the entries of `rows`, committed in `commitments` with `randomness`, multiply to
+/// `product`
+pub(super) fn prove_product<C: ProjectiveCurve, D: Digest, R: Rng>(
+    rng: &mut R,
+    ck: &CommitKey<C>,
+    commitments: &[C::Affine],
+    rows: &[Vec<C::ScalarField>],
+    randomness: &[C::ScalarField],
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<ProductProof<C>, CryptoError> {
+    if rows.len() == 1 {
+        let single_value = prove_single_value_product::<C, D, R>(
+            rng,
+            ck,
+            &commitments[0],
+            &rows[0],
+            &randomness[0],
+            fs_rng,
+        )?;
+
+        return Ok(ProductProof {
+            hadamard: None,
+            single_value,
+        });
+    }
+
+    let product = rows[1..].iter().fold(rows[0].clone(), |product, row| {
+        hadamard_product(&product, row)
+    });
+    let s = C::ScalarField::rand(rng);
+    let product_commitment = ck.commit(&product, &s).into_affine();
+
+    let hadamard = prove_hadamard::<C, D, R>(
+        rng,
+        ck,
+        (commitments, rows, randomness),
+        &product_commitment,
+        &s,
+        fs_rng,
+    )?;
+    let single_value =
+        prove_single_value_product::<C, D, R>(rng, ck, &product_commitment, &product, &s, fs_rng)?;
+
+    Ok(ProductProof {
+        hadamard: Some(hadamard),
+        single_value,
+    })
+}
+
+/// Check that the entries of the rows of length `n` committed in `commitments` multiply to
+/// `product`
+pub(super) fn verify_product<C: ProjectiveCurve, D: Digest>(
+    ck: &CommitKey<C>,
+    commitments: &[C::Affine],
+    n: usize,
+    product: &C::ScalarField,
+    proof: &ProductProof<C>,
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<(), CryptoError> {
+    let product_commitment = match &proof.hadamard {
+        None if commitments.len() == 1 => commitments[0],
+        Some(hadamard) if commitments.len() > 1 => {
+            verify_hadamard::<C, D>(ck, commitments, n, hadamard, fs_rng)?;
+            hadamard.product_commitment
+        }
+        _ => return Err(product_error()),
+    };
+
+    verify_single_value_product::<C, D>(
+        ck,
+        &product_commitment,
+        n,
+        product,
+        &proof.single_value,
+        fs_rng,
+    )
+}
+
+fn prove_hadamard<C: ProjectiveCurve, D: Digest, R: Rng>(
+    rng: &mut R,
+    ck: &CommitKey<C>,
+    (commitments, rows, randomness): CommittedRows<C>,
+    product_commitment: &C::Affine,
+    product_randomness: &C::ScalarField,
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<HadamardProof<C>, CryptoError> {
+    let m = rows.len();
+    let n = rows[0].len();
+
+    // The i-th partial product multiplies the rows up to the i-th, the last one is the product
+    let mut partials = vec![rows[0].clone()];
+    for row in &rows[1..] {
+        partials.push(hadamard_product(&partials[partials.len() - 1], row));
+    }
+    let mut partial_randomness = vec![randomness[0]];
+    partial_randomness.extend(sample_scalars::<C, R>(rng, m - 2));
+    partial_randomness.push(*product_randomness);
+
+    let partial_commitments = partials[1..m - 1]
+        .iter()
+        .zip(&partial_randomness[1..m - 1])
+        .map(|(partial, s)| ck.commit(partial, s))
+        .collect::<Vec<_>>();
+    let partial_commitments = C::batch_normalization_into_affine(&partial_commitments);
+
+    let (x, y) = hadamard_challenges::<C, D>(product_commitment, &partial_commitments, fs_rng)?;
+    let (a_commitments, b_commitments) = zero_argument_statement(
+        ck,
+        commitments,
+        &partial_commitments,
+        product_commitment,
+        n,
+        &x,
+    );
+
+    // The zero argument pairs a_2, ..., a_m, -1 with x b_1, ..., x^(m-1) b_(m-1), sum x^i b_(i+1)
+    let x_powers = powers(&x, m);
+    let mut a_rows = rows[1..].to_vec();
+    a_rows.push(vec![-C::ScalarField::one(); n]);
+    let mut a_randomness = randomness[1..].to_vec();
+    a_randomness.push(C::ScalarField::zero());
+
+    let mut b_rows = partials[..m - 1]
+        .iter()
+        .zip(&x_powers[1..])
+        .map(|(partial, power)| combine_rows(std::slice::from_ref(partial), &[*power]))
+        .collect::<Vec<_>>();
+    b_rows.push(combine_rows(&partials[1..], &x_powers[1..]));
+    let mut b_randomness = partial_randomness[..m - 1]
+        .iter()
+        .zip(&x_powers[1..])
+        .map(|(s, power)| *s * power)
+        .collect::<Vec<_>>();
+    b_randomness.push(
+        partial_randomness[1..]
+            .iter()
+            .zip(&x_powers[1..])
+            .map(|(s, power)| *s * power)
+            .sum(),
+    );
+
+    let zero_argument = prove_zero_argument::<C, D, R>(
+        rng,
+        ck,
+        (&a_commitments, &a_rows, &a_randomness),
+        (&b_commitments, &b_rows, &b_randomness),
+        &y,
+        fs_rng,
+    )?;
+
+    Ok(HadamardProof {
+        product_commitment: *product_commitment,
+        partial_commitments,
+        zero_argument,
+    })
+}
+
+fn verify_hadamard<C: ProjectiveCurve, D: Digest>(
+    ck: &CommitKey<C>,
+    commitments: &[C::Affine],
+    n: usize,
+    proof: &HadamardProof<C>,
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<(), CryptoError> {
+    if proof.partial_commitments.len() != commitments.len() - 2 {
+        return Err(hadamard_error());
+    }
+
+    let (x, y) = hadamard_challenges::<C, D>(
+        &proof.product_commitment,
+        &proof.partial_commitments,
+        fs_rng,
+    )?;
+    let (a_commitments, b_commitments) = zero_argument_statement(
+        ck,
+        commitments,
+        &proof.partial_commitments,
+        &proof.product_commitment,
+        n,
+        &x,
+    );
+
+    // The zero argument is the only check of the Hadamard product argument
+    verify_zero_argument::<C, D>(
+        ck,
+        &a_commitments,
+        &b_commitments,
+        n,
+        &y,
+        &proof.zero_argument,
+        fs_rng,
+    )
+    .map_err(|_| hadamard_error())
+}
+
+fn hadamard_challenges<C: ProjectiveCurve, D: Digest>(
+    product_commitment: &C::Affine,
+    partial_commitments: &[C::Affine],
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<(C::ScalarField, C::ScalarField), CryptoError> {
+    absorb_points::<C, D>(fs_rng, &[*product_commitment])?;
+    absorb_points::<C, D>(fs_rng, partial_commitments)?;
+
+    let x = C::ScalarField::rand(fs_rng);
+    let y = C::ScalarField::rand(fs_rng);
+
+    Ok((x, y))
+}
+
+/// The commitments of the zero argument the Hadamard product argument reduces to, computed from
+/// the commitments to the rows and to their partial products
+fn zero_argument_statement<C: ProjectiveCurve>(
+    ck: &CommitKey<C>,
+    commitments: &[C::Affine],
+    partial_commitments: &[C::Affine],
+    product_commitment: &C::Affine,
+    n: usize,
+    x: &C::ScalarField,
+) -> (Vec<C::Affine>, Vec<C::Affine>) {
+    let m = commitments.len();
+    let x_powers = powers(x, m);
+
+    let mut a_commitments = commitments[1..].to_vec();
+    a_commitments.push(
+        ck.commit_public(&vec![-C::ScalarField::one(); n], &C::ScalarField::zero())
+            .into_affine(),
+    );
+
+    // The partial products start with the first row and end with the product
+    let partials = iter::once(&commitments[0])
+        .chain(partial_commitments)
+        .chain(iter::once(product_commitment))
+        .copied()
+        .collect::<Vec<_>>();
+    let mut b_commitments = partials[..m - 1]
+        .iter()
+        .zip(&x_powers[1..])
+        .map(|(commitment, power)| commitment.mul(power.into_repr()))
+        .collect::<Vec<_>>();
+    b_commitments.push(linear_combination::<C>(&partials[1..], &x_powers[1..]));
+
+    (
+        a_commitments,
+        C::batch_normalization_into_affine(&b_commitments),
+    )
+}
+
+/// Rows of scalars, with their commitments and randomness
+type CommittedRows<'a, C> = (
+    &'a [<C as ProjectiveCurve>::Affine],
+    &'a [Vec<<C as ProjectiveCurve>::ScalarField>],
+    &'a [<C as ProjectiveCurve>::ScalarField],
+);
+
+fn prove_zero_argument<C: ProjectiveCurve, D: Digest, R: Rng>(
+    rng: &mut R,
+    ck: &CommitKey<C>,
+    a_side: CommittedRows<C>,
+    b_side: CommittedRows<C>,
+    y: &C::ScalarField,
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<ZeroArgumentProof<C>, CryptoError> {
+    let (_, a_rows, a_randomness) = a_side;
+    let (_, b_rows, b_randomness) = b_side;
+    let m = a_rows.len();
+    let n = a_rows[0].len();
+    let y_powers = powers(y, n + 1);
+
+    // Blind the a side with a random a_0 in front and the b side with a random b_(m+1) at the back
+    let a_0 = sample_scalars::<C, R>(rng, n);
+    let r_0 = C::ScalarField::rand(rng);
+    let b_m = sample_scalars::<C, R>(rng, n);
+    let s_m = C::ScalarField::rand(rng);
+    let a_0_commitment = ck.commit(&a_0, &r_0).into_affine();
+    let b_m_commitment = ck.commit(&b_m, &s_m).into_affine();
+
+    let a_rows = iter::once(a_0).chain(a_rows.to_vec()).collect::<Vec<_>>();
+    let a_randomness = iter::once(r_0)
+        .chain(a_randomness.to_vec())
+        .collect::<Vec<_>>();
+    let b_rows = b_rows
+        .iter()
+        .cloned()
+        .chain(iter::once(b_m))
+        .collect::<Vec<_>>();
+    let b_randomness = b_randomness
+        .iter()
+        .copied()
+        .chain(iter::once(s_m))
+        .collect::<Vec<_>>();
+
+    // The k-th diagonal sums the products of a_i and b_j for i - j = k - m. The (m + 1)-th one
+    // pairs every row with its counterpart and sums to zero.
+    let mut diagonals = vec![C::ScalarField::zero(); 2 * m + 1];
+    for (i, a) in a_rows.iter().enumerate() {
+        for (j, b) in b_rows.iter().enumerate() {
+            diagonals[i + m - j] += bilinear_map(a, b, &y_powers);
+        }
+    }
+    let mut t = sample_scalars::<C, R>(rng, 2 * m + 1);
+    t[m + 1] = C::ScalarField::zero();
+    let diagonal_commitments = diagonals
+        .iter()
+        .zip(&t)
+        .enumerate()
+        .filter(|(k, _)| *k != m + 1)
+        .map(|(_, (d, t))| ck.commit(&[*d], t))
+        .collect::<Vec<_>>();
+    let diagonal_commitments = C::batch_normalization_into_affine(&diagonal_commitments);
+
+    let x = zero_argument_challenge::<C, D>(
+        &a_0_commitment,
+        &b_m_commitment,
+        &diagonal_commitments,
+        fs_rng,
+    )?;
+    let x_powers = powers(&x, 2 * m + 1);
+    let reversed_powers = x_powers[..=m].iter().rev().copied().collect::<Vec<_>>();
+
+    Ok(ZeroArgumentProof {
+        a_0_commitment,
+        b_m_commitment,
+        diagonal_commitments,
+        a: combine_rows(&a_rows, &x_powers[..=m]),
+        b: combine_rows(&b_rows, &reversed_powers),
+        r: inner_product(&a_randomness, &x_powers[..=m]),
+        s: inner_product(&b_randomness, &reversed_powers),
+        t: inner_product(&t, &x_powers),
+    })
+}
+
+fn verify_zero_argument<C: ProjectiveCurve, D: Digest>(
+    ck: &CommitKey<C>,
+    a_commitments: &[C::Affine],
+    b_commitments: &[C::Affine],
+    n: usize,
+    y: &C::ScalarField,
+    proof: &ZeroArgumentProof<C>,
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<(), CryptoError> {
+    let m = a_commitments.len();
+    if proof.diagonal_commitments.len() != 2 * m || proof.a.len() != n || proof.b.len() != n {
+        return Err(zero_argument_error());
+    }
+
+    let x = zero_argument_challenge::<C, D>(
+        &proof.a_0_commitment,
+        &proof.b_m_commitment,
+        &proof.diagonal_commitments,
+        fs_rng,
+    )?;
+    let x_powers = powers(&x, 2 * m + 1);
+    let reversed_powers = x_powers[..=m].iter().rev().copied().collect::<Vec<_>>();
+    let diagonal_powers = x_powers
+        .iter()
+        .enumerate()
+        .filter(|(k, _)| *k != m + 1)
+        .map(|(_, power)| *power)
+        .collect::<Vec<_>>();
+
+    let a_commitments = iter::once(&proof.a_0_commitment)
+        .chain(a_commitments)
+        .copied()
+        .collect::<Vec<_>>();
+    let b_commitments = b_commitments
+        .iter()
+        .chain(iter::once(&proof.b_m_commitment))
+        .copied()
+        .collect::<Vec<_>>();
+    let y_powers = powers(y, n + 1);
+
+    if linear_combination::<C>(&a_commitments, &x_powers[..=m])
+        != ck.commit_public(&proof.a, &proof.r)
+        || linear_combination::<C>(&b_commitments, &reversed_powers)
+            != ck.commit_public(&proof.b, &proof.s)
+        || linear_combination::<C>(&proof.diagonal_commitments, &diagonal_powers)
+            != ck.commit_public(&[bilinear_map(&proof.a, &proof.b, &y_powers)], &proof.t)
+    {
+        return Err(zero_argument_error());
+    }
+
+    Ok(())
+}
+
+fn zero_argument_challenge<C: ProjectiveCurve, D: Digest>(
+    a_0_commitment: &C::Affine,
+    b_m_commitment: &C::Affine,
+    diagonal_commitments: &[C::Affine],
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<C::ScalarField, CryptoError> {
+    absorb_points::<C, D>(fs_rng, &[*a_0_commitment, *b_m_commitment])?;
+    absorb_points::<C, D>(fs_rng, diagonal_commitments)?;
+
+    Ok(C::ScalarField::rand(fs_rng))
+}
+
+fn prove_single_value_product<C: ProjectiveCurve, D: Digest, R: Rng>(
+    rng: &mut R,
+    ck: &CommitKey<C>,
+    commitment: &C::Affine,
+    row: &[C::ScalarField],
+    randomness: &C::ScalarField,
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<SingleValueProductProof<C>, CryptoError> {
+    let n = row.len();
+
+    // b_k = a_1 ... a_k, the last one is the product
+    let mut partials = vec![row[0]];
+    for a in &row[1..] {
+        partials.push(partials[partials.len() - 1] * a);
+    }
+
+    // delta_1 = d_1 and delta_n = 0: for a single entry, which is the public product, d_1 is zero
+    let mut d = sample_scalars::<C, R>(rng, n);
+    if n == 1 {
+        d[0] = C::ScalarField::zero();
+    }
+    let r_d = C::ScalarField::rand(rng);
+    let mut delta = sample_scalars::<C, R>(rng, n);
+    delta[0] = d[0];
+    delta[n - 1] = C::ScalarField::zero();
+    let s_1 = C::ScalarField::rand(rng);
+    let s_x = C::ScalarField::rand(rng);
+
+    let delta_products = (0..n - 1).map(|k| -delta[k] * d[k + 1]).collect::<Vec<_>>();
+    let differences = (0..n - 1)
+        .map(|k| delta[k + 1] - row[k + 1] * delta[k] - partials[k] * d[k + 1])
+        .collect::<Vec<_>>();
+
+    let commitments = C::batch_normalization_into_affine(&[
+        ck.commit(&d, &r_d),
+        ck.commit(&delta_products, &s_1),
+        ck.commit(&differences, &s_x),
+    ]);
+    let (d_commitment, delta_commitment, difference_commitment) =
+        (commitments[0], commitments[1], commitments[2]);
+
+    let x = single_value_product_challenge::<C, D>(
+        commitment,
+        &d_commitment,
+        &delta_commitment,
+        &difference_commitment,
+        fs_rng,
+    )?;
+
+    Ok(SingleValueProductProof {
+        d_commitment,
+        delta_commitment,
+        difference_commitment,
+        a_blinded: row.iter().zip(&d).map(|(a, d)| x * a + d).collect(),
+        b_blinded: partials
+            .iter()
+            .zip(&delta)
+            .map(|(b, delta)| x * b + delta)
+            .collect(),
+        r_blinded: x * randomness + r_d,
+        s_blinded: x * s_x + s_1,
+    })
+}
+
+fn verify_single_value_product<C: ProjectiveCurve, D: Digest>(
+    ck: &CommitKey<C>,
+    commitment: &C::Affine,
+    n: usize,
+    product: &C::ScalarField,
+    proof: &SingleValueProductProof<C>,
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<(), CryptoError> {
+    if proof.a_blinded.len() != n || proof.b_blinded.len() != n {
+        return Err(single_value_product_error());
+    }
+
+    let x = single_value_product_challenge::<C, D>(
+        commitment,
+        &proof.d_commitment,
+        &proof.delta_commitment,
+        &proof.difference_commitment,
+        fs_rng,
+    )?;
+    let (a, b) = (&proof.a_blinded, &proof.b_blinded);
+    let links = (0..n - 1)
+        .map(|k| x * b[k + 1] - b[k] * a[k + 1])
+        .collect::<Vec<_>>();
+    let x = x.into_repr();
+
+    if commitment.mul(x).add_mixed(&proof.d_commitment) != ck.commit_public(a, &proof.r_blinded)
+        || proof
+            .difference_commitment
+            .mul(x)
+            .add_mixed(&proof.delta_commitment)
+            != ck.commit_public(&links, &proof.s_blinded)
+        || b[0] != a[0]
+        || b[n - 1] != *product * C::ScalarField::from_repr(x).unwrap()
+    {
+        return Err(single_value_product_error());
+    }
+
+    Ok(())
+}
+
+fn single_value_product_challenge<C: ProjectiveCurve, D: Digest>(
+    commitment: &C::Affine,
+    d_commitment: &C::Affine,
+    delta_commitment: &C::Affine,
+    difference_commitment: &C::Affine,
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<C::ScalarField, CryptoError> {
+    absorb_points::<C, D>(
+        fs_rng,
+        &[
+            *commitment,
+            *d_commitment,
+            *delta_commitment,
+            *difference_commitment,
+        ],
+    )?;
+
+    Ok(C::ScalarField::rand(fs_rng))
+}
+
+/// `a_1 b_1 y + ... + a_n b_n y^n`, for the powers `1, y, ..., y^n` of `y`
+fn bilinear_map<F: PrimeField>(a: &[F], b: &[F], y_powers: &[F]) -> F {
+    a.iter()
+        .zip(b)
+        .zip(&y_powers[1..])
+        .map(|((a, b), y)| *a * b * y)
+        .sum()
+}
+
+fn product_error() -> CryptoError {
+    CryptoError::ProofVerificationError(String::from("Product Argument (5)"))
+}
+
+fn hadamard_error() -> CryptoError {
+    CryptoError::ProofVerificationError(String::from("Hadamard Product (5.1)"))
+}
+
+fn zero_argument_error() -> CryptoError {
+    CryptoError::ProofVerificationError(String::from("Zero Argument (5.2)"))
+}
+
+fn single_value_product_error() -> CryptoError {
+    CryptoError::ProofVerificationError(String::from("Single Value Product Argument (5.3)"))
+}
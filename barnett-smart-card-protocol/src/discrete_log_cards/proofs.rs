@@ -0,0 +1,251 @@
+use crate::discrete_log_cards::constant_time::ladder_mul;
+use crate::discrete_log_cards::{FixedBaseTable, BATCH_RNG_SEED};
+
+use ark_ec::msm::VariableBaseMSM;
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{to_bytes, PrimeField, UniformRand};
+use ark_marlin::rng::FiatShamirRng;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use ark_std::rand::Rng;
+use digest::Digest;
+use proof_essentials::error::CryptoError;
+
+/// Chaum-Pedersen proof that `x = g * w` and `y = h * w` for the same secret `w`. This is the proof
+/// of masking, remasking, reveal and rekey operations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct DLEqualityProof<C: ProjectiveCurve> {
+    pub a: C::Affine,
+    pub b: C::Affine,
+    pub r: C::ScalarField,
+}
+
+/// Schnorr proof of knowledge of the discrete log of a public key
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SchnorrProof<C: ProjectiveCurve> {
+    pub random_commit: C::Affine,
+    pub opening: C::ScalarField,
+}
+
+/// A base point of a proof. The encryption generator is multiplied through its window table, other
+/// points with the ladder when the scalar is secret and with `AffineCurve::mul` when it is public.
+#[derive(Clone, Copy)]
+pub(super) enum Base<'a, C: ProjectiveCurve> {
+    Point(&'a C::Affine),
+    Table(&'a FixedBaseTable<C>),
+}
+
+impl<'a, C: ProjectiveCurve> Base<'a, C> {
+    pub(super) fn point(&self) -> &'a C::Affine {
+        match self {
+            Self::Point(point) => point,
+            Self::Table(table) => table.base(),
+        }
+    }
+
+    pub(super) fn mul_secret(&self, scalar: &C::ScalarField) -> C {
+        match self {
+            Self::Point(point) => ladder_mul::<C>(point, scalar),
+            Self::Table(table) => table.mul(scalar),
+        }
+    }
+
+    pub(super) fn mul_public(&self, scalar: &C::ScalarField) -> C {
+        match self {
+            Self::Point(point) => point.mul(scalar.into_repr()),
+            Self::Table(table) => table.mul(scalar),
+        }
+    }
+}
+
+/// Prove that `statement = (g * witness, h * witness)` for the parameters `(g, h)`, with the
+/// challenge bound to `seed`. The nonce is multiplied in constant time, so the prover can be used
+/// with secret keys and masking factors.
+pub(super) fn prove_dl_equality<C: ProjectiveCurve, D: Digest, R: Rng>(
+    rng: &mut R,
+    parameters: (Base<C>, Base<C>),
+    statement: (&C::Affine, &C::Affine),
+    witness: &C::ScalarField,
+    seed: &[u8],
+) -> Result<DLEqualityProof<C>, CryptoError> {
+    let (g, h) = parameters;
+
+    let omega = C::ScalarField::rand(rng);
+    let a = g.mul_secret(&omega).into_affine();
+    let b = h.mul_secret(&omega).into_affine();
+
+    let challenge = dl_equality_challenge::<C, D>(seed, (g.point(), h.point()), statement, &a, &b)?;
+
+    Ok(DLEqualityProof {
+        a,
+        b,
+        r: omega + challenge * witness,
+    })
+}
+
+/// Check that `g * r == a + x * c` and `h * r == b + y * c` for the challenge `c` of the proof
+pub(super) fn verify_dl_equality<C: ProjectiveCurve, D: Digest>(
+    parameters: (Base<C>, Base<C>),
+    statement: (&C::Affine, &C::Affine),
+    proof: &DLEqualityProof<C>,
+    seed: &[u8],
+) -> Result<(), CryptoError> {
+    let (g, h) = parameters;
+    let (x, y) = statement;
+
+    let challenge =
+        dl_equality_challenge::<C, D>(seed, (g.point(), h.point()), statement, &proof.a, &proof.b)?
+            .into_repr();
+
+    if g.mul_public(&proof.r) != x.mul(challenge).add_mixed(&proof.a)
+        || h.mul_public(&proof.r) != y.mul(challenge).add_mixed(&proof.b)
+    {
+        return Err(CryptoError::ProofVerificationError(String::from(
+            "Chaum-Pedersen",
+        )));
+    }
+
+    Ok(())
+}
+
+/// A Chaum-Pedersen statement `(parameters, statement)` together with its proof
+pub(super) type DLEqualityInstance<'a, C> = (
+    (
+        &'a <C as ProjectiveCurve>::Affine,
+        &'a <C as ProjectiveCurve>::Affine,
+    ),
+    (
+        &'a <C as ProjectiveCurve>::Affine,
+        &'a <C as ProjectiveCurve>::Affine,
+    ),
+    &'a DLEqualityProof<C>,
+);
+
+/// Verify many Chaum-Pedersen proofs, each with its challenge bound to `seed`, at once.
+/// The two equations of every proof are weighed by scalars drawn from a transcript of the whole
+/// batch and summed into a single multi-scalar multiplication, which is the identity for a batch of
+/// valid proofs and, except with negligible probability, for no batch holding an invalid one.
+pub(super) fn batch_verify_dl_equality<C: ProjectiveCurve, D: Digest>(
+    seed: &[u8],
+    instances: &[DLEqualityInstance<C>],
+) -> Result<(), CryptoError> {
+    let mut challenges = Vec::with_capacity(instances.len());
+    let mut weights_rng = FiatShamirRng::<D>::from_seed(&to_bytes![BATCH_RNG_SEED, seed]?);
+    for (parameters, statement, proof) in instances {
+        challenges.push(dl_equality_challenge::<C, D>(
+            seed,
+            *parameters,
+            *statement,
+            &proof.a,
+            &proof.b,
+        )?);
+
+        weights_rng.absorb(&to_bytes![
+            parameters.0,
+            parameters.1,
+            statement.0,
+            statement.1,
+            proof.a,
+            proof.b,
+            proof.r
+        ]?);
+    }
+
+    let mut bases = Vec::with_capacity(6 * instances.len());
+    let mut scalars = Vec::with_capacity(6 * instances.len());
+    for (((g, h), (x, y), proof), challenge) in instances.iter().zip(challenges) {
+        let first = C::ScalarField::rand(&mut weights_rng);
+        let second = C::ScalarField::rand(&mut weights_rng);
+
+        // first * (g * r - x * c - a) + second * (h * r - y * c - b)
+        bases.extend_from_slice(&[**g, **x, proof.a, **h, **y, proof.b]);
+        scalars.extend_from_slice(&[
+            first * proof.r,
+            -(first * challenge),
+            -first,
+            second * proof.r,
+            -(second * challenge),
+            -second,
+        ]);
+    }
+    let scalars = scalars.iter().map(|s| s.into_repr()).collect::<Vec<_>>();
+
+    if !VariableBaseMSM::multi_scalar_mul(&bases, &scalars).is_zero() {
+        return Err(CryptoError::ProofVerificationError(String::from(
+            "Chaum-Pedersen",
+        )));
+    }
+
+    Ok(())
+}
+
+fn dl_equality_challenge<C: ProjectiveCurve, D: Digest>(
+    seed: &[u8],
+    parameters: (&C::Affine, &C::Affine),
+    statement: (&C::Affine, &C::Affine),
+    a: &C::Affine,
+    b: &C::Affine,
+) -> Result<C::ScalarField, CryptoError> {
+    challenge::<C, D>(
+        seed,
+        &[parameters.0, parameters.1, statement.0, statement.1, a, b],
+    )
+}
+
+/// Prove knowledge of the secret key `sk` of `pk = g * sk`, with the challenge bound to `seed` and
+/// the nonce multiplied in constant time
+pub(super) fn prove_schnorr<C: ProjectiveCurve, D: Digest, R: Rng>(
+    rng: &mut R,
+    g: Base<C>,
+    pk: &C::Affine,
+    sk: &C::ScalarField,
+    seed: &[u8],
+) -> Result<SchnorrProof<C>, CryptoError> {
+    let nonce = C::ScalarField::rand(rng);
+    let random_commit = g.mul_secret(&nonce).into_affine();
+
+    let challenge = challenge::<C, D>(seed, &[g.point(), pk, &random_commit])?;
+
+    Ok(SchnorrProof {
+        random_commit,
+        opening: nonce + challenge * sk,
+    })
+}
+
+/// Check that `g * opening == random_commit + pk * c` for the challenge `c` of the proof
+pub(super) fn verify_schnorr<C: ProjectiveCurve, D: Digest>(
+    g: Base<C>,
+    pk: &C::Affine,
+    proof: &SchnorrProof<C>,
+    seed: &[u8],
+) -> Result<(), CryptoError> {
+    let challenge = challenge::<C, D>(seed, &[g.point(), pk, &proof.random_commit])?;
+
+    if g.mul_public(&proof.opening)
+        != pk
+            .mul(challenge.into_repr())
+            .add_mixed(&proof.random_commit)
+    {
+        return Err(CryptoError::ProofVerificationError(String::from(
+            "Schnorr Identification",
+        )));
+    }
+
+    Ok(())
+}
+
+/// The challenge of a Chaum-Pedersen or Schnorr proof: the digest of `seed` followed by the
+/// encodings of `points`, read as a little-endian integer modulo the group order. It is a single
+/// call to `D` rather than a draw from `FiatShamirRng`, so that a circuit can recompute it when `D`
+/// is `PoseidonDigest`.
+pub(super) fn challenge<C: ProjectiveCurve, D: Digest>(
+    seed: &[u8],
+    points: &[&C::Affine],
+) -> Result<C::ScalarField, CryptoError> {
+    let mut hasher = D::new();
+    hasher.update(seed);
+    for point in points {
+        hasher.update(to_bytes![point]?);
+    }
+
+    Ok(C::ScalarField::from_le_bytes_mod_order(&hasher.finalize()))
+}
@@ -0,0 +1,370 @@
+use crate::discrete_log_cards::{
+    proofs, Card, DLCards, DLEqualityProof, MaskedCard, Parameters, PlayerSecretKey, PublicKey,
+    RevealToken, REVEAL_RNG_SEED,
+};
+use crate::error::CardProtocolError;
+use crate::{BarnettSmartProtocol, Reveal};
+
+use ark_ec::ProjectiveCurve;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use ark_std::rand::Rng;
+use ark_std::Zero;
+use blake2::Blake2s;
+use digest::Digest;
+
+type RevealProof<C> = DLEqualityProof<C>;
+
+/// A card opened to the whole table. The record carries the reveal token of every player together
+/// with its proof, so that any observer who knows the players' public keys can check the opening
+/// without having been at the table.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct OpenedCard<C: ProjectiveCurve> {
+    pub masked_card: MaskedCard<C>,
+    pub card: Card<C>,
+    pub reveal_tokens: Vec<(RevealToken<C>, RevealProof<C>, PublicKey<C>)>,
+}
+
+impl<C: ProjectiveCurve> OpenedCard<C> {
+    /// Check that the opening carries exactly one valid reveal token per player key and that the
+    /// tokens open the masked card to the claimed card.
+    pub fn verify(
+        &self,
+        pp: &Parameters<C>,
+        player_keys: &Vec<PublicKey<C>>,
+    ) -> Result<(), CardProtocolError> {
+        self.verify_with_transcript::<Blake2s>(pp, player_keys)
+    }
+
+    /// Same as `verify`, for openings produced by `DLCards<C, D>`
+    pub fn verify_with_transcript<D: Digest>(
+        &self,
+        pp: &Parameters<C>,
+        player_keys: &Vec<PublicKey<C>>,
+    ) -> Result<(), CardProtocolError> {
+        let token_keys = self
+            .reveal_tokens
+            .iter()
+            .map(|(_, _, pk)| *pk)
+            .collect::<Vec<_>>();
+        check_one_per_key::<C>(player_keys, &token_keys)?;
+
+        let card = DLCards::<C, D>::open_verified(pp, &self.masked_card, &self.reveal_tokens)?;
+        if card != self.card {
+            return Err(CardProtocolError::InvalidOpenedCard);
+        }
+
+        Ok(())
+    }
+}
+
+impl<C: ProjectiveCurve, D: Digest> DLCards<C, D> {
+    /// A player computes their reveal tokens for the cards at the given deck indices, to be opened
+    /// to the whole table.
+    pub fn compute_public_reveal_tokens<R: Rng>(
+        rng: &mut R,
+        pp: &Parameters<C>,
+        sk: &PlayerSecretKey<C>,
+        pk: &PublicKey<C>,
+        deck: &Vec<MaskedCard<C>>,
+        indices: &Vec<usize>,
+    ) -> Result<Vec<(RevealToken<C>, RevealProof<C>)>, CardProtocolError> {
+        indices
+            .iter()
+            .map(|&i| {
+                let masked_card = deck.get(i).ok_or(CardProtocolError::InvalidCardIndex(i))?;
+                Self::compute_reveal_token(rng, pp, sk, pk, masked_card)
+            })
+            .collect()
+    }
+
+    /// Open the cards at the given deck indices to everyone. `seat_tokens` holds, for each player,
+    /// their public key and their tokens for `indices` in the same order. There must be exactly one
+    /// entry for each of `player_keys`, and all tokens are verified before any card is returned.
+    pub fn reveal_public(
+        pp: &Parameters<C>,
+        player_keys: &[PublicKey<C>],
+        deck: &Vec<MaskedCard<C>>,
+        indices: &Vec<usize>,
+        seat_tokens: &Vec<(PublicKey<C>, Vec<(RevealToken<C>, RevealProof<C>)>)>,
+    ) -> Result<Vec<OpenedCard<C>>, CardProtocolError> {
+        let seat_keys = seat_tokens.iter().map(|(pk, _)| *pk).collect::<Vec<_>>();
+        check_one_per_key::<C>(player_keys, &seat_keys)?;
+        if seat_tokens
+            .iter()
+            .any(|(_, tokens)| tokens.len() != indices.len())
+        {
+            return Err(CardProtocolError::RevealTokenMismatch);
+        }
+
+        let openings = indices
+            .iter()
+            .enumerate()
+            .map(|(j, &i)| {
+                let masked_card = *deck.get(i).ok_or(CardProtocolError::InvalidCardIndex(i))?;
+
+                let reveal_tokens = seat_tokens
+                    .iter()
+                    .map(|(pk, tokens)| (tokens[j].0, tokens[j].1, *pk))
+                    .collect::<Vec<_>>();
+
+                Ok((masked_card, reveal_tokens))
+            })
+            .collect::<Result<Vec<_>, CardProtocolError>>()?;
+
+        let reveals = openings
+            .iter()
+            .flat_map(|(masked_card, reveal_tokens)| {
+                reveal_tokens
+                    .iter()
+                    .map(move |(token, proof, pk)| (*masked_card, *token, *proof, *pk))
+            })
+            .collect::<Vec<_>>();
+        Self::verify_reveal_batch(pp, &reveals)?;
+
+        openings
+            .into_iter()
+            .map(|(masked_card, reveal_tokens)| {
+                let card = aggregate_tokens(&reveal_tokens).reveal(&masked_card)?;
+
+                Ok(OpenedCard {
+                    masked_card,
+                    card,
+                    reveal_tokens,
+                })
+            })
+            .collect()
+    }
+
+    /// Verify the reveal tokens of many masked cards, each given with its proof and the key of the
+    /// player who computed it. Same as calling `verify_reveal` on every token, with all proofs
+    /// checked in a single multi-scalar multiplication.
+    pub fn verify_reveal_batch(
+        pp: &Parameters<C>,
+        reveals: &[(MaskedCard<C>, RevealToken<C>, RevealProof<C>, PublicKey<C>)],
+    ) -> Result<(), CardProtocolError> {
+        for (masked_card, reveal_token, _, pk) in reveals {
+            Self::validate_public_key(pk)?;
+            Self::validate_masked_card(masked_card)?;
+            Self::validate_reveal_token(reveal_token)?;
+        }
+
+        let instances = reveals
+            .iter()
+            .map(|(masked_card, reveal_token, proof, pk)| {
+                (
+                    (&masked_card.0, &pp.enc_parameters.generator),
+                    (&reveal_token.0, pk),
+                    proof,
+                )
+            })
+            .collect::<Vec<_>>();
+        proofs::batch_verify_dl_equality::<C, D>(REVEAL_RNG_SEED, &instances)?;
+
+        Ok(())
+    }
+
+    /// Batch-verify the reveal tokens of a single card and open it
+    fn open_verified(
+        pp: &Parameters<C>,
+        masked_card: &MaskedCard<C>,
+        reveal_tokens: &Vec<(RevealToken<C>, RevealProof<C>, PublicKey<C>)>,
+    ) -> Result<Card<C>, CardProtocolError> {
+        let reveals = reveal_tokens
+            .iter()
+            .map(|(token, proof, pk)| (*masked_card, *token, *proof, *pk))
+            .collect::<Vec<_>>();
+        Self::verify_reveal_batch(pp, &reveals)?;
+
+        aggregate_tokens(reveal_tokens).reveal(masked_card)
+    }
+}
+
+/// Check that `token_keys` holds every key of `player_keys` exactly once and no other key. Without
+/// any player key there is nothing to verify the opening against, so it is rejected as well.
+fn check_one_per_key<C: ProjectiveCurve>(
+    player_keys: &[PublicKey<C>],
+    token_keys: &[PublicKey<C>],
+) -> Result<(), CardProtocolError> {
+    if player_keys.is_empty() || token_keys.len() != player_keys.len() {
+        return Err(CardProtocolError::RevealTokenMismatch);
+    }
+
+    for pk in player_keys {
+        let issued = token_keys.iter().filter(|&token_pk| token_pk == pk).count();
+        if issued != 1 {
+            return Err(CardProtocolError::RevealTokenMismatch);
+        }
+    }
+
+    Ok(())
+}
+
+fn aggregate_tokens<C: ProjectiveCurve>(
+    reveal_tokens: &Vec<(RevealToken<C>, RevealProof<C>, PublicKey<C>)>,
+) -> RevealToken<C> {
+    reveal_tokens
+        .iter()
+        .fold(RevealToken::<C>::zero(), |aggregate, (token, _, _)| {
+            aggregate + *token
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::discrete_log_cards;
+    use crate::error::CardProtocolError;
+    use crate::BarnettSmartProtocol;
+
+    use ark_ff::UniformRand;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use proof_essentials::error::CryptoError;
+    use proof_essentials::utils::rand::sample_vector;
+    use rand::thread_rng;
+
+    // Choose elliptic curve setting
+    type Curve = starknet_curve::Projective;
+    type Scalar = starknet_curve::Fr;
+
+    // Instantiate concrete type for our card protocol
+    type CardProtocol = discrete_log_cards::DLCards<Curve>;
+    type PublicKey = discrete_log_cards::PublicKey<Curve>;
+
+    type Card = discrete_log_cards::Card<Curve>;
+    type MaskedCard = discrete_log_cards::MaskedCard<Curve>;
+    type OpenedCard = discrete_log_cards::OpenedCard<Curve>;
+
+    #[test]
+    fn test_reveal_public() {
+        let rng = &mut thread_rng();
+        let m = 2;
+        let n = 3;
+
+        let num_of_players = 4;
+
+        let parameters = CardProtocol::setup(rng, m, n).unwrap();
+
+        let players = (0..num_of_players)
+            .map(|_| CardProtocol::player_keygen(rng, &parameters).unwrap())
+            .collect::<Vec<_>>();
+        let player_keys = players.iter().map(|p| p.0).collect::<Vec<PublicKey>>();
+        let shared_key = player_keys.iter().sum::<PublicKey>();
+
+        let cards: Vec<Card> = sample_vector(rng, m * n);
+        let deck = cards
+            .iter()
+            .map(|card| {
+                let alpha = Scalar::rand(rng);
+                CardProtocol::mask(rng, &parameters, &shared_key, card, &alpha)
+                    .unwrap()
+                    .0
+            })
+            .collect::<Vec<MaskedCard>>();
+
+        let indices = vec![1, 4, 5];
+        let seat_tokens = players
+            .iter()
+            .map(|(pk, sk)| {
+                let tokens = CardProtocol::compute_public_reveal_tokens(
+                    rng,
+                    &parameters,
+                    sk,
+                    pk,
+                    &deck,
+                    &indices,
+                )
+                .unwrap();
+                (*pk, tokens)
+            })
+            .collect::<Vec<_>>();
+
+        let opened =
+            CardProtocol::reveal_public(&parameters, &player_keys, &deck, &indices, &seat_tokens)
+                .unwrap();
+
+        for (opened_card, &i) in opened.iter().zip(indices.iter()) {
+            assert_eq!(opened_card.card, cards[i]);
+
+            // An observer checks the serialized record against the table's keys
+            let mut bytes = Vec::new();
+            opened_card.serialize(&mut bytes).unwrap();
+            let received = OpenedCard::deserialize(&bytes[..]).unwrap();
+            assert_eq!(Ok(()), received.verify(&parameters, &player_keys));
+        }
+
+        let mut missing_seat = opened[0].clone();
+        missing_seat.reveal_tokens.pop();
+        assert_eq!(
+            missing_seat.verify(&parameters, &player_keys),
+            Err(CardProtocolError::RevealTokenMismatch)
+        );
+
+        let mut wrong_card = opened[0].clone();
+        wrong_card.card = Card::rand(rng);
+        assert_eq!(
+            wrong_card.verify(&parameters, &player_keys),
+            Err(CardProtocolError::InvalidOpenedCard)
+        );
+
+        // A single invalid proof among all the tokens fails the whole batch
+        let mut forged_tokens = seat_tokens.clone();
+        forged_tokens[2].1[1].1.r += Scalar::rand(rng);
+        assert_eq!(
+            CardProtocol::reveal_public(&parameters, &player_keys, &deck, &indices, &forged_tokens)
+                .err(),
+            Some(CardProtocolError::ProofVerificationError(
+                CryptoError::ProofVerificationError(String::from("Chaum-Pedersen"))
+            ))
+        );
+
+        // Every player of the table opens the cards exactly once
+        let reveal_public = |seat_tokens: &Vec<_>| {
+            CardProtocol::reveal_public(&parameters, &player_keys, &deck, &indices, seat_tokens)
+                .err()
+        };
+        let mut missing_seat = seat_tokens.clone();
+        missing_seat.pop();
+        let mut duplicate_seat = seat_tokens.clone();
+        duplicate_seat[3] = duplicate_seat[0].clone();
+        assert_eq!(
+            reveal_public(&vec![]),
+            Some(CardProtocolError::RevealTokenMismatch)
+        );
+        assert_eq!(
+            reveal_public(&missing_seat),
+            Some(CardProtocolError::RevealTokenMismatch)
+        );
+        assert_eq!(
+            reveal_public(&duplicate_seat),
+            Some(CardProtocolError::RevealTokenMismatch)
+        );
+        assert_eq!(
+            CardProtocol::reveal_public(&parameters, &[], &deck, &indices, &vec![]).err(),
+            Some(CardProtocolError::RevealTokenMismatch)
+        );
+
+        let mut no_tokens = opened[0].clone();
+        no_tokens.reveal_tokens.clear();
+        assert_eq!(
+            no_tokens.verify(&parameters, &vec![]),
+            Err(CardProtocolError::RevealTokenMismatch)
+        );
+
+        let mut forged_opening = opened[1].clone();
+        forged_opening.reveal_tokens[3].1.a = forged_opening.reveal_tokens[0].1.a;
+        assert!(forged_opening.verify(&parameters, &player_keys).is_err());
+
+        let (pk, sk) = &players[0];
+        assert_eq!(
+            CardProtocol::compute_public_reveal_tokens(
+                rng,
+                &parameters,
+                sk,
+                pk,
+                &deck,
+                &vec![m * n]
+            )
+            .err(),
+            Some(CardProtocolError::InvalidCardIndex(m * n))
+        );
+    }
+}
@@ -1,9 +1,9 @@
+use crate::discrete_log_cards::constant_time::ladder_mul;
 use crate::discrete_log_cards::MaskedCard;
 use crate::error::CardProtocolError;
-use crate::{Mask, Remask};
+use crate::Remask;
 
 use ark_ec::ProjectiveCurve;
-use ark_ff::Zero;
 use proof_essentials::homomorphic_encryption::{el_gamal, el_gamal::ElGamal};
 
 impl<C: ProjectiveCurve> Remask<C::ScalarField, ElGamal<C>> for MaskedCard<C> {
@@ -13,8 +13,11 @@ impl<C: ProjectiveCurve> Remask<C::ScalarField, ElGamal<C>> for MaskedCard<C> {
         shared_key: &el_gamal::PublicKey<C>,
         alpha: &C::ScalarField,
     ) -> Result<el_gamal::Ciphertext<C>, CardProtocolError> {
-        let zero = el_gamal::Plaintext::zero();
-        let masking_point = zero.mask(pp, shared_key, alpha)?;
+        // A mask of zero with the masking factor `alpha`
+        let masking_point = el_gamal::Ciphertext(
+            ladder_mul::<C>(&pp.generator, alpha).into_affine(),
+            ladder_mul::<C>(shared_key, alpha).into_affine(),
+        );
         let remasked_cipher = *self + masking_point;
 
         Ok(remasked_cipher)
@@ -24,12 +27,12 @@ impl<C: ProjectiveCurve> Remask<C::ScalarField, ElGamal<C>> for MaskedCard<C> {
 #[cfg(test)]
 mod test {
     use crate::discrete_log_cards;
+    use crate::error::CardProtocolError;
     use crate::BarnettSmartProtocol;
 
     use ark_ff::UniformRand;
     use ark_std::{rand::Rng, Zero};
     use proof_essentials::error::CryptoError;
-    use proof_essentials::zkp::proofs::chaum_pedersen_dl_equality;
     use rand::thread_rng;
 
     // Choose elliptic curve setting
@@ -44,7 +47,7 @@ mod test {
 
     type MaskedCard = discrete_log_cards::MaskedCard<Curve>;
 
-    type RemaskingProof = chaum_pedersen_dl_equality::proof::Proof<Curve>;
+    type RemaskingProof = discrete_log_cards::DLEqualityProof<Curve>;
 
     fn setup_players<R: Rng>(
         rng: &mut R,
@@ -107,9 +110,9 @@ mod test {
                 &wrong_output,
                 &remasking_proof
             ),
-            Err(CryptoError::ProofVerificationError(String::from(
-                "Chaum-Pedersen"
-            )))
+            Err(CardProtocolError::ProofVerificationError(
+                CryptoError::ProofVerificationError(String::from("Chaum-Pedersen"))
+            ))
         )
     }
 }
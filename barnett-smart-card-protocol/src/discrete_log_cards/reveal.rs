@@ -22,23 +22,25 @@ impl<C: ProjectiveCurve> Reveal<C::ScalarField, ElGamal<C>> for RevealToken<C> {
 #[cfg(test)]
 mod test {
     use crate::discrete_log_cards;
+    use crate::error::CardProtocolError;
     use crate::BarnettSmartProtocol;
 
     use ark_ff::UniformRand;
     use proof_essentials::error::CryptoError;
-    use proof_essentials::zkp::proofs::chaum_pedersen_dl_equality;
     use rand::thread_rng;
 
     // Choose elliptic curve setting
     type Curve = starknet_curve::Projective;
+    type Scalar = starknet_curve::Fr;
 
     // Instantiate concrete type for our card protocol
     type CardProtocol = discrete_log_cards::DLCards<Curve>;
 
+    type Card = discrete_log_cards::Card<Curve>;
     type MaskedCard = discrete_log_cards::MaskedCard<Curve>;
     type RevealToken = discrete_log_cards::RevealToken<Curve>;
 
-    type RevealProof = chaum_pedersen_dl_equality::proof::Proof<Curve>;
+    type RevealProof = discrete_log_cards::DLEqualityProof<Curve>;
 
     #[test]
     fn test_verify_reveal() {
@@ -77,9 +79,54 @@ mod test {
                 &some_masked_card,
                 &reveal_proof
             ),
-            Err(CryptoError::ProofVerificationError(String::from(
-                "Chaum-Pedersen"
-            )))
+            Err(CardProtocolError::ProofVerificationError(
+                CryptoError::ProofVerificationError(String::from("Chaum-Pedersen"))
+            ))
         )
     }
+
+    #[test]
+    fn test_unmask_with_own_key() {
+        let rng = &mut thread_rng();
+
+        let parameters = CardProtocol::setup(rng, 2, 5).unwrap();
+        let (pk, sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+        let (other_pk, other_sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+        let shared_key = pk + other_pk;
+
+        let card = Card::rand(rng);
+        let alpha = Scalar::rand(rng);
+        let (masked_card, _) =
+            CardProtocol::mask(rng, &parameters, &shared_key, &card, &alpha).unwrap();
+
+        let (other_token, other_proof) = CardProtocol::compute_reveal_token(
+            rng,
+            &parameters,
+            &other_sk,
+            &other_pk,
+            &masked_card,
+        )
+        .unwrap();
+        let others = vec![(other_token, other_proof, other_pk)];
+
+        assert_eq!(
+            Ok(card),
+            CardProtocol::unmask_with_own_key(&parameters, &sk, &others, &masked_card)
+        );
+
+        // The unproven token is the one that would be sent with a proof
+        let (own_token, _) =
+            CardProtocol::compute_reveal_token(rng, &parameters, &sk, &pk, &masked_card).unwrap();
+        assert_eq!(
+            Ok(own_token),
+            CardProtocol::compute_reveal_token_unproven(&parameters, &sk, &masked_card)
+        );
+
+        // Tokens from other players are still verified
+        let mut forged = others;
+        forged[0].0 = RevealToken::rand(rng);
+        assert!(
+            CardProtocol::unmask_with_own_key(&parameters, &sk, &forged, &masked_card).is_err()
+        );
+    }
 }
@@ -0,0 +1,1002 @@
+use crate::discrete_log_cards::constant_time::permute;
+use crate::discrete_log_cards::log_shuffle::LogShuffleProof;
+use crate::discrete_log_cards::multi_exp::{
+    prove_multi_exp, verify_multi_exp, EncryptionBases, MultiExpProof, MultiExpStatement,
+    MultiExpWitness,
+};
+use crate::discrete_log_cards::pedersen::CommitKey;
+use crate::discrete_log_cards::product_argument::{prove_product, verify_product, ProductProof};
+use crate::discrete_log_cards::proofs::Base;
+use crate::discrete_log_cards::validation::in_prime_order_subgroup;
+use crate::discrete_log_cards::{DLCards, MaskedCard, Parameters, SHUFFLE_RNG_SEED};
+
+use ark_ec::msm::VariableBaseMSM;
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{to_bytes, Field, PrimeField, UniformRand, Zero};
+use ark_marlin::rng::FiatShamirRng;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use ark_std::rand::Rng;
+use digest::Digest;
+use proof_essentials::error::CryptoError;
+use proof_essentials::homomorphic_encryption::el_gamal;
+
+/// The argument with which `shuffle_and_remask` proves shuffles, selected when the parameters are
+/// set up. Both hide the permutation and the masking factors; they trade proof size for prover and
+/// verifier time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShuffleArgument {
+    /// The argument of Bayer and Groth, whose proofs grow with `m + n` for a deck of `m * n` cards.
+    /// Its commitment key holds `n` generators.
+    BayerGroth,
+    /// An argument whose proofs grow with the logarithm of the number of cards, see
+    /// `LogShuffleProof`. Verification takes time linear in the number of cards and the
+    /// commitment key holds two to four generators per card.
+    Logarithmic,
+}
+
+impl ShuffleArgument {
+    /// The number of generators of the commitment key of the argument, for decks of `m * n` cards
+    pub fn commit_key_len(&self, m: usize, n: usize) -> usize {
+        match self {
+            Self::BayerGroth => n,
+            Self::Logarithmic => 2 * (m * n).next_power_of_two() + 1,
+        }
+    }
+}
+
+/// Proof of a shuffle, made with the argument selected in the parameters. It is encoded as a tag
+/// byte, 0 for Bayer-Groth and 1 for the logarithmic argument, followed by the proof.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShuffleProof<C: ProjectiveCurve> {
+    BayerGroth(BayerGrothProof<C>),
+    Logarithmic(LogShuffleProof<C>),
+}
+
+impl<C: ProjectiveCurve> ShuffleProof<C> {
+    fn tag(&self) -> u8 {
+        match self {
+            Self::BayerGroth(_) => 0,
+            Self::Logarithmic(_) => 1,
+        }
+    }
+}
+
+impl<C: ProjectiveCurve> CanonicalSerialize for ShuffleProof<C> {
+    fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        self.tag().serialize(&mut writer)?;
+        match self {
+            Self::BayerGroth(proof) => proof.serialize(writer),
+            Self::Logarithmic(proof) => proof.serialize(writer),
+        }
+    }
+
+    fn serialized_size(&self) -> usize {
+        1 + match self {
+            Self::BayerGroth(proof) => proof.serialized_size(),
+            Self::Logarithmic(proof) => proof.serialized_size(),
+        }
+    }
+
+    fn serialize_uncompressed<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        self.tag().serialize_uncompressed(&mut writer)?;
+        match self {
+            Self::BayerGroth(proof) => proof.serialize_uncompressed(writer),
+            Self::Logarithmic(proof) => proof.serialize_uncompressed(writer),
+        }
+    }
+
+    fn serialize_unchecked<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        self.tag().serialize_unchecked(&mut writer)?;
+        match self {
+            Self::BayerGroth(proof) => proof.serialize_unchecked(writer),
+            Self::Logarithmic(proof) => proof.serialize_unchecked(writer),
+        }
+    }
+
+    fn uncompressed_size(&self) -> usize {
+        1 + match self {
+            Self::BayerGroth(proof) => proof.uncompressed_size(),
+            Self::Logarithmic(proof) => proof.uncompressed_size(),
+        }
+    }
+}
+
+impl<C: ProjectiveCurve> CanonicalDeserialize for ShuffleProof<C> {
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        match u8::deserialize(&mut reader)? {
+            0 => Ok(Self::BayerGroth(BayerGrothProof::deserialize(reader)?)),
+            1 => Ok(Self::Logarithmic(LogShuffleProof::deserialize(reader)?)),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+
+    fn deserialize_uncompressed<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        match u8::deserialize_uncompressed(&mut reader)? {
+            0 => Ok(Self::BayerGroth(BayerGrothProof::deserialize_uncompressed(
+                reader,
+            )?)),
+            1 => Ok(Self::Logarithmic(
+                LogShuffleProof::deserialize_uncompressed(reader)?,
+            )),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+
+    fn deserialize_unchecked<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        match u8::deserialize_unchecked(&mut reader)? {
+            0 => Ok(Self::BayerGroth(BayerGrothProof::deserialize_unchecked(
+                reader,
+            )?)),
+            1 => Ok(Self::Logarithmic(LogShuffleProof::deserialize_unchecked(
+                reader,
+            )?)),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+}
+
+/// Proof of a shuffle after Bayer and Groth. The deck is arranged in `m` rows of `n` cards.
+/// The prover commits to the permutation `a` and to `b = x^a` for a challenge `x`, shows with the
+/// product argument that `y a + b - z` is a permutation of the same values for the identity, and
+/// with the multi-exponentiation argument that the shuffled deck raised to `b` is a remasking of
+/// the original deck raised to the powers of `x`.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct BayerGrothProof<C: ProjectiveCurve> {
+    pub a_commitments: Vec<C::Affine>,
+    pub b_commitments: Vec<C::Affine>,
+    pub product: ProductProof<C>,
+    pub multi_exp: MultiExpProof<C>,
+}
+
+impl<C: ProjectiveCurve, D: Digest> DLCards<C, D> {
+    /// Prove that `shuffled_deck[i]` is `deck[positions[i]]` remasked with `masking_factors[i]`,
+    /// with the argument selected in the parameters. Multiplications by the witness go through the
+    /// ladder or through window tables and values at permuted positions are read with an oblivious
+    /// selection, so the running time does not depend on the permutation or on the masking
+    /// factors.
+    pub(super) fn prove_shuffle<R: Rng>(
+        rng: &mut R,
+        pp: &Parameters<C>,
+        shared_key: Base<C>,
+        deck: &[MaskedCard<C>],
+        shuffled_deck: &[MaskedCard<C>],
+        masking_factors: &[C::ScalarField],
+        positions: &[usize],
+    ) -> Result<ShuffleProof<C>, CryptoError> {
+        match pp.shuffle_argument {
+            ShuffleArgument::BayerGroth => Self::prove_bayer_groth(
+                rng,
+                pp,
+                shared_key,
+                deck,
+                shuffled_deck,
+                masking_factors,
+                positions,
+            )
+            .map(ShuffleProof::BayerGroth),
+            ShuffleArgument::Logarithmic => Self::prove_log_shuffle(
+                rng,
+                pp,
+                shared_key,
+                deck,
+                shuffled_deck,
+                masking_factors,
+                positions,
+            )
+            .map(ShuffleProof::Logarithmic),
+        }
+    }
+
+    /// A proof is only checked against parameters set up for its argument
+    pub(super) fn verify_shuffle_proof(
+        pp: &Parameters<C>,
+        shared_key: Base<C>,
+        original_deck: &[MaskedCard<C>],
+        shuffled_deck: &[MaskedCard<C>],
+        proof: &ShuffleProof<C>,
+    ) -> Result<(), CryptoError> {
+        match (pp.shuffle_argument, proof) {
+            (ShuffleArgument::BayerGroth, ShuffleProof::BayerGroth(proof)) => {
+                Self::verify_bayer_groth(pp, shared_key, original_deck, shuffled_deck, proof)
+            }
+            (ShuffleArgument::Logarithmic, ShuffleProof::Logarithmic(proof)) => {
+                Self::verify_log_shuffle(pp, shared_key, original_deck, shuffled_deck, proof)
+            }
+            _ => Err(shuffle_error()),
+        }
+    }
+
+    fn prove_bayer_groth<R: Rng>(
+        rng: &mut R,
+        pp: &Parameters<C>,
+        shared_key: Base<C>,
+        deck: &[MaskedCard<C>],
+        shuffled_deck: &[MaskedCard<C>],
+        masking_factors: &[C::ScalarField],
+        positions: &[usize],
+    ) -> Result<BayerGrothProof<C>, CryptoError> {
+        let (m, n) = (pp.m, pp.n);
+        let ck = &pp.commit_parameters;
+        let mut fs_rng = Self::shuffle_transcript(
+            SHUFFLE_RNG_SEED,
+            pp,
+            shared_key.point(),
+            deck,
+            shuffled_deck,
+        )?;
+
+        // a_k is the position in the original deck of the k-th card of the shuffled deck, from 1
+        let indices = (1..=m * n)
+            .map(|i| C::ScalarField::from(i as u64))
+            .collect::<Vec<_>>();
+        let a = permute(&indices, positions);
+        let r = sample_scalars::<C, R>(rng, m);
+        let a_commitments = commit_rows(ck, &a, &r, n);
+
+        absorb_points::<C, D>(&mut fs_rng, &a_commitments)?;
+        let x = C::ScalarField::rand(&mut fs_rng);
+
+        let x_powers = powers(&x, m * n + 1);
+        let b = permute(&x_powers[1..], positions);
+        let s = sample_scalars::<C, R>(rng, m);
+        let b_commitments = commit_rows(ck, &b, &s, n);
+
+        absorb_points::<C, D>(&mut fs_rng, &b_commitments)?;
+        let y = C::ScalarField::rand(&mut fs_rng);
+        let z = C::ScalarField::rand(&mut fs_rng);
+
+        // The entries of y a + b - z multiply to the product for the identity permutation
+        let d_rows = a
+            .iter()
+            .zip(&b)
+            .map(|(a, b)| y * a + b - z)
+            .collect::<Vec<_>>()
+            .chunks(n)
+            .map(|row| row.to_vec())
+            .collect::<Vec<_>>();
+        let d_randomness = r.iter().zip(&s).map(|(r, s)| y * r + s).collect::<Vec<_>>();
+        let d_commitments = product_statement(ck, &a_commitments, &b_commitments, &y, &z, n);
+        let product =
+            prove_product::<C, D, R>(rng, ck, &d_commitments, &d_rows, &d_randomness, &mut fs_rng)?;
+
+        // The shuffled deck raised to b is the original deck raised to the powers of x, remasked
+        // with the masking factors weighted by b
+        let rho = -b
+            .iter()
+            .zip(masking_factors)
+            .map(|(b, alpha)| *b * alpha)
+            .sum::<C::ScalarField>();
+        let shuffled_rows = shuffled_deck
+            .chunks(n)
+            .map(|row| row.to_vec())
+            .collect::<Vec<_>>();
+        let b_rows = b.chunks(n).map(|row| row.to_vec()).collect::<Vec<_>>();
+        let target = multi_exp_target(deck, &x_powers[1..]);
+        let multi_exp = prove_multi_exp::<C, D, R>(
+            rng,
+            ck,
+            &EncryptionBases {
+                generator: pp.generator_base(),
+                shared_key,
+                message_generator: &pp.generator.0,
+            },
+            &MultiExpStatement {
+                rows: &shuffled_rows,
+                target: &target,
+                commitments: &b_commitments,
+            },
+            &MultiExpWitness {
+                exponents: &b_rows,
+                randomness: &s,
+                rho: &rho,
+            },
+            pp.multi_exp_mode,
+            &mut fs_rng,
+        )?;
+
+        Ok(BayerGrothProof {
+            a_commitments,
+            b_commitments,
+            product,
+            multi_exp,
+        })
+    }
+
+    fn verify_bayer_groth(
+        pp: &Parameters<C>,
+        shared_key: Base<C>,
+        original_deck: &[MaskedCard<C>],
+        shuffled_deck: &[MaskedCard<C>],
+        proof: &BayerGrothProof<C>,
+    ) -> Result<(), CryptoError> {
+        let (m, n) = (pp.m, pp.n);
+        let ck = &pp.commit_parameters;
+        let well_formed = original_deck.len() == m * n
+            && shuffled_deck.len() == m * n
+            && ck.max_len() >= n
+            && proof.a_commitments.len() == m
+            && proof.b_commitments.len() == m
+            && proof_points(proof).all(in_prime_order_subgroup::<C>);
+        if !well_formed {
+            return Err(shuffle_error());
+        }
+
+        let mut fs_rng = Self::shuffle_transcript(
+            SHUFFLE_RNG_SEED,
+            pp,
+            shared_key.point(),
+            original_deck,
+            shuffled_deck,
+        )?;
+        absorb_points::<C, D>(&mut fs_rng, &proof.a_commitments)?;
+        let x = C::ScalarField::rand(&mut fs_rng);
+        absorb_points::<C, D>(&mut fs_rng, &proof.b_commitments)?;
+        let y = C::ScalarField::rand(&mut fs_rng);
+        let z = C::ScalarField::rand(&mut fs_rng);
+
+        let x_powers = powers(&x, m * n + 1);
+        let product = x_powers[1..]
+            .iter()
+            .enumerate()
+            .map(|(k, power)| y * C::ScalarField::from((k + 1) as u64) + power - z)
+            .product::<C::ScalarField>();
+        let d_commitments =
+            product_statement(ck, &proof.a_commitments, &proof.b_commitments, &y, &z, n);
+        verify_product::<C, D>(ck, &d_commitments, n, &product, &proof.product, &mut fs_rng)?;
+
+        let shuffled_rows = shuffled_deck
+            .chunks(n)
+            .map(|row| row.to_vec())
+            .collect::<Vec<_>>();
+        let target = multi_exp_target(original_deck, &x_powers[1..]);
+        verify_multi_exp::<C, D>(
+            ck,
+            &EncryptionBases {
+                generator: pp.generator_base(),
+                shared_key,
+                message_generator: &pp.generator.0,
+            },
+            &MultiExpStatement {
+                rows: &shuffled_rows,
+                target: &target,
+                commitments: &proof.b_commitments,
+            },
+            &proof.multi_exp,
+            &mut fs_rng,
+        )
+    }
+
+    /// Bind the transcript to the parameters and to the statement
+    pub(super) fn shuffle_transcript(
+        seed: &[u8],
+        pp: &Parameters<C>,
+        shared_key: &C::Affine,
+        original_deck: &[MaskedCard<C>],
+        shuffled_deck: &[MaskedCard<C>],
+    ) -> Result<FiatShamirRng<D>, CryptoError> {
+        let mut fs_rng = FiatShamirRng::<D>::from_seed(&to_bytes![seed]?);
+        fs_rng.absorb(&to_bytes![
+            pp.enc_parameters.generator,
+            pp.generator.0,
+            shared_key
+        ]?);
+        for generator in pp.commit_parameters.generators() {
+            fs_rng.absorb(&to_bytes![generator]?);
+        }
+        for card in original_deck.iter().chain(shuffled_deck) {
+            fs_rng.absorb(&to_bytes![card.0, card.1]?);
+        }
+
+        Ok(fs_rng)
+    }
+}
+
+/// Commitments to `y a_i + b_i - z` for the committed rows `a_i` and `b_i`
+pub(super) fn product_statement<C: ProjectiveCurve>(
+    ck: &CommitKey<C>,
+    a_commitments: &[C::Affine],
+    b_commitments: &[C::Affine],
+    y: &C::ScalarField,
+    z: &C::ScalarField,
+    n: usize,
+) -> Vec<C::Affine> {
+    let minus_z = ck.commit_public(&vec![-*z; n], &C::ScalarField::zero());
+    let d_commitments = a_commitments
+        .iter()
+        .zip(b_commitments)
+        .map(|(a, b)| (a.mul(y.into_repr()) + minus_z).add_mixed(b))
+        .collect::<Vec<_>>();
+
+    C::batch_normalization_into_affine(&d_commitments)
+}
+
+/// `sum_k x^(k + 1) deck_k`, which the shuffled deck raised to `b` remasks
+pub(super) fn multi_exp_target<C: ProjectiveCurve>(
+    deck: &[MaskedCard<C>],
+    x_powers: &[C::ScalarField],
+) -> MaskedCard<C> {
+    let (deck_0, deck_1): (Vec<_>, Vec<_>) = deck.iter().map(|card| (card.0, card.1)).unzip();
+    let scalars = x_powers.iter().map(|x| x.into_repr()).collect::<Vec<_>>();
+
+    el_gamal::Ciphertext(
+        VariableBaseMSM::multi_scalar_mul(&deck_0, &scalars).into_affine(),
+        VariableBaseMSM::multi_scalar_mul(&deck_1, &scalars).into_affine(),
+    )
+}
+
+/// Commit to `values` row by row, with one row of `n` values per randomness
+fn commit_rows<C: ProjectiveCurve>(
+    ck: &CommitKey<C>,
+    values: &[C::ScalarField],
+    randomness: &[C::ScalarField],
+    n: usize,
+) -> Vec<C::Affine> {
+    let commitments = values
+        .chunks(n)
+        .zip(randomness)
+        .map(|(row, r)| ck.commit(row, r))
+        .collect::<Vec<_>>();
+
+    C::batch_normalization_into_affine(&commitments)
+}
+
+/// Every group element of the proof
+fn proof_points<C: ProjectiveCurve>(
+    proof: &BayerGrothProof<C>,
+) -> impl Iterator<Item = &C::Affine> {
+    proof
+        .a_commitments
+        .iter()
+        .chain(&proof.b_commitments)
+        .chain(proof.product.points())
+        .chain(proof.multi_exp.points())
+}
+
+/// `1, x, ..., x^(length - 1)`
+pub(super) fn powers<F: Field>(x: &F, length: usize) -> Vec<F> {
+    let mut powers = Vec::with_capacity(length);
+    let mut power = F::one();
+    for _ in 0..length {
+        powers.push(power);
+        power *= x;
+    }
+
+    powers
+}
+
+/// `sum_i scalars_i rows_i`, entry by entry
+pub(super) fn combine_rows<F: Field>(rows: &[Vec<F>], scalars: &[F]) -> Vec<F> {
+    let mut combination = vec![F::zero(); rows[0].len()];
+    for (row, scalar) in rows.iter().zip(scalars) {
+        for (entry, value) in combination.iter_mut().zip(row) {
+            *entry += *scalar * value;
+        }
+    }
+
+    combination
+}
+
+pub(super) fn hadamard_product<F: Field>(a: &[F], b: &[F]) -> Vec<F> {
+    a.iter().zip(b).map(|(a, b)| *a * b).collect()
+}
+
+pub(super) fn inner_product<F: Field>(a: &[F], b: &[F]) -> F {
+    a.iter().zip(b).fold(F::zero(), |sum, (a, b)| sum + *a * b)
+}
+
+/// `sum_i scalars_i points_i` for public scalars
+pub(super) fn linear_combination<C: ProjectiveCurve>(
+    points: &[C::Affine],
+    scalars: &[C::ScalarField],
+) -> C {
+    let scalars = scalars.iter().map(|s| s.into_repr()).collect::<Vec<_>>();
+
+    VariableBaseMSM::multi_scalar_mul(points, &scalars)
+}
+
+pub(super) fn absorb_points<C: ProjectiveCurve, D: Digest>(
+    fs_rng: &mut FiatShamirRng<D>,
+    points: &[C::Affine],
+) -> Result<(), CryptoError> {
+    for point in points {
+        fs_rng.absorb(&to_bytes![point]?);
+    }
+
+    Ok(())
+}
+
+pub(super) fn sample_scalars<C: ProjectiveCurve, R: Rng>(
+    rng: &mut R,
+    length: usize,
+) -> Vec<C::ScalarField> {
+    (0..length).map(|_| C::ScalarField::rand(rng)).collect()
+}
+
+pub(super) fn shuffle_error() -> CryptoError {
+    CryptoError::ProofVerificationError(String::from("Shuffle"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Base, ShuffleArgument, ShuffleProof};
+    use crate::discrete_log_cards;
+    use crate::discrete_log_cards::MultiExpMode;
+    use crate::error::CardProtocolError;
+    use crate::BarnettSmartProtocol;
+
+    use ark_ff::UniformRand;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use proof_essentials::error::CryptoError;
+    use proof_essentials::utils::permutation::Permutation;
+    use proof_essentials::utils::rand::sample_vector;
+    use rand::thread_rng;
+
+    // Choose elliptic curve setting
+    type Curve = starknet_curve::Projective;
+    type Scalar = starknet_curve::Fr;
+
+    // Instantiate concrete type for our card protocol
+    type CardProtocol = discrete_log_cards::DLCards<Curve>;
+
+    type MaskedCard = discrete_log_cards::MaskedCard<Curve>;
+
+    fn verification_error(argument: &str) -> Result<(), CardProtocolError> {
+        Err(CardProtocolError::ProofVerificationError(
+            CryptoError::ProofVerificationError(String::from(argument)),
+        ))
+    }
+
+    #[test]
+    fn test_shuffle_argument() {
+        let rng = &mut thread_rng();
+
+        // A single row skips the Hadamard product argument
+        for (m, n) in [(1, 5), (2, 3), (3, 4)] {
+            let parameters = CardProtocol::setup(rng, m, n).unwrap();
+            let (shared_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            let deck: Vec<MaskedCard> = sample_vector(rng, m * n);
+
+            let permutation = Permutation::new(rng, m * n);
+            let masking_factors: Vec<Scalar> = sample_vector(rng, m * n);
+            let (shuffled_deck, proof) = CardProtocol::shuffle_and_remask(
+                rng,
+                &parameters,
+                &shared_key,
+                &deck,
+                &masking_factors,
+                &permutation,
+            )
+            .unwrap();
+
+            // The output is the permuted deck, remasked card by card
+            let positions = permutation.permute_array(&(0..m * n).collect());
+            for (i, &position) in positions.iter().enumerate() {
+                assert_eq!(
+                    shuffled_deck[i],
+                    CardProtocol::remask(
+                        rng,
+                        &parameters,
+                        &shared_key,
+                        &deck[position],
+                        &masking_factors[i]
+                    )
+                    .unwrap()
+                    .0
+                );
+            }
+
+            assert_eq!(
+                Ok(()),
+                CardProtocol::verify_shuffle(
+                    &parameters,
+                    &shared_key,
+                    &deck,
+                    &shuffled_deck,
+                    &proof
+                )
+            );
+
+            // Against a different shared key, or with two cards exchanged, the proof fails
+            let (other_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            assert!(CardProtocol::verify_shuffle(
+                &parameters,
+                &other_key,
+                &deck,
+                &shuffled_deck,
+                &proof
+            )
+            .is_err());
+            let mut tampered = shuffled_deck.clone();
+            tampered.swap(0, 1);
+            assert!(CardProtocol::verify_shuffle(
+                &parameters,
+                &shared_key,
+                &deck,
+                &tampered,
+                &proof
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn test_interpolation_mode() {
+        let rng = &mut thread_rng();
+
+        for (m, n) in [(1, 4), (4, 3)] {
+            let parameters = CardProtocol::setup(rng, m, n)
+                .unwrap()
+                .with_multi_exp_mode(MultiExpMode::Interpolation);
+            let (shared_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            let deck: Vec<MaskedCard> = sample_vector(rng, m * n);
+            let masking_factors: Vec<Scalar> = sample_vector(rng, m * n);
+            let permutation = Permutation::new(rng, m * n);
+
+            let (shuffled_deck, proof) = CardProtocol::shuffle_and_remask(
+                rng,
+                &parameters,
+                &shared_key,
+                &deck,
+                &masking_factors,
+                &permutation,
+            )
+            .unwrap();
+            assert_eq!(
+                Ok(()),
+                CardProtocol::verify_shuffle(
+                    &parameters,
+                    &shared_key,
+                    &deck,
+                    &shuffled_deck,
+                    &proof
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_logarithmic_argument() {
+        let rng = &mut thread_rng();
+
+        // Decks whose size is not a power of two are padded
+        for (m, n) in [(1, 1), (1, 5), (2, 4), (4, 13)] {
+            let parameters =
+                CardProtocol::setup_with_argument(rng, m, n, ShuffleArgument::Logarithmic).unwrap();
+            let (shared_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            let deck: Vec<MaskedCard> = sample_vector(rng, m * n);
+            let masking_factors: Vec<Scalar> = sample_vector(rng, m * n);
+            let permutation = Permutation::new(rng, m * n);
+
+            let (shuffled_deck, proof) = CardProtocol::shuffle_and_remask(
+                rng,
+                &parameters,
+                &shared_key,
+                &deck,
+                &masking_factors,
+                &permutation,
+            )
+            .unwrap();
+            assert!(matches!(proof, ShuffleProof::Logarithmic(_)));
+            assert_eq!(
+                Ok(()),
+                CardProtocol::verify_shuffle(
+                    &parameters,
+                    &shared_key,
+                    &deck,
+                    &shuffled_deck,
+                    &proof
+                )
+            );
+
+            let mut bytes = Vec::new();
+            proof.serialize(&mut bytes).unwrap();
+            assert_eq!(proof, ShuffleProof::deserialize(&bytes[..]).unwrap());
+
+            let (other_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            assert!(CardProtocol::verify_shuffle(
+                &parameters,
+                &other_key,
+                &deck,
+                &shuffled_deck,
+                &proof
+            )
+            .is_err());
+            if m * n > 1 {
+                let mut tampered = shuffled_deck.clone();
+                tampered.swap(0, 1);
+                assert!(CardProtocol::verify_shuffle(
+                    &parameters,
+                    &shared_key,
+                    &deck,
+                    &tampered,
+                    &proof
+                )
+                .is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn test_logarithmic_proof_size() {
+        let rng = &mut thread_rng();
+
+        let mut sizes = Vec::new();
+        for argument in [ShuffleArgument::BayerGroth, ShuffleArgument::Logarithmic] {
+            let parameters = CardProtocol::setup_with_argument(rng, 4, 13, argument).unwrap();
+            let (shared_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            let deck: Vec<MaskedCard> = sample_vector(rng, 52);
+            let masking_factors: Vec<Scalar> = sample_vector(rng, 52);
+            let permutation = Permutation::new(rng, 52);
+            let (_, proof) = CardProtocol::shuffle_and_remask(
+                rng,
+                &parameters,
+                &shared_key,
+                &deck,
+                &masking_factors,
+                &permutation,
+            )
+            .unwrap();
+            sizes.push(proof.serialized_size());
+        }
+
+        assert!(sizes[1] < sizes[0]);
+    }
+
+    #[test]
+    fn test_malformed_logarithmic_proof() {
+        let rng = &mut thread_rng();
+
+        let parameters =
+            CardProtocol::setup_with_argument(rng, 2, 3, ShuffleArgument::Logarithmic).unwrap();
+        let (shared_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+        let deck: Vec<MaskedCard> = sample_vector(rng, 6);
+        let masking_factors: Vec<Scalar> = sample_vector(rng, 6);
+        let permutation = Permutation::new(rng, 6);
+        let (shuffled_deck, proof) = CardProtocol::shuffle_and_remask(
+            rng,
+            &parameters,
+            &shared_key,
+            &deck,
+            &masking_factors,
+            &permutation,
+        )
+        .unwrap();
+        let proof = match proof {
+            ShuffleProof::Logarithmic(proof) => proof,
+            ShuffleProof::BayerGroth(_) => panic!("the parameters select the logarithmic argument"),
+        };
+
+        let mut truncated = proof.clone();
+        truncated.product.inner_product.lefts.pop();
+        assert_eq!(
+            CardProtocol::verify_shuffle(
+                &parameters,
+                &shared_key,
+                &deck,
+                &shuffled_deck,
+                &ShuffleProof::Logarithmic(truncated)
+            ),
+            verification_error("Logarithmic Product Argument")
+        );
+        let mut truncated = proof.clone();
+        truncated.multi_exp.left_ciphertexts.pop();
+        assert_eq!(
+            CardProtocol::verify_shuffle(
+                &parameters,
+                &shared_key,
+                &deck,
+                &shuffled_deck,
+                &ShuffleProof::Logarithmic(truncated)
+            ),
+            verification_error("Logarithmic Multi-exponentiation")
+        );
+
+        let mut tampered = proof.clone();
+        tampered.product.t += Scalar::rand(rng);
+        assert_eq!(
+            CardProtocol::verify_shuffle(
+                &parameters,
+                &shared_key,
+                &deck,
+                &shuffled_deck,
+                &ShuffleProof::Logarithmic(tampered)
+            ),
+            verification_error("Logarithmic Product Argument")
+        );
+        let mut tampered = proof.clone();
+        tampered.multi_exp.z += Scalar::rand(rng);
+        assert_eq!(
+            CardProtocol::verify_shuffle(
+                &parameters,
+                &shared_key,
+                &deck,
+                &shuffled_deck,
+                &ShuffleProof::Logarithmic(tampered)
+            ),
+            verification_error("Logarithmic Multi-exponentiation")
+        );
+
+        // A proof of the other argument is rejected
+        let bayer_groth = CardProtocol::setup(rng, 2, 3).unwrap();
+        let (_, other_proof) = CardProtocol::shuffle_and_remask(
+            rng,
+            &bayer_groth,
+            &shared_key,
+            &deck,
+            &masking_factors,
+            &permutation,
+        )
+        .unwrap();
+        assert_eq!(
+            CardProtocol::verify_shuffle(
+                &parameters,
+                &shared_key,
+                &deck,
+                &shuffled_deck,
+                &other_proof
+            ),
+            verification_error("Shuffle")
+        );
+    }
+
+    #[test]
+    fn test_shuffle_of_a_different_deck() {
+        let rng = &mut thread_rng();
+
+        for (argument, error) in [
+            (ShuffleArgument::BayerGroth, "Multi-exponentiation (4)"),
+            (
+                ShuffleArgument::Logarithmic,
+                "Logarithmic Multi-exponentiation",
+            ),
+        ] {
+            let parameters = CardProtocol::setup_with_argument(rng, 2, 2, argument).unwrap();
+            let (shared_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+            let deck: Vec<MaskedCard> = sample_vector(rng, 4);
+            let masking_factors: Vec<Scalar> = sample_vector(rng, 4);
+
+            // Duplicating a card and dropping another is not a permutation; an honest run of the
+            // prover on such an output does not produce a valid proof
+            let mut output = deck.clone();
+            output[1] = output[0];
+            let output = output
+                .iter()
+                .zip(&masking_factors)
+                .map(|(card, alpha)| {
+                    CardProtocol::remask(rng, &parameters, &shared_key, card, alpha)
+                        .unwrap()
+                        .0
+                })
+                .collect::<Vec<_>>();
+            let proof = CardProtocol::prove_shuffle(
+                rng,
+                &parameters,
+                Base::Point(&shared_key),
+                &deck,
+                &output,
+                &masking_factors,
+                &[0, 1, 2, 3],
+            )
+            .unwrap();
+            assert_eq!(
+                CardProtocol::verify_shuffle(&parameters, &shared_key, &deck, &output, &proof),
+                verification_error(error)
+            );
+        }
+    }
+
+    #[test]
+    fn test_malformed_shuffle_proof() {
+        let rng = &mut thread_rng();
+
+        let parameters = CardProtocol::setup(rng, 2, 2).unwrap();
+        let (shared_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+        let deck: Vec<MaskedCard> = sample_vector(rng, 4);
+        let masking_factors: Vec<Scalar> = sample_vector(rng, 4);
+        let permutation = Permutation::new(rng, 4);
+        let (shuffled_deck, proof) = CardProtocol::shuffle_and_remask(
+            rng,
+            &parameters,
+            &shared_key,
+            &deck,
+            &masking_factors,
+            &permutation,
+        )
+        .unwrap();
+        let proof = match proof {
+            ShuffleProof::BayerGroth(proof) => proof,
+            ShuffleProof::Logarithmic(_) => panic!("the default argument is Bayer-Groth"),
+        };
+
+        // Vectors of the wrong length are rejected rather than indexed out of bounds
+        let mut truncated = proof.clone();
+        truncated.b_commitments.pop();
+        assert_eq!(
+            CardProtocol::verify_shuffle(
+                &parameters,
+                &shared_key,
+                &deck,
+                &shuffled_deck,
+                &ShuffleProof::BayerGroth(truncated)
+            ),
+            verification_error("Shuffle")
+        );
+        let mut truncated = proof.clone();
+        truncated.product.single_value.b_blinded.clear();
+        assert_eq!(
+            CardProtocol::verify_shuffle(
+                &parameters,
+                &shared_key,
+                &deck,
+                &shuffled_deck,
+                &ShuffleProof::BayerGroth(truncated)
+            ),
+            verification_error("Single Value Product Argument (5.3)")
+        );
+        let mut truncated = proof.clone();
+        truncated.multi_exp.diagonals.pop();
+        assert_eq!(
+            CardProtocol::verify_shuffle(
+                &parameters,
+                &shared_key,
+                &deck,
+                &shuffled_deck,
+                &ShuffleProof::BayerGroth(truncated)
+            ),
+            verification_error("Multi-exponentiation (4)")
+        );
+        let mut truncated = proof.clone();
+        truncated.product.hadamard = None;
+        assert_eq!(
+            CardProtocol::verify_shuffle(
+                &parameters,
+                &shared_key,
+                &deck,
+                &shuffled_deck,
+                &ShuffleProof::BayerGroth(truncated)
+            ),
+            verification_error("Product Argument (5)")
+        );
+
+        let mut tampered = proof.clone();
+        tampered.multi_exp.tau += Scalar::rand(rng);
+        assert_eq!(
+            CardProtocol::verify_shuffle(
+                &parameters,
+                &shared_key,
+                &deck,
+                &shuffled_deck,
+                &ShuffleProof::BayerGroth(tampered)
+            ),
+            verification_error("Multi-exponentiation (4)")
+        );
+
+        // The prover expects a deck of the size of the parameters and one factor per card
+        let short_deck: Vec<MaskedCard> = sample_vector(rng, 3);
+        let short_factors: Vec<Scalar> = sample_vector(rng, 3);
+        assert_eq!(
+            CardProtocol::shuffle_and_remask(
+                rng,
+                &parameters,
+                &shared_key,
+                &short_deck,
+                &short_factors,
+                &Permutation::new(&mut thread_rng(), 3),
+            )
+            .err(),
+            Some(CardProtocolError::InvalidDeckSize(3))
+        );
+        assert_eq!(
+            CardProtocol::shuffle_and_remask(
+                rng,
+                &parameters,
+                &shared_key,
+                &deck,
+                &short_factors,
+                &permutation,
+            )
+            .err(),
+            Some(CardProtocolError::MaskingFactorsMismatch)
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use crate::discrete_log_cards::{DLCards, MaskedCard, Parameters, PublicKey, ShuffleProof};
+use crate::error::CardProtocolError;
+use crate::BarnettSmartProtocol;
+
+use ark_ec::ProjectiveCurve;
+use ark_serialize::CanonicalSerialize;
+use blake2::Blake2s;
+use digest::Digest;
+use std::marker::PhantomData;
+
+const CHAIN_DOMAIN: &'static [u8] = b"Shuffle Chain";
+
+/// Verifies a sequence of shuffles as they arrive, one per player, while only keeping the latest
+/// deck in memory. Every accepted link is folded into a hash chain, and the commitment of the chain
+/// is that hash followed by a hash of the aggregate key, length and latest deck. An observer joining
+/// late can `resume` from the latest deck with a commitment it trusts, instead of replaying the
+/// whole history, and the deck is checked against the commitment.
+pub struct ShuffleChain<C: ProjectiveCurve, D: Digest = Blake2s> {
+    shared_key: PublicKey<C>,
+    deck: Vec<MaskedCard<C>>,
+    length: usize,
+    history: Vec<u8>,
+    commitment: Vec<u8>,
+    _transcript: PhantomData<D>,
+}
+
+impl<C: ProjectiveCurve, D: Digest> ShuffleChain<C, D> {
+    /// Start a chain from the deck that the first player will shuffle
+    pub fn new(
+        shared_key: &PublicKey<C>,
+        initial_deck: &Vec<MaskedCard<C>>,
+    ) -> Result<Self, CardProtocolError> {
+        let mut hasher = D::new();
+        hasher.update(CHAIN_DOMAIN);
+        hasher.update(serialize(shared_key)?);
+        hasher.update(serialize(initial_deck)?);
+
+        Self::from_history(
+            shared_key,
+            initial_deck.clone(),
+            0,
+            hasher.finalize().to_vec(),
+        )
+    }
+
+    /// Continue a chain of `length` shuffles ending with `deck`, whose commitment was obtained
+    /// from a trusted source. Fails if the key, length or deck do not match the commitment.
+    pub fn resume(
+        shared_key: &PublicKey<C>,
+        deck: &Vec<MaskedCard<C>>,
+        length: usize,
+        commitment: &[u8],
+    ) -> Result<Self, CardProtocolError> {
+        if commitment.len() != 2 * D::output_size() {
+            return Err(CardProtocolError::ShuffleChainMismatch);
+        }
+
+        let (history, _) = commitment.split_at(D::output_size());
+        let chain = Self::from_history(shared_key, deck.clone(), length, history.to_vec())?;
+        if chain.commitment != commitment {
+            return Err(CardProtocolError::ShuffleChainMismatch);
+        }
+
+        Ok(chain)
+    }
+
+    fn from_history(
+        shared_key: &PublicKey<C>,
+        deck: Vec<MaskedCard<C>>,
+        length: usize,
+        history: Vec<u8>,
+    ) -> Result<Self, CardProtocolError> {
+        let mut hasher = D::new();
+        hasher.update(CHAIN_DOMAIN);
+        hasher.update(serialize(shared_key)?);
+        hasher.update((length as u64).to_le_bytes());
+        hasher.update(serialize(&deck)?);
+
+        let mut commitment = history.clone();
+        commitment.extend_from_slice(&hasher.finalize());
+
+        Ok(Self {
+            shared_key: *shared_key,
+            deck,
+            length,
+            history,
+            commitment,
+            _transcript: PhantomData,
+        })
+    }
+
+    /// Verify a shuffle of the latest deck and append it to the chain. The chain is left unchanged
+    /// if the proof does not verify.
+    pub fn push(
+        &mut self,
+        pp: &Parameters<C>,
+        shuffled_deck: Vec<MaskedCard<C>>,
+        proof: &ShuffleProof<C>,
+    ) -> Result<(), CardProtocolError> {
+        DLCards::<C, D>::verify_shuffle(pp, &self.shared_key, &self.deck, &shuffled_deck, proof)?;
+
+        let mut hasher = D::new();
+        hasher.update(&self.history);
+        hasher.update(serialize(&shuffled_deck)?);
+        hasher.update(serialize(proof)?);
+
+        *self = Self::from_history(
+            &self.shared_key,
+            shuffled_deck,
+            self.length + 1,
+            hasher.finalize().to_vec(),
+        )?;
+
+        Ok(())
+    }
+
+    /// The latest deck of the chain
+    pub fn deck(&self) -> &Vec<MaskedCard<C>> {
+        &self.deck
+    }
+
+    /// Number of shuffles in the chain
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Commitment to the initial deck and to every shuffle and proof accepted so far, followed by a
+    /// hash of the aggregate key, length and latest deck
+    pub fn commitment(&self) -> &[u8] {
+        &self.commitment
+    }
+}
+
+fn serialize<T: CanonicalSerialize>(value: &T) -> Result<Vec<u8>, CardProtocolError> {
+    let mut bytes = Vec::new();
+    value
+        .serialize(&mut bytes)
+        .map_err(|e| CardProtocolError::IoError(e.to_string()))?;
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::discrete_log_cards;
+    use crate::error::CardProtocolError;
+    use crate::BarnettSmartProtocol;
+
+    use proof_essentials::utils::permutation::Permutation;
+    use proof_essentials::utils::rand::sample_vector;
+    use rand::thread_rng;
+
+    // Choose elliptic curve setting
+    type Curve = starknet_curve::Projective;
+    type Scalar = starknet_curve::Fr;
+
+    // Instantiate concrete type for our card protocol
+    type CardProtocol = discrete_log_cards::DLCards<Curve>;
+    type ShuffleChain = discrete_log_cards::ShuffleChain<Curve>;
+
+    type MaskedCard = discrete_log_cards::MaskedCard<Curve>;
+
+    #[test]
+    fn test_shuffle_chain() {
+        let rng = &mut thread_rng();
+        let m = 2;
+        let n = 5;
+
+        let parameters = CardProtocol::setup(rng, m, n).unwrap();
+        let (shared_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+
+        let initial_deck: Vec<MaskedCard> = sample_vector(rng, m * n);
+        let mut chain = ShuffleChain::new(&shared_key, &initial_deck).unwrap();
+        let mut late_observer: Option<ShuffleChain> = None;
+
+        for player in 0..3 {
+            let permutation = Permutation::new(rng, m * n);
+            let masking_factors: Vec<Scalar> = sample_vector(rng, m * n);
+            let (shuffled_deck, proof) = CardProtocol::shuffle_and_remask(
+                rng,
+                &parameters,
+                &shared_key,
+                chain.deck(),
+                &masking_factors,
+                &permutation,
+            )
+            .unwrap();
+
+            // A shuffle of another deck is rejected and leaves the chain untouched
+            let commitment = chain.commitment().to_vec();
+            let unrelated: Vec<MaskedCard> = sample_vector(rng, m * n);
+            assert!(chain.push(&parameters, unrelated, &proof).is_err());
+            assert_eq!(chain.commitment(), &commitment[..]);
+
+            chain
+                .push(&parameters, shuffled_deck.clone(), &proof)
+                .unwrap();
+            if let Some(observer) = late_observer.as_mut() {
+                observer.push(&parameters, shuffled_deck, &proof).unwrap();
+            }
+
+            if player == 0 {
+                // The deck, length and key must match the commitment
+                let other_deck: Vec<MaskedCard> = sample_vector(rng, m * n);
+                let (other_key, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+                let commitment = chain.commitment();
+                for resumed in [
+                    ShuffleChain::resume(&shared_key, &other_deck, chain.len(), commitment),
+                    ShuffleChain::resume(&shared_key, chain.deck(), chain.len() + 1, commitment),
+                    ShuffleChain::resume(&other_key, chain.deck(), chain.len(), commitment),
+                    ShuffleChain::resume(&shared_key, chain.deck(), chain.len(), &commitment[1..]),
+                ] {
+                    assert_eq!(resumed.err(), Some(CardProtocolError::ShuffleChainMismatch));
+                }
+
+                late_observer = Some(
+                    ShuffleChain::resume(&shared_key, chain.deck(), chain.len(), commitment)
+                        .unwrap(),
+                );
+            }
+        }
+
+        let late_observer = late_observer.unwrap();
+        assert_eq!(chain.len(), 3);
+        assert_eq!(late_observer.len(), 3);
+        assert_eq!(late_observer.commitment(), chain.commitment());
+        assert_eq!(late_observer.deck(), chain.deck());
+    }
+}
@@ -0,0 +1,421 @@
+//! A StarkNet-friendly mode of the masking, remasking and reveal proofs of `DLCards` over the STARK
+//! curve.
+//!
+//! The base field of the STARK curve is the field of Cairo felts, so points are encoded as pairs of
+//! felts with no conversion. Proofs of this mode take their challenge from the StarkNet Pedersen
+//! hash chain (`compute_hash_on_elements` in Cairo) over the felt encoding of the statement,
+//! reduced modulo the curve order, and serialize to arrays of felts `[a.x, a.y, b.x, b.y, r]`: the
+//! same Chaum-Pedersen shape as `ZKProofMasking`, `ZKProofRemasking` and `ZKProofReveal`.
+
+use crate::discrete_log_cards::constant_time::ladder_mul;
+use crate::discrete_log_cards::{
+    Card, DLCards, MaskedCard, Parameters, PlayerSecretKey, PublicKey, RevealToken,
+};
+use crate::error::CardProtocolError;
+use crate::{Mask, Remask};
+
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{BigInteger, One, PrimeField, UniformRand, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use ark_std::rand::Rng;
+use digest::Digest;
+use proof_essentials::error::CryptoError;
+use proof_essentials::homomorphic_encryption::el_gamal;
+use starknet_curve::{Affine, Fq, Fr, Projective};
+
+/// A Cairo field element
+pub type Felt = Fq;
+
+const MASKING_DOMAIN: &'static [u8] = b"Masking Proof";
+const REMASKING_DOMAIN: &'static [u8] = b"Remasking Proof";
+const REVEAL_DOMAIN: &'static [u8] = b"Reveal Proof";
+
+// Constant points of the StarkNet Pedersen hash: the shift point followed by P0 to P3
+const PEDERSEN_POINTS: [(&'static str, &'static str); 5] = [
+    (
+        "049ee3eba8c1600700ee1b87eb599f16716b0b1022947733551fde4050ca6804",
+        "03ca0cfe4b3bc6ddf346d49d06ea0ed34e621062c0e056c1d0405d266e10268a",
+    ),
+    (
+        "0234287dcbaffe7f969c748655fca9e58fa8120b6d56eb0c1080d17957ebe47b",
+        "03b056f100f96fb21e889527d41f4e39940135dd7a6c94cc6ed0268ee89e5615",
+    ),
+    (
+        "04fa56f376c83db33f9dab2656558f3399099ec1de5e3018b7a6932dba8aa378",
+        "03fa0984c931c9e38113e0c0e47e4401562761f92a7a23b45168f4e80ff5b54d",
+    ),
+    (
+        "04ba4cc166be8dec764910f75b45f74b40c690c74709e90f3aa372f0bd2d6997",
+        "0040301cf5c1751f4b971e46c4ede85fcac5c59a5ce5ae7c48151f27b24b219c",
+    ),
+    (
+        "054302dcb0e6cc1c6e44cca8f61a63bb2ca65048d53fb325d36ff12c49a58202",
+        "01b77b3e37d13504b348046268d8ae25ce98ad783c25561a879dcc77e99c2426",
+    ),
+];
+
+// Felts are split into their 248 low bits and 4 high bits
+const LOW_PART_BITS: usize = 248;
+const HIGH_PART_BITS: usize = 4;
+
+/// Chaum-Pedersen proof checkable in Cairo
+#[derive(Clone, Copy, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct StarknetProof {
+    pub a: Affine,
+    pub b: Affine,
+    pub r: Fr,
+}
+
+impl StarknetProof {
+    pub fn to_felts(&self) -> Vec<Felt> {
+        let mut felts = point_to_felts(&self.a).to_vec();
+        felts.extend_from_slice(&point_to_felts(&self.b));
+        felts.push(Felt::from_le_bytes_mod_order(
+            &self.r.into_repr().to_bytes_le(),
+        ));
+
+        felts
+    }
+
+    pub fn from_felts(felts: &[Felt]) -> Result<Self, CardProtocolError> {
+        if felts.len() != 5 {
+            return Err(CardProtocolError::IoError(String::from(
+                "a proof is made of 5 felts",
+            )));
+        }
+
+        let r = Fr::from_le_bytes_mod_order(&felts[4].into_repr().to_bytes_le());
+        if Felt::from_le_bytes_mod_order(&r.into_repr().to_bytes_le()) != felts[4] {
+            return Err(CardProtocolError::IoError(String::from(
+                "response is larger than the curve order",
+            )));
+        }
+
+        Ok(Self {
+            a: point_from_felts(&felts[0], &felts[1])?,
+            b: point_from_felts(&felts[2], &felts[3])?,
+            r,
+        })
+    }
+}
+
+pub fn point_to_felts(point: &Affine) -> [Felt; 2] {
+    if point.is_zero() {
+        [Felt::zero(), Felt::zero()]
+    } else {
+        [point.x, point.y]
+    }
+}
+
+fn point_from_felts(x: &Felt, y: &Felt) -> Result<Affine, CardProtocolError> {
+    if x.is_zero() && y.is_zero() {
+        return Ok(Affine::zero());
+    }
+
+    let point = Affine::new(*x, *y, false);
+    if !point.is_on_curve() {
+        return Err(CardProtocolError::IoError(String::from(
+            "point is not on the curve",
+        )));
+    }
+
+    Ok(point)
+}
+
+fn felt_from_hex(hex: &str) -> Felt {
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect::<Vec<_>>();
+    Felt::from_be_bytes_mod_order(&bytes)
+}
+
+fn mul_bits(point: &Affine, bits: &[bool]) -> Projective {
+    let mut acc = Projective::zero();
+    for bit in bits.iter().rev() {
+        acc.double_in_place();
+        if *bit {
+            acc.add_assign_mixed(point);
+        }
+    }
+
+    acc
+}
+
+/// The StarkNet Pedersen hash of two felts
+pub fn pedersen_hash(x: &Felt, y: &Felt) -> Felt {
+    let points = PEDERSEN_POINTS
+        .iter()
+        .map(|(px, py)| Affine::new(felt_from_hex(px), felt_from_hex(py), false))
+        .collect::<Vec<_>>();
+
+    let mut acc = points[0].into_projective();
+    for (i, felt) in [x, y].iter().enumerate() {
+        let bits = felt.into_repr().to_bits_le();
+        let (low, high) = bits.split_at(LOW_PART_BITS);
+
+        acc += &mul_bits(&points[1 + 2 * i], low);
+        acc += &mul_bits(&points[2 + 2 * i], &high[..HIGH_PART_BITS]);
+    }
+
+    acc.into_affine().x
+}
+
+/// Hash of an array of felts, as computed by `compute_hash_on_elements` in Cairo
+pub fn hash_on_elements(elements: &[Felt]) -> Felt {
+    let acc = elements
+        .iter()
+        .fold(Felt::zero(), |acc, element| pedersen_hash(&acc, element));
+
+    pedersen_hash(&acc, &Felt::from(elements.len() as u64))
+}
+
+/// Challenge of a Chaum-Pedersen proof for the statement `(g, h, x, y)` with commitments `a` and
+/// `b`. The domain separator is encoded as a short string felt, as in Cairo.
+fn challenge(domain: &[u8], points: [&Affine; 6]) -> Fr {
+    let mut elements = vec![Felt::from_be_bytes_mod_order(domain)];
+    for point in points {
+        elements.extend_from_slice(&point_to_felts(point));
+    }
+
+    let hash = hash_on_elements(&elements);
+    Fr::from_le_bytes_mod_order(&hash.into_repr().to_bytes_le())
+}
+
+fn prove_dl_equality<R: Rng>(
+    rng: &mut R,
+    domain: &[u8],
+    (g, h): (&Affine, &Affine),
+    (x, y): (&Affine, &Affine),
+    witness: &Fr,
+) -> StarknetProof {
+    let k = Fr::rand(rng);
+    let a = ladder_mul::<Projective>(g, &k).into_affine();
+    let b = ladder_mul::<Projective>(h, &k).into_affine();
+    let c = challenge(domain, [g, h, x, y, &a, &b]);
+
+    StarknetProof {
+        a,
+        b,
+        r: k + c * witness,
+    }
+}
+
+fn verify_dl_equality(
+    domain: &[u8],
+    (g, h): (&Affine, &Affine),
+    (x, y): (&Affine, &Affine),
+    proof: &StarknetProof,
+) -> Result<(), CryptoError> {
+    let c = challenge(domain, [g, h, x, y, &proof.a, &proof.b]);
+
+    if g.mul(proof.r) != x.mul(c).add_mixed(&proof.a)
+        || h.mul(proof.r) != y.mul(c).add_mixed(&proof.b)
+    {
+        return Err(CryptoError::ProofVerificationError(String::from(
+            "StarkNet Chaum-Pedersen",
+        )));
+    }
+
+    Ok(())
+}
+
+impl<D: Digest> DLCards<Projective, D> {
+    /// Same as `mask`, with a proof checkable in Cairo
+    pub fn mask_starknet<R: Rng>(
+        rng: &mut R,
+        pp: &Parameters<Projective>,
+        shared_key: &PublicKey<Projective>,
+        original_card: &Card<Projective>,
+        r: &Fr,
+    ) -> Result<(MaskedCard<Projective>, StarknetProof), CardProtocolError> {
+        let masked_card = original_card.mask(&pp.enc_parameters, shared_key, r)?;
+        let statement_cipher = masked_card.1 + original_card.0.mul(-Fr::one()).into_affine();
+
+        let proof = prove_dl_equality(
+            rng,
+            MASKING_DOMAIN,
+            (&pp.enc_parameters.generator, shared_key),
+            (&masked_card.0, &statement_cipher),
+            r,
+        );
+
+        Ok((masked_card, proof))
+    }
+
+    pub fn verify_mask_starknet(
+        pp: &Parameters<Projective>,
+        shared_key: &PublicKey<Projective>,
+        card: &Card<Projective>,
+        masked_card: &MaskedCard<Projective>,
+        proof: &StarknetProof,
+    ) -> Result<(), CryptoError> {
+        let statement_cipher = masked_card.1 + card.0.mul(-Fr::one()).into_affine();
+
+        verify_dl_equality(
+            MASKING_DOMAIN,
+            (&pp.enc_parameters.generator, shared_key),
+            (&masked_card.0, &statement_cipher),
+            proof,
+        )
+    }
+
+    /// Same as `remask`, with a proof checkable in Cairo
+    pub fn remask_starknet<R: Rng>(
+        rng: &mut R,
+        pp: &Parameters<Projective>,
+        shared_key: &PublicKey<Projective>,
+        original_card: &MaskedCard<Projective>,
+        alpha: &Fr,
+    ) -> Result<(MaskedCard<Projective>, StarknetProof), CardProtocolError> {
+        let remasked = original_card.remask(&pp.enc_parameters, shared_key, alpha)?;
+        let statement_cipher = remasked + *original_card * (-Fr::one());
+
+        let proof = prove_dl_equality(
+            rng,
+            REMASKING_DOMAIN,
+            (&pp.enc_parameters.generator, shared_key),
+            (&statement_cipher.0, &statement_cipher.1),
+            alpha,
+        );
+
+        Ok((remasked, proof))
+    }
+
+    pub fn verify_remask_starknet(
+        pp: &Parameters<Projective>,
+        shared_key: &PublicKey<Projective>,
+        original_masked: &MaskedCard<Projective>,
+        remasked: &MaskedCard<Projective>,
+        proof: &StarknetProof,
+    ) -> Result<(), CryptoError> {
+        let statement_cipher = *remasked + *original_masked * (-Fr::one());
+
+        verify_dl_equality(
+            REMASKING_DOMAIN,
+            (&pp.enc_parameters.generator, shared_key),
+            (&statement_cipher.0, &statement_cipher.1),
+            proof,
+        )
+    }
+
+    /// Same as `compute_reveal_token`, with a proof checkable in Cairo
+    pub fn compute_reveal_token_starknet<R: Rng>(
+        rng: &mut R,
+        pp: &Parameters<Projective>,
+        sk: &PlayerSecretKey<Projective>,
+        pk: &PublicKey<Projective>,
+        masked_card: &MaskedCard<Projective>,
+    ) -> Result<(RevealToken<Projective>, StarknetProof), CardProtocolError> {
+        Self::validate_masked_card(masked_card)?;
+
+        let reveal_token =
+            el_gamal::Plaintext(ladder_mul::<Projective>(&masked_card.0, sk).into_affine());
+
+        let proof = prove_dl_equality(
+            rng,
+            REVEAL_DOMAIN,
+            (&masked_card.0, &pp.enc_parameters.generator),
+            (&reveal_token.0, pk),
+            sk,
+        );
+
+        Ok((reveal_token, proof))
+    }
+
+    pub fn verify_reveal_starknet(
+        pp: &Parameters<Projective>,
+        pk: &PublicKey<Projective>,
+        reveal_token: &RevealToken<Projective>,
+        masked_card: &MaskedCard<Projective>,
+        proof: &StarknetProof,
+    ) -> Result<(), CryptoError> {
+        verify_dl_equality(
+            REVEAL_DOMAIN,
+            (&masked_card.0, &pp.enc_parameters.generator),
+            (&reveal_token.0, pk),
+            proof,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::discrete_log_cards::starknet::{
+        felt_from_hex, hash_on_elements, pedersen_hash, Felt, StarknetProof,
+    };
+    use crate::discrete_log_cards::{self, DLCards};
+    use crate::BarnettSmartProtocol;
+
+    use ark_ff::{UniformRand, Zero};
+    use rand::thread_rng;
+
+    type Curve = starknet_curve::Projective;
+    type Scalar = starknet_curve::Fr;
+
+    type CardProtocol = DLCards<Curve>;
+    type Card = discrete_log_cards::Card<Curve>;
+
+    #[test]
+    fn test_pedersen_vectors() {
+        // Reference vector of the StarkNet Pedersen hash
+        let x = felt_from_hex("03d937c035c878245caf64531a5756109c53068da139362728feb561405371cb");
+        let y = felt_from_hex("0208a0a10250e382e1e4bbe2880906c2791bf6275695e02fbbc6aeff9cd8b31a");
+        let expected =
+            felt_from_hex("030e480bed5fe53fa909cc0f8c4d99b8f9f2c016be4c41e13a4848797979c662");
+        assert_eq!(pedersen_hash(&x, &y), expected);
+
+        assert_eq!(
+            hash_on_elements(&[]),
+            pedersen_hash(&Felt::zero(), &Felt::zero())
+        );
+    }
+
+    #[test]
+    fn test_starknet_proofs() {
+        let rng = &mut thread_rng();
+        let parameters = CardProtocol::setup(rng, 2, 5).unwrap();
+        let (pk, sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+
+        let card = Card::rand(rng);
+        let r = Scalar::rand(rng);
+        let (masked, masking_proof) =
+            CardProtocol::mask_starknet(rng, &parameters, &pk, &card, &r).unwrap();
+        assert_eq!(
+            Ok(()),
+            CardProtocol::verify_mask_starknet(&parameters, &pk, &card, &masked, &masking_proof)
+        );
+
+        let alpha = Scalar::rand(rng);
+        let (remasked, remasking_proof) =
+            CardProtocol::remask_starknet(rng, &parameters, &pk, &masked, &alpha).unwrap();
+        assert_eq!(
+            Ok(()),
+            CardProtocol::verify_remask_starknet(
+                &parameters,
+                &pk,
+                &masked,
+                &remasked,
+                &remasking_proof
+            )
+        );
+        assert!(CardProtocol::verify_remask_starknet(
+            &parameters,
+            &pk,
+            &remasked,
+            &masked,
+            &remasking_proof
+        )
+        .is_err());
+
+        let (token, reveal_proof) =
+            CardProtocol::compute_reveal_token_starknet(rng, &parameters, &sk, &pk, &remasked)
+                .unwrap();
+        let decoded = StarknetProof::from_felts(&reveal_proof.to_felts()).unwrap();
+        assert_eq!(decoded, reveal_proof);
+        assert_eq!(
+            Ok(()),
+            CardProtocol::verify_reveal_starknet(&parameters, &pk, &token, &remasked, &decoded)
+        );
+    }
+}
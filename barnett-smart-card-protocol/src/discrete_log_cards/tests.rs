@@ -4,7 +4,8 @@ mod test {
     use crate::error::CardProtocolError;
     use crate::BarnettSmartProtocol;
 
-    use ark_ff::UniformRand;
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ff::{PrimeField, UniformRand};
     use ark_std::{rand::Rng, Zero};
     use proof_essentials::error::CryptoError;
     use proof_essentials::utils::permutation::Permutation;
@@ -54,6 +55,13 @@ mod test {
         let parameters = CardProtocol::setup(rng, m, n).unwrap();
 
         let (pk, sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+        assert_eq!(
+            pk,
+            parameters
+                .encryption_generator()
+                .mul(sk.into_repr())
+                .into_affine()
+        );
         let player_name = b"Alice";
 
         let p1_keyproof =
@@ -71,9 +79,9 @@ mod test {
 
         assert_eq!(
             CardProtocol::verify_key_ownership(&parameters, &pk, &player_name, &wrong_proof),
-            Err(CryptoError::ProofVerificationError(String::from(
-                "Schnorr Identification"
-            )))
+            Err(CardProtocolError::ProofVerificationError(
+                CryptoError::ProofVerificationError(String::from("Schnorr Identification"))
+            ))
         )
     }
 
@@ -116,9 +124,9 @@ mod test {
 
         assert_eq!(
             test_fail_aggregate,
-            Err(CardProtocolError::ProofVerificationError(
-                CryptoError::ProofVerificationError(String::from("Schnorr Identification"))
-            ))
+            Err(CardProtocolError::InvalidGroupElement(String::from(
+                "public key is the identity"
+            )))
         )
     }
 
@@ -220,9 +228,154 @@ mod test {
                 &wrong_output,
                 &shuffle_proof
             ),
-            Err(CryptoError::ProofVerificationError(String::from(
-                "Hadamard Product (5.1)"
-            )))
+            Err(CardProtocolError::ProofVerificationError(
+                CryptoError::ProofVerificationError(String::from("Hadamard Product (5.1)"))
+            ))
+        )
+    }
+
+    #[test]
+    fn test_rekey_deck() {
+        let rng = &mut thread_rng();
+        let m = 2;
+        let n = 2;
+
+        let num_of_players = 3;
+
+        let parameters = CardProtocol::setup(rng, m, n).unwrap();
+
+        let (mut players, aggregate_key) = setup_players(rng, &parameters, num_of_players);
+
+        let cards: Vec<Card> = sample_vector(rng, m * n);
+        let deck = cards
+            .iter()
+            .map(|card| {
+                let alpha = Scalar::rand(rng);
+                CardProtocol::mask(rng, &parameters, &aggregate_key, card, &alpha)
+                    .unwrap()
+                    .0
+            })
+            .collect::<Vec<MaskedCard>>();
+
+        // A new player joins the table
+        let (new_pk, new_sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+        let new_info = Scalar::rand(rng);
+        let new_key_proof =
+            CardProtocol::prove_key_ownership(rng, &parameters, &new_pk, &new_sk, &new_info)
+                .unwrap();
+
+        let extended_key = CardProtocol::add_to_aggregate_key(
+            &parameters,
+            &aggregate_key,
+            &new_pk,
+            &new_key_proof,
+            &new_info,
         )
+        .unwrap();
+        assert_eq!(extended_key, aggregate_key + new_pk);
+
+        let (extended_deck, add_proofs) =
+            CardProtocol::add_key_to_deck(rng, &parameters, &new_sk, &new_pk, &deck).unwrap();
+
+        assert_eq!(
+            Ok(()),
+            CardProtocol::verify_add_key_to_deck(
+                &parameters,
+                &new_pk,
+                &deck,
+                &extended_deck,
+                &add_proofs
+            )
+        );
+
+        let (wrong_pk, _) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+        assert_eq!(
+            CardProtocol::verify_add_key_to_deck(
+                &parameters,
+                &wrong_pk,
+                &deck,
+                &extended_deck,
+                &add_proofs
+            ),
+            Err(CardProtocolError::ProofVerificationError(
+                CryptoError::ProofVerificationError(String::from("Chaum-Pedersen"))
+            ))
+        );
+
+        players.push((new_pk, new_sk, new_info));
+
+        for (card, masked) in cards.iter().zip(extended_deck.iter()) {
+            let decryption_key = players
+                .iter()
+                .map(|player| {
+                    let (token, proof) = CardProtocol::compute_reveal_token(
+                        rng,
+                        &parameters,
+                        &player.1,
+                        &player.0,
+                        masked,
+                    )
+                    .unwrap();
+
+                    (token, proof, player.0)
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                *card,
+                CardProtocol::unmask(&parameters, &decryption_key, masked).unwrap()
+            );
+        }
+
+        // The first player leaves the table
+        let leaving = players.remove(0);
+
+        let reduced_key =
+            CardProtocol::remove_from_aggregate_key(&parameters, &extended_key, &leaving.0)
+                .unwrap();
+        assert_eq!(reduced_key, players[0].0 + players[1].0 + players[2].0);
+
+        let (reduced_deck, remove_proofs) = CardProtocol::remove_key_from_deck(
+            rng,
+            &parameters,
+            &leaving.1,
+            &leaving.0,
+            &extended_deck,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Ok(()),
+            CardProtocol::verify_remove_key_from_deck(
+                &parameters,
+                &leaving.0,
+                &extended_deck,
+                &reduced_deck,
+                &remove_proofs
+            )
+        );
+
+        for (card, masked) in cards.iter().zip(reduced_deck.iter()) {
+            let decryption_key = players
+                .iter()
+                .map(|player| {
+                    let (token, proof) = CardProtocol::compute_reveal_token(
+                        rng,
+                        &parameters,
+                        &player.1,
+                        &player.0,
+                        masked,
+                    )
+                    .unwrap();
+
+                    (token, proof, player.0)
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                *card,
+                CardProtocol::unmask(&parameters, &decryption_key, masked).unwrap()
+            );
+        }
     }
 }
@@ -0,0 +1,237 @@
+use crate::discrete_log_cards::deck_state::write_atomically;
+use crate::discrete_log_cards::{
+    DLCards, DLEqualityProof, MaskedCard, Parameters, PlayerSecretKey, PublicKey, RevealToken,
+};
+use crate::error::CardProtocolError;
+use crate::BarnettSmartProtocol;
+
+use ark_ec::ProjectiveCurve;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use ark_std::rand::Rng;
+use digest::Digest;
+use std::fs::File;
+use std::path::Path;
+
+type RevealProof<C> = DLEqualityProof<C>;
+
+/// Cache of the reveal tokens computed by one player. Tokens are stored per masked card and per
+/// session (e.g. a hand), so that a player produces a single token and proof for a card however
+/// many times it is opened, e.g. privately when it is dealt and publicly at showdown.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct RevealTokenStore<C: ProjectiveCurve> {
+    pk: PublicKey<C>,
+    entries: Vec<(Vec<u8>, MaskedCard<C>, (RevealToken<C>, RevealProof<C>))>,
+}
+
+impl<C: ProjectiveCurve> RevealTokenStore<C> {
+    /// An empty store for the player owning `pk`
+    pub fn new(pk: &PublicKey<C>) -> Self {
+        Self {
+            pk: *pk,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn public_key(&self) -> &PublicKey<C> {
+        &self.pk
+    }
+
+    pub fn get(
+        &self,
+        session: &[u8],
+        masked_card: &MaskedCard<C>,
+    ) -> Option<&(RevealToken<C>, RevealProof<C>)> {
+        self.entries
+            .iter()
+            .find(|(s, card, _)| s == session && card == masked_card)
+            .map(|(_, _, token)| token)
+    }
+
+    pub fn insert(
+        &mut self,
+        session: &[u8],
+        masked_card: &MaskedCard<C>,
+        reveal_token: RevealToken<C>,
+        proof: RevealProof<C>,
+    ) {
+        self.entries
+            .retain(|(s, card, _)| s != session || card != masked_card);
+        self.entries
+            .push((session.to_vec(), *masked_card, (reveal_token, proof)));
+    }
+
+    /// Drop every token of a session, e.g. once the hand is over
+    pub fn clear_session(&mut self, session: &[u8]) {
+        self.entries.retain(|(s, _, _)| s != session);
+    }
+
+    /// Number of cached tokens, over all sessions
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Write the store to `path` atomically, like `DeckState::save`
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), CardProtocolError> {
+        let mut content = Vec::new();
+        self.serialize(&mut content)
+            .map_err(|e| CardProtocolError::IoError(e.to_string()))?;
+
+        write_atomically(path.as_ref(), &content)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, CardProtocolError> {
+        let file = File::open(path)?;
+        Self::deserialize(file).map_err(|e| CardProtocolError::IoError(e.to_string()))
+    }
+}
+
+impl<C: ProjectiveCurve, D: Digest> DLCards<C, D> {
+    /// Same as `compute_reveal_token`, but returns the token cached in `store` for this card and
+    /// session if there is one, and caches a newly computed token otherwise.
+    pub fn compute_reveal_token_cached<R: Rng>(
+        rng: &mut R,
+        pp: &Parameters<C>,
+        sk: &PlayerSecretKey<C>,
+        pk: &PublicKey<C>,
+        store: &mut RevealTokenStore<C>,
+        session: &[u8],
+        masked_card: &MaskedCard<C>,
+    ) -> Result<(RevealToken<C>, RevealProof<C>), CardProtocolError> {
+        if store.public_key() != pk {
+            return Err(CardProtocolError::RevealTokenMismatch);
+        }
+
+        if let Some(cached) = store.get(session, masked_card) {
+            return Ok(cached.clone());
+        }
+
+        let (reveal_token, proof) = Self::compute_reveal_token(rng, pp, sk, pk, masked_card)?;
+        store.insert(session, masked_card, reveal_token, proof.clone());
+
+        Ok((reveal_token, proof))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::discrete_log_cards;
+    use crate::error::CardProtocolError;
+    use crate::BarnettSmartProtocol;
+
+    use ark_serialize::CanonicalSerialize;
+    use proof_essentials::utils::rand::sample_vector;
+    use rand::thread_rng;
+
+    // Choose elliptic curve setting
+    type Curve = starknet_curve::Projective;
+
+    // Instantiate concrete type for our card protocol
+    type CardProtocol = discrete_log_cards::DLCards<Curve>;
+    type RevealTokenStore = discrete_log_cards::RevealTokenStore<Curve>;
+
+    type MaskedCard = discrete_log_cards::MaskedCard<Curve>;
+
+    fn to_bytes<T: CanonicalSerialize>(value: &T) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        value.serialize(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_reveal_token_store() {
+        let rng = &mut thread_rng();
+        let parameters = CardProtocol::setup(rng, 2, 5).unwrap();
+        let (pk, sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+
+        let deck: Vec<MaskedCard> = sample_vector(rng, 3);
+        let mut store = RevealTokenStore::new(&pk);
+
+        let first = CardProtocol::compute_reveal_token_cached(
+            rng,
+            &parameters,
+            &sk,
+            &pk,
+            &mut store,
+            b"hand 1",
+            &deck[0],
+        )
+        .unwrap();
+        let cached = CardProtocol::compute_reveal_token_cached(
+            rng,
+            &parameters,
+            &sk,
+            &pk,
+            &mut store,
+            b"hand 1",
+            &deck[0],
+        )
+        .unwrap();
+        assert_eq!(to_bytes(&first), to_bytes(&cached));
+        assert_eq!(store.len(), 1);
+
+        // A new session gets a fresh proof
+        let next_hand = CardProtocol::compute_reveal_token_cached(
+            rng,
+            &parameters,
+            &sk,
+            &pk,
+            &mut store,
+            b"hand 2",
+            &deck[0],
+        )
+        .unwrap();
+        assert_eq!(first.0, next_hand.0);
+        assert_ne!(to_bytes(&first.1), to_bytes(&next_hand.1));
+        assert_eq!(
+            Ok(()),
+            CardProtocol::verify_reveal(&parameters, &pk, &next_hand.0, &deck[0], &next_hand.1)
+        );
+
+        store.clear_session(b"hand 2");
+        assert_eq!(store.len(), 1);
+
+        // The store survives a restart, and saving replaces the file without leaving the temporary
+        // file behind
+        let dir = std::env::temp_dir().join(format!("reveal-tokens-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("tokens");
+        RevealTokenStore::new(&pk).save(&path).unwrap();
+        store.save(&path).unwrap();
+        let mut restored = RevealTokenStore::load(&path).unwrap();
+        let leftovers = std::fs::read_dir(&dir).unwrap().count();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(leftovers, 1);
+
+        let reloaded = CardProtocol::compute_reveal_token_cached(
+            rng,
+            &parameters,
+            &sk,
+            &pk,
+            &mut restored,
+            b"hand 1",
+            &deck[0],
+        )
+        .unwrap();
+        assert_eq!(to_bytes(&first), to_bytes(&reloaded));
+
+        // A store belongs to a single player
+        let (other_pk, other_sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+        assert_eq!(
+            CardProtocol::compute_reveal_token_cached(
+                rng,
+                &parameters,
+                &other_sk,
+                &other_pk,
+                &mut store,
+                b"hand 1",
+                &deck[1],
+            )
+            .err(),
+            Some(CardProtocolError::RevealTokenMismatch)
+        );
+    }
+}
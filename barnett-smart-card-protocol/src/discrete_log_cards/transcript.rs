@@ -0,0 +1,228 @@
+//! Hash functions available to the Fiat-Shamir transform of `DLCards`. Any `Digest` with an output
+//! of at least 32 bytes can be used; the hash is chosen with the second type parameter of
+//! `DLCards`, e.g. `DLCards<C, Keccak256>`, and defaults to Blake2s. Provers and verifiers must use
+//! the same backend: a proof produced with one hash does not verify with another.
+
+pub use blake2::Blake2s;
+pub use sha2::Sha256;
+pub use sha3::Keccak256;
+
+use digest::consts::U32;
+use digest::generic_array::GenericArray;
+use digest::{FixedOutput, Reset, Update};
+use merlin::Transcript;
+
+const MERLIN_LABEL: &'static [u8] = b"Barnett-Smart Card Protocol";
+
+/// A Merlin (STROBE-based) transcript exposed as a hash function. Every call to `update` appends a
+/// message to the transcript and the digest is a 32-byte challenge.
+#[derive(Clone)]
+pub struct MerlinDigest {
+    transcript: Transcript,
+}
+
+impl Default for MerlinDigest {
+    fn default() -> Self {
+        Self {
+            transcript: Transcript::new(MERLIN_LABEL),
+        }
+    }
+}
+
+impl Update for MerlinDigest {
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.transcript.append_message(b"data", data.as_ref());
+    }
+}
+
+impl FixedOutput for MerlinDigest {
+    type OutputSize = U32;
+
+    fn finalize_into(mut self, out: &mut GenericArray<u8, Self::OutputSize>) {
+        self.transcript.challenge_bytes(b"digest", out);
+    }
+
+    fn finalize_into_reset(&mut self, out: &mut GenericArray<u8, Self::OutputSize>) {
+        self.transcript.challenge_bytes(b"digest", out);
+        self.reset();
+    }
+}
+
+impl Reset for MerlinDigest {
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(feature = "poseidon")]
+pub use poseidon::PoseidonDigest;
+
+#[cfg(feature = "poseidon")]
+pub(super) mod poseidon {
+    use ark_ff::{BigInteger, PrimeField};
+    use ark_sponge::poseidon::{find_poseidon_ark_and_mds, PoseidonParameters, PoseidonSponge};
+    use ark_sponge::CryptographicSponge;
+    use digest::consts::U32;
+    use digest::generic_array::GenericArray;
+    use digest::{FixedOutput, Reset, Update};
+    use once_cell::sync::Lazy;
+    use std::any::{Any, TypeId};
+    use std::collections::HashMap;
+    use std::marker::PhantomData;
+    use std::sync::{Arc, Mutex};
+
+    // Width 3, x^17, 8 full and 31 partial rounds. The exponent is coprime to p - 1 for the scalar
+    // fields of BN254, BLS12-381 and BLS12-377, so the same instance serves every SNARK field.
+    const RATE: usize = 2;
+    const ALPHA: u64 = 17;
+    const FULL_ROUNDS: u64 = 8;
+    const PARTIAL_ROUNDS: u64 = 31;
+
+    // Bytes packed in each field element, so that every chunk fits below the modulus
+    pub(crate) const CHUNK_SIZE: usize = 31;
+
+    // The round constants of each field, derived once
+    static PARAMETERS: Lazy<Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>> =
+        Lazy::new(Default::default);
+
+    /// The Poseidon instance of the crate over `F`, with round constants from the Grain LFSR. Both
+    /// `PoseidonDigest` and the gadgets of the `constraints` module use it.
+    pub(crate) fn poseidon_parameters<F: PrimeField>() -> Arc<PoseidonParameters<F>> {
+        let parameters = PARAMETERS
+            .lock()
+            .unwrap()
+            .entry(TypeId::of::<F>())
+            .or_insert_with(|| {
+                let (ark, mds) = find_poseidon_ark_and_mds::<F>(
+                    F::size_in_bits() as u64,
+                    RATE,
+                    FULL_ROUNDS,
+                    PARTIAL_ROUNDS,
+                    0,
+                );
+                Arc::new(PoseidonParameters::new(
+                    FULL_ROUNDS as u32,
+                    PARTIAL_ROUNDS as u32,
+                    ALPHA,
+                    mds,
+                    ark,
+                ))
+            })
+            .clone();
+
+        parameters.downcast().unwrap()
+    }
+
+    /// Poseidon over the prime field `F`, by default the scalar field of BN254, exposed as a hash
+    /// function. Input bytes are packed into field elements 31 bytes at a time, followed by their
+    /// length; the digest is made of the low 16 bytes of two squeezed elements. With `F` the field
+    /// of the constraints of an embedded curve `C`, the challenges of the reveal and key ownership
+    /// proofs of `DLCards<C, PoseidonDigest<F>>` can be checked in-circuit.
+    #[derive(Clone, Default)]
+    pub struct PoseidonDigest<F: PrimeField = ark_bn254::Fr> {
+        data: Vec<u8>,
+        _field: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> PoseidonDigest<F> {
+        fn hash(&self) -> [u8; 32] {
+            let mut elements = self
+                .data
+                .chunks(CHUNK_SIZE)
+                .map(F::from_le_bytes_mod_order)
+                .collect::<Vec<_>>();
+            elements.push(F::from(self.data.len() as u64));
+
+            let mut sponge = PoseidonSponge::new(&*poseidon_parameters::<F>());
+            sponge.absorb(&elements);
+            let squeezed: Vec<F> = sponge.squeeze_field_elements(2);
+
+            let mut digest = [0u8; 32];
+            for (i, element) in squeezed.iter().enumerate() {
+                digest[16 * i..16 * (i + 1)]
+                    .copy_from_slice(&element.into_repr().to_bytes_le()[..16]);
+            }
+
+            digest
+        }
+    }
+
+    impl<F: PrimeField> Update for PoseidonDigest<F> {
+        fn update(&mut self, data: impl AsRef<[u8]>) {
+            self.data.extend_from_slice(data.as_ref());
+        }
+    }
+
+    impl<F: PrimeField> FixedOutput for PoseidonDigest<F> {
+        type OutputSize = U32;
+
+        fn finalize_into(self, out: &mut GenericArray<u8, Self::OutputSize>) {
+            out.copy_from_slice(&self.hash());
+        }
+
+        fn finalize_into_reset(&mut self, out: &mut GenericArray<u8, Self::OutputSize>) {
+            out.copy_from_slice(&self.hash());
+            self.reset();
+        }
+    }
+
+    impl<F: PrimeField> Reset for PoseidonDigest<F> {
+        fn reset(&mut self) {
+            self.data.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::discrete_log_cards::transcript::{Keccak256, MerlinDigest, Sha256};
+    use crate::discrete_log_cards::{self, DLCards};
+    use crate::BarnettSmartProtocol;
+
+    use ark_ff::UniformRand;
+    use digest::Digest;
+    use rand::thread_rng;
+
+    // Choose elliptic curve setting
+    type Curve = starknet_curve::Projective;
+    type Scalar = starknet_curve::Fr;
+
+    type Card = discrete_log_cards::Card<Curve>;
+
+    fn check_backend<D: Digest>() {
+        let rng = &mut thread_rng();
+        let parameters = DLCards::<Curve, D>::setup(rng, 2, 5).unwrap();
+        let (pk, sk) = DLCards::<Curve, D>::player_keygen(rng, &parameters).unwrap();
+
+        let card = Card::rand(rng);
+        let r = Scalar::rand(rng);
+        let (masked, proof) = DLCards::<Curve, D>::mask(rng, &parameters, &pk, &card, &r).unwrap();
+        assert_eq!(
+            Ok(()),
+            DLCards::<Curve, D>::verify_mask(&parameters, &pk, &card, &masked, &proof)
+        );
+
+        // A proof is bound to the hash it was produced with
+        assert!(DLCards::<Curve>::verify_mask(&parameters, &pk, &card, &masked, &proof).is_err());
+
+        let name = b"Player".to_vec();
+        let key_proof =
+            DLCards::<Curve, D>::prove_key_ownership(rng, &parameters, &pk, &sk, &name).unwrap();
+        assert_eq!(
+            Ok(()),
+            DLCards::<Curve, D>::verify_key_ownership(&parameters, &pk, &name, &key_proof)
+        );
+    }
+
+    #[test]
+    fn test_transcript_backends() {
+        check_backend::<Sha256>();
+        check_backend::<Keccak256>();
+        check_backend::<MerlinDigest>();
+
+        #[cfg(feature = "poseidon")]
+        check_backend::<crate::discrete_log_cards::transcript::PoseidonDigest>();
+        #[cfg(feature = "poseidon")]
+        check_backend::<crate::discrete_log_cards::transcript::PoseidonDigest<ark_bls12_377::Fr>>();
+    }
+}
@@ -0,0 +1,235 @@
+use crate::discrete_log_cards::{Card, DLCards, MaskedCard, PublicKey, RevealToken};
+use crate::error::CardProtocolError;
+
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{FpParameters, PrimeField, Zero};
+use digest::Digest;
+
+/// Checks applied to the group elements received from other players before they reach a verifier.
+/// Deserialization only guarantees that points lie on the curve: on curves with a cofactor, such as
+/// BLS12-377, a point with a small-order component could pass a proof it should fail or leak bits
+/// of a secret key once multiplied by it.
+impl<C: ProjectiveCurve, D: Digest> DLCards<C, D> {
+    /// Check that a player or aggregate key is in the prime-order subgroup and is not the identity
+    pub fn validate_public_key(pk: &PublicKey<C>) -> Result<(), CardProtocolError> {
+        if pk.is_zero() {
+            return Err(CardProtocolError::InvalidGroupElement(String::from(
+                "public key is the identity",
+            )));
+        }
+        if !in_prime_order_subgroup::<C>(pk) {
+            return Err(CardProtocolError::InvalidGroupElement(String::from(
+                "public key is not in the prime-order subgroup",
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Check that an open card is in the prime-order subgroup. Cards are public, but a card
+    /// received from another player enters the statement of a masking proof.
+    pub fn validate_card(card: &Card<C>) -> Result<(), CardProtocolError> {
+        if !in_prime_order_subgroup::<C>(&card.0) {
+            return Err(CardProtocolError::InvalidGroupElement(String::from(
+                "card is not in the prime-order subgroup",
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Check that both components of a masked card are in the prime-order subgroup. The first
+    /// component is `g * r` for the masking factor `r`, and is the identity only if the card is not
+    /// masked at all.
+    pub fn validate_masked_card(masked_card: &MaskedCard<C>) -> Result<(), CardProtocolError> {
+        if masked_card.0.is_zero() {
+            return Err(CardProtocolError::InvalidGroupElement(String::from(
+                "masked card is not masked",
+            )));
+        }
+        if !in_prime_order_subgroup::<C>(&masked_card.0)
+            || !in_prime_order_subgroup::<C>(&masked_card.1)
+        {
+            return Err(CardProtocolError::InvalidGroupElement(String::from(
+                "masked card is not in the prime-order subgroup",
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Check that a reveal token is in the prime-order subgroup and is not the identity, which
+    /// would only be the token of an unmasked card or of a zero secret key
+    pub fn validate_reveal_token(reveal_token: &RevealToken<C>) -> Result<(), CardProtocolError> {
+        if reveal_token.0.is_zero() {
+            return Err(CardProtocolError::InvalidGroupElement(String::from(
+                "reveal token is the identity",
+            )));
+        }
+        if !in_prime_order_subgroup::<C>(&reveal_token.0) {
+            return Err(CardProtocolError::InvalidGroupElement(String::from(
+                "reveal token is not in the prime-order subgroup",
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn validate_deck(deck: &Vec<MaskedCard<C>>) -> Result<(), CardProtocolError> {
+        deck.iter().try_for_each(Self::validate_masked_card)
+    }
+}
+
+pub(super) fn in_prime_order_subgroup<C: ProjectiveCurve>(point: &C::Affine) -> bool {
+    // Every point of a curve of cofactor one is in the subgroup
+    if matches!(C::Affine::COFACTOR, [1]) {
+        return true;
+    }
+
+    point
+        .mul(<C::ScalarField as PrimeField>::Params::MODULUS)
+        .is_zero()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::discrete_log_cards;
+    use crate::error::CardProtocolError;
+    use crate::BarnettSmartProtocol;
+
+    use ark_ff::{UniformRand, Zero};
+    use proof_essentials::homomorphic_encryption::el_gamal;
+    use rand::thread_rng;
+
+    // A curve with a cofactor
+    type Curve = ark_bls12_377::G1Projective;
+    type Affine = ark_bls12_377::G1Affine;
+    type Fq = ark_bls12_377::Fq;
+    type Scalar = ark_bls12_377::Fr;
+
+    // Instantiate concrete type for our card protocol
+    type CardProtocol = discrete_log_cards::DLCards<Curve>;
+
+    type Card = discrete_log_cards::Card<Curve>;
+    type MaskedCard = discrete_log_cards::MaskedCard<Curve>;
+
+    /// A point of the curve outside of the prime-order subgroup
+    fn small_order_point() -> Affine {
+        let rng = &mut thread_rng();
+        loop {
+            let x = Fq::rand(rng);
+            if let Some(point) = Affine::get_point_from_x(x, false) {
+                if !point.is_in_correct_subgroup_assuming_on_curve() {
+                    return point;
+                }
+            }
+        }
+    }
+
+    fn invalid_element(what: &str) -> Option<CardProtocolError> {
+        Some(CardProtocolError::InvalidGroupElement(String::from(what)))
+    }
+
+    #[test]
+    fn test_validate_group_elements() {
+        let rng = &mut thread_rng();
+        let parameters = CardProtocol::setup(rng, 2, 5).unwrap();
+        let (pk, sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+        let name = b"Alice";
+        let key_proof =
+            CardProtocol::prove_key_ownership(rng, &parameters, &pk, &sk, &name).unwrap();
+
+        let invalid = small_order_point();
+
+        assert_eq!(Ok(()), CardProtocol::validate_public_key(&pk));
+        assert_eq!(
+            CardProtocol::validate_public_key(&Affine::zero()).err(),
+            invalid_element("public key is the identity")
+        );
+        assert_eq!(
+            CardProtocol::validate_public_key(&invalid).err(),
+            invalid_element("public key is not in the prime-order subgroup")
+        );
+
+        // Aggregation rejects invalid keys before checking their proofs
+        assert_eq!(
+            CardProtocol::compute_aggregate_key(&parameters, &vec![(invalid, key_proof, name)])
+                .err(),
+            invalid_element("public key is not in the prime-order subgroup")
+        );
+        assert_eq!(
+            CardProtocol::remove_from_aggregate_key(&parameters, &pk, &invalid).err(),
+            invalid_element("public key is not in the prime-order subgroup")
+        );
+
+        let masked = MaskedCard::rand(rng);
+        assert_eq!(Ok(()), CardProtocol::validate_masked_card(&masked));
+        assert_eq!(
+            CardProtocol::validate_masked_card(&el_gamal::Ciphertext(Affine::zero(), masked.1))
+                .err(),
+            invalid_element("masked card is not masked")
+        );
+        assert_eq!(
+            CardProtocol::validate_masked_card(&el_gamal::Ciphertext(masked.0, invalid)).err(),
+            invalid_element("masked card is not in the prime-order subgroup")
+        );
+
+        let (token, proof) =
+            CardProtocol::compute_reveal_token(rng, &parameters, &sk, &pk, &masked).unwrap();
+        assert_eq!(Ok(()), CardProtocol::validate_reveal_token(&token));
+        assert_eq!(
+            CardProtocol::validate_reveal_token(&el_gamal::Plaintext(invalid)).err(),
+            invalid_element("reveal token is not in the prime-order subgroup")
+        );
+
+        // Provers refuse to multiply their secret key by an invalid masked card
+        let invalid_masked = el_gamal::Ciphertext(invalid, masked.1);
+        assert_eq!(
+            CardProtocol::compute_reveal_token(rng, &parameters, &sk, &pk, &invalid_masked).err(),
+            invalid_element("masked card is not in the prime-order subgroup")
+        );
+        assert_eq!(
+            CardProtocol::add_key_to_deck(
+                rng,
+                &parameters,
+                &sk,
+                &pk,
+                &vec![masked, invalid_masked]
+            )
+            .err(),
+            invalid_element("masked card is not in the prime-order subgroup")
+        );
+        assert_eq!(
+            CardProtocol::remove_key_from_deck(rng, &parameters, &sk, &pk, &vec![invalid_masked])
+                .err(),
+            invalid_element("masked card is not in the prime-order subgroup")
+        );
+
+        // Verifiers validate their inputs before checking the proof
+        assert_eq!(
+            CardProtocol::verify_reveal(&parameters, &pk, &token, &invalid_masked, &proof).err(),
+            invalid_element("masked card is not in the prime-order subgroup")
+        );
+
+        let card = Card::rand(rng);
+        let alpha = Scalar::rand(rng);
+        let (masked_card, masking_proof) =
+            CardProtocol::mask(rng, &parameters, &pk, &card, &alpha).unwrap();
+        assert_eq!(Ok(()), CardProtocol::validate_card(&card));
+        assert_eq!(
+            CardProtocol::validate_card(&el_gamal::Plaintext(invalid)).err(),
+            invalid_element("card is not in the prime-order subgroup")
+        );
+        assert_eq!(
+            CardProtocol::verify_mask(
+                &parameters,
+                &pk,
+                &el_gamal::Plaintext(invalid),
+                &masked_card,
+                &masking_proof
+            )
+            .err(),
+            invalid_element("card is not in the prime-order subgroup")
+        );
+    }
+}
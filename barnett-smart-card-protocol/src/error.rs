@@ -9,6 +9,36 @@ pub enum CardProtocolError {
 
     #[error("IoError: {0}")]
     IoError(String),
+
+    #[error("Card index {0} is out of the deck")]
+    InvalidCardIndex(usize),
+
+    #[error("Reveal tokens do not match the players at the table")]
+    RevealTokenMismatch,
+
+    #[error("Opened card does not match its reveal tokens")]
+    InvalidOpenedCard,
+
+    #[error("A deck of {0} cards does not match the parameters")]
+    InvalidDeckSize(usize),
+
+    #[error("Expected one masking factor per card")]
+    MaskingFactorsMismatch,
+
+    #[error("Cards do not satisfy the predicate")]
+    PredicateNotSatisfied,
+
+    #[error("Unsupported snapshot version {0}")]
+    UnsupportedSnapshotVersion(u8),
+
+    #[error("Snapshot is corrupted")]
+    CorruptedSnapshot,
+
+    #[error("Deck does not match the commitment of the shuffle chain")]
+    ShuffleChainMismatch,
+
+    #[error("Invalid group element: {0}")]
+    InvalidGroupElement(String),
 }
 
 impl From<std::io::Error> for CardProtocolError {
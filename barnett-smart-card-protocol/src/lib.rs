@@ -12,6 +12,9 @@ use std::ops::{Add, Mul};
 
 pub mod discrete_log_cards;
 pub mod error;
+#[cfg(feature = "network")]
+pub mod network;
+pub mod texas_holdem;
 
 pub trait Mask<Scalar: Field, Enc: HomomorphicEncryptionScheme<Scalar>> {
     fn mask(
@@ -69,6 +72,8 @@ pub trait BarnettSmartProtocol {
     type ZKProofRemasking: CanonicalDeserialize + CanonicalSerialize;
     type ZKProofReveal: CanonicalDeserialize + CanonicalSerialize;
     type ZKProofShuffle: CanonicalDeserialize + CanonicalSerialize;
+    type ZKProofRekey: CanonicalDeserialize + CanonicalSerialize;
+    type ZKProofCut: CanonicalDeserialize + CanonicalSerialize;
 
     /// Randomly produce the scheme parameters
     fn setup<R: Rng>(
@@ -98,7 +103,7 @@ pub trait BarnettSmartProtocol {
         pk: &Self::PlayerPublicKey,
         player_public_info: &B,
         proof: &Self::ZKProofKeyOwnership,
-    ) -> Result<(), CryptoError>;
+    ) -> Result<(), CardProtocolError>;
 
     /// Use all the public keys and zk-proofs to compute a verified aggregate public key
     fn compute_aggregate_key<B: ToBytes>(
@@ -123,7 +128,7 @@ pub trait BarnettSmartProtocol {
         card: &Self::Card,
         masked_card: &Self::MaskedCard,
         proof: &Self::ZKProofMasking,
-    ) -> Result<(), CryptoError>;
+    ) -> Result<(), CardProtocolError>;
 
     /// Use the shared public key and a (private) random scalar `alpha` to remask a masked card.
     /// Returns a masked card and a zk-proof that the remasking operation was applied correctly.
@@ -142,7 +147,7 @@ pub trait BarnettSmartProtocol {
         original_masked: &Self::MaskedCard,
         remasked: &Self::MaskedCard,
         proof: &Self::ZKProofRemasking,
-    ) -> Result<(), CryptoError>;
+    ) -> Result<(), CardProtocolError>;
 
     /// Players can use this function to compute their reveal token for a given masked card.
     /// The token is accompanied by a proof that it is a valid reveal for the specified card issued
@@ -162,7 +167,15 @@ pub trait BarnettSmartProtocol {
         reveal_token: &Self::RevealToken,
         masked_card: &Self::MaskedCard,
         proof: &Self::ZKProofReveal,
-    ) -> Result<(), CryptoError>;
+    ) -> Result<(), CardProtocolError>;
+
+    /// Compute a reveal token without a proof. Only meant for a player opening a card for
+    /// themselves, whose token is never sent to anyone.
+    fn compute_reveal_token_unproven(
+        pp: &Self::Parameters,
+        sk: &Self::PlayerSecretKey,
+        masked_card: &Self::MaskedCard,
+    ) -> Result<Self::RevealToken, CardProtocolError>;
 
     /// After collecting all the necessary reveal tokens and proofs that these are correctly issued,
     /// players can unmask a masked card to recover the underlying card.
@@ -176,6 +189,20 @@ pub trait BarnettSmartProtocol {
         masked_card: &Self::MaskedCard,
     ) -> Result<Self::Card, CardProtocolError>;
 
+    /// A player privately opens a card dealt to them: the reveal tokens of the other players are
+    /// verified and combined with the player's own secret key directly, without producing a token
+    /// for the player's own key.
+    fn unmask_with_own_key(
+        pp: &Self::Parameters,
+        sk: &Self::PlayerSecretKey,
+        decryption_key: &Vec<(
+            Self::RevealToken,
+            Self::ZKProofReveal,
+            Self::PlayerPublicKey,
+        )>,
+        masked_card: &Self::MaskedCard,
+    ) -> Result<Self::Card, CardProtocolError>;
+
     /// Shuffle and remask a deck of masked cards using a player-chosen permutation and vector of
     /// masking factors.
     fn shuffle_and_remask<R: Rng>(
@@ -194,5 +221,82 @@ pub trait BarnettSmartProtocol {
         original_deck: &Vec<Self::MaskedCard>,
         shuffled_deck: &Vec<Self::MaskedCard>,
         proof: &Self::ZKProofShuffle,
-    ) -> Result<(), CryptoError>;
+    ) -> Result<(), CardProtocolError>;
+
+    /// Cut a deck of masked cards: rotate it so that the card at position `offset` ends up on top,
+    /// and remask every card. Unlike a shuffle, a cut can be applied to decks of any length.
+    fn cut_and_remask<R: Rng>(
+        rng: &mut R,
+        pp: &Self::Parameters,
+        shared_key: &Self::AggregatePublicKey,
+        deck: &Vec<Self::MaskedCard>,
+        masking_factors: &Vec<Self::Scalar>,
+        offset: usize,
+    ) -> Result<(Vec<Self::MaskedCard>, Self::ZKProofCut), CardProtocolError>;
+
+    /// Verify a proof that a deck is a remasked cut of another, without learning the offset
+    fn verify_cut(
+        pp: &Self::Parameters,
+        shared_key: &Self::AggregatePublicKey,
+        original_deck: &Vec<Self::MaskedCard>,
+        cut_deck: &Vec<Self::MaskedCard>,
+        proof: &Self::ZKProofCut,
+    ) -> Result<(), CardProtocolError>;
+
+    /// Verify the key ownership proof of a player joining the table and add their public key to
+    /// the current aggregate key.
+    fn add_to_aggregate_key<B: ToBytes>(
+        pp: &Self::Parameters,
+        shared_key: &Self::AggregatePublicKey,
+        pk: &Self::PlayerPublicKey,
+        proof: &Self::ZKProofKeyOwnership,
+        player_public_info: &B,
+    ) -> Result<Self::AggregatePublicKey, CardProtocolError>;
+
+    /// Remove the public key of a player leaving the table from the current aggregate key.
+    fn remove_from_aggregate_key(
+        pp: &Self::Parameters,
+        shared_key: &Self::AggregatePublicKey,
+        pk: &Self::PlayerPublicKey,
+    ) -> Result<Self::AggregatePublicKey, CardProtocolError>;
+
+    /// A player joining the table uses this function to re-encrypt every card of a live deck under
+    /// the aggregate key extended with their own key. Returns the re-keyed deck and one zk-proof per
+    /// card that the joining player's key was applied correctly.
+    fn add_key_to_deck<R: Rng>(
+        rng: &mut R,
+        pp: &Self::Parameters,
+        sk: &Self::PlayerSecretKey,
+        pk: &Self::PlayerPublicKey,
+        deck: &Vec<Self::MaskedCard>,
+    ) -> Result<(Vec<Self::MaskedCard>, Vec<Self::ZKProofRekey>), CardProtocolError>;
+
+    /// Verify that a deck was correctly re-keyed by a joining player
+    fn verify_add_key_to_deck(
+        pp: &Self::Parameters,
+        pk: &Self::PlayerPublicKey,
+        original_deck: &Vec<Self::MaskedCard>,
+        rekeyed_deck: &Vec<Self::MaskedCard>,
+        proofs: &Vec<Self::ZKProofRekey>,
+    ) -> Result<(), CardProtocolError>;
+
+    /// A player leaving the table uses this function to strip their key from every card of a live
+    /// deck, leaving it encrypted under the aggregate key of the remaining players. Returns the
+    /// re-keyed deck and one zk-proof per card that the departing player's key was removed correctly.
+    fn remove_key_from_deck<R: Rng>(
+        rng: &mut R,
+        pp: &Self::Parameters,
+        sk: &Self::PlayerSecretKey,
+        pk: &Self::PlayerPublicKey,
+        deck: &Vec<Self::MaskedCard>,
+    ) -> Result<(Vec<Self::MaskedCard>, Vec<Self::ZKProofRekey>), CardProtocolError>;
+
+    /// Verify that a deck was correctly re-keyed by a departing player
+    fn verify_remove_key_from_deck(
+        pp: &Self::Parameters,
+        pk: &Self::PlayerPublicKey,
+        original_deck: &Vec<Self::MaskedCard>,
+        rekeyed_deck: &Vec<Self::MaskedCard>,
+        proofs: &Vec<Self::ZKProofRekey>,
+    ) -> Result<(), CardProtocolError>;
 }
@@ -0,0 +1,138 @@
+use super::NetworkError;
+use crate::discrete_log_cards::{
+    Card, DLEqualityProof, MaskedCard, PublicKey, RevealToken, SchnorrProof, ShuffleProof,
+};
+
+use ark_ec::ProjectiveCurve;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+type ProofKeyOwnership<C> = SchnorrProof<C>;
+type RevealProof<C> = DLEqualityProof<C>;
+
+const KEY_TAG: u8 = 0;
+const INITIAL_DECK_TAG: u8 = 1;
+const SHUFFLE_TAG: u8 = 2;
+const REVEAL_TOKENS_TAG: u8 = 3;
+
+/// Protocol messages exchanged by the players of a table
+pub enum Message<C: ProjectiveCurve> {
+    /// A player announces their public key with a proof of ownership
+    Key {
+        pk: PublicKey<C>,
+        proof: ProofKeyOwnership<C>,
+        name: Vec<u8>,
+    },
+    /// The first seat announces the encoded cards making up the deck
+    InitialDeck { cards: Vec<Card<C>> },
+    /// A player publishes their shuffle of the current deck
+    Shuffle {
+        deck: Vec<MaskedCard<C>>,
+        proof: ShuffleProof<C>,
+    },
+    /// A player publishes their reveal tokens for the cards at the given deck indices
+    RevealTokens {
+        indices: Vec<usize>,
+        tokens: Vec<(RevealToken<C>, RevealProof<C>)>,
+    },
+}
+
+impl<C: ProjectiveCurve> Message<C> {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, NetworkError> {
+        let mut bytes = Vec::new();
+
+        match self {
+            Self::Key { pk, proof, name } => {
+                KEY_TAG.serialize(&mut bytes)?;
+                pk.serialize(&mut bytes)?;
+                proof.serialize(&mut bytes)?;
+                name.serialize(&mut bytes)?;
+            }
+            Self::InitialDeck { cards } => {
+                INITIAL_DECK_TAG.serialize(&mut bytes)?;
+                cards.serialize(&mut bytes)?;
+            }
+            Self::Shuffle { deck, proof } => {
+                SHUFFLE_TAG.serialize(&mut bytes)?;
+                deck.serialize(&mut bytes)?;
+                proof.serialize(&mut bytes)?;
+            }
+            Self::RevealTokens { indices, tokens } => {
+                REVEAL_TOKENS_TAG.serialize(&mut bytes)?;
+                indices.serialize(&mut bytes)?;
+                tokens.serialize(&mut bytes)?;
+            }
+        }
+
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, NetworkError> {
+        let tag = u8::deserialize(&mut bytes)?;
+
+        let message = match tag {
+            KEY_TAG => Self::Key {
+                pk: CanonicalDeserialize::deserialize(&mut bytes)?,
+                proof: CanonicalDeserialize::deserialize(&mut bytes)?,
+                name: CanonicalDeserialize::deserialize(&mut bytes)?,
+            },
+            INITIAL_DECK_TAG => Self::InitialDeck {
+                cards: CanonicalDeserialize::deserialize(&mut bytes)?,
+            },
+            SHUFFLE_TAG => Self::Shuffle {
+                deck: CanonicalDeserialize::deserialize(&mut bytes)?,
+                proof: CanonicalDeserialize::deserialize(&mut bytes)?,
+            },
+            REVEAL_TOKENS_TAG => Self::RevealTokens {
+                indices: CanonicalDeserialize::deserialize(&mut bytes)?,
+                tokens: CanonicalDeserialize::deserialize(&mut bytes)?,
+            },
+            _ => {
+                return Err(NetworkError::MalformedMessage(format!(
+                    "unknown tag {}",
+                    tag
+                )))
+            }
+        };
+
+        if !bytes.is_empty() {
+            return Err(NetworkError::MalformedMessage(String::from(
+                "trailing bytes",
+            )));
+        }
+
+        Ok(message)
+    }
+}
+
+/// A serialized message together with the seat that sent it and its position in the sequence of
+/// messages sent by that seat. Sequence numbers let receivers restore the sending order and drop
+/// duplicates whatever the transport does.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Envelope {
+    pub sender: usize,
+    pub sequence: u64,
+    pub payload: Vec<u8>,
+}
+
+impl Envelope {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, NetworkError> {
+        let mut bytes = Vec::with_capacity(self.payload.len() + 24);
+        self.sender.serialize(&mut bytes)?;
+        self.sequence.serialize(&mut bytes)?;
+        self.payload.serialize(&mut bytes)?;
+
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, NetworkError> {
+        let sender = usize::deserialize(&mut bytes)?;
+        let sequence = u64::deserialize(&mut bytes)?;
+        let payload = Vec::<u8>::deserialize(&mut bytes)?;
+
+        Ok(Self {
+            sender,
+            sequence,
+            payload,
+        })
+    }
+}
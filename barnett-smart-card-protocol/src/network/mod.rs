@@ -0,0 +1,52 @@
+//! A transport-agnostic networking layer for running the card protocol between separate parties.
+//! Each player runs a `PlayerNode`, which exchanges protocol messages with the other players
+//! through a relay that broadcasts every message to the rest of the table. Two transports are
+//! provided: an in-memory loopback relay, for tests and single-process games, and a tokio TCP relay
+//! that lets players run in separate processes.
+
+use crate::error::CardProtocolError;
+
+use ark_serialize::SerializationError;
+use proof_essentials::error::CryptoError;
+use thiserror::Error;
+
+mod message;
+mod node;
+mod transport;
+
+pub use message::{Envelope, Message};
+pub use node::{HandTranscript, Inbox, PlayerNode, TableConfig};
+pub use transport::{run_tcp_relay, LoopbackRelay, LoopbackTransport, TcpTransport, Transport};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum NetworkError {
+    #[error("IoError: {0}")]
+    IoError(String),
+
+    #[error("Malformed message: {0}")]
+    MalformedMessage(String),
+
+    #[error("Unexpected message from seat {0}")]
+    UnexpectedMessage(usize),
+
+    #[error("Timed out waiting for seat {0}")]
+    Timeout(usize),
+
+    #[error("Card protocol error: {0}")]
+    CardProtocolError(#[from] CardProtocolError),
+
+    #[error("Failed to verify proof")]
+    ProofVerificationError(#[from] CryptoError),
+}
+
+impl From<std::io::Error> for NetworkError {
+    fn from(err: std::io::Error) -> Self {
+        Self::IoError(err.to_string())
+    }
+}
+
+impl From<SerializationError> for NetworkError {
+    fn from(err: SerializationError) -> Self {
+        Self::MalformedMessage(err.to_string())
+    }
+}
@@ -0,0 +1,533 @@
+use super::{Envelope, Message, NetworkError, Transport};
+use crate::discrete_log_cards::{
+    Card, DLCards, DLEqualityProof, MaskedCard, Parameters, PlayerSecretKey, PublicKey, RevealToken,
+};
+use crate::BarnettSmartProtocol;
+
+use ark_ec::ProjectiveCurve;
+use ark_ff::One;
+use ark_std::rand::Rng;
+use proof_essentials::utils::permutation::Permutation;
+use proof_essentials::utils::rand::sample_vector;
+use std::collections::HashMap;
+use std::time::Duration;
+
+type RevealProof<C> = DLEqualityProof<C>;
+
+const MAX_SEND_ATTEMPTS: usize = 5;
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(100);
+// Envelopes further ahead of the next expected one are dropped, so a peer cannot fill the inbox
+const SEQUENCE_WINDOW: u64 = 16;
+
+/// Buffers envelopes until they can be delivered in the order in which each seat sent them.
+/// Duplicated envelopes and envelopes outside the window of `SEQUENCE_WINDOW` sequence numbers
+/// following the next expected one are dropped.
+#[derive(Default)]
+pub struct Inbox {
+    next_sequence: HashMap<usize, u64>,
+    pending: HashMap<(usize, u64), Vec<u8>>,
+}
+
+impl Inbox {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store an envelope. Returns `false` if the envelope was already received or lies outside the
+    /// sequence window.
+    pub fn push(&mut self, envelope: Envelope) -> bool {
+        let next = self
+            .next_sequence
+            .get(&envelope.sender)
+            .copied()
+            .unwrap_or(0);
+        let id = (envelope.sender, envelope.sequence);
+
+        if envelope.sequence < next
+            || envelope.sequence - next >= SEQUENCE_WINDOW
+            || self.pending.contains_key(&id)
+        {
+            return false;
+        }
+
+        self.pending.insert(id, envelope.payload);
+        true
+    }
+
+    /// Take the next payload sent by `sender`, if it has arrived
+    pub fn pop(&mut self, sender: usize) -> Option<Vec<u8>> {
+        let next = self.next_sequence.entry(sender).or_insert(0);
+        let payload = self.pending.remove(&(sender, *next))?;
+        *next += 1;
+
+        Some(payload)
+    }
+}
+
+/// Public configuration of a table, agreed upon by all players before the game starts.
+#[derive(Clone, Debug)]
+pub struct TableConfig {
+    /// Public domain tag from which every player derives the same protocol parameters
+    pub domain: Vec<u8>,
+    pub m: usize,
+    pub n: usize,
+    pub num_of_players: usize,
+    pub cards_per_player: usize,
+    /// How long to wait for a message before giving up on a player
+    pub timeout: Duration,
+}
+
+/// Everything a player learned during a hand
+pub struct HandTranscript<C: ProjectiveCurve> {
+    pub shared_key: PublicKey<C>,
+    pub deck: Vec<MaskedCard<C>>,
+    pub own_cards: Vec<Card<C>>,
+    /// Cards of every seat, opened to the table at the end of the hand
+    pub opened_cards: Vec<Vec<Card<C>>>,
+}
+
+/// A player taking part in a game over a `Transport`. Every message received from other players is
+/// verified before being acted upon.
+pub struct PlayerNode<C: ProjectiveCurve, T: Transport> {
+    seat: usize,
+    name: Vec<u8>,
+    config: TableConfig,
+    parameters: Parameters<C>,
+    pk: PublicKey<C>,
+    sk: PlayerSecretKey<C>,
+    transport: T,
+    inbox: Inbox,
+    sequence: u64,
+}
+
+impl<C: ProjectiveCurve, T: Transport> PlayerNode<C, T> {
+    /// Create a player sitting at the seat assigned by the relay of `transport`
+    pub fn new<R: Rng>(
+        rng: &mut R,
+        name: &Vec<u8>,
+        config: TableConfig,
+        transport: T,
+    ) -> Result<Self, NetworkError> {
+        let parameters = DLCards::<C>::setup_from_domain(&config.domain, config.m, config.n)?;
+        let (pk, sk) = DLCards::<C>::player_keygen(rng, &parameters)?;
+
+        Ok(Self {
+            seat: transport.seat(),
+            name: name.clone(),
+            config,
+            parameters,
+            pk,
+            sk,
+            transport,
+            inbox: Inbox::new(),
+            sequence: 0,
+        })
+    }
+
+    pub fn seat(&self) -> usize {
+        self.seat
+    }
+
+    pub fn public_key(&self) -> &PublicKey<C> {
+        &self.pk
+    }
+
+    /// Run a hand: agree on the aggregate key and on the deck, shuffle in seat order, deal
+    /// `cards_per_player` private cards to every seat and finally open all dealt cards to the table.
+    pub async fn play_hand<R: Rng>(
+        &mut self,
+        rng: &mut R,
+    ) -> Result<HandTranscript<C>, NetworkError> {
+        let num_of_players = self.config.num_of_players;
+        let num_of_cards = self.config.m * self.config.n;
+
+        // 1. Exchange keys and proofs of key ownership
+        let key_proof = DLCards::<C>::prove_key_ownership(
+            rng,
+            &self.parameters,
+            &self.pk,
+            &self.sk,
+            &self.name,
+        )?;
+        self.send(&Message::Key {
+            pk: self.pk,
+            proof: key_proof,
+            name: self.name.clone(),
+        })
+        .await?;
+
+        let mut key_proof_info = Vec::with_capacity(num_of_players);
+        for seat in 0..num_of_players {
+            if seat == self.seat {
+                key_proof_info.push((self.pk, key_proof, self.name.clone()));
+                continue;
+            }
+
+            match self.receive_from(seat).await? {
+                Message::Key { pk, proof, name } => key_proof_info.push((pk, proof, name)),
+                _ => return Err(NetworkError::UnexpectedMessage(seat)),
+            }
+        }
+
+        let shared_key = DLCards::<C>::compute_aggregate_key(&self.parameters, &key_proof_info)?;
+        let player_keys = key_proof_info.iter().map(|k| k.0).collect::<Vec<_>>();
+
+        // 2. The first seat chooses the encoding of the cards, everyone masks it the same way
+        let cards = if self.seat == 0 {
+            let cards: Vec<Card<C>> = sample_vector(rng, num_of_cards);
+            self.send(&Message::InitialDeck {
+                cards: cards.clone(),
+            })
+            .await?;
+            cards
+        } else {
+            match self.receive_from(0).await? {
+                Message::InitialDeck { cards } if cards.len() == num_of_cards => cards,
+                _ => return Err(NetworkError::UnexpectedMessage(0)),
+            }
+        };
+
+        let one = C::ScalarField::one();
+        let mut deck = cards
+            .iter()
+            .map(|card| Ok(DLCards::<C>::mask(rng, &self.parameters, &shared_key, card, &one)?.0))
+            .collect::<Result<Vec<_>, NetworkError>>()?;
+
+        // 3. Shuffle in seat order, verifying every other player's shuffle
+        for seat in 0..num_of_players {
+            if seat == self.seat {
+                let permutation = Permutation::new(rng, num_of_cards);
+                let masking_factors: Vec<C::ScalarField> = sample_vector(rng, num_of_cards);
+
+                let (shuffled, proof) = DLCards::<C>::shuffle_and_remask(
+                    rng,
+                    &self.parameters,
+                    &shared_key,
+                    &deck,
+                    &masking_factors,
+                    &permutation,
+                )?;
+
+                self.send(&Message::Shuffle {
+                    deck: shuffled.clone(),
+                    proof,
+                })
+                .await?;
+                deck = shuffled;
+                continue;
+            }
+
+            match self.receive_from(seat).await? {
+                Message::Shuffle {
+                    deck: shuffled,
+                    proof,
+                } => {
+                    DLCards::<C>::verify_shuffle(
+                        &self.parameters,
+                        &shared_key,
+                        &deck,
+                        &shuffled,
+                        &proof,
+                    )?;
+                    deck = shuffled;
+                }
+                _ => return Err(NetworkError::UnexpectedMessage(seat)),
+            }
+        }
+
+        // 4. Deal: everyone sends their tokens for the cards of the other seats
+        let mut collected: HashMap<usize, Vec<(RevealToken<C>, RevealProof<C>, PublicKey<C>)>> =
+            HashMap::new();
+
+        let indices = self.dealt_indices(|seat| seat != self.seat);
+        let tokens = DLCards::<C>::compute_public_reveal_tokens(
+            rng,
+            &self.parameters,
+            &self.sk,
+            &self.pk,
+            &deck,
+            &indices,
+        )?;
+        self.send(&Message::RevealTokens { indices, tokens })
+            .await?;
+
+        for seat in (0..num_of_players).filter(|&seat| seat != self.seat) {
+            let expected = self.dealt_indices(|other| other != seat);
+            self.collect_tokens(seat, &player_keys[seat], &expected, &mut collected)
+                .await?;
+        }
+
+        let own_indices = self.dealt_indices(|seat| seat == self.seat);
+        let mut own_cards = Vec::with_capacity(own_indices.len());
+        for i in own_indices.iter() {
+            let reveal_tokens = collected.get(i).cloned().unwrap_or_default();
+            own_cards.push(DLCards::<C>::unmask_with_own_key(
+                &self.parameters,
+                &self.sk,
+                &reveal_tokens,
+                &deck[*i],
+            )?);
+        }
+
+        // 5. Open every dealt card to the table
+        let tokens = DLCards::<C>::compute_public_reveal_tokens(
+            rng,
+            &self.parameters,
+            &self.sk,
+            &self.pk,
+            &deck,
+            &own_indices,
+        )?;
+        self.send(&Message::RevealTokens {
+            indices: own_indices,
+            tokens,
+        })
+        .await?;
+
+        let mut opened_cards = Vec::with_capacity(num_of_players);
+        for seat in 0..num_of_players {
+            if seat == self.seat {
+                opened_cards.push(own_cards.clone());
+                continue;
+            }
+
+            let expected = self.dealt_indices(|other| other == seat);
+            self.collect_tokens(seat, &player_keys[seat], &expected, &mut collected)
+                .await?;
+
+            let cards = expected
+                .iter()
+                .map(|i| {
+                    let reveal_tokens = collected.get(i).cloned().unwrap_or_default();
+                    Ok(DLCards::<C>::unmask(
+                        &self.parameters,
+                        &reveal_tokens,
+                        &deck[*i],
+                    )?)
+                })
+                .collect::<Result<Vec<_>, NetworkError>>()?;
+            opened_cards.push(cards);
+        }
+
+        Ok(HandTranscript {
+            shared_key,
+            deck,
+            own_cards,
+            opened_cards,
+        })
+    }
+
+    /// Indices of the dealt cards that belong to seats matching `predicate`. Cards are dealt one at
+    /// a time around the table.
+    fn dealt_indices<P: Fn(usize) -> bool>(&self, predicate: P) -> Vec<usize> {
+        let num_of_players = self.config.num_of_players;
+
+        (0..num_of_players * self.config.cards_per_player)
+            .filter(|i| predicate(i % num_of_players))
+            .collect()
+    }
+
+    async fn collect_tokens(
+        &mut self,
+        seat: usize,
+        pk: &PublicKey<C>,
+        expected: &Vec<usize>,
+        collected: &mut HashMap<usize, Vec<(RevealToken<C>, RevealProof<C>, PublicKey<C>)>>,
+    ) -> Result<(), NetworkError> {
+        match self.receive_from(seat).await? {
+            Message::RevealTokens { indices, tokens }
+                if indices == *expected && tokens.len() == indices.len() =>
+            {
+                for (i, (token, proof)) in indices.into_iter().zip(tokens.into_iter()) {
+                    collected
+                        .entry(i)
+                        .or_insert_with(Vec::new)
+                        .push((token, proof, *pk));
+                }
+                Ok(())
+            }
+            _ => Err(NetworkError::UnexpectedMessage(seat)),
+        }
+    }
+
+    /// Broadcast a message, retrying with exponential backoff if the transport fails. The relay
+    /// stamps the sender, so the seat set here is only informative.
+    async fn send(&mut self, message: &Message<C>) -> Result<(), NetworkError> {
+        let envelope = Envelope {
+            sender: self.seat,
+            sequence: self.sequence,
+            payload: message.to_bytes()?,
+        };
+        self.sequence += 1;
+
+        let mut delay = INITIAL_RETRY_DELAY;
+        let mut attempt = 1;
+        loop {
+            match self.transport.broadcast(&envelope).await {
+                Ok(()) => return Ok(()),
+                Err(err) if attempt == MAX_SEND_ATTEMPTS => return Err(err),
+                Err(_) => {
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Wait for the next message sent by `sender`, buffering messages from other seats
+    async fn receive_from(&mut self, sender: usize) -> Result<Message<C>, NetworkError> {
+        loop {
+            if let Some(payload) = self.inbox.pop(sender) {
+                return Message::from_bytes(&payload);
+            }
+
+            let envelope = tokio::time::timeout(self.config.timeout, self.transport.receive())
+                .await
+                .map_err(|_| NetworkError::Timeout(sender))??;
+
+            if envelope.sender < self.config.num_of_players && envelope.sender != self.seat {
+                self.inbox.push(envelope);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SEQUENCE_WINDOW;
+    use crate::network::{
+        run_tcp_relay, Envelope, Inbox, LoopbackRelay, PlayerNode, TableConfig, TcpTransport,
+        Transport,
+    };
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::HashSet;
+    use std::time::Duration;
+    use tokio::net::TcpListener;
+
+    // Choose elliptic curve setting
+    type Curve = starknet_curve::Projective;
+
+    fn config(num_of_players: usize) -> TableConfig {
+        TableConfig {
+            domain: b"test table".to_vec(),
+            m: 2,
+            n: 5,
+            num_of_players,
+            cards_per_player: 2,
+            timeout: Duration::from_secs(120),
+        }
+    }
+
+    async fn play<T: Transport + 'static>(transports: Vec<T>) {
+        let num_of_players = transports.len();
+
+        let mut handles = Vec::with_capacity(num_of_players);
+        for transport in transports.into_iter() {
+            handles.push(tokio::spawn(async move {
+                let mut rng = StdRng::from_entropy();
+                let name = format!("Player {}", transport.seat()).into_bytes();
+                let mut node =
+                    PlayerNode::<Curve, T>::new(&mut rng, &name, config(num_of_players), transport)
+                        .unwrap();
+                (node.seat(), node.play_hand(&mut rng).await.unwrap())
+            }));
+        }
+
+        let mut transcripts = Vec::with_capacity(num_of_players);
+        for handle in handles {
+            transcripts.push(handle.await.unwrap());
+        }
+        transcripts.sort_by_key(|(seat, _)| *seat);
+        let transcripts = transcripts
+            .into_iter()
+            .map(|(_, transcript)| transcript)
+            .collect::<Vec<_>>();
+
+        // Everyone agrees on the final deck and on the cards of every seat
+        for (seat, transcript) in transcripts.iter().enumerate() {
+            assert_eq!(transcript.shared_key, transcripts[0].shared_key);
+            assert_eq!(transcript.deck, transcripts[0].deck);
+            assert_eq!(transcript.opened_cards, transcripts[0].opened_cards);
+            assert_eq!(transcript.own_cards, transcripts[0].opened_cards[seat]);
+        }
+
+        let dealt = transcripts[0]
+            .opened_cards
+            .iter()
+            .flatten()
+            .collect::<HashSet<_>>();
+        assert_eq!(dealt.len(), num_of_players * 2);
+    }
+
+    #[test]
+    fn test_inbox_ordering() {
+        let mut inbox = Inbox::new();
+        let envelope = |sequence: u64| Envelope {
+            sender: 1,
+            sequence,
+            payload: vec![sequence as u8],
+        };
+
+        assert!(inbox.push(envelope(1)));
+        assert_eq!(inbox.pop(1), None);
+
+        assert!(inbox.push(envelope(0)));
+        assert!(!inbox.push(envelope(0)));
+        assert_eq!(inbox.pop(1), Some(vec![0]));
+        assert_eq!(inbox.pop(1), Some(vec![1]));
+
+        // Replayed envelopes are dropped
+        assert!(!inbox.push(envelope(1)));
+        assert_eq!(inbox.pop(1), None);
+
+        // So are envelopes too far ahead of the next expected one
+        assert!(!inbox.push(envelope(2 + SEQUENCE_WINDOW)));
+        assert!(inbox.push(envelope(1 + SEQUENCE_WINDOW)));
+    }
+
+    #[tokio::test]
+    async fn test_relay_stamps_sender() {
+        let relay = LoopbackRelay::new();
+        let honest = relay.connect();
+        let mut receiver = relay.connect();
+
+        // A player claiming another seat is delivered under their own
+        let envelope = Envelope {
+            sender: receiver.seat(),
+            sequence: 0,
+            payload: vec![0],
+        };
+        honest.broadcast(&envelope).await.unwrap();
+
+        let received = receiver.receive().await.unwrap();
+        assert_eq!(received.sender, honest.seat());
+        assert_eq!(received.payload, envelope.payload);
+    }
+
+    #[tokio::test]
+    async fn test_loopback_hand() {
+        let relay = LoopbackRelay::new();
+        let transports = (0..3).map(|_| relay.connect()).collect::<Vec<_>>();
+
+        play(transports).await;
+    }
+
+    #[tokio::test]
+    async fn test_tcp_hand() {
+        let num_of_players = 2;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(run_tcp_relay(listener, num_of_players));
+
+        let mut transports = Vec::with_capacity(num_of_players);
+        for _ in 0..num_of_players {
+            transports.push(TcpTransport::connect(addr).await.unwrap());
+        }
+
+        play(transports).await;
+    }
+}
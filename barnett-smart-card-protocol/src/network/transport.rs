@@ -0,0 +1,205 @@
+use super::{Envelope, NetworkError};
+
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::{mpsc, Mutex};
+
+// Frames larger than this are rejected rather than allocated
+const MAX_FRAME_SIZE: usize = 1 << 26;
+
+/// A channel connecting a player to the rest of the table. Every envelope sent by a player must be
+/// delivered to every other player; delivery order and uniqueness are restored by the receiver.
+/// The relay assigns every player a seat and stamps it as the sender of the envelopes the player
+/// broadcasts, so that no player can send messages on behalf of another.
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// The seat assigned to this player by the relay
+    fn seat(&self) -> usize;
+
+    async fn broadcast(&self, envelope: &Envelope) -> Result<(), NetworkError>;
+
+    async fn receive(&mut self) -> Result<Envelope, NetworkError>;
+}
+
+/// An in-memory relay connecting players running in the same process.
+#[derive(Clone, Default)]
+pub struct LoopbackRelay {
+    peers: Arc<std::sync::Mutex<Vec<mpsc::UnboundedSender<Envelope>>>>,
+}
+
+impl LoopbackRelay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Connect a new player to the relay
+    pub fn connect(&self) -> LoopbackTransport {
+        let (sender, inbox) = mpsc::unbounded_channel();
+
+        let mut peers = self.peers.lock().unwrap();
+        let id = peers.len();
+        peers.push(sender);
+
+        LoopbackTransport {
+            id,
+            peers: self.peers.clone(),
+            inbox,
+        }
+    }
+}
+
+pub struct LoopbackTransport {
+    id: usize,
+    peers: Arc<std::sync::Mutex<Vec<mpsc::UnboundedSender<Envelope>>>>,
+    inbox: mpsc::UnboundedReceiver<Envelope>,
+}
+
+#[async_trait]
+impl Transport for LoopbackTransport {
+    fn seat(&self) -> usize {
+        self.id
+    }
+
+    async fn broadcast(&self, envelope: &Envelope) -> Result<(), NetworkError> {
+        let envelope = Envelope {
+            sender: self.id,
+            ..envelope.clone()
+        };
+
+        let peers = self.peers.lock().unwrap();
+        for (_, peer) in peers.iter().enumerate().filter(|(i, _)| *i != self.id) {
+            // A player that already left the table no longer needs the message
+            let _ = peer.send(envelope.clone());
+        }
+
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> Result<Envelope, NetworkError> {
+        self.inbox
+            .recv()
+            .await
+            .ok_or_else(|| NetworkError::IoError(String::from("relay closed")))
+    }
+}
+
+/// A connection to a TCP relay started with `run_tcp_relay`. Envelopes are sent as length-prefixed
+/// frames.
+pub struct TcpTransport {
+    seat: usize,
+    reader: OwnedReadHalf,
+    writer: Mutex<OwnedWriteHalf>,
+}
+
+impl TcpTransport {
+    pub async fn connect<A: ToSocketAddrs>(addr: A) -> Result<Self, NetworkError> {
+        let stream = TcpStream::connect(addr).await?;
+        stream.set_nodelay(true)?;
+        let (mut reader, writer) = stream.into_split();
+
+        // The relay starts by announcing the seat it assigned to the connection
+        let seat = reader.read_u64().await? as usize;
+
+        Ok(Self {
+            seat,
+            reader,
+            writer: Mutex::new(writer),
+        })
+    }
+}
+
+#[async_trait]
+impl Transport for TcpTransport {
+    fn seat(&self) -> usize {
+        self.seat
+    }
+
+    async fn broadcast(&self, envelope: &Envelope) -> Result<(), NetworkError> {
+        let frame = envelope.to_bytes()?;
+        let mut writer = self.writer.lock().await;
+        write_frame(&mut *writer, &frame).await
+    }
+
+    async fn receive(&mut self) -> Result<Envelope, NetworkError> {
+        let frame = read_frame(&mut self.reader).await?;
+        Envelope::from_bytes(&frame)
+    }
+}
+
+/// Accept `num_of_players` connections on `listener` and forward every envelope received from a
+/// player to all other players, until every player has disconnected. Players are seated in the order
+/// in which they connect, and every envelope is stamped with the seat of the connection it came
+/// from. Frames that do not decode to an envelope are dropped.
+pub async fn run_tcp_relay(
+    listener: TcpListener,
+    num_of_players: usize,
+) -> Result<(), NetworkError> {
+    let mut readers = Vec::with_capacity(num_of_players);
+    let mut writers = Vec::with_capacity(num_of_players);
+    for seat in 0..num_of_players {
+        let (stream, _) = listener.accept().await?;
+        stream.set_nodelay(true)?;
+        let (reader, mut writer) = stream.into_split();
+        writer.write_u64(seat as u64).await?;
+        readers.push(reader);
+        writers.push(Mutex::new(writer));
+    }
+    let writers = Arc::new(writers);
+
+    let mut tasks = Vec::with_capacity(num_of_players);
+    for (id, mut reader) in readers.into_iter().enumerate() {
+        let writers = writers.clone();
+        tasks.push(tokio::spawn(async move {
+            while let Ok(frame) = read_frame(&mut reader).await {
+                let frame = match Envelope::from_bytes(&frame).and_then(|envelope| {
+                    Envelope {
+                        sender: id,
+                        ..envelope
+                    }
+                    .to_bytes()
+                }) {
+                    Ok(frame) => frame,
+                    Err(_) => continue,
+                };
+
+                for (_, writer) in writers.iter().enumerate().filter(|(i, _)| *i != id) {
+                    let mut writer = writer.lock().await;
+                    // A player that already left the table no longer needs the frame
+                    let _ = write_frame(&mut *writer, &frame).await;
+                }
+            }
+        }));
+    }
+
+    for task in tasks {
+        task.await
+            .map_err(|e| NetworkError::IoError(e.to_string()))?;
+    }
+
+    Ok(())
+}
+
+async fn write_frame(writer: &mut OwnedWriteHalf, frame: &[u8]) -> Result<(), NetworkError> {
+    writer.write_u32(frame.len() as u32).await?;
+    writer.write_all(frame).await?;
+    writer.flush().await?;
+
+    Ok(())
+}
+
+async fn read_frame(reader: &mut OwnedReadHalf) -> Result<Vec<u8>, NetworkError> {
+    let len = reader.read_u32().await? as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(NetworkError::MalformedMessage(String::from(
+            "frame too large",
+        )));
+    }
+
+    let mut frame = vec![0u8; len];
+    reader.read_exact(&mut frame).await?;
+
+    Ok(frame)
+}
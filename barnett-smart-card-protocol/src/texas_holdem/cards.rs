@@ -0,0 +1,128 @@
+use ark_ec::ProjectiveCurve;
+use ark_ff::UniformRand;
+use ark_std::rand::Rng;
+use std::collections::HashMap;
+
+use crate::discrete_log_cards::predicates::PlayingCard;
+use crate::discrete_log_cards::Card;
+
+#[derive(PartialEq, Clone, Copy, Eq, Hash, Debug)]
+pub enum Suite {
+    Club,
+    Diamond,
+    Heart,
+    Spade,
+}
+
+impl Suite {
+    pub const VALUES: [Self; 4] = [Self::Club, Self::Diamond, Self::Heart, Self::Spade];
+}
+
+#[derive(PartialEq, PartialOrd, Ord, Clone, Copy, Eq, Hash, Debug)]
+pub enum Value {
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+    Ace,
+}
+
+impl Value {
+    pub const VALUES: [Self; 13] = [
+        Self::Two,
+        Self::Three,
+        Self::Four,
+        Self::Five,
+        Self::Six,
+        Self::Seven,
+        Self::Eight,
+        Self::Nine,
+        Self::Ten,
+        Self::Jack,
+        Self::Queen,
+        Self::King,
+        Self::Ace,
+    ];
+}
+
+#[derive(PartialEq, Clone, Eq, Copy, Hash)]
+pub struct ClassicPlayingCard {
+    pub value: Value,
+    pub suite: Suite,
+}
+
+impl ClassicPlayingCard {
+    pub fn new(value: Value, suite: Suite) -> Self {
+        Self { value, suite }
+    }
+}
+
+impl PlayingCard for ClassicPlayingCard {
+    fn value(&self) -> u8 {
+        self.value as u8
+    }
+
+    fn suite(&self) -> u8 {
+        self.suite as u8
+    }
+}
+
+impl std::fmt::Debug for ClassicPlayingCard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let suite = match self.suite {
+            Suite::Club => "♣",
+            Suite::Diamond => "♦",
+            Suite::Heart => "♥",
+            Suite::Spade => "♠",
+        };
+
+        let val = match self.value {
+            Value::Two => "2",
+            Value::Three => "3",
+            Value::Four => "4",
+            Value::Five => "5",
+            Value::Six => "6",
+            Value::Seven => "7",
+            Value::Eight => "8",
+            Value::Nine => "9",
+            Value::Ten => "10",
+            Value::Jack => "J",
+            Value::Queen => "Q",
+            Value::King => "K",
+            Value::Ace => "A",
+        };
+
+        write!(f, "{}{}", val, suite)
+    }
+}
+
+/// Number of cards in a classic deck
+pub const DECK_SIZE: usize = 52;
+
+/// Sample a random plaintext for each of the 52 classic playing cards. All players must agree on
+/// this public mapping before the deck is masked.
+pub fn encode_cards<C: ProjectiveCurve, R: Rng>(
+    rng: &mut R,
+) -> HashMap<Card<C>, ClassicPlayingCard> {
+    let mut map: HashMap<Card<C>, ClassicPlayingCard> = HashMap::with_capacity(DECK_SIZE);
+
+    for value in Value::VALUES.iter().copied() {
+        for suite in Suite::VALUES.iter().copied() {
+            let mut plaintext = Card::<C>::rand(rng);
+            while map.contains_key(&plaintext) {
+                plaintext = Card::<C>::rand(rng);
+            }
+            map.insert(plaintext, ClassicPlayingCard::new(value, suite));
+        }
+    }
+
+    map
+}
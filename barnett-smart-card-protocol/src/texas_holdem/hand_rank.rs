@@ -0,0 +1,212 @@
+use super::cards::{ClassicPlayingCard, Value};
+
+/// Poker hand categories, ordered from weakest to strongest.
+#[derive(PartialEq, PartialOrd, Ord, Clone, Copy, Eq, Debug)]
+pub enum HandCategory {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+}
+
+/// Strength of a five card poker hand. Ranks compare first by category, then by the tie-breaking
+/// values in decreasing order of significance, so the derived ordering is the poker ordering.
+#[derive(PartialEq, PartialOrd, Ord, Clone, Eq, Debug)]
+pub struct HandRank {
+    pub category: HandCategory,
+    pub tie_breakers: Vec<Value>,
+}
+
+/// Rank exactly five cards.
+pub fn rank_five(cards: &[ClassicPlayingCard; 5]) -> HandRank {
+    let mut values = cards.iter().map(|c| c.value).collect::<Vec<_>>();
+    values.sort_unstable_by(|a, b| b.cmp(a));
+
+    let is_flush = cards.iter().all(|c| c.suite == cards[0].suite);
+    let straight_high = straight_high_card(&values);
+
+    // Group equal values, largest groups first and higher values first within equal sizes
+    let mut groups: Vec<(usize, Value)> = Vec::with_capacity(5);
+    for &value in values.iter() {
+        match groups.iter_mut().find(|(_, v)| *v == value) {
+            Some(group) => group.0 += 1,
+            None => groups.push((1, value)),
+        }
+    }
+    groups.sort_unstable_by(|a, b| b.cmp(a));
+
+    let grouped_values = groups.iter().map(|(_, v)| *v).collect::<Vec<_>>();
+
+    let (category, tie_breakers) = match (straight_high, is_flush, groups[0].0, groups[1].0) {
+        (Some(high), true, _, _) => (HandCategory::StraightFlush, vec![high]),
+        (_, _, 4, _) => (HandCategory::FourOfAKind, grouped_values),
+        (_, _, 3, 2) => (HandCategory::FullHouse, grouped_values),
+        (_, true, _, _) => (HandCategory::Flush, values),
+        (Some(high), false, _, _) => (HandCategory::Straight, vec![high]),
+        (_, _, 3, _) => (HandCategory::ThreeOfAKind, grouped_values),
+        (_, _, 2, 2) => (HandCategory::TwoPair, grouped_values),
+        (_, _, 2, _) => (HandCategory::OnePair, grouped_values),
+        _ => (HandCategory::HighCard, values),
+    };
+
+    HandRank {
+        category,
+        tie_breakers,
+    }
+}
+
+/// Rank the best five card hand that can be formed from five to seven cards.
+pub fn best_hand(cards: &[ClassicPlayingCard]) -> Option<HandRank> {
+    if cards.len() < 5 || cards.len() > 7 {
+        return None;
+    }
+
+    let k = cards.len();
+    let mut best: Option<HandRank> = None;
+
+    // Each five card hand is obtained by leaving out up to two cards
+    for skip_a in 0..k {
+        for skip_b in skip_a..k {
+            if (k == 5 && (skip_a, skip_b) != (0, 0))
+                || (k == 6 && skip_a != skip_b)
+                || (k == 7 && skip_a == skip_b)
+            {
+                continue;
+            }
+
+            let hand = cards
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| k == 5 || (*i != skip_a && *i != skip_b))
+                .map(|(_, c)| *c)
+                .collect::<Vec<_>>();
+
+            let rank = rank_five(&[hand[0], hand[1], hand[2], hand[3], hand[4]]);
+            if best.as_ref().map_or(true, |b| rank > *b) {
+                best = Some(rank);
+            }
+        }
+    }
+
+    best
+}
+
+/// Return the highest card of a straight if the (sorted, decreasing) values form one.
+fn straight_high_card(values: &[Value]) -> Option<Value> {
+    let ranks = values.iter().map(|v| *v as usize).collect::<Vec<_>>();
+
+    if ranks.windows(2).all(|w| w[0] == w[1] + 1) {
+        return Some(values[0]);
+    }
+
+    // The wheel: A-2-3-4-5, where the ace plays low
+    if ranks
+        == vec![
+            Value::Ace as usize,
+            Value::Five as usize,
+            Value::Four as usize,
+            Value::Three as usize,
+            Value::Two as usize,
+        ]
+    {
+        return Some(Value::Five);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::{best_hand, rank_five, HandCategory};
+    use crate::texas_holdem::cards::{ClassicPlayingCard, Suite, Value};
+
+    fn card(value: Value, suite: Suite) -> ClassicPlayingCard {
+        ClassicPlayingCard::new(value, suite)
+    }
+
+    #[test]
+    fn test_rank_categories() {
+        let royal = [
+            card(Value::Ace, Suite::Spade),
+            card(Value::King, Suite::Spade),
+            card(Value::Queen, Suite::Spade),
+            card(Value::Jack, Suite::Spade),
+            card(Value::Ten, Suite::Spade),
+        ];
+        assert_eq!(rank_five(&royal).category, HandCategory::StraightFlush);
+
+        let wheel = [
+            card(Value::Ace, Suite::Heart),
+            card(Value::Two, Suite::Spade),
+            card(Value::Three, Suite::Club),
+            card(Value::Four, Suite::Spade),
+            card(Value::Five, Suite::Diamond),
+        ];
+        let wheel_rank = rank_five(&wheel);
+        assert_eq!(wheel_rank.category, HandCategory::Straight);
+        assert_eq!(wheel_rank.tie_breakers, vec![Value::Five]);
+
+        let full_house = [
+            card(Value::Three, Suite::Heart),
+            card(Value::Three, Suite::Spade),
+            card(Value::Three, Suite::Club),
+            card(Value::Nine, Suite::Spade),
+            card(Value::Nine, Suite::Diamond),
+        ];
+        assert_eq!(rank_five(&full_house).category, HandCategory::FullHouse);
+
+        let two_pair = [
+            card(Value::King, Suite::Heart),
+            card(Value::King, Suite::Spade),
+            card(Value::Two, Suite::Club),
+            card(Value::Two, Suite::Spade),
+            card(Value::Nine, Suite::Diamond),
+        ];
+        let two_pair_rank = rank_five(&two_pair);
+        assert_eq!(two_pair_rank.category, HandCategory::TwoPair);
+        assert_eq!(
+            two_pair_rank.tie_breakers,
+            vec![Value::King, Value::Two, Value::Nine]
+        );
+
+        assert!(rank_five(&full_house) > rank_five(&wheel));
+        assert!(rank_five(&wheel) > two_pair_rank);
+    }
+
+    #[test]
+    fn test_best_of_seven() {
+        let board = [
+            card(Value::Ace, Suite::Heart),
+            card(Value::Seven, Suite::Heart),
+            card(Value::Seven, Suite::Club),
+            card(Value::Two, Suite::Heart),
+            card(Value::Jack, Suite::Spade),
+        ];
+
+        let mut flush = board.to_vec();
+        flush.extend_from_slice(&[
+            card(Value::Four, Suite::Heart),
+            card(Value::Six, Suite::Heart),
+        ]);
+
+        let mut trips = board.to_vec();
+        trips.extend_from_slice(&[
+            card(Value::Seven, Suite::Spade),
+            card(Value::King, Suite::Diamond),
+        ]);
+
+        let flush_rank = best_hand(&flush).unwrap();
+        let trips_rank = best_hand(&trips).unwrap();
+
+        assert_eq!(flush_rank.category, HandCategory::Flush);
+        assert_eq!(trips_rank.category, HandCategory::ThreeOfAKind);
+        assert!(flush_rank > trips_rank);
+
+        assert_eq!(best_hand(&board[..4]), None);
+    }
+}
@@ -0,0 +1,49 @@
+//! A reference Texas Hold'em engine built on top of the discrete log card protocol. Every hidden
+//! card operation (masking, shuffling, dealing, peeking and opening) goes through
+//! `BarnettSmartProtocol`, with all proofs verified as they are produced. All seats are simulated
+//! in a single process, which makes the engine usable both as a reference implementation for
+//! networked games and as a regression test for multi-round card usage.
+
+use crate::error::CardProtocolError;
+
+use proof_essentials::error::CryptoError;
+use thiserror::Error;
+
+pub mod cards;
+pub mod hand_rank;
+pub mod table;
+
+pub use table::{Action, Stage, Table};
+
+/// Maximum number of seats at a table
+pub const MAX_SEATS: usize = 10;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum GameError {
+    #[error("A table needs between 2 and {} players", MAX_SEATS)]
+    InvalidNumberOfPlayers,
+
+    #[error("Not enough chips")]
+    InsufficientChips,
+
+    #[error("It is not this seat's turn to act")]
+    OutOfTurn,
+
+    #[error("Illegal action")]
+    IllegalAction,
+
+    #[error("The current betting round is not over")]
+    BettingRoundOpen,
+
+    #[error("No hand in progress")]
+    NoHandInProgress,
+
+    #[error("Invalid card")]
+    InvalidCard,
+
+    #[error("Card protocol error: {0}")]
+    CardProtocolError(#[from] CardProtocolError),
+
+    #[error("Failed to verify proof")]
+    ProofVerificationError(#[from] CryptoError),
+}
@@ -0,0 +1,817 @@
+use super::cards::{encode_cards, ClassicPlayingCard, DECK_SIZE};
+use super::hand_rank::{best_hand, HandRank};
+use super::{GameError, MAX_SEATS};
+use crate::discrete_log_cards::{
+    Card, DLCards, DLEqualityProof, DeckState, MaskedCard, OpenedCard, Parameters, PlayerSecretKey,
+    PrecomputedKey, PublicKey, RevealToken, RevealTokenStore, SchnorrProof,
+};
+use crate::BarnettSmartProtocol;
+
+use ark_ec::ProjectiveCurve;
+use ark_ff::One;
+use ark_std::rand::Rng;
+use proof_essentials::utils::permutation::Permutation;
+use proof_essentials::utils::rand::sample_vector;
+use std::collections::HashMap;
+
+type ProofKeyOwnership<C> = SchnorrProof<C>;
+type RevealProof<C> = DLEqualityProof<C>;
+
+// A 52 card deck is arranged as a 4 x 13 matrix for the shuffle argument
+const M: usize = 4;
+const N: usize = 13;
+
+/// Stages of a hand. Betting happens in every stage but `Complete`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    PreFlop,
+    Flop,
+    Turn,
+    River,
+    Complete,
+}
+
+/// Betting actions. `Raise` carries the total amount the seat commits in the current betting round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Fold,
+    Check,
+    Call,
+    Raise(u64),
+}
+
+pub struct Seat<C: ProjectiveCurve> {
+    name: Vec<u8>,
+    pk: PublicKey<C>,
+    sk: PlayerSecretKey<C>,
+    key_proof: ProofKeyOwnership<C>,
+    chips: u64,
+    hole_cards: Vec<MaskedCard<C>>,
+    // Only known to the player sitting at this seat
+    opened_hole_cards: Vec<ClassicPlayingCard>,
+    // Tokens of the current hand, reused when a hole card dealt privately is opened at showdown
+    token_store: RevealTokenStore<C>,
+    // Seats that cannot cover the big blind sit the hand out, as if they had folded
+    sitting_out: bool,
+    folded: bool,
+    committed: u64,
+    acted: bool,
+}
+
+impl<C: ProjectiveCurve> Seat<C> {
+    pub fn name(&self) -> &Vec<u8> {
+        &self.name
+    }
+
+    pub fn public_key(&self) -> &PublicKey<C> {
+        &self.pk
+    }
+
+    pub fn key_proof(&self) -> &ProofKeyOwnership<C> {
+        &self.key_proof
+    }
+
+    pub fn chips(&self) -> u64 {
+        self.chips
+    }
+
+    pub fn hole_cards(&self) -> &Vec<MaskedCard<C>> {
+        &self.hole_cards
+    }
+
+    pub fn has_folded(&self) -> bool {
+        self.folded
+    }
+
+    pub fn is_sitting_out(&self) -> bool {
+        self.sitting_out
+    }
+
+    fn compute_reveal_token<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        pp: &Parameters<C>,
+        session: &[u8],
+        card: &MaskedCard<C>,
+    ) -> Result<(RevealToken<C>, RevealProof<C>, PublicKey<C>), GameError> {
+        let (reveal_token, reveal_proof) = DLCards::<C>::compute_reveal_token_cached(
+            rng,
+            pp,
+            &self.sk,
+            &self.pk,
+            &mut self.token_store,
+            session,
+            card,
+        )?;
+
+        Ok((reveal_token, reveal_proof, self.pk))
+    }
+
+    fn pay(&mut self, amount: u64) -> Result<(), GameError> {
+        if amount > self.chips {
+            return Err(GameError::InsufficientChips);
+        }
+
+        self.chips -= amount;
+        self.committed += amount;
+        Ok(())
+    }
+}
+
+/// A Texas Hold'em table. Side pots are not supported: every seat must be able to cover the bets
+/// it calls. A seat with fewer chips than the big blind sits out the next hands, but keeps
+/// shuffling and providing reveal tokens since its key is part of the aggregate key.
+pub struct Table<C: ProjectiveCurve> {
+    parameters: Parameters<C>,
+    card_mapping: HashMap<Card<C>, ClassicPlayingCard>,
+    encoded_deck: Vec<Card<C>>,
+    shared_key: PublicKey<C>,
+    precomputed_key: PrecomputedKey<C>,
+    seats: Vec<Seat<C>>,
+    small_blind: u64,
+    big_blind: u64,
+    dealer: usize,
+    hands_played: u64,
+
+    // State of the current hand
+    stage: Stage,
+    deck_state: DeckState<C>,
+    board: Vec<ClassicPlayingCard>,
+    board_openings: Vec<OpenedCard<C>>,
+    pot: u64,
+    current_bet: u64,
+    to_act: usize,
+    winnings: Vec<(usize, u64)>,
+}
+
+impl<C: ProjectiveCurve> Table<C> {
+    /// Seat the players, identified by their name and chip count, and compute the aggregate key
+    /// from their verified keys.
+    pub fn new<R: Rng>(
+        rng: &mut R,
+        players: &Vec<(Vec<u8>, u64)>,
+        small_blind: u64,
+        big_blind: u64,
+    ) -> Result<Self, GameError> {
+        if players.len() < 2 || players.len() > MAX_SEATS {
+            return Err(GameError::InvalidNumberOfPlayers);
+        }
+
+        let parameters = DLCards::<C>::setup(rng, M, N)?;
+        let card_mapping = encode_cards::<C, _>(rng);
+        let encoded_deck = card_mapping.keys().copied().collect::<Vec<_>>();
+
+        let mut seats = Vec::with_capacity(players.len());
+        for (name, chips) in players {
+            let (pk, sk) = DLCards::<C>::player_keygen(rng, &parameters)?;
+            let key_proof = DLCards::<C>::prove_key_ownership(rng, &parameters, &pk, &sk, name)?;
+            seats.push(Seat {
+                name: name.clone(),
+                pk,
+                sk,
+                key_proof,
+                chips: *chips,
+                hole_cards: vec![],
+                opened_hole_cards: vec![],
+                token_store: RevealTokenStore::new(&pk),
+                sitting_out: false,
+                folded: false,
+                committed: 0,
+                acted: false,
+            });
+        }
+
+        let key_proof_info = seats
+            .iter()
+            .map(|s| (s.pk, s.key_proof, s.name.clone()))
+            .collect::<Vec<_>>();
+        let shared_key = DLCards::<C>::compute_aggregate_key(&parameters, &key_proof_info)?;
+        let precomputed_key = PrecomputedKey::new(&shared_key, DECK_SIZE);
+
+        Ok(Self {
+            parameters,
+            card_mapping,
+            encoded_deck,
+            shared_key,
+            precomputed_key,
+            seats,
+            small_blind,
+            big_blind,
+            dealer: 0,
+            hands_played: 0,
+            stage: Stage::Complete,
+            deck_state: DeckState::new(&shared_key, &vec![]),
+            board: vec![],
+            board_openings: vec![],
+            pot: 0,
+            current_bet: 0,
+            to_act: 0,
+            winnings: vec![],
+        })
+    }
+
+    pub fn parameters(&self) -> &Parameters<C> {
+        &self.parameters
+    }
+
+    pub fn shared_key(&self) -> &PublicKey<C> {
+        &self.shared_key
+    }
+
+    pub fn seats(&self) -> &Vec<Seat<C>> {
+        &self.seats
+    }
+
+    pub fn dealer(&self) -> usize {
+        self.dealer
+    }
+
+    pub fn stage(&self) -> Stage {
+        self.stage
+    }
+
+    pub fn deck(&self) -> &Vec<MaskedCard<C>> {
+        self.deck_state.deck()
+    }
+
+    /// Cards dealt and reveal tokens collected in the current hand, which can be snapshotted to
+    /// resume the hand
+    pub fn deck_state(&self) -> &DeckState<C> {
+        &self.deck_state
+    }
+
+    pub fn board(&self) -> &Vec<ClassicPlayingCard> {
+        &self.board
+    }
+
+    /// Publicly checkable openings of the community cards
+    pub fn board_openings(&self) -> &Vec<OpenedCard<C>> {
+        &self.board_openings
+    }
+
+    pub fn pot(&self) -> u64 {
+        self.pot
+    }
+
+    pub fn to_act(&self) -> usize {
+        self.to_act
+    }
+
+    /// Amount the seat to act must add to stay in the hand
+    pub fn amount_to_call(&self) -> u64 {
+        self.current_bet - self.seats[self.to_act].committed
+    }
+
+    /// Payouts of the last completed hand as `(seat, amount)` pairs
+    pub fn winnings(&self) -> &Vec<(usize, u64)> {
+        &self.winnings
+    }
+
+    /// The hole cards of a seat, as privately opened by the player sitting there
+    pub fn private_hole_cards(&self, seat: usize) -> &Vec<ClassicPlayingCard> {
+        &self.seats[seat].opened_hole_cards
+    }
+
+    /// Mask and shuffle a fresh deck, post the blinds and deal two private hole cards to each seat.
+    /// Seats that cannot cover the big blind sit the hand out; at least two seats must be able to.
+    pub fn start_hand<R: Rng>(&mut self, rng: &mut R) -> Result<(), GameError> {
+        let playing = self
+            .seats
+            .iter()
+            .filter(|s| s.chips >= self.big_blind)
+            .count();
+        if playing < 2 {
+            return Err(GameError::InsufficientChips);
+        }
+
+        let previous_session = self.session();
+        self.hands_played += 1;
+
+        let big_blind = self.big_blind;
+        for seat in self.seats.iter_mut() {
+            seat.token_store.clear_session(&previous_session);
+            seat.hole_cards = vec![];
+            seat.opened_hole_cards = vec![];
+            seat.sitting_out = seat.chips < big_blind;
+            seat.folded = seat.sitting_out;
+            seat.committed = 0;
+            seat.acted = false;
+        }
+        self.board = vec![];
+        self.board_openings = vec![];
+        self.pot = 0;
+        self.winnings = vec![];
+
+        self.deck_state = DeckState::new(&self.shared_key, &self.shuffled_deck(rng)?);
+
+        // The button skips the seats sitting out. Heads-up the dealer posts the small blind.
+        if self.seats[self.dealer].sitting_out {
+            self.dealer = self.next_active_seat(self.dealer);
+        }
+        let (sb, bb) = if playing == 2 {
+            (self.dealer, self.next_active_seat(self.dealer))
+        } else {
+            let sb = self.next_active_seat(self.dealer);
+            (sb, self.next_active_seat(sb))
+        };
+        self.seats[sb].pay(self.small_blind)?;
+        self.seats[bb].pay(self.big_blind)?;
+        self.pot = self.small_blind + self.big_blind;
+        self.current_bet = self.big_blind;
+
+        self.deal_hole_cards(rng)?;
+
+        self.stage = Stage::PreFlop;
+        self.to_act = self.next_active_seat(bb);
+
+        Ok(())
+    }
+
+    /// Apply the action of the seat whose turn it is.
+    pub fn act(&mut self, seat: usize, action: Action) -> Result<(), GameError> {
+        if self.stage == Stage::Complete {
+            return Err(GameError::NoHandInProgress);
+        }
+        if self.betting_round_complete() {
+            return Err(GameError::IllegalAction);
+        }
+        if seat != self.to_act {
+            return Err(GameError::OutOfTurn);
+        }
+
+        let to_call = self.current_bet - self.seats[seat].committed;
+        match action {
+            Action::Fold => self.seats[seat].folded = true,
+            Action::Check => {
+                if to_call != 0 {
+                    return Err(GameError::IllegalAction);
+                }
+            }
+            Action::Call => {
+                self.seats[seat].pay(to_call)?;
+                self.pot += to_call;
+            }
+            Action::Raise(total) => {
+                if total < self.current_bet + self.big_blind {
+                    return Err(GameError::IllegalAction);
+                }
+                let amount = total - self.seats[seat].committed;
+                self.seats[seat].pay(amount)?;
+                self.pot += amount;
+                self.current_bet = total;
+
+                // Everyone else has to respond to the raise
+                for other in self.seats.iter_mut() {
+                    other.acted = false;
+                }
+            }
+        }
+        self.seats[seat].acted = true;
+
+        let remaining = self.active_seats();
+        if remaining.len() == 1 {
+            self.award(&remaining);
+            return Ok(());
+        }
+
+        if !self.betting_round_complete() {
+            self.to_act = self.next_active_seat(seat);
+        }
+
+        Ok(())
+    }
+
+    /// A betting round is complete once every seat still in the hand has acted and matched the
+    /// current bet.
+    pub fn betting_round_complete(&self) -> bool {
+        self.seats
+            .iter()
+            .filter(|s| !s.folded)
+            .all(|s| s.acted && s.committed == self.current_bet)
+    }
+
+    /// Close the current betting round and move the hand forward: reveal the flop, the turn or the
+    /// river to everyone, or go to showdown after the river.
+    pub fn next_stage<R: Rng>(&mut self, rng: &mut R) -> Result<Stage, GameError> {
+        if self.stage == Stage::Complete {
+            return Err(GameError::NoHandInProgress);
+        }
+        if !self.betting_round_complete() {
+            return Err(GameError::BettingRoundOpen);
+        }
+
+        for seat in self.seats.iter_mut() {
+            seat.committed = 0;
+            seat.acted = false;
+        }
+        self.current_bet = 0;
+
+        let (next, num_of_cards) = match self.stage {
+            Stage::PreFlop => (Stage::Flop, 3),
+            Stage::Flop => (Stage::Turn, 1),
+            Stage::Turn => (Stage::River, 1),
+            _ => {
+                self.showdown(rng)?;
+                return Ok(self.stage);
+            }
+        };
+
+        // Burn one card before revealing the community cards
+        self.draw(None)?;
+        let indices = (0..num_of_cards)
+            .map(|_| Ok(self.draw(None)?.0))
+            .collect::<Result<Vec<_>, GameError>>()?;
+
+        let seat_tokens = self
+            .seats
+            .iter()
+            .map(|seat| {
+                let tokens = DLCards::<C>::compute_public_reveal_tokens(
+                    rng,
+                    &self.parameters,
+                    &seat.sk,
+                    &seat.pk,
+                    self.deck_state.deck(),
+                    &indices,
+                )?;
+                Ok((seat.pk, tokens))
+            })
+            .collect::<Result<Vec<_>, GameError>>()?;
+
+        let player_keys = self.seats.iter().map(|seat| seat.pk).collect::<Vec<_>>();
+        let openings = DLCards::<C>::reveal_public(
+            &self.parameters,
+            &player_keys,
+            self.deck_state.deck(),
+            &indices,
+            &seat_tokens,
+        )?;
+        for opening in openings {
+            let card = self.card_mapping.get(&opening.card);
+            self.board.push(*card.ok_or(GameError::InvalidCard)?);
+            self.board_openings.push(opening);
+        }
+
+        self.stage = next;
+        self.to_act = self.next_active_seat(self.dealer);
+
+        Ok(self.stage)
+    }
+
+    /// Every seat still in the hand opens its hole cards to the table. The best hands split the pot.
+    fn showdown<R: Rng>(&mut self, rng: &mut R) -> Result<(), GameError> {
+        let contenders = self.active_seats();
+
+        let mut ranks: Vec<(usize, HandRank)> = Vec::with_capacity(contenders.len());
+        for &i in contenders.iter() {
+            let mut cards = self.board.clone();
+            for masked in self.seats[i].hole_cards.clone() {
+                let reveal_tokens = self.reveal_tokens(rng, &masked, None)?;
+                cards.push(self.open_card(&reveal_tokens, &masked)?);
+            }
+
+            let rank = best_hand(&cards).ok_or(GameError::InvalidCard)?;
+            ranks.push((i, rank));
+        }
+
+        let best = ranks
+            .iter()
+            .map(|(_, rank)| rank.clone())
+            .max()
+            .ok_or(GameError::NoHandInProgress)?;
+        let winners = ranks
+            .iter()
+            .filter(|(_, rank)| *rank == best)
+            .map(|(i, _)| *i)
+            .collect::<Vec<_>>();
+
+        self.award(&winners);
+        Ok(())
+    }
+
+    /// Split the pot between the winners, giving odd chips to the first winners left of the dealer,
+    /// and move the button.
+    fn award(&mut self, winners: &Vec<usize>) {
+        let mut ordered = winners.clone();
+        ordered.sort_by_key(|&i| (i + self.seats.len() - self.dealer - 1) % self.seats.len());
+
+        let share = self.pot / ordered.len() as u64;
+        let mut remainder = self.pot % ordered.len() as u64;
+
+        self.winnings = ordered
+            .iter()
+            .map(|&i| {
+                let mut amount = share;
+                if remainder > 0 {
+                    amount += 1;
+                    remainder -= 1;
+                }
+                self.seats[i].chips += amount;
+                (i, amount)
+            })
+            .collect();
+
+        self.pot = 0;
+        self.stage = Stage::Complete;
+        self.dealer = self.next_seat(self.dealer);
+    }
+
+    /// Mask the encoded deck publicly, then let every seat shuffle and remask it in turn. Each
+    /// shuffle is verified before the next seat shuffles.
+    fn shuffled_deck<R: Rng>(&self, rng: &mut R) -> Result<Vec<MaskedCard<C>>, GameError> {
+        let one = C::ScalarField::one();
+
+        let mut deck = Vec::with_capacity(DECK_SIZE);
+        for card in self.encoded_deck.iter() {
+            let (masked, proof) = DLCards::<C>::mask_precomputed(
+                rng,
+                &self.parameters,
+                &self.precomputed_key,
+                card,
+                &one,
+            )?;
+            DLCards::<C>::verify_mask(&self.parameters, &self.shared_key, card, &masked, &proof)?;
+            deck.push(masked);
+        }
+
+        for _ in self.seats.iter() {
+            let permutation = Permutation::new(rng, DECK_SIZE);
+            let masking_factors: Vec<C::ScalarField> = sample_vector(rng, DECK_SIZE);
+
+            let (shuffled, proof) = DLCards::<C>::shuffle_and_remask_precomputed(
+                rng,
+                &self.parameters,
+                &self.precomputed_key,
+                &deck,
+                &masking_factors,
+                &permutation,
+            )?;
+
+            DLCards::<C>::verify_shuffle(
+                &self.parameters,
+                &self.shared_key,
+                &deck,
+                &shuffled,
+                &proof,
+            )?;
+
+            deck = shuffled;
+        }
+
+        Ok(deck)
+    }
+
+    /// Deal two hole cards to every seat in the hand, one at a time starting left of the dealer.
+    /// Other seats provide their reveal tokens so that the receiving seat can privately open its
+    /// card.
+    fn deal_hole_cards<R: Rng>(&mut self, rng: &mut R) -> Result<(), GameError> {
+        for _ in 0..2 {
+            let mut seat = self.dealer;
+            for _ in 0..self.seats.len() {
+                seat = self.next_seat(seat);
+                if self.seats[seat].sitting_out {
+                    continue;
+                }
+
+                let recipient = self.seats[seat].pk;
+                let (position, masked) = self.draw(Some(&recipient))?;
+                let reveal_tokens = self.reveal_tokens(rng, &masked, Some(seat))?;
+                for token in reveal_tokens.iter() {
+                    self.deck_state
+                        .add_reveal_token(&self.parameters, position, *token)?;
+                }
+
+                // The receiving seat opens the card with its own key, without issuing a token
+                let unmasked = DLCards::<C>::unmask_with_own_key(
+                    &self.parameters,
+                    &self.seats[seat].sk,
+                    &reveal_tokens,
+                    &masked,
+                )?;
+                let card = *self
+                    .card_mapping
+                    .get(&unmasked)
+                    .ok_or(GameError::InvalidCard)?;
+                self.seats[seat].hole_cards.push(masked);
+                self.seats[seat].opened_hole_cards.push(card);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Deal the next card of the deck, to a seat or to the board
+    fn draw(
+        &mut self,
+        recipient: Option<&PublicKey<C>>,
+    ) -> Result<(usize, MaskedCard<C>), GameError> {
+        Ok(self.deck_state.deal(recipient)?)
+    }
+
+    /// Collect the reveal tokens of every seat (optionally except one) for a masked card
+    fn reveal_tokens<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        masked: &MaskedCard<C>,
+        except: Option<usize>,
+    ) -> Result<Vec<(RevealToken<C>, RevealProof<C>, PublicKey<C>)>, GameError> {
+        let session = self.session();
+        let parameters = &self.parameters;
+        self.seats
+            .iter_mut()
+            .enumerate()
+            .filter(|(i, _)| Some(*i) != except)
+            .map(|(_, seat)| seat.compute_reveal_token(rng, parameters, &session, masked))
+            .collect()
+    }
+
+    /// Reveal tokens are cached per hand
+    fn session(&self) -> Vec<u8> {
+        self.hands_played.to_le_bytes().to_vec()
+    }
+
+    fn open_card(
+        &self,
+        reveal_tokens: &Vec<(RevealToken<C>, RevealProof<C>, PublicKey<C>)>,
+        masked: &MaskedCard<C>,
+    ) -> Result<ClassicPlayingCard, GameError> {
+        let unmasked = DLCards::<C>::unmask(&self.parameters, reveal_tokens, masked)?;
+        let opened = self.card_mapping.get(&unmasked);
+
+        opened.copied().ok_or(GameError::InvalidCard)
+    }
+
+    fn active_seats(&self) -> Vec<usize> {
+        (0..self.seats.len())
+            .filter(|&i| !self.seats[i].folded)
+            .collect()
+    }
+
+    fn next_seat(&self, seat: usize) -> usize {
+        (seat + 1) % self.seats.len()
+    }
+
+    fn next_active_seat(&self, seat: usize) -> usize {
+        let mut next = self.next_seat(seat);
+        while self.seats[next].folded {
+            next = self.next_seat(next);
+        }
+        next
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Action, Stage, Table};
+    use crate::texas_holdem::GameError;
+
+    use rand::thread_rng;
+    use std::collections::HashSet;
+
+    // Choose elliptic curve setting
+    type Curve = starknet_curve::Projective;
+
+    fn players(num_of_players: usize, chips: u64) -> Vec<(Vec<u8>, u64)> {
+        (0..num_of_players)
+            .map(|i| (format!("Player {}", i).into_bytes(), chips))
+            .collect()
+    }
+
+    #[test]
+    fn test_hand_to_showdown() {
+        let rng = &mut thread_rng();
+        let num_of_players = 3;
+
+        let mut table = Table::<Curve>::new(rng, &players(num_of_players, 100), 1, 2).unwrap();
+        table.start_hand(rng).unwrap();
+
+        assert_eq!(table.stage(), Stage::PreFlop);
+        assert_eq!(table.pot(), 3);
+
+        // Calling out of turn is rejected
+        let out_of_turn = (table.to_act() + 1) % num_of_players;
+        assert_eq!(
+            table.act(out_of_turn, Action::Call),
+            Err(GameError::OutOfTurn)
+        );
+
+        // Everyone limps, then checks down to showdown
+        let mut stage = table.stage();
+        while stage != Stage::Complete {
+            while !table.betting_round_complete() {
+                let action = if table.amount_to_call() > 0 {
+                    Action::Call
+                } else {
+                    Action::Check
+                };
+                table.act(table.to_act(), action).unwrap();
+            }
+            stage = table.next_stage(rng).unwrap();
+        }
+
+        assert_eq!(table.board().len(), 5);
+
+        // Hole cards, three burns and the board were dealt through the deck state, which holds the
+        // tokens of the other seats for every hole card
+        let deck_state = table.deck_state();
+        assert_eq!(deck_state.next_card(), 2 * num_of_players + 3 + 5);
+        for position in 0..2 * num_of_players {
+            assert_eq!(deck_state.reveal_tokens(position).len(), num_of_players - 1);
+        }
+
+        let player_keys = table
+            .seats()
+            .iter()
+            .map(|s| *s.public_key())
+            .collect::<Vec<_>>();
+        for opening in table.board_openings().iter() {
+            assert_eq!(Ok(()), opening.verify(table.parameters(), &player_keys));
+        }
+
+        let mut dealt = HashSet::new();
+        for card in table.board().iter() {
+            assert!(dealt.insert(*card));
+        }
+        for seat in 0..num_of_players {
+            assert_eq!(table.private_hole_cards(seat).len(), 2);
+            for card in table.private_hole_cards(seat).iter() {
+                assert!(dealt.insert(*card));
+            }
+        }
+
+        let total_won: u64 = table.winnings().iter().map(|(_, amount)| amount).sum();
+        assert_eq!(total_won, 6);
+
+        let total_chips: u64 = table.seats().iter().map(|s| s.chips()).sum();
+        assert_eq!(total_chips, 300);
+    }
+
+    #[test]
+    fn test_everyone_folds() {
+        let rng = &mut thread_rng();
+
+        let mut table = Table::<Curve>::new(rng, &players(3, 100), 1, 2).unwrap();
+        table.start_hand(rng).unwrap();
+
+        // Dealer is seat 0, so seat 1 posts the small blind and seat 2 the big blind
+        table.act(0, Action::Raise(6)).unwrap();
+        table.act(1, Action::Fold).unwrap();
+        table.act(2, Action::Fold).unwrap();
+
+        assert_eq!(table.stage(), Stage::Complete);
+        assert_eq!(table.winnings(), &vec![(0, 9)]);
+        assert_eq!(table.seats()[0].chips(), 103);
+        assert_eq!(table.seats()[1].chips(), 99);
+        assert_eq!(table.seats()[2].chips(), 98);
+        assert_eq!(table.dealer(), 1);
+    }
+
+    #[test]
+    fn test_short_stacked_seat_sits_out() {
+        let rng = &mut thread_rng();
+
+        let mut stacks = players(3, 100);
+        stacks[1].1 = 1;
+        let mut table = Table::<Curve>::new(rng, &stacks, 1, 2).unwrap();
+        table.start_hand(rng).unwrap();
+
+        // Seat 1 cannot cover the big blind: it gets no cards and the two other seats play
+        // heads-up, the dealer posting the small blind
+        assert!(table.seats()[1].is_sitting_out());
+        assert!(table.seats()[1].hole_cards().is_empty());
+        assert_eq!(table.private_hole_cards(0).len(), 2);
+        assert_eq!(table.private_hole_cards(2).len(), 2);
+        assert_eq!(table.pot(), 3);
+        assert_eq!(table.seats()[0].chips(), 99);
+        assert_eq!(table.seats()[1].chips(), 1);
+        assert_eq!(table.seats()[2].chips(), 98);
+
+        // The hand plays out between the two other seats, and the board openings still carry the
+        // tokens of every seat
+        assert_eq!(table.to_act(), 0);
+        table.act(0, Action::Call).unwrap();
+        table.act(2, Action::Check).unwrap();
+        let mut stage = table.next_stage(rng).unwrap();
+        while stage != Stage::Complete {
+            while !table.betting_round_complete() {
+                table.act(table.to_act(), Action::Check).unwrap();
+            }
+            stage = table.next_stage(rng).unwrap();
+        }
+        assert!(table.winnings().iter().all(|(seat, _)| *seat != 1));
+        let player_keys = table
+            .seats()
+            .iter()
+            .map(|s| *s.public_key())
+            .collect::<Vec<_>>();
+        for opening in table.board_openings().iter() {
+            assert_eq!(Ok(()), opening.verify(table.parameters(), &player_keys));
+        }
+
+        // A hand needs two seats able to cover the big blind
+        let mut stacks = players(3, 1);
+        stacks[0].1 = 100;
+        let mut table = Table::<Curve>::new(rng, &stacks, 1, 2).unwrap();
+        assert_eq!(table.start_hand(rng), Err(GameError::InsufficientChips));
+    }
+}